@@ -323,6 +323,66 @@ ipcMain.handle('save-configuration', async (event, config) => {
   }
 });
 
+ipcMain.handle('set-hotkeys-enabled', async (event, enabled) => {
+  try {
+    if (!ipcClient || !ipcClient.connected) {
+      throw new Error('Not connected to core service');
+    }
+
+    await ipcClient.sendMessage('SetHotkeysEnabled', { enabled });
+    return { success: true };
+  } catch (err) {
+    console.error('Failed to set hotkeys enabled:', err);
+    return { success: false, error: err.message };
+  }
+});
+
+ipcMain.handle('show-all-notecards', async () => {
+  try {
+    if (!ipcClient || !ipcClient.connected) {
+      throw new Error('Not connected to core service');
+    }
+
+    await ipcClient.sendMessage('ShowAllNotecards');
+    return { success: true };
+  } catch (err) {
+    console.error('Failed to show all notecards:', err);
+    return { success: false, error: err.message };
+  }
+});
+
+ipcMain.handle('hide-all-notecards', async () => {
+  try {
+    if (!ipcClient || !ipcClient.connected) {
+      throw new Error('Not connected to core service');
+    }
+
+    await ipcClient.sendMessage('HideAllNotecards');
+    return { success: true };
+  } catch (err) {
+    console.error('Failed to hide all notecards:', err);
+    return { success: false, error: err.message };
+  }
+});
+
+ipcMain.handle('get-platform-info', async () => {
+  try {
+    if (!ipcClient || !ipcClient.connected) {
+      throw new Error('Not connected to core service');
+    }
+
+    const response = await ipcClient.sendMessage('GetPlatformInfo');
+    return {
+      success: true,
+      capabilities: response.capabilities,
+      launchOnStartup: response.launch_on_startup ?? null
+    };
+  } catch (err) {
+    console.error('Failed to get platform info:', err);
+    return { success: false, error: err.message };
+  }
+});
+
 // App event handlers
 app.whenReady().then(createWindow);
 
@@ -15,6 +15,16 @@ contextBridge.exposeInMainWorld('notecognitoAPI', {
   // Save the entire configuration
   saveConfiguration: (config) => ipcRenderer.invoke('save-configuration', config),
 
+  // Pause or resume hotkeys right away, without a save
+  setHotkeysEnabled: (enabled) => ipcRenderer.invoke('set-hotkeys-enabled', enabled),
+
+  // Show every notecard at once, or hide them all, to preview the layout
+  showAllNotecards: () => ipcRenderer.invoke('show-all-notecards'),
+  hideAllNotecards: () => ipcRenderer.invoke('hide-all-notecards'),
+
+  // Get the optional display features of the running platform
+  getPlatformInfo: () => ipcRenderer.invoke('get-platform-info'),
+
   // Listen for menu events
   onMenuAction: (callback) => {
     ipcRenderer.on('menu-save', () => callback('save'));
@@ -9,12 +9,16 @@ const elements = {
   connectionStatus: document.getElementById('connectionStatus'),
   connectionText: document.getElementById('connectionText'),
   saveBtn: document.getElementById('saveBtn'),
+  showAllBtn: document.getElementById('showAllBtn'),
+  hideAllBtn: document.getElementById('hideAllBtn'),
   clearBtn: document.getElementById('clearBtn'),
   notecardButtons: document.getElementById('notecardButtons'),
   currentNotecardId: document.getElementById('currentNotecardId'),
   notecardContent: document.getElementById('notecardContent'),
   charCount: document.getElementById('charCount'),
   launchOnStartup: document.getElementById('launchOnStartup'),
+  hideAllHotkey: document.getElementById('hideAllHotkey'),
+  pauseHotkeys: document.getElementById('pauseHotkeys'),
   hotkeyModifiers: document.getElementById('hotkeyModifiers'),
   hotkeyDisplay: document.getElementById('hotkeyDisplay'),
   opacity: document.getElementById('opacity'),
@@ -25,7 +29,15 @@ const elements = {
   autoHide: document.getElementById('autoHide'),
   autoHideValue: document.getElementById('autoHideValue'),
   fontFamily: document.getElementById('fontFamily'),
+  backgroundStyle: document.getElementById('backgroundStyle'),
   algorithmicSpacing: document.getElementById('algorithmicSpacing'),
+  animations: document.getElementById('animations'),
+  excludeFromCapture: document.getElementById('excludeFromCapture'),
+  clickThrough: document.getElementById('clickThrough'),
+  announcements: document.getElementById('announcements'),
+  showAtCursor: document.getElementById('showAtCursor'),
+  markdown: document.getElementById('markdown'),
+  wrapPages: document.getElementById('wrapPages'),
   aboutModal: document.getElementById('aboutModal'),
   toastContainer: document.getElementById('toastContainer')
 };
@@ -59,6 +71,7 @@ async function initialize() {
     updateConnectionStatus(true);
     setupUI();
     loadConfiguration();
+    await applyPlatformCapabilities();
     hideLoadingOverlay();
 
   } catch (error) {
@@ -76,11 +89,39 @@ function updateConnectionStatus(connected) {
     elements.connectionStatus.classList.add('connected');
     elements.connectionText.textContent = 'Connected';
     elements.saveBtn.disabled = false;
+    elements.showAllBtn.disabled = false;
+    elements.hideAllBtn.disabled = false;
   } else {
     elements.connectionStatus.classList.remove('connected');
     elements.connectionStatus.classList.add('disconnected');
     elements.connectionText.textContent = 'Disconnected';
     elements.saveBtn.disabled = true;
+    elements.showAllBtn.disabled = true;
+    elements.hideAllBtn.disabled = true;
+  }
+}
+
+// Disable settings the running platform can't honor; without an answer everything stays enabled
+async function applyPlatformCapabilities() {
+  const result = await window.notecognitoAPI.getPlatformInfo();
+  if (!result.success) return;
+
+  if (!result.capabilities.screen_capture_exclusion) {
+    elements.excludeFromCapture.disabled = true;
+    elements.excludeFromCapture.parentElement.title = 'Not supported on this version of the operating system';
+  }
+
+  // Blurred backgrounds only where the platform draws them
+  if (!result.capabilities.blur) {
+    Array.from(elements.backgroundStyle.options)
+      .filter(option => option.value !== 'solid')
+      .forEach(option => { option.disabled = true; });
+    elements.backgroundStyle.title = 'Not supported on this platform';
+  }
+
+  // What the system reports beats the stored setting, e.g. after the user removed the login item
+  if (result.launchOnStartup !== null && result.launchOnStartup !== undefined) {
+    elements.launchOnStartup.checked = result.launchOnStartup;
   }
 }
 
@@ -110,7 +151,11 @@ function setupUI() {
   elements.notecardContent.addEventListener('input', handleContentChange);
   elements.saveBtn.addEventListener('click', saveConfiguration);
   elements.clearBtn.addEventListener('click', clearCurrentNotecard);
+  elements.showAllBtn.addEventListener('click', showAllNotecards);
+  elements.hideAllBtn.addEventListener('click', hideAllNotecards);
   elements.launchOnStartup.addEventListener('change', markAsChanged);
+  elements.hideAllHotkey.addEventListener('change', markAsChanged);
+  elements.pauseHotkeys.addEventListener('change', handlePauseHotkeysChange);
   elements.hotkeyModifiers.addEventListener('change', handleHotkeyChange);
 
   // Display settings listeners
@@ -118,7 +163,15 @@ function setupUI() {
   elements.fontSize.addEventListener('input', handleFontSizeChange);
   elements.autoHide.addEventListener('input', handleAutoHideChange);
   elements.fontFamily.addEventListener('change', markAsChanged);
+  elements.backgroundStyle.addEventListener('change', markAsChanged);
   elements.algorithmicSpacing.addEventListener('change', markAsChanged);
+  elements.animations.addEventListener('change', markAsChanged);
+  elements.excludeFromCapture.addEventListener('change', markAsChanged);
+  elements.clickThrough.addEventListener('change', markAsChanged);
+  elements.announcements.addEventListener('change', markAsChanged);
+  elements.showAtCursor.addEventListener('change', markAsChanged);
+  elements.markdown.addEventListener('change', markAsChanged);
+  elements.wrapPages.addEventListener('change', markAsChanged);
 
   // Listen for menu actions
   window.notecognitoAPI.onMenuAction((action) => {
@@ -146,9 +199,11 @@ function loadConfiguration() {
 
   // Global settings
   elements.launchOnStartup.checked = currentConfig.launch_on_startup;
+  elements.hideAllHotkey.checked = currentConfig.hide_all_hotkey ?? true;
+  elements.pauseHotkeys.checked = !(currentConfig.hotkeys_enabled ?? true);
 
   // Set hotkey modifiers
-  const modifiers = currentConfig.hotkey_modifiers || ['Control', 'Shift'];
+  const modifiers = parseHotkeyModifiers(currentConfig.hotkey_modifiers ?? 'Ctrl+Shift');
   Array.from(elements.hotkeyModifiers.options).forEach(option => {
     option.selected = modifiers.includes(option.value);
   });
@@ -160,7 +215,15 @@ function loadConfiguration() {
   elements.fontSize.value = defaults.font_size;
   elements.autoHide.value = defaults.auto_hide_duration;
   elements.fontFamily.value = defaults.font_family;
+  elements.backgroundStyle.value = defaults.background_style?.blur?.material ?? 'solid';
   elements.algorithmicSpacing.checked = defaults.algorithmic_spacing;
+  elements.animations.checked = defaults.animations ?? true;
+  elements.excludeFromCapture.checked = defaults.exclude_from_capture ?? true;
+  elements.clickThrough.checked = defaults.click_through ?? false;
+  elements.announcements.checked = defaults.announcements ?? true;
+  elements.showAtCursor.checked = defaults.placement?.at_cursor !== undefined;
+  elements.markdown.checked = defaults.markdown ?? false;
+  elements.wrapPages.checked = defaults.wrap_pages ?? false;
 
   // Update display values
   handleOpacityChange();
@@ -226,6 +289,32 @@ function handleHotkeyChange() {
   markAsChanged();
 }
 
+// Stored names of the modifier options, as written by the core ("Ctrl+Shift")
+const HOTKEY_MODIFIER_NAMES = {
+  Control: 'Ctrl',
+  Alt: 'Alt',
+  Shift: 'Shift',
+  Command: 'Cmd',
+  Windows: 'Win'
+};
+
+// Converts stored hotkey modifiers into option values; older configs store an array
+function parseHotkeyModifiers(stored) {
+  if (Array.isArray(stored)) return stored;
+
+  const names = stored.split('+').map(name => name.trim().toLowerCase());
+  return Object.keys(HOTKEY_MODIFIER_NAMES)
+    .filter(value => names.includes(HOTKEY_MODIFIER_NAMES[value].toLowerCase()));
+}
+
+// Converts selected option values into the stored hotkey string
+function formatHotkeyModifiers(values) {
+  return Object.keys(HOTKEY_MODIFIER_NAMES)
+    .filter(value => values.includes(value))
+    .map(value => HOTKEY_MODIFIER_NAMES[value])
+    .join('+');
+}
+
 // Update hotkey display
 function updateHotkeyDisplay() {
   const selected = Array.from(elements.hotkeyModifiers.selectedOptions)
@@ -271,7 +360,9 @@ function markAsChanged() {
 
 // Save current notecard to config
 function saveCurrentNotecard() {
+  // Spread the existing card so its display properties, e.g. where it was dragged, survive
   const notecard = {
+    ...currentConfig.notecards[currentNotecardId.toString()],
     id: currentNotecardId,
     content: elements.notecardContent.value
   };
@@ -288,6 +379,35 @@ function clearCurrentNotecard() {
   }
 }
 
+// Pausing takes effect immediately, unlike the settings that wait for Save
+async function handlePauseHotkeysChange() {
+  const enabled = !elements.pauseHotkeys.checked;
+  const result = await window.notecognitoAPI.setHotkeysEnabled(enabled);
+
+  if (result.success) {
+    currentConfig.hotkeys_enabled = enabled;
+    showToast(enabled ? 'Hotkeys resumed' : 'Hotkeys paused', 'success');
+  } else {
+    elements.pauseHotkeys.checked = enabled;
+    showToast('Failed to pause hotkeys: ' + result.error, 'danger');
+  }
+}
+
+// Previews the saved notecards together; unsaved edits aren't shown until Save
+async function showAllNotecards() {
+  const result = await window.notecognitoAPI.showAllNotecards();
+  if (!result.success) {
+    showToast('Failed to show notecards: ' + result.error, 'danger');
+  }
+}
+
+async function hideAllNotecards() {
+  const result = await window.notecognitoAPI.hideAllNotecards();
+  if (!result.success) {
+    showToast('Failed to hide notecards: ' + result.error, 'danger');
+  }
+}
+
 // Save configuration
 async function saveConfiguration() {
   try {
@@ -296,17 +416,32 @@ async function saveConfiguration() {
 
     // Update config from UI
     currentConfig.launch_on_startup = elements.launchOnStartup.checked;
-    currentConfig.hotkey_modifiers = Array.from(elements.hotkeyModifiers.selectedOptions)
-      .map(option => option.value);
+    currentConfig.hide_all_hotkey = elements.hideAllHotkey.checked;
+    currentConfig.hotkey_modifiers = formatHotkeyModifiers(
+      Array.from(elements.hotkeyModifiers.selectedOptions).map(option => option.value)
+    );
+
+    // An offset set in the config file is kept; new ones open just below and right of the pointer
+    const cursorOffset = currentConfig.default_display_properties.placement?.at_cursor?.offset ?? [12, 12];
 
+    // Spread the existing properties so fields without a control here survive
     currentConfig.default_display_properties = {
+      ...currentConfig.default_display_properties,
       opacity: parseInt(elements.opacity.value),
-      position: currentConfig.default_display_properties.position,
-      size: currentConfig.default_display_properties.size,
       auto_hide_duration: parseInt(elements.autoHide.value),
       font_family: elements.fontFamily.value,
+      background_style: elements.backgroundStyle.value === 'solid'
+        ? 'solid'
+        : { blur: { material: elements.backgroundStyle.value } },
       font_size: parseInt(elements.fontSize.value),
-      algorithmic_spacing: elements.algorithmicSpacing.checked
+      algorithmic_spacing: elements.algorithmicSpacing.checked,
+      animations: elements.animations.checked,
+      exclude_from_capture: elements.excludeFromCapture.checked,
+      click_through: elements.clickThrough.checked,
+      announcements: elements.announcements.checked,
+      markdown: elements.markdown.checked,
+      wrap_pages: elements.wrapPages.checked,
+      placement: elements.showAtCursor.checked ? { at_cursor: { offset: cursorOffset } } : 'fixed'
     };
 
     // Save to core
@@ -97,7 +97,17 @@ elif [[ "$OS" == "windows" ]]; then
     fi
     cd ..
 else
-    echo "Platform-specific build not available for Linux yet"
+    echo "=================================="
+    echo "Building Linux Application"
+    echo "=================================="
+    cd linux
+    if [[ -f "build.sh" ]]; then
+        chmod +x build.sh
+        ./build.sh
+    else
+        cargo build --release
+    fi
+    cd ..
 fi
 
 echo ""
@@ -119,6 +129,9 @@ if [[ "$OS" == "macos" ]]; then
 elif [[ "$OS" == "windows" ]]; then
     echo "✓ Windows application built"
     echo "  - Executable: windows/target/release/notecognito.exe"
+else
+    echo "✓ Linux application built"
+    echo "  - Executable: linux/target/release/notecognito"
 fi
 
 echo ""
@@ -134,6 +147,8 @@ if [[ "$OS" == "macos" ]]; then
     echo "   open macos/target/release/Notecognito.app"
 elif [[ "$OS" == "windows" ]]; then
     echo "   windows\\target\\release\\notecognito.exe"
+else
+    echo "   linux/target/release/notecognito"
 fi
 echo ""
 echo "3. Configure notecards using the UI"
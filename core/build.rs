@@ -0,0 +1,23 @@
+use std::env;
+use std::process::Command;
+
+fn main() {
+    // Target triple for notecognito_build_info_json
+    let target = env::var("TARGET").unwrap();
+    println!("cargo:rustc-env=NOTECOGNITO_TARGET={}", target);
+
+    // Packagers can pin the hash when building outside a git checkout
+    println!("cargo:rerun-if-env-changed=NOTECOGNITO_GIT_HASH");
+    if env::var("NOTECOGNITO_GIT_HASH").is_err() {
+        let output = Command::new("git").args(["rev-parse", "--short", "HEAD"]).output();
+        if let Ok(output) = output {
+            if output.status.success() {
+                let hash = String::from_utf8_lossy(&output.stdout);
+                println!("cargo:rustc-env=NOTECOGNITO_GIT_HASH={}", hash.trim());
+            }
+        }
+    }
+
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+}
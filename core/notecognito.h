@@ -8,36 +8,203 @@
 extern "C" {
 #endif
 
-/* Opaque type for the configuration manager */
+/* Opaque handle to a configuration manager. Handles may be used from any
+ * thread; clones share the same manager. */
 typedef struct ConfigManager ConfigManager;
 
+/* Opaque connection to a running IPC server */
+typedef struct NotecognitoIpcClient NotecognitoIpcClient;
+
+/* ABI version; compare against notecognito_ffi_abi_version() before use */
+#define NOTECOGNITO_FFI_ABI_VERSION 2
+
+/* Stable error codes reported in FfiResult.error_code */
+typedef enum {
+    NOTECOGNITO_ERROR_CODE_SUCCESS = 0,
+    NOTECOGNITO_ERROR_CODE_IO = 1,
+    NOTECOGNITO_ERROR_CODE_JSON = 2,
+    NOTECOGNITO_ERROR_CODE_CONFIG = 3,
+    NOTECOGNITO_ERROR_CODE_VALIDATION = 4,
+    NOTECOGNITO_ERROR_CODE_IPC = 5,
+    NOTECOGNITO_ERROR_CODE_INVALID_NOTECARD_ID = 6,
+    NOTECOGNITO_ERROR_CODE_PLATFORM = 7,
+    NOTECOGNITO_ERROR_CODE_CONNECTION_LOST = 8,
+    NOTECOGNITO_ERROR_CODE_INVALID_MESSAGE = 9,
+    NOTECOGNITO_ERROR_CODE_PERMISSION_DENIED = 10,
+    NOTECOGNITO_ERROR_CODE_NULL_POINTER = 11,
+    NOTECOGNITO_ERROR_CODE_INVALID_UTF8 = 12,
+    NOTECOGNITO_ERROR_CODE_INVALID_UTF16 = 13,
+    NOTECOGNITO_ERROR_CODE_INTERNAL = 14, /* a panic was caught inside the library */
+    NOTECOGNITO_ERROR_CODE_NOT_VISIBLE = 15,
+} NotecognitoErrorCode;
+
 /* Result type for FFI functions */
 typedef struct {
     bool success;
+    int error_code; /* NotecognitoErrorCode, 0 on success */
     char* error_message;
 } FfiResult;
 
+/* Gets the ABI version of the loaded library */
+int notecognito_ffi_abi_version(void);
+
+/* Gets the library version (static string, must not be freed) */
+const char* notecognito_version(void);
+
+/* Gets version, ABI version, git hash, target, features and the conservative
+ * platform capabilities as JSON (caller must free the returned string) */
+char* notecognito_build_info_json(void);
+
+/* Gets the static name of an error code (must not be freed) */
+const char* notecognito_error_code_name(int code);
+
 /* Frees a string allocated by Rust */
 void notecognito_free_string(char* s);
 
+/* Releases the error message owned by a result; safe on successful or zeroed
+ * results. Free each result at most once. */
+void notecognito_result_free(FfiResult result);
+
 /* Creates a new configuration manager */
 ConfigManager* notecognito_config_manager_new(void);
 
-/* Frees a configuration manager */
+/* Creates a configuration manager backed by the config file at path.
+ * Returns NULL on failure; see notecognito_config_manager_last_error. */
+ConfigManager* notecognito_config_manager_new_with_path(const char* path);
+
+/* Gets why the last constructor on this thread returned NULL, or NULL if none
+ * (caller must free the returned string) */
+char* notecognito_config_manager_last_error(void);
+
+/* Gets why the last constructor or connect call on this thread returned NULL,
+ * or NULL if none (caller must free the returned string) */
+char* notecognito_last_error(void);
+
+/* Creates another handle to the same manager; free each handle separately */
+ConfigManager* notecognito_config_manager_clone_handle(ConfigManager* manager);
+
+/* Frees a configuration manager handle and its change callback. The manager
+ * is dropped with its last handle. */
 void notecognito_config_manager_free(ConfigManager* manager);
 
 /* Updates a notecard (id must be 1-9) */
 FfiResult notecognito_update_notecard(ConfigManager* manager, int id, const char* content);
 
+/* Clears a notecard (id must be 1-9); its content reads back as an empty string */
+FfiResult notecognito_clear_notecard(ConfigManager* manager, int id);
+
 /* Gets notecard content (caller must free the returned string) */
 char* notecognito_get_notecard_content(ConfigManager* manager, int id);
 
+/* Gets a whole notecard as JSON (caller must free the returned string) */
+char* notecognito_get_notecard_json(ConfigManager* manager, int id);
+
+/* Updates a notecard from JSON in the same shape; the embedded id must be 1-9
+ * and unknown fields are ignored */
+FfiResult notecognito_update_notecard_json(ConfigManager* manager, const char* json);
+
+/* UTF-16 variants for hosts that work in wide strings (e.g. Windows). Strings
+ * are NUL-terminated; content with lone surrogates is rejected with
+ * NOTECOGNITO_ERROR_CODE_INVALID_UTF16. */
+FfiResult notecognito_update_notecard_w(ConfigManager* manager, int id, const uint16_t* content);
+
+/* Gets notecard content as UTF-16 (caller must free with notecognito_free_wstring) */
+uint16_t* notecognito_get_notecard_content_w(ConfigManager* manager, int id);
+
+/* Frees a UTF-16 string returned by a _w function */
+void notecognito_free_wstring(uint16_t* s);
+
+/* Validates content without saving or touching the filesystem. On success
+ * *out_issues_json receives a JSON array of {"path", "message"} objects, empty
+ * when the content is valid (caller must free it). The _for variant uses the
+ * manager's configured content limits instead of the defaults. */
+FfiResult notecognito_validate_content(const char* content, char** out_issues_json);
+FfiResult notecognito_validate_content_for(ConfigManager* manager, const char* content, char** out_issues_json);
+
 /* Gets the configuration as JSON (caller must free the returned string) */
 char* notecognito_get_config_json(ConfigManager* manager);
 
+/* Lists all notecards as a JSON array of
+ * { id, title, summary, is_empty, modified_at } (caller must free the returned string) */
+char* notecognito_list_notecards(ConfigManager* manager);
+
+/* Validates, applies and saves a full configuration from JSON.
+ * replace=true replaces everything; false keeps existing notecards unless the
+ * incoming one is non-empty. On failure nothing changes and the error names the field. */
+FfiResult notecognito_apply_config_json(ConfigManager* manager, const char* json, bool replace);
+
+/* Replaces the in-memory configuration with the file on disk. If the file is
+ * unreadable or invalid the current configuration is kept. */
+FfiResult notecognito_reload_config(ConfigManager* manager);
+
+/* Returns 1 if there are unsaved changes, 0 if not, -1 if manager is NULL */
+int notecognito_config_is_dirty(ConfigManager* manager);
+
 /* Sets the launch on startup flag */
 FfiResult notecognito_set_launch_on_startup(ConfigManager* manager, bool enabled);
 
+/* Gets the default display properties as JSON (caller must free the returned string) */
+char* notecognito_get_display_properties_json(ConfigManager* manager);
+
+/* Validates, clamps and saves new default display properties from JSON */
+FfiResult notecognito_set_display_properties_json(ConfigManager* manager, const char* json);
+
+/* Kinds of change reported to a change callback */
+typedef enum {
+    NOTECOGNITO_CHANGE_KIND_UPDATED = 1,
+    NOTECOGNITO_CHANGE_KIND_CLEARED = 2,
+    NOTECOGNITO_CHANGE_KIND_SAVED = 3,
+    NOTECOGNITO_CHANGE_KIND_RELOADED = 4,
+} NotecognitoChangeKind;
+
+/* Change callback; notecard_id is 0 for SAVED and RELOADED events */
+typedef void (*NotecognitoChangeCallback)(int event_kind, int notecard_id, void* user_data);
+
+/* Registers a callback for notecard updates, clears and saves, replacing any
+ * previous one on this handle. It runs synchronously on the thread making the
+ * change while the manager is locked, so it must not call back into it. */
+FfiResult notecognito_set_change_callback(ConfigManager* manager, NotecognitoChangeCallback callback, void* user_data);
+
+/* Removes the change callback, if any */
+FfiResult notecognito_clear_change_callback(ConfigManager* manager);
+
+/* Opaque queue of change events for hosts that poll instead of using callbacks */
+typedef struct NotecognitoEventQueue NotecognitoEventQueue;
+
+/* Creates a queue recording changes made through the manager from now on.
+ * Holds the newest 256 events, dropping the oldest when full. */
+NotecognitoEventQueue* notecognito_event_queue_new(ConfigManager* manager);
+
+/* Dequeues the oldest event as JSON, e.g. {"kind": "Updated", "notecard_id": 3},
+ * into *out_json (caller must free it). Returns 1 if an event was dequeued,
+ * 0 if the queue is empty, -1 on invalid parameters. */
+int notecognito_event_queue_poll(NotecognitoEventQueue* queue, char** out_json);
+
+/* Stops recording events and frees the queue */
+void notecognito_event_queue_free(NotecognitoEventQueue* queue);
+
+/* Connects to the IPC server on localhost. Returns NULL on failure; see
+ * notecognito_last_error. Must not be called from a thread running a Tokio runtime. */
+NotecognitoIpcClient* notecognito_ipc_connect(uint32_t timeout_ms);
+
+/* Gets the server's configuration as JSON (caller must free the returned string) */
+char* notecognito_ipc_get_config_json(NotecognitoIpcClient* client);
+
+/* Updates a notecard through the server (id must be 1-9) */
+FfiResult notecognito_ipc_update_notecard(NotecognitoIpcClient* client, int id, const char* content);
+
+/* Asks the server's platform to show a notecard (id must be 1-9) */
+FfiResult notecognito_ipc_show_notecard(NotecognitoIpcClient* client, int id);
+
+/* Asks the server's platform to show every notecard with content at once */
+FfiResult notecognito_ipc_show_all_notecards(NotecognitoIpcClient* client);
+
+/* Asks the server's platform to hide every notecard */
+FfiResult notecognito_ipc_hide_all_notecards(NotecognitoIpcClient* client);
+
+/* Disconnects and frees an IPC client */
+void notecognito_ipc_free(NotecognitoIpcClient* client);
+
 #ifdef __cplusplus
 }
 #endif
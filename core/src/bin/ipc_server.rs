@@ -1,7 +1,6 @@
 use notecognito_core::{ConfigManager, IpcServer};
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing_subscriber;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
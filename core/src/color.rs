@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+/// A color with straight (not premultiplied) alpha
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Rgba {
+    /// Alpha as a fraction from 0.0 to 1.0
+    pub fn alpha(&self) -> f64 {
+        self.a as f64 / 255.0
+    }
+}
+
+/// Parses a CSS-style hex color: `#rgb`, `#rrggbb` or `#rrggbbaa`
+///
+/// The `#` is optional and case doesn't matter. Colors without alpha are
+/// opaque. Anything else, including `#rgba`, gives `None`.
+pub fn parse_hex_color(text: &str) -> Option<Rgba> {
+    let hex = text.trim();
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if !hex.is_ascii() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let short = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|value| value * 17);
+
+    match hex.len() {
+        3 => Some(Rgba { r: short(0)?, g: short(1)?, b: short(2)?, a: 255 }),
+        6 => Some(Rgba { r: channel(0)?, g: channel(2)?, b: channel(4)?, a: 255 }),
+        8 => Some(Rgba { r: channel(0)?, g: channel(2)?, b: channel(4)?, a: channel(6)? }),
+        _ => None,
+    }
+}
@@ -1,27 +1,108 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use crate::error::Result;
-use crate::notecard::{Notecard, NotecardId};
-use crate::platform::HotkeyModifier;
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::error::{NotecognitoError, Result};
+use crate::layout::{Anchor, MonitorSelector, PixelUnits, Placement, SizeMode};
+use crate::color::parse_hex_color;
+use crate::notecard::{ContentLimits, Notecard, NotecardId, NotecardSummary};
+use crate::platform::{HotkeyAction, HotkeyBinding, HotkeyKey, HotkeyModifier};
+
+/// What's drawn behind a notecard's text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackgroundStyle {
+    /// A flat card in colors matching the light or dark appearance
+    #[default]
+    Solid,
+    /// The windows behind the card, blurred; solid where
+    /// `PlatformCapabilities::blur` says the platform can't
+    Blur { material: BlurMaterial },
+}
+
+/// Look of a blurred background, after the system surfaces that use it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlurMaterial {
+    /// Dark whatever the appearance, like heads-up displays
+    #[default]
+    Hud,
+    /// Light or dark with the appearance, like popovers
+    Popover,
+}
 
 /// Display properties for notecards
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DisplayProperties {
     /// Opacity level (0-100)
     pub opacity: u8,
-    /// Position on screen (x, y coordinates)
+    /// Position on screen (x, y coordinates); an offset from `anchor` when one is set
     pub position: (i32, i32),
-    /// Size (width, height)
+    /// Size (width, height); with `SizeMode::FitContent`, the width is the
+    /// widest the notecard gets and the height is unused
     pub size: (u32, u32),
+    /// Whether the notecard is exactly `size` or fitted to its content;
+    /// fitting is honored by the Windows app
+    #[serde(default)]
+    pub size_mode: SizeMode,
     /// Auto-hide duration in seconds (0 for manual dismiss)
     pub auto_hide_duration: u32,
     /// Font family name
     pub font_family: String,
     /// Font size in points
     pub font_size: u32,
-    /// Enable algorithmic spacing
+    /// Space lines and paragraphs out by how dense the content is, as
+    /// `spacing::apply_spacing` works out; honored by the macOS app
     pub algorithmic_spacing: bool,
+    /// Draw the content as markdown, as `markdown::parse_markdown` reads it,
+    /// instead of as plain text; honored by the macOS app
+    #[serde(default)]
+    pub markdown: bool,
+    /// Point of the work area the notecard is pinned to
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+    /// Screen the notecard is placed on
+    #[serde(default)]
+    pub monitor: MonitorSelector,
+    /// Whether the notecard opens at `position` or at the mouse cursor
+    #[serde(default)]
+    pub placement: Placement,
+    /// Unit of `position`, `size` and `font_size`; configurations saved
+    /// before this existed are read as physical pixels
+    #[serde(default = "PixelUnits::legacy")]
+    pub units: PixelUnits,
+    /// Fade notecards in and out where the platform animates them
+    #[serde(default = "enabled")]
+    pub animations: bool,
+    /// Keep notecards out of screenshots and screen sharing; honored by the
+    /// macOS and Windows apps where `PlatformCapabilities::screen_capture_exclusion`
+    /// says it works
+    #[serde(default = "enabled")]
+    pub exclude_from_capture: bool,
+    /// Let clicks through to the windows underneath; such notecards can't be
+    /// clicked away, dragged or closed with ESC, only by hotkey, auto-hide or
+    /// hide-all. Honored by the macOS and Windows apps
+    #[serde(default)]
+    pub click_through: bool,
+    /// Have the screen reader announce notecards as they appear and go;
+    /// honored by the macOS app, for VoiceOver
+    #[serde(default = "enabled")]
+    pub announcements: bool,
+    /// Flat or blurred card background; honored by the macOS app
+    #[serde(default)]
+    pub background_style: BackgroundStyle,
+    /// Go round from the last page of a card split with `---` lines to the
+    /// first, instead of hiding it; honored by the macOS app
+    #[serde(default)]
+    pub wrap_pages: bool,
+    /// Text color as `#rrggbb` or `#rrggbbaa`, as `color::parse_hex_color`
+    /// reads it; `None` for the platform's default. Honored by the Windows app
+    #[serde(default)]
+    pub text_color: Option<String>,
+    /// Background color in the same form; its alpha is combined with
+    /// `opacity`. Honored by the Windows app
+    #[serde(default)]
+    pub background_color: Option<String>,
 }
 
 impl Default for DisplayProperties {
@@ -30,26 +111,159 @@ impl Default for DisplayProperties {
             opacity: 95,
             position: (100, 100),
             size: (400, 200),
+            size_mode: SizeMode::Fixed,
             auto_hide_duration: 0,
             font_family: "System".to_string(),
             font_size: 16,
             algorithmic_spacing: false,
+            markdown: false,
+            anchor: None,
+            monitor: MonitorSelector::Cursor,
+            placement: Placement::Fixed,
+            units: PixelUnits::Logical,
+            animations: true,
+            exclude_from_capture: true,
+            click_through: false,
+            announcements: true,
+            background_style: BackgroundStyle::Solid,
+            wrap_pages: false,
+            text_color: None,
+            background_color: None,
         }
     }
 }
 
+impl DisplayProperties {
+    /// Smallest opacity a notecard is allowed to render with
+    pub const MIN_OPACITY: u8 = 10;
+    /// Smallest width or height of a notecard window
+    pub const MIN_DIMENSION: u32 = 50;
+    /// Largest width or height of a notecard window
+    pub const MAX_DIMENSION: u32 = 10000;
+    /// Supported font size range in points
+    pub const FONT_SIZE_RANGE: (u32, u32) = (6, 144);
+    /// Longest supported auto-hide duration in seconds
+    pub const MAX_AUTO_HIDE_DURATION: u32 = 3600;
+
+    /// Factor that turns configured lengths into device pixels on a screen
+    /// with `scale_factor`
+    pub fn scale_for(&self, scale_factor: f64) -> f64 {
+        match self.units {
+            PixelUnits::Logical if scale_factor.is_finite() && scale_factor > 0.0 => scale_factor,
+            _ => 1.0,
+        }
+    }
+
+    /// Rejects values that can't be interpreted at all
+    pub fn validate(&self) -> Result<()> {
+        if self.opacity > 100 {
+            return Err(NotecognitoError::validation(
+                "opacity",
+                format!("must be between 0 and 100 (got {})", self.opacity),
+            ));
+        }
+
+        if self.size.0 == 0 || self.size.1 == 0 {
+            return Err(NotecognitoError::validation(
+                "size",
+                format!("must be non-zero (got {}x{})", self.size.0, self.size.1),
+            ));
+        }
+
+        if self.font_size == 0 {
+            return Err(NotecognitoError::validation("font_size", "must be non-zero"));
+        }
+
+        Ok(())
+    }
+
+    /// Clamps values into the ranges the platforms can render
+    pub fn clamp(&mut self) {
+        self.opacity = self.opacity.clamp(Self::MIN_OPACITY, 100);
+        self.size.0 = self.size.0.clamp(Self::MIN_DIMENSION, Self::MAX_DIMENSION);
+        self.size.1 = self.size.1.clamp(Self::MIN_DIMENSION, Self::MAX_DIMENSION);
+        self.font_size = self.font_size.clamp(Self::FONT_SIZE_RANGE.0, Self::FONT_SIZE_RANGE.1);
+        self.auto_hide_duration = self.auto_hide_duration.min(Self::MAX_AUTO_HIDE_DURATION);
+
+        if self.font_family.trim().is_empty() {
+            self.font_family = DisplayProperties::default().font_family;
+        }
+
+        // A color that doesn't parse falls back to the platform's default
+        for color in [&mut self.text_color, &mut self.background_color] {
+            if color.as_deref().is_some_and(|text| parse_hex_color(text).is_none()) {
+                *color = None;
+            }
+        }
+    }
+}
+
+/// How an incoming configuration is combined with the current one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApplyMode {
+    /// The incoming configuration replaces the current one entirely
+    Replace,
+    /// Settings are replaced, but only non-empty incoming notecards overwrite existing ones
+    MergeNonEmpty,
+}
+
+/// How the app is launched on login, where a platform has more than one way
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum StartupMethod {
+    /// An entry under the user's Run key, started once Explorer is up
+    #[default]
+    RunKey,
+    /// A Task Scheduler task triggered by the user's logon
+    ScheduledTask {
+        /// Seconds to wait after logon before starting
+        #[serde(default)]
+        delay_seconds: u32,
+    },
+}
+
 /// Global application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     /// Launch on system login
     pub launch_on_startup: bool,
+    /// How `launch_on_startup` is carried out, on Windows
+    #[serde(default)]
+    pub startup_method: StartupMethod,
     /// Default notecard settings
     pub default_display_properties: DisplayProperties,
-    /// Hotkey modifier keys
+    /// Hotkey modifier keys, stored as a string like "Ctrl+Shift"
+    #[serde(serialize_with = "serialize_hotkey_modifiers", deserialize_with = "deserialize_hotkey_modifiers")]
     pub hotkey_modifiers: Vec<HotkeyModifier>,
     /// All notecards (keyed by ID)
     #[serde(serialize_with = "serialize_notecards", deserialize_with = "deserialize_notecards")]
     pub notecards: HashMap<NotecardId, Notecard>,
+    /// Limits enforced on notecard content
+    #[serde(default)]
+    pub content_limits: ContentLimits,
+    /// Names or identifiers of applications in front of which hotkeys don't show notecards
+    #[serde(default)]
+    pub blocked_applications: Vec<String>,
+    /// Whether hotkeys are active; cleared to pause them and hand the key
+    /// combinations back to other apps
+    #[serde(default = "enabled")]
+    pub hotkeys_enabled: bool,
+    /// Register the hide-all hotkey (the hotkey modifiers with 0)
+    #[serde(default = "enabled")]
+    pub hide_all_hotkey: bool,
+    /// On macOS, detect hotkeys with an event tap instead of `RegisterEventHotKey`;
+    /// needs accessibility access and takes effect on the next launch
+    #[serde(default)]
+    pub event_tap_hotkeys: bool,
+    /// Only fire a hotkey when exactly its modifiers are held, so a bigger
+    /// combination on the same key stays with the app in front. Carbon and
+    /// Windows hotkeys always match exactly; this governs the macOS event tap
+    #[serde(default = "enabled")]
+    pub strict_modifier_match: bool,
+}
+
+fn enabled() -> bool {
+    true
 }
 
 // Custom serialization for notecards to handle NotecardId as string keys in JSON
@@ -88,6 +302,42 @@ where
     Ok(result)
 }
 
+fn serialize_hotkey_modifiers<S>(
+    modifiers: &[HotkeyModifier],
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(&HotkeyModifier::canonical_list(modifiers))
+}
+
+// Accepts the enum array written by earlier versions as well as the string form
+fn deserialize_hotkey_modifiers<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Vec<HotkeyModifier>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StoredModifiers {
+        Canonical(String),
+        Legacy(Vec<HotkeyModifier>),
+    }
+
+    match StoredModifiers::deserialize(deserializer)? {
+        StoredModifiers::Canonical(s) => {
+            HotkeyModifier::parse_list(&s).map_err(serde::de::Error::custom)
+        }
+        StoredModifiers::Legacy(mut modifiers) => {
+            modifiers.sort();
+            modifiers.dedup();
+            Ok(modifiers)
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         let mut notecards = HashMap::new();
@@ -100,24 +350,151 @@ impl Default for Config {
 
         Config {
             launch_on_startup: false,
+            startup_method: StartupMethod::default(),
             default_display_properties: DisplayProperties::default(),
             hotkey_modifiers: vec![HotkeyModifier::Control, HotkeyModifier::Shift],
             notecards,
+            content_limits: ContentLimits::default(),
+            blocked_applications: Vec::new(),
+            hotkeys_enabled: true,
+            hide_all_hotkey: true,
+            event_tap_hotkeys: false,
+            strict_modifier_match: true,
+        }
+    }
+}
+
+impl Config {
+    /// Validates every field, reporting the path of the first invalid one
+    pub fn validate(&self) -> Result<()> {
+        self.default_display_properties
+            .validate()
+            .map_err(|e| e.within("default_display_properties"))?;
+
+        let mut ids: Vec<_> = self.notecards.keys().copied().collect();
+        ids.sort_by_key(|id| id.value());
+
+        for id in ids {
+            let notecard = &self.notecards[&id];
+            let path = format!("notecards.{}", id);
+
+            if notecard.id != id {
+                return Err(NotecognitoError::validation(
+                    format!("{}.id", path),
+                    format!("does not match its slot (got {})", notecard.id),
+                ));
+            }
+
+            notecard
+                .validate_with(&self.content_limits)
+                .map_err(|e| e.within(&path))?;
+
+            if let Some(properties) = &notecard.display_properties {
+                properties
+                    .validate()
+                    .map_err(|e| e.within(&format!("{}.display_properties", path)))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The display properties a notecard is shown with: its own override, or the defaults
+    pub fn display_properties_for(&self, id: NotecardId) -> &DisplayProperties {
+        self.notecards
+            .get(&id)
+            .and_then(|notecard| notecard.display_properties.as_ref())
+            .unwrap_or(&self.default_display_properties)
+    }
+
+    /// Every notecard with content, in id order, with its content and the
+    /// display properties it's shown with
+    pub fn notecards_with_content(&self) -> Vec<(NotecardId, String, DisplayProperties)> {
+        let mut notecards: Vec<_> = self
+            .notecards
+            .values()
+            .filter(|notecard| !notecard.is_empty())
+            .map(|notecard| {
+                (notecard.id, notecard.content.clone(), self.display_properties_for(notecard.id).clone())
+            })
+            .collect();
+        notecards.sort_by_key(|(id, _, _)| id.value());
+        notecards
+    }
+
+    /// Whether notecard `id` gets a global hotkey; empty cards don't, so their
+    /// combinations stay free for other apps
+    pub fn has_hotkey(&self, id: NotecardId) -> bool {
+        self.notecards.get(&id).is_some_and(|notecard| !notecard.is_empty())
+    }
+
+    /// The global hotkeys this configuration asks for: one per notecard
+    /// `has_hotkey` accepts, plus hide-all when it's turned on
+    pub fn hotkey_bindings(&self) -> HashMap<HotkeyAction, HotkeyBinding> {
+        let mut bindings: HashMap<_, _> = self
+            .notecards
+            .keys()
+            .filter(|id| self.has_hotkey(**id))
+            .map(|id| {
+                let binding = HotkeyBinding::new(&self.hotkey_modifiers, HotkeyKey::for_notecard(*id));
+                (HotkeyAction::ToggleNotecard(*id), binding)
+            })
+            .collect();
+
+        if self.hide_all_hotkey {
+            bindings.insert(
+                HotkeyAction::HideAll,
+                HotkeyBinding::new(&self.hotkey_modifiers, HotkeyKey::hide_all()),
+            );
         }
+
+        bindings
     }
+
+    /// The hotkeys to keep registered right now: `hotkey_bindings`, or none
+    /// while hotkeys are paused so their combinations reach other apps
+    pub fn active_hotkey_bindings(&self) -> HashMap<HotkeyAction, HotkeyBinding> {
+        if self.hotkeys_enabled {
+            self.hotkey_bindings()
+        } else {
+            HashMap::new()
+        }
+    }
+}
+
+/// A change made through the config manager
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigEvent {
+    /// A notecard's content was updated
+    Updated(NotecardId),
+    /// A notecard was cleared
+    Cleared(NotecardId),
+    /// The configuration was written to disk
+    Saved,
+    /// The configuration was replaced by the contents of the file on disk
+    Reloaded,
 }
 
+/// Handle returned by `ConfigManager::add_listener`
+pub type ListenerId = u64;
+
+type ConfigListener = Box<dyn Fn(&ConfigEvent) + Send + Sync>;
+
 /// Manages configuration file operations
 pub struct ConfigManager {
     config_path: PathBuf,
     config: Config,
+    listeners: Vec<(ListenerId, ConfigListener)>,
+    next_listener_id: ListenerId,
+    /// Set by in-memory changes, cleared by save and reload
+    dirty: AtomicBool,
 }
 
 impl ConfigManager {
     /// Creates a new ConfigManager with the default config path
     pub fn new() -> Result<Self> {
         let config_dir = dirs::config_dir()
-            .ok_or_else(|| crate::error::NotecognitoError::Config(
+            .ok_or_else(|| NotecognitoError::Config(
                 "Could not determine config directory".to_string()
             ))?;
 
@@ -135,6 +512,9 @@ impl ConfigManager {
         Ok(ConfigManager {
             config_path,
             config,
+            listeners: Vec::new(),
+            next_listener_id: 1,
+            dirty: AtomicBool::new(false),
         })
     }
 
@@ -151,9 +531,40 @@ impl ConfigManager {
         Ok(ConfigManager {
             config_path,
             config,
+            listeners: Vec::new(),
+            next_listener_id: 1,
+            dirty: AtomicBool::new(false),
         })
     }
 
+    /// Creates a ConfigManager at `path`, setting an unreadable file aside
+    ///
+    /// A file that doesn't parse is renamed to `<path>.corrupt` and the
+    /// defaults are used; the new location is returned so the user can be
+    /// told. Other errors, such as a file that can't be read, still fail.
+    pub fn with_path_or_recover<P: AsRef<Path>>(path: P) -> Result<(Self, Option<PathBuf>)> {
+        let config_path = path.as_ref();
+
+        match Self::with_path(config_path) {
+            Ok(manager) => Ok((manager, None)),
+            Err(NotecognitoError::Json(error)) => {
+                let mut set_aside = config_path.as_os_str().to_owned();
+                set_aside.push(".corrupt");
+                let set_aside = PathBuf::from(set_aside);
+
+                tracing::warn!("Setting unreadable config aside at {}: {}", set_aside.display(), error);
+                std::fs::rename(config_path, &set_aside)?;
+                Ok((Self::with_path(config_path)?, Some(set_aside)))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Where the configuration is saved
+    pub fn config_path(&self) -> &Path {
+        &self.config_path
+    }
+
     /// Loads configuration from a file
     fn load_from_file(path: &Path) -> Result<Config> {
         let contents = std::fs::read_to_string(path)?;
@@ -162,31 +573,275 @@ impl ConfigManager {
     }
 
     /// Saves the current configuration to file
+    ///
+    /// The file is written next to the target and renamed into place, so a
+    /// failed write never leaves a truncated config behind.
     pub fn save(&self) -> Result<()> {
         let json = serde_json::to_string_pretty(&self.config)?;
-        std::fs::write(&self.config_path, json)?;
+
+        let mut temp_path = self.config_path.clone().into_os_string();
+        temp_path.push(".tmp");
+        let temp_path = PathBuf::from(temp_path);
+
+        std::fs::write(&temp_path, json)?;
+        if let Err(e) = std::fs::rename(&temp_path, &self.config_path) {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(e.into());
+        }
+
+        self.dirty.store(false, Ordering::Relaxed);
+        self.notify(ConfigEvent::Saved);
+        Ok(())
+    }
+
+    /// Replaces the in-memory configuration with the file on disk
+    ///
+    /// If the file can't be read or doesn't hold a valid configuration, the
+    /// current configuration is kept and the error says why. Unsaved changes
+    /// are discarded on success; check `is_dirty` first.
+    pub fn reload(&mut self) -> Result<()> {
+        let config = Self::load_from_file(&self.config_path)
+            .and_then(|config| config.validate().map(|_| config))
+            .map_err(|e| NotecognitoError::Config(format!(
+                "Failed to reload {}: {}",
+                self.config_path.display(),
+                e
+            )))?;
+
+        self.config = config;
+        self.dirty.store(false, Ordering::Relaxed);
+        self.notify(ConfigEvent::Reloaded);
         Ok(())
     }
 
+    /// Returns true if the configuration has changed since it was last loaded or saved
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.load(Ordering::Relaxed)
+    }
+
+    fn mark_dirty(&self) {
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Registers a listener called synchronously, on the mutating thread,
+    /// after every notecard update, clear, and save
+    pub fn add_listener<F>(&mut self, listener: F) -> ListenerId
+    where
+        F: Fn(&ConfigEvent) + Send + Sync + 'static,
+    {
+        let id = self.next_listener_id;
+        self.next_listener_id += 1;
+        self.listeners.push((id, Box::new(listener)));
+        id
+    }
+
+    /// Removes a listener, returning whether it was registered
+    pub fn remove_listener(&mut self, id: ListenerId) -> bool {
+        let before = self.listeners.len();
+        self.listeners.retain(|(listener_id, _)| *listener_id != id);
+        self.listeners.len() != before
+    }
+
+    fn notify(&self, event: ConfigEvent) {
+        for (_, listener) in &self.listeners {
+            listener(&event);
+        }
+    }
+
     /// Gets a reference to the current configuration
     pub fn config(&self) -> &Config {
         &self.config
     }
 
     /// Gets a mutable reference to the current configuration
+    ///
+    /// Marks the configuration dirty, since changes can't be observed.
     pub fn config_mut(&mut self) -> &mut Config {
+        self.mark_dirty();
         &mut self.config
     }
 
     /// Updates a notecard
-    pub fn update_notecard(&mut self, notecard: Notecard) -> Result<()> {
-        notecard.validate()?;
-        self.config.notecards.insert(notecard.id, notecard);
+    ///
+    /// `modified_at` only moves when the content changes, not for display
+    /// property overrides alone.
+    pub fn update_notecard(&mut self, mut notecard: Notecard) -> Result<()> {
+        notecard.validate_with(&self.config.content_limits)?;
+        let id = notecard.id;
+        notecard.modified_at = match self.config.notecards.get(&id) {
+            Some(current) if current.content == notecard.content => current.modified_at,
+            _ => Some(chrono::Utc::now()),
+        };
+        self.config.notecards.insert(id, notecard);
+        self.mark_dirty();
+        self.notify(ConfigEvent::Updated(id));
         Ok(())
     }
 
+    /// Clears a notecard's content, leaving an empty card in its slot
+    pub fn clear_notecard(&mut self, id: NotecardId) -> Result<()> {
+        let mut notecard = Notecard::empty(id);
+        notecard.modified_at = Some(chrono::Utc::now());
+        self.config.notecards.insert(id, notecard);
+        self.mark_dirty();
+        self.notify(ConfigEvent::Cleared(id));
+        Ok(())
+    }
+
+    /// Summarizes every notecard slot, ordered by ID
+    pub fn notecard_summaries(&self) -> Vec<NotecardSummary> {
+        let mut summaries: Vec<NotecardSummary> = self.config.notecards
+            .values()
+            .map(Notecard::summary)
+            .collect();
+        summaries.sort_by_key(|summary| summary.id.value());
+        summaries
+    }
+
     /// Gets a notecard by ID
     pub fn get_notecard(&self, id: NotecardId) -> Option<&Notecard> {
         self.config.notecards.get(&id)
     }
-}
\ No newline at end of file
+
+    /// Validates, applies and saves a complete configuration
+    ///
+    /// Display values are clamped after validation. If validation or the
+    /// save fails, the current configuration is left untouched.
+    pub fn apply_config(&mut self, mut config: Config, mode: ApplyMode) -> Result<()> {
+        config.validate()?;
+        config.default_display_properties.clamp();
+        for properties in config.notecards.values_mut().filter_map(|n| n.display_properties.as_mut()) {
+            properties.clamp();
+        }
+
+        if mode == ApplyMode::MergeNonEmpty {
+            let mut notecards = self.config.notecards.clone();
+            for (id, notecard) in config.notecards {
+                if !notecard.is_empty() {
+                    notecards.insert(id, notecard);
+                }
+            }
+            config.notecards = notecards;
+        }
+
+        let previous = std::mem::replace(&mut self.config, config);
+        if let Err(e) = self.save() {
+            self.config = previous;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Validates, clamps and stores the default display properties
+    pub fn set_display_properties(&mut self, mut properties: DisplayProperties) -> Result<()> {
+        properties.validate()?;
+        properties.clamp();
+        self.config.default_display_properties = properties;
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Validates, clamps and stores display properties for one notecard,
+    /// overriding the defaults
+    pub fn set_notecard_display_properties(&mut self, id: NotecardId, mut properties: DisplayProperties) -> Result<()> {
+        properties.validate()?;
+        properties.clamp();
+        self.config
+            .notecards
+            .entry(id)
+            .or_insert_with(|| Notecard::empty(id))
+            .display_properties = Some(properties);
+        self.mark_dirty();
+        self.notify(ConfigEvent::Updated(id));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::HotkeyChanges;
+
+    fn id(value: u8) -> NotecardId {
+        NotecardId::new(value).unwrap()
+    }
+
+    fn config_with(cards: &[(u8, &str)]) -> Config {
+        let mut config = Config::default();
+        for (value, content) in cards {
+            config.notecards.insert(id(*value), Notecard::new(id(*value), content.to_string()));
+        }
+        config
+    }
+
+    #[test]
+    fn only_cards_with_content_get_a_hotkey() {
+        let mut config = config_with(&[(1, "First"), (3, "   \n\t")]);
+        config.notecards.remove(&id(5));
+
+        assert!(config.has_hotkey(id(1)));
+        assert!(!config.has_hotkey(id(2)));
+        assert!(!config.has_hotkey(id(3)));
+        assert!(!config.has_hotkey(id(5)));
+    }
+
+    #[test]
+    fn hotkey_bindings_skip_empty_cards() {
+        let config = config_with(&[(1, "First"), (3, "   \n\t"), (4, "Fourth")]);
+
+        let bindings = config.hotkey_bindings();
+
+        let mut actions: Vec<_> = bindings.keys().copied().collect();
+        actions.sort_by_key(|action| match action {
+            HotkeyAction::ToggleNotecard(id) => id.value(),
+            HotkeyAction::HideAll => u8::MAX,
+        });
+        assert_eq!(
+            actions,
+            [HotkeyAction::ToggleNotecard(id(1)), HotkeyAction::ToggleNotecard(id(4)), HotkeyAction::HideAll]
+        );
+        assert_eq!(
+            bindings[&HotkeyAction::ToggleNotecard(id(4))],
+            HotkeyBinding::new(&[HotkeyModifier::Control, HotkeyModifier::Shift], HotkeyKey::for_notecard(id(4)))
+        );
+    }
+
+    #[test]
+    fn hotkey_bindings_leave_out_hide_all_when_it_is_off() {
+        let config = Config { hide_all_hotkey: false, ..config_with(&[(1, "First")]) };
+
+        assert!(!config.hotkey_bindings().contains_key(&HotkeyAction::HideAll));
+    }
+
+    #[test]
+    fn card_that_becomes_empty_is_unregistered() {
+        let before = config_with(&[(1, "First"), (2, "Second")]);
+        let mut after = before.clone();
+        after.notecards.insert(id(2), Notecard::empty(id(2)));
+
+        let changes = HotkeyChanges::between(&before.hotkey_bindings(), &after.hotkey_bindings());
+
+        assert_eq!(changes, HotkeyChanges { unregister: vec![HotkeyAction::ToggleNotecard(id(2))], register: Vec::new() });
+    }
+
+    #[test]
+    fn card_that_gets_content_is_registered() {
+        let before = config_with(&[(1, "First")]);
+        let after = config_with(&[(1, "First"), (6, "Sixth")]);
+
+        let changes = HotkeyChanges::between(&before.hotkey_bindings(), &after.hotkey_bindings());
+
+        assert!(changes.unregister.is_empty());
+        let sixth = HotkeyAction::ToggleNotecard(id(6));
+        assert_eq!(changes.register, [(sixth, after.hotkey_bindings()[&sixth].clone())]);
+    }
+
+    #[test]
+    fn paused_hotkeys_have_no_active_bindings() {
+        let config = Config { hotkeys_enabled: false, ..config_with(&[(1, "First")]) };
+
+        assert!(config.active_hotkey_bindings().is_empty());
+        assert!(!config.hotkey_bindings().is_empty());
+    }
+}
@@ -1,4 +1,9 @@
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use crate::notecard::NotecardId;
+
+/// Boxed error kept as the cause of a `PlatformSource` error
+pub type BoxedSource = Box<dyn std::error::Error + Send + Sync>;
 
 #[derive(Error, Debug)]
 pub enum NotecognitoError {
@@ -11,6 +16,9 @@ pub enum NotecognitoError {
     #[error("Configuration error: {0}")]
     Config(String),
 
+    #[error("Invalid value for {path}: {message}")]
+    Validation { path: String, message: String },
+
     #[error("IPC error: {0}")]
     Ipc(String),
 
@@ -20,6 +28,14 @@ pub enum NotecognitoError {
     #[error("Platform-specific error: {0}")]
     Platform(String),
 
+    /// A platform error that keeps the OS error it came from
+    #[error("Platform-specific error: {message}")]
+    PlatformSource {
+        message: String,
+        #[source]
+        source: BoxedSource,
+    },
+
     #[error("Connection lost")]
     ConnectionLost,
 
@@ -28,6 +44,275 @@ pub enum NotecognitoError {
 
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
+
+    #[error("Notecard {0} is not visible")]
+    NotVisible(NotecardId),
+}
+
+impl NotecognitoError {
+    /// Creates a validation error for the field at `path`
+    pub fn validation(path: impl Into<String>, message: impl Into<String>) -> Self {
+        NotecognitoError::Validation {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Creates a platform error that keeps `source`, and with it any OS error code
+    pub fn platform_with_source(message: impl Into<String>, source: impl Into<BoxedSource>) -> Self {
+        NotecognitoError::PlatformSource {
+            message: message.into(),
+            source: source.into(),
+        }
+    }
+
+    /// Gets the stable numeric code of the error
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            NotecognitoError::Io(_) => ErrorCode::Io,
+            NotecognitoError::Json(_) => ErrorCode::Json,
+            NotecognitoError::Config(_) => ErrorCode::Config,
+            NotecognitoError::Validation { .. } => ErrorCode::Validation,
+            NotecognitoError::Ipc(_) => ErrorCode::Ipc,
+            NotecognitoError::InvalidNotecardId(_) => ErrorCode::InvalidNotecardId,
+            NotecognitoError::Platform(_) | NotecognitoError::PlatformSource { .. } => ErrorCode::Platform,
+            NotecognitoError::ConnectionLost => ErrorCode::ConnectionLost,
+            NotecognitoError::InvalidMessage => ErrorCode::InvalidMessage,
+            NotecognitoError::PermissionDenied(_) => ErrorCode::PermissionDenied,
+            NotecognitoError::NotVisible(_) => ErrorCode::NotVisible,
+        }
+    }
+
+    /// Gets the broad kind of failure
+    pub fn category(&self) -> ErrorCategory {
+        self.code().category()
+    }
+
+    /// Whether trying the same operation again may succeed
+    ///
+    /// Connection failures and transient I/O are recoverable; bad input, missing
+    /// permissions and platform failures need something to change first.
+    pub fn is_recoverable(&self) -> bool {
+        use std::io::ErrorKind;
+
+        match self {
+            NotecognitoError::Io(e) => matches!(
+                e.kind(),
+                ErrorKind::Interrupted
+                    | ErrorKind::WouldBlock
+                    | ErrorKind::TimedOut
+                    | ErrorKind::ConnectionRefused
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::NotConnected
+                    | ErrorKind::BrokenPipe
+            ),
+            NotecognitoError::Ipc(_) | NotecognitoError::ConnectionLost => true,
+            _ => false,
+        }
+    }
+
+    /// Gets the OS error code behind the error, if it came from one
+    pub fn os_error_code(&self) -> Option<i32> {
+        let mut source: Option<&(dyn std::error::Error + 'static)> = Some(self);
+        while let Some(error) = source {
+            if let Some(code) = error.downcast_ref::<std::io::Error>().and_then(|e| e.raw_os_error()) {
+                return Some(code);
+            }
+            source = error.source();
+        }
+        None
+    }
+
+    /// Prefixes the field path of a validation error with its parent path
+    pub fn within(self, parent: &str) -> Self {
+        match self {
+            NotecognitoError::Validation { path, message } => NotecognitoError::Validation {
+                path: format!("{}.{}", parent, path),
+                message,
+            },
+            other => other,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, NotecognitoError>;
+
+/// Stable numeric code of a `NotecognitoError`
+///
+/// The values are shared by `FfiResult::error_code` and the `error_code` of IPC
+/// error responses, so they are part of both protocols and must never change.
+/// 11-14 are taken by codes only the FFI layer reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "u16", try_from = "u16")]
+#[repr(u16)]
+pub enum ErrorCode {
+    Io = 1,
+    Json = 2,
+    Config = 3,
+    Validation = 4,
+    Ipc = 5,
+    InvalidNotecardId = 6,
+    Platform = 7,
+    ConnectionLost = 8,
+    InvalidMessage = 9,
+    PermissionDenied = 10,
+    NotVisible = 15,
+}
+
+impl ErrorCode {
+    const ALL: [ErrorCode; 11] = [
+        ErrorCode::Io,
+        ErrorCode::Json,
+        ErrorCode::Config,
+        ErrorCode::Validation,
+        ErrorCode::Ipc,
+        ErrorCode::InvalidNotecardId,
+        ErrorCode::Platform,
+        ErrorCode::ConnectionLost,
+        ErrorCode::InvalidMessage,
+        ErrorCode::PermissionDenied,
+        ErrorCode::NotVisible,
+    ];
+
+    /// Gets the numeric value
+    pub fn value(self) -> u16 {
+        self as u16
+    }
+
+    /// Finds the code with a numeric value
+    pub fn from_value(value: u16) -> Option<Self> {
+        Self::ALL.iter().copied().find(|code| code.value() == value)
+    }
+
+    /// Gets the broad kind of failure the code stands for
+    pub fn category(self) -> ErrorCategory {
+        match self {
+            ErrorCode::Io => ErrorCategory::Io,
+            ErrorCode::Json
+            | ErrorCode::Config
+            | ErrorCode::Validation
+            | ErrorCode::InvalidNotecardId => ErrorCategory::Validation,
+            ErrorCode::Ipc | ErrorCode::ConnectionLost | ErrorCode::InvalidMessage => ErrorCategory::Ipc,
+            ErrorCode::Platform | ErrorCode::NotVisible => ErrorCategory::Platform,
+            ErrorCode::PermissionDenied => ErrorCategory::Permission,
+        }
+    }
+}
+
+impl From<ErrorCode> for u16 {
+    fn from(code: ErrorCode) -> Self {
+        code.value()
+    }
+}
+
+impl TryFrom<u16> for ErrorCode {
+    type Error = String;
+
+    fn try_from(value: u16) -> std::result::Result<Self, Self::Error> {
+        ErrorCode::from_value(value).ok_or_else(|| format!("Unknown error code {}", value))
+    }
+}
+
+/// Broad kind of failure, for callers deciding how to react
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ErrorCategory {
+    Io,
+    Validation,
+    Ipc,
+    Platform,
+    Permission,
 }
 
-pub type Result<T> = std::result::Result<T, NotecognitoError>;
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code_values_are_pinned() {
+        let pinned = [
+            (ErrorCode::Io, 1),
+            (ErrorCode::Json, 2),
+            (ErrorCode::Config, 3),
+            (ErrorCode::Validation, 4),
+            (ErrorCode::Ipc, 5),
+            (ErrorCode::InvalidNotecardId, 6),
+            (ErrorCode::Platform, 7),
+            (ErrorCode::ConnectionLost, 8),
+            (ErrorCode::InvalidMessage, 9),
+            (ErrorCode::PermissionDenied, 10),
+            (ErrorCode::NotVisible, 15),
+        ];
+
+        assert_eq!(pinned.len(), ErrorCode::ALL.len());
+        for (code, value) in pinned {
+            assert_eq!(code.value(), value, "{:?}", code);
+            assert_eq!(ErrorCode::from_value(value), Some(code));
+        }
+    }
+
+    #[test]
+    fn values_without_a_code_are_rejected() {
+        // 11-14 belong to the FFI layer
+        for value in [0, 11, 12, 13, 14, 16, u16::MAX] {
+            assert_eq!(ErrorCode::from_value(value), None, "{}", value);
+            assert!(ErrorCode::try_from(value).is_err());
+        }
+    }
+
+    #[test]
+    fn codes_serialize_as_numbers() {
+        assert_eq!(serde_json::to_string(&ErrorCode::NotVisible).unwrap(), "15");
+        assert_eq!(serde_json::from_str::<ErrorCode>("7").unwrap(), ErrorCode::Platform);
+        assert!(serde_json::from_str::<ErrorCode>("12").is_err());
+    }
+
+    #[test]
+    fn errors_map_to_their_codes() {
+        let id = NotecardId::new(1).unwrap();
+        let cases = [
+            (NotecognitoError::Io(std::io::Error::other("disk")), ErrorCode::Io),
+            (serde_json::from_str::<u8>("x").unwrap_err().into(), ErrorCode::Json),
+            (NotecognitoError::Config("bad".to_string()), ErrorCode::Config),
+            (NotecognitoError::validation("opacity", "too high"), ErrorCode::Validation),
+            (NotecognitoError::Ipc("gone".to_string()), ErrorCode::Ipc),
+            (NotecognitoError::InvalidNotecardId(0), ErrorCode::InvalidNotecardId),
+            (NotecognitoError::Platform("no".to_string()), ErrorCode::Platform),
+            (NotecognitoError::platform_with_source("no", std::io::Error::other("os")), ErrorCode::Platform),
+            (NotecognitoError::ConnectionLost, ErrorCode::ConnectionLost),
+            (NotecognitoError::InvalidMessage, ErrorCode::InvalidMessage),
+            (NotecognitoError::PermissionDenied("ax".to_string()), ErrorCode::PermissionDenied),
+            (NotecognitoError::NotVisible(id), ErrorCode::NotVisible),
+        ];
+
+        for (error, code) in cases {
+            assert_eq!(error.code(), code, "{}", error);
+            assert_eq!(error.category(), code.category());
+        }
+    }
+
+    #[test]
+    fn only_connection_trouble_is_recoverable() {
+        assert!(NotecognitoError::ConnectionLost.is_recoverable());
+        assert!(NotecognitoError::Io(std::io::ErrorKind::TimedOut.into()).is_recoverable());
+        assert!(!NotecognitoError::Io(std::io::ErrorKind::NotFound.into()).is_recoverable());
+        assert!(!NotecognitoError::Platform("no".to_string()).is_recoverable());
+    }
+
+    #[test]
+    fn os_error_code_is_found_through_the_source_chain() {
+        let error = NotecognitoError::platform_with_source("Failed to register", std::io::Error::from_raw_os_error(1409));
+
+        assert_eq!(error.os_error_code(), Some(1409));
+        assert_eq!(NotecognitoError::Platform("no".to_string()).os_error_code(), None);
+    }
+
+    #[test]
+    fn within_prefixes_validation_paths_only() {
+        let error = NotecognitoError::validation("opacity", "too high").within("notecards.3");
+        assert!(matches!(error, NotecognitoError::Validation { ref path, .. } if path == "notecards.3.opacity"));
+
+        let error = NotecognitoError::ConnectionLost.within("notecards.3");
+        assert!(matches!(error, NotecognitoError::ConnectionLost));
+    }
+}
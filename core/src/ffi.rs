@@ -1,12 +1,126 @@
+// Pointer arguments are validated for null before use; the C API can't
+// express the remaining safety contract through `unsafe fn`.
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::ffi::{CStr, CString};
-use std::os::raw::{c_char, c_int};
+use std::os::raw::{c_char, c_int, c_void};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, MutexGuard, Once, PoisonError};
 use std::ptr;
-use crate::{ConfigManager, NotecardId, Notecard};
+use crate::ipc::IpcClient;
+use crate::{
+    ApplyMode, Config, ConfigEvent, ConfigManager, ContentLimits, DisplayProperties, IpcMessage, IpcMessageType,
+    ListenerId, NotecardId, Notecard, NotecognitoError, PlatformCapabilities,
+};
+
+/// Version of the C ABI; bumped whenever a struct layout or signature changes
+pub const NOTECOGNITO_FFI_ABI_VERSION: c_int = 2;
+
+/// Stable numeric error codes reported in `FfiResult::error_code`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotecognitoErrorCode {
+    Success = 0,
+    Io = 1,
+    Json = 2,
+    Config = 3,
+    Validation = 4,
+    Ipc = 5,
+    InvalidNotecardId = 6,
+    Platform = 7,
+    ConnectionLost = 8,
+    InvalidMessage = 9,
+    PermissionDenied = 10,
+    NullPointer = 11,
+    InvalidUtf8 = 12,
+    InvalidUtf16 = 13,
+    /// A panic was caught at the C boundary
+    Internal = 14,
+    NotVisible = 15,
+}
+
+impl NotecognitoErrorCode {
+    const ALL: [NotecognitoErrorCode; 16] = [
+        NotecognitoErrorCode::Success,
+        NotecognitoErrorCode::Io,
+        NotecognitoErrorCode::Json,
+        NotecognitoErrorCode::Config,
+        NotecognitoErrorCode::Validation,
+        NotecognitoErrorCode::Ipc,
+        NotecognitoErrorCode::InvalidNotecardId,
+        NotecognitoErrorCode::Platform,
+        NotecognitoErrorCode::ConnectionLost,
+        NotecognitoErrorCode::InvalidMessage,
+        NotecognitoErrorCode::PermissionDenied,
+        NotecognitoErrorCode::NullPointer,
+        NotecognitoErrorCode::InvalidUtf8,
+        NotecognitoErrorCode::InvalidUtf16,
+        NotecognitoErrorCode::Internal,
+        NotecognitoErrorCode::NotVisible,
+    ];
+
+    fn from_c(code: c_int) -> Option<Self> {
+        Self::ALL.iter().copied().find(|known| *known as c_int == code)
+    }
+
+    /// NUL-terminated name of the code, for logging on the C side
+    fn name(self) -> &'static CStr {
+        match self {
+            NotecognitoErrorCode::Success => c"SUCCESS",
+            NotecognitoErrorCode::Io => c"IO",
+            NotecognitoErrorCode::Json => c"JSON",
+            NotecognitoErrorCode::Config => c"CONFIG",
+            NotecognitoErrorCode::Validation => c"VALIDATION",
+            NotecognitoErrorCode::Ipc => c"IPC",
+            NotecognitoErrorCode::InvalidNotecardId => c"INVALID_NOTECARD_ID",
+            NotecognitoErrorCode::Platform => c"PLATFORM",
+            NotecognitoErrorCode::ConnectionLost => c"CONNECTION_LOST",
+            NotecognitoErrorCode::InvalidMessage => c"INVALID_MESSAGE",
+            NotecognitoErrorCode::PermissionDenied => c"PERMISSION_DENIED",
+            NotecognitoErrorCode::NullPointer => c"NULL_POINTER",
+            NotecognitoErrorCode::InvalidUtf8 => c"INVALID_UTF8",
+            NotecognitoErrorCode::InvalidUtf16 => c"INVALID_UTF16",
+            NotecognitoErrorCode::Internal => c"INTERNAL",
+            NotecognitoErrorCode::NotVisible => c"NOT_VISIBLE",
+        }
+    }
+}
+
+impl From<&NotecognitoError> for NotecognitoErrorCode {
+    fn from(error: &NotecognitoError) -> Self {
+        // `ErrorCode` values are the FFI values, so the two can't disagree
+        NotecognitoErrorCode::from_c(error.code().value() as c_int).unwrap_or(NotecognitoErrorCode::Internal)
+    }
+}
+
+thread_local! {
+    /// Reason the last constructor or connect call on this thread returned null
+    static LAST_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(msg: impl Into<String>) {
+    let msg = msg.into();
+    tracing::debug!("FFI error: {}", msg);
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(msg));
+}
+
+/// Addresses of error messages handed out in `FfiResult`s and not yet freed
+#[cfg(debug_assertions)]
+static OWNED_ERROR_MESSAGES: std::sync::LazyLock<Mutex<std::collections::HashSet<usize>>> =
+    std::sync::LazyLock::new(Default::default);
 
 /// Result type for FFI functions
+///
+/// On failure `error_message` is owned by the caller and must be released
+/// with `notecognito_result_free` (or `notecognito_free_string`) exactly once.
 #[repr(C)]
 pub struct FfiResult {
     success: bool,
+    /// A `NotecognitoErrorCode` value; 0 on success
+    error_code: c_int,
     error_message: *mut c_char,
 }
 
@@ -14,147 +128,2146 @@ impl FfiResult {
     fn success() -> Self {
         FfiResult {
             success: true,
+            error_code: NotecognitoErrorCode::Success as c_int,
             error_message: ptr::null_mut(),
         }
     }
 
-    fn error(msg: &str) -> Self {
-        let error_message = CString::new(msg).unwrap_or_else(|_| CString::new("Unknown error").unwrap());
+    /// Builds a failed result; every error result is created here
+    fn error(code: NotecognitoErrorCode, msg: &str) -> Self {
+        let error_message = CString::new(msg)
+            .unwrap_or_else(|_| CString::new("Unknown error").unwrap())
+            .into_raw();
+
+        #[cfg(debug_assertions)]
+        OWNED_ERROR_MESSAGES
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(error_message as usize);
+
         FfiResult {
             success: false,
-            error_message: error_message.into_raw(),
+            error_code: code as c_int,
+            error_message,
+        }
+    }
+
+    fn from_error(error: &NotecognitoError) -> Self {
+        FfiResult::error(error.into(), &error.to_string())
+    }
+}
+
+/// Value an extern function returns when its body panics
+trait PanicFallback {
+    fn from_panic(message: &str) -> Self;
+}
+
+impl PanicFallback for FfiResult {
+    fn from_panic(message: &str) -> Self {
+        FfiResult::error(NotecognitoErrorCode::Internal, &format!("internal panic: {}", message))
+    }
+}
+
+impl<T> PanicFallback for *mut T {
+    fn from_panic(_: &str) -> Self {
+        ptr::null_mut()
+    }
+}
+
+impl<T> PanicFallback for *const T {
+    fn from_panic(_: &str) -> Self {
+        ptr::null()
+    }
+}
+
+impl PanicFallback for c_int {
+    fn from_panic(_: &str) -> Self {
+        -1
+    }
+}
+
+impl PanicFallback for () {
+    fn from_panic(_: &str) {}
+}
+
+/// Logs panics with a backtrace through tracing, then defers to the previous hook
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let backtrace = std::backtrace::Backtrace::force_capture();
+            tracing::error!("Panic in FFI call: {}\n{}", info, backtrace);
+            previous(info);
+        }));
+    });
+}
+
+/// Extracts the message from a panic payload
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+/// Runs an extern function body, turning a panic into the return type's
+/// fallback (an error result, null, or -1) instead of unwinding into C
+///
+/// Every `#[no_mangle]` function wraps its body in this.
+macro_rules! ffi_guard {
+    ($body:block) => {{
+        install_panic_hook();
+        match catch_unwind(AssertUnwindSafe(|| $body)) {
+            Ok(value) => value,
+            Err(payload) => PanicFallback::from_panic(&panic_message(&*payload)),
+        }
+    }};
+}
+
+/// Forgets a tracked error message, returning whether it was tracked
+#[cfg(debug_assertions)]
+fn release_error_message(ptr: *mut c_char) -> bool {
+    OWNED_ERROR_MESSAGES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .remove(&(ptr as usize))
+}
+
+/// Releases the error message owned by a result
+///
+/// Safe to call on successful and zeroed results. Each error result must be
+/// freed at most once; debug builds assert on double frees.
+#[no_mangle]
+pub extern "C" fn notecognito_result_free(result: FfiResult) {
+    ffi_guard!({
+        if result.error_message.is_null() {
+            return;
+        }
+
+        #[cfg(debug_assertions)]
+        {
+            let owned = release_error_message(result.error_message);
+            debug_assert!(owned, "FfiResult error message was not allocated here or was already freed");
+        }
+
+        unsafe {
+            let _ = CString::from_raw(result.error_message);
+        }
+    })
+}
+
+/// Gets the ABI version of this library
+#[no_mangle]
+pub extern "C" fn notecognito_ffi_abi_version() -> c_int {
+    ffi_guard!({
+        NOTECOGNITO_FFI_ABI_VERSION
+    })
+}
+
+/// Gets the library version (static string, must not be freed)
+#[no_mangle]
+pub extern "C" fn notecognito_version() -> *const c_char {
+    ffi_guard!({
+        concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+    })
+}
+
+/// Gets details of how the library was built as JSON (caller must free the returned string)
+///
+/// ```json
+/// { "version": "0.1.0", "abi_version": 2, "git_hash": "1a2b3c4",
+///   "target": "x86_64-apple-darwin", "debug": false, "features": ["ffi"],
+///   "capabilities": { "platform_name": "macos", "os_version": null, "blur": false, ... } }
+/// ```
+///
+/// `git_hash` is `null` when the library was built outside a git checkout.
+/// The library does no OS detection, so `capabilities` is the conservative
+/// descriptor; a running app reports detected ones through `GetPlatformInfo`.
+#[no_mangle]
+pub extern "C" fn notecognito_build_info_json() -> *mut c_char {
+    ffi_guard!({
+        let mut features = Vec::new();
+        if cfg!(feature = "ffi") {
+            features.push("ffi");
+        }
+
+        let info = serde_json::json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "abi_version": NOTECOGNITO_FFI_ABI_VERSION,
+            "git_hash": option_env!("NOTECOGNITO_GIT_HASH"),
+            "target": env!("NOTECOGNITO_TARGET"),
+            "debug": cfg!(debug_assertions),
+            "features": features,
+            "capabilities": PlatformCapabilities::conservative(),
+        });
+
+        match CString::new(info.to_string()) {
+            Ok(c_str) => c_str.into_raw(),
+            Err(_) => ptr::null_mut(),
+        }
+    })
+}
+
+/// Gets the static name of an error code (must not be freed)
+#[no_mangle]
+pub extern "C" fn notecognito_error_code_name(code: c_int) -> *const c_char {
+    ffi_guard!({
+        match NotecognitoErrorCode::from_c(code) {
+            Some(code) => code.name().as_ptr(),
+            None => c"UNKNOWN".as_ptr(),
         }
+    })
+}
+
+/// Converts a C notecard ID, rejecting out-of-range values before narrowing
+fn notecard_id_from_c(id: c_int) -> Option<NotecardId> {
+    u8::try_from(id).ok().and_then(|id| NotecardId::new(id).ok())
+}
+
+/// Length of a NUL-terminated UTF-16 string, excluding the terminator
+///
+/// # Safety
+/// `s` must be non-null and point to a NUL-terminated UTF-16 buffer.
+unsafe fn wide_len(s: *const u16) -> usize {
+    let mut len = 0;
+    while *s.add(len) != 0 {
+        len += 1;
+    }
+    len
+}
+
+/// Decodes a NUL-terminated UTF-16 string, rejecting lone surrogates
+///
+/// # Safety
+/// `s` must be non-null and point to a NUL-terminated UTF-16 buffer.
+unsafe fn string_from_wide(s: *const u16) -> Result<String, std::string::FromUtf16Error> {
+    let units = std::slice::from_raw_parts(s, wide_len(s));
+    String::from_utf16(units)
+}
+
+/// Encodes a string as a NUL-terminated UTF-16 buffer owned by the caller
+///
+/// Returns null if the string contains an interior NUL, matching the
+/// `CString` behaviour of the UTF-8 getters.
+fn string_into_wide(s: &str) -> *mut u16 {
+    if s.contains('\0') {
+        return ptr::null_mut();
     }
+
+    let units: Box<[u16]> = s.encode_utf16().chain(std::iter::once(0)).collect();
+    Box::into_raw(units) as *mut u16
 }
 
 /// Frees a string allocated by Rust
 #[no_mangle]
 pub extern "C" fn notecognito_free_string(s: *mut c_char) {
-    if s.is_null() {
-        return;
+    ffi_guard!({
+        if s.is_null() {
+            return;
+        }
+
+        // Error messages from FfiResult may also be released through here
+        #[cfg(debug_assertions)]
+        release_error_message(s);
+
+        unsafe {
+            let _ = CString::from_raw(s);
+        }
+    })
+}
+
+/// Opaque handle to a configuration manager shared between threads
+///
+/// Every handle created or cloned through the C API refers to the same
+/// manager until the last one is freed. Each handle owns at most one change
+/// callback, which is removed when the handle is freed.
+pub struct FfiHandle {
+    manager: Arc<Mutex<ConfigManager>>,
+    change_listener: Mutex<Option<ListenerId>>,
+}
+
+impl FfiHandle {
+    fn into_raw(manager: ConfigManager) -> *mut FfiHandle {
+        Self::share(Arc::new(Mutex::new(manager)))
+    }
+
+    fn share(manager: Arc<Mutex<ConfigManager>>) -> *mut FfiHandle {
+        Box::into_raw(Box::new(FfiHandle {
+            manager,
+            change_listener: Mutex::new(None),
+        }))
+    }
+
+    /// Locks the shared manager
+    ///
+    /// A panic while the lock was held can't leave the manager half-updated
+    /// (mutations are whole-value replacements), so poisoning is ignored
+    /// rather than turned into an abort at the C boundary.
+    fn lock(&self) -> MutexGuard<'_, ConfigManager> {
+        self.manager.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Swaps this handle's change listener, removing the previous one
+    fn replace_listener(&self, manager: &mut ConfigManager, listener_id: Option<ListenerId>) {
+        let mut current = self.change_listener.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(previous) = std::mem::replace(&mut *current, listener_id) {
+            manager.remove_listener(previous);
+        }
     }
-    unsafe {
-        let _ = CString::from_raw(s);
+}
+
+impl Drop for FfiHandle {
+    fn drop(&mut self) {
+        let mut manager = self.lock();
+        self.replace_listener(&mut manager, None);
     }
 }
 
 /// Creates a new configuration manager
 #[no_mangle]
-pub extern "C" fn notecognito_config_manager_new() -> *mut ConfigManager {
-    match ConfigManager::new() {
-        Ok(manager) => Box::into_raw(Box::new(manager)),
-        Err(_) => ptr::null_mut(),
-    }
+pub extern "C" fn notecognito_config_manager_new() -> *mut FfiHandle {
+    ffi_guard!({
+        match ConfigManager::new() {
+            Ok(manager) => FfiHandle::into_raw(manager),
+            Err(e) => {
+                set_last_error(e.to_string());
+                ptr::null_mut()
+            }
+        }
+    })
 }
 
-/// Frees a configuration manager
+/// Creates a configuration manager backed by the config file at `path`
+///
+/// Returns null if the path is not valid UTF-8, its directory doesn't exist,
+/// or an existing file can't be read; see
+/// `notecognito_config_manager_last_error` for the reason.
 #[no_mangle]
-pub extern "C" fn notecognito_config_manager_free(manager: *mut ConfigManager) {
-    if manager.is_null() {
-        return;
-    }
-    unsafe {
-        let _ = Box::from_raw(manager);
-    }
+pub extern "C" fn notecognito_config_manager_new_with_path(path: *const c_char) -> *mut FfiHandle {
+    ffi_guard!({
+        if path.is_null() {
+            set_last_error("Invalid parameters");
+            return ptr::null_mut();
+        }
+
+        let path_str = unsafe {
+            match CStr::from_ptr(path).to_str() {
+                Ok(s) => s,
+                Err(_) => {
+                    set_last_error("Invalid UTF-8 in path");
+                    return ptr::null_mut();
+                }
+            }
+        };
+
+        let config_path = std::path::Path::new(path_str);
+        if let Some(dir) = config_path.parent() {
+            if !dir.as_os_str().is_empty() && !dir.is_dir() {
+                set_last_error(format!("Config directory does not exist: {}", dir.display()));
+                return ptr::null_mut();
+            }
+        }
+
+        match ConfigManager::with_path(config_path) {
+            Ok(manager) => FfiHandle::into_raw(manager),
+            Err(e) => {
+                set_last_error(e.to_string());
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Gets the reason the last manager constructor on this thread returned null
+///
+/// Returns null if no error has occurred; the caller must free the returned string.
+#[no_mangle]
+pub extern "C" fn notecognito_config_manager_last_error() -> *mut c_char {
+    ffi_guard!({
+        notecognito_last_error()
+    })
+}
+
+/// Gets the reason the last constructor or connect call on this thread returned null
+///
+/// Returns null if no error has occurred; the caller must free the returned string.
+#[no_mangle]
+pub extern "C" fn notecognito_last_error() -> *mut c_char {
+    ffi_guard!({
+        LAST_ERROR.with(|last| match last.borrow().as_deref() {
+            Some(msg) => match CString::new(msg) {
+                Ok(c_str) => c_str.into_raw(),
+                Err(_) => ptr::null_mut(),
+            },
+            None => ptr::null_mut(),
+        })
+    })
+}
+
+/// Creates another handle to the same configuration manager
+///
+/// Each handle must be freed with `notecognito_config_manager_free`; the
+/// manager itself is dropped with the last handle.
+#[no_mangle]
+pub extern "C" fn notecognito_config_manager_clone_handle(manager: *mut FfiHandle) -> *mut FfiHandle {
+    ffi_guard!({
+        if manager.is_null() {
+            return ptr::null_mut();
+        }
+
+        let handle = unsafe { &*manager };
+        FfiHandle::share(Arc::clone(&handle.manager))
+    })
+}
+
+/// Frees a configuration manager handle
+#[no_mangle]
+pub extern "C" fn notecognito_config_manager_free(manager: *mut FfiHandle) {
+    ffi_guard!({
+        if manager.is_null() {
+            return;
+        }
+        unsafe {
+            let _ = Box::from_raw(manager);
+        }
+    })
 }
 
 /// Updates a notecard
 #[no_mangle]
 pub extern "C" fn notecognito_update_notecard(
-    manager: *mut ConfigManager,
+    manager: *mut FfiHandle,
     id: c_int,
     content: *const c_char,
 ) -> FfiResult {
-    if manager.is_null() || content.is_null() {
-        return FfiResult::error("Invalid parameters");
-    }
+    ffi_guard!({
+        if manager.is_null() || content.is_null() {
+            return FfiResult::error(NotecognitoErrorCode::NullPointer, "Invalid parameters");
+        }
 
-    let manager = unsafe { &mut *manager };
+        let mut manager = unsafe { &*manager }.lock();
 
-    let content_str = unsafe {
-        match CStr::from_ptr(content).to_str() {
-            Ok(s) => s,
-            Err(_) => return FfiResult::error("Invalid UTF-8 in content"),
+        let content_str = unsafe {
+            match CStr::from_ptr(content).to_str() {
+                Ok(s) => s,
+                Err(_) => return FfiResult::error(NotecognitoErrorCode::InvalidUtf8, "Invalid UTF-8 in content"),
+            }
+        };
+
+        let notecard_id = match notecard_id_from_c(id) {
+            Some(id) => id,
+            None => return FfiResult::error(NotecognitoErrorCode::InvalidNotecardId, "Invalid notecard ID (must be 1-9)"),
+        };
+
+        let notecard = Notecard::new(notecard_id, content_str.to_string());
+
+        match manager.update_notecard(notecard) {
+            Ok(_) => match manager.save() {
+                Ok(_) => FfiResult::success(),
+                Err(e) => FfiResult::from_error(&e),
+            },
+            Err(e) => FfiResult::from_error(&e),
         }
-    };
+    })
+}
 
-    let notecard_id = match NotecardId::new(id as u8) {
-        Ok(id) => id,
-        Err(_) => return FfiResult::error("Invalid notecard ID (must be 1-9)"),
-    };
+/// Clears a notecard's content
+#[no_mangle]
+pub extern "C" fn notecognito_clear_notecard(
+    manager: *mut FfiHandle,
+    id: c_int,
+) -> FfiResult {
+    ffi_guard!({
+        if manager.is_null() {
+            return FfiResult::error(NotecognitoErrorCode::NullPointer, "Invalid manager");
+        }
 
-    let notecard = Notecard::new(notecard_id, content_str.to_string());
+        let mut manager = unsafe { &*manager }.lock();
 
-    match manager.update_notecard(notecard) {
-        Ok(_) => match manager.save() {
-            Ok(_) => FfiResult::success(),
-            Err(e) => FfiResult::error(&e.to_string()),
-        },
-        Err(e) => FfiResult::error(&e.to_string()),
-    }
+        let notecard_id = match notecard_id_from_c(id) {
+            Some(id) => id,
+            None => return FfiResult::error(NotecognitoErrorCode::InvalidNotecardId, "Invalid notecard ID (must be 1-9)"),
+        };
+
+        match manager.clear_notecard(notecard_id) {
+            Ok(_) => match manager.save() {
+                Ok(_) => FfiResult::success(),
+                Err(e) => FfiResult::from_error(&e),
+            },
+            Err(e) => FfiResult::from_error(&e),
+        }
+    })
 }
 
 /// Gets notecard content
 #[no_mangle]
 pub extern "C" fn notecognito_get_notecard_content(
-    manager: *mut ConfigManager,
+    manager: *mut FfiHandle,
     id: c_int,
 ) -> *mut c_char {
-    if manager.is_null() {
-        return ptr::null_mut();
-    }
+    ffi_guard!({
+        if manager.is_null() {
+            return ptr::null_mut();
+        }
 
-    let manager = unsafe { &*manager };
+        let manager = unsafe { &*manager }.lock();
 
-    let notecard_id = match NotecardId::new(id as u8) {
-        Ok(id) => id,
-        Err(_) => return ptr::null_mut(),
-    };
+        let notecard_id = match notecard_id_from_c(id) {
+            Some(id) => id,
+            None => return ptr::null_mut(),
+        };
 
-    match manager.get_notecard(notecard_id) {
-        Some(notecard) => {
-            match CString::new(notecard.content.clone()) {
-                Ok(c_str) => c_str.into_raw(),
+        match manager.get_notecard(notecard_id) {
+            Some(notecard) => {
+                match CString::new(notecard.content.clone()) {
+                    Ok(c_str) => c_str.into_raw(),
+                    Err(_) => ptr::null_mut(),
+                }
+            }
+            None => ptr::null_mut(),
+        }
+    })
+}
+
+/// Gets a whole notecard as JSON (caller must free the returned string)
+///
+/// Returns null if the manager is null, the ID is out of range, or the slot
+/// is missing.
+#[no_mangle]
+pub extern "C" fn notecognito_get_notecard_json(
+    manager: *mut FfiHandle,
+    id: c_int,
+) -> *mut c_char {
+    ffi_guard!({
+        if manager.is_null() {
+            return ptr::null_mut();
+        }
+
+        let manager = unsafe { &*manager }.lock();
+
+        let notecard_id = match notecard_id_from_c(id) {
+            Some(id) => id,
+            None => return ptr::null_mut(),
+        };
+
+        match manager.get_notecard(notecard_id) {
+            Some(notecard) => match serde_json::to_string(notecard) {
+                Ok(json) => match CString::new(json) {
+                    Ok(c_str) => c_str.into_raw(),
+                    Err(_) => ptr::null_mut(),
+                },
                 Err(_) => ptr::null_mut(),
+            },
+            None => ptr::null_mut(),
+        }
+    })
+}
+
+/// Updates a notecard from JSON in the shape returned by `notecognito_get_notecard_json`
+///
+/// The embedded `id` selects the slot and must be 1-9. Unknown fields are
+/// ignored so older hosts keep working; `modified_at` is always set by the
+/// manager.
+#[no_mangle]
+pub extern "C" fn notecognito_update_notecard_json(
+    manager: *mut FfiHandle,
+    json: *const c_char,
+) -> FfiResult {
+    ffi_guard!({
+        if manager.is_null() || json.is_null() {
+            return FfiResult::error(NotecognitoErrorCode::NullPointer, "Invalid parameters");
+        }
+
+        let mut manager = unsafe { &*manager }.lock();
+
+        let json_str = unsafe {
+            match CStr::from_ptr(json).to_str() {
+                Ok(s) => s,
+                Err(_) => return FfiResult::error(NotecognitoErrorCode::InvalidUtf8, "Invalid UTF-8 in JSON"),
             }
+        };
+
+        let notecard: Notecard = match serde_json::from_str(json_str) {
+            Ok(notecard) => notecard,
+            Err(e) => return FfiResult::error(NotecognitoErrorCode::Json, &format!("Invalid notecard JSON: {}", e)),
+        };
+
+        match manager.update_notecard(notecard) {
+            Ok(_) => match manager.save() {
+                Ok(_) => FfiResult::success(),
+                Err(e) => FfiResult::from_error(&e),
+            },
+            Err(e) => FfiResult::from_error(&e),
         }
-        None => ptr::null_mut(),
-    }
+    })
 }
 
-/// Gets the configuration as JSON
+/// Frees a UTF-16 string returned by one of the `_w` functions
 #[no_mangle]
-pub extern "C" fn notecognito_get_config_json(manager: *mut ConfigManager) -> *mut c_char {
-    if manager.is_null() {
-        return ptr::null_mut();
-    }
+pub extern "C" fn notecognito_free_wstring(s: *mut u16) {
+    ffi_guard!({
+        if s.is_null() {
+            return;
+        }
 
-    let manager = unsafe { &*manager };
+        unsafe {
+            let len = wide_len(s) + 1;
+            let _ = Box::from_raw(ptr::slice_from_raw_parts_mut(s, len));
+        }
+    })
+}
 
-    match serde_json::to_string(manager.config()) {
-        Ok(json) => match CString::new(json) {
-            Ok(c_str) => c_str.into_raw(),
-            Err(_) => ptr::null_mut(),
-        },
-        Err(_) => ptr::null_mut(),
-    }
+/// Updates a notecard from NUL-terminated UTF-16 content
+#[no_mangle]
+pub extern "C" fn notecognito_update_notecard_w(
+    manager: *mut FfiHandle,
+    id: c_int,
+    content: *const u16,
+) -> FfiResult {
+    ffi_guard!({
+        if manager.is_null() || content.is_null() {
+            return FfiResult::error(NotecognitoErrorCode::NullPointer, "Invalid parameters");
+        }
+
+        let mut manager = unsafe { &*manager }.lock();
+
+        let content_str = match unsafe { string_from_wide(content) } {
+            Ok(s) => s,
+            Err(_) => return FfiResult::error(NotecognitoErrorCode::InvalidUtf16, "Invalid UTF-16 in content (lone surrogate)"),
+        };
+
+        let notecard_id = match notecard_id_from_c(id) {
+            Some(id) => id,
+            None => return FfiResult::error(NotecognitoErrorCode::InvalidNotecardId, "Invalid notecard ID (must be 1-9)"),
+        };
+
+        let notecard = Notecard::new(notecard_id, content_str);
+
+        match manager.update_notecard(notecard) {
+            Ok(_) => match manager.save() {
+                Ok(_) => FfiResult::success(),
+                Err(e) => FfiResult::from_error(&e),
+            },
+            Err(e) => FfiResult::from_error(&e),
+        }
+    })
 }
 
-/// Sets the launch on startup flag
+/// Gets notecard content as NUL-terminated UTF-16
+///
+/// The caller must free the returned string with `notecognito_free_wstring`.
 #[no_mangle]
-pub extern "C" fn notecognito_set_launch_on_startup(
-    manager: *mut ConfigManager,
-    enabled: bool,
+pub extern "C" fn notecognito_get_notecard_content_w(
+    manager: *mut FfiHandle,
+    id: c_int,
+) -> *mut u16 {
+    ffi_guard!({
+        if manager.is_null() {
+            return ptr::null_mut();
+        }
+
+        let manager = unsafe { &*manager }.lock();
+
+        let notecard_id = match notecard_id_from_c(id) {
+            Some(id) => id,
+            None => return ptr::null_mut(),
+        };
+
+        match manager.get_notecard(notecard_id) {
+            Some(notecard) => string_into_wide(&notecard.content),
+            None => ptr::null_mut(),
+        }
+    })
+}
+
+/// Checks content against the limits and writes the issues found to `out_issues_json`
+fn validate_content_with(
+    limits: &ContentLimits,
+    content: *const c_char,
+    out_issues_json: *mut *mut c_char,
 ) -> FfiResult {
-    if manager.is_null() {
-        return FfiResult::error("Invalid manager");
+    if content.is_null() || out_issues_json.is_null() {
+        return FfiResult::error(NotecognitoErrorCode::NullPointer, "Invalid parameters");
     }
 
-    let manager = unsafe { &mut *manager };
-    manager.config_mut().launch_on_startup = enabled;
+    let content_str = unsafe {
+        match CStr::from_ptr(content).to_str() {
+            Ok(s) => s,
+            Err(_) => return FfiResult::error(NotecognitoErrorCode::InvalidUtf8, "Invalid UTF-8 in content"),
+        }
+    };
+
+    let json = match serde_json::to_string(&limits.check(content_str)) {
+        Ok(json) => json,
+        Err(e) => return FfiResult::from_error(&e.into()),
+    };
 
-    match manager.save() {
-        Ok(_) => FfiResult::success(),
-        Err(e) => FfiResult::error(&e.to_string()),
+    match CString::new(json) {
+        Ok(c_str) => {
+            unsafe { *out_issues_json = c_str.into_raw() };
+            FfiResult::success()
+        }
+        Err(_) => FfiResult::error(NotecognitoErrorCode::Json, "Issues JSON contains a NUL byte"),
     }
-}
\ No newline at end of file
+}
+
+/// Validates notecard content against the default limits without saving it
+///
+/// On success `*out_issues_json` receives a JSON array of
+/// `{ "path": ..., "message": ... }` objects, empty if the content is valid;
+/// the caller must free it. Never touches the filesystem.
+#[no_mangle]
+pub extern "C" fn notecognito_validate_content(
+    content: *const c_char,
+    out_issues_json: *mut *mut c_char,
+) -> FfiResult {
+    ffi_guard!({
+        validate_content_with(&ContentLimits::default(), content, out_issues_json)
+    })
+}
+
+/// Validates notecard content against the manager's configured limits without saving it
+///
+/// See `notecognito_validate_content` for the output format.
+#[no_mangle]
+pub extern "C" fn notecognito_validate_content_for(
+    manager: *mut FfiHandle,
+    content: *const c_char,
+    out_issues_json: *mut *mut c_char,
+) -> FfiResult {
+    ffi_guard!({
+        if manager.is_null() {
+            return FfiResult::error(NotecognitoErrorCode::NullPointer, "Invalid manager");
+        }
+
+        let limits = unsafe { &*manager }.lock().config().content_limits;
+        validate_content_with(&limits, content, out_issues_json)
+    })
+}
+
+/// Gets the configuration as JSON
+#[no_mangle]
+pub extern "C" fn notecognito_get_config_json(manager: *mut FfiHandle) -> *mut c_char {
+    ffi_guard!({
+        if manager.is_null() {
+            return ptr::null_mut();
+        }
+
+        let manager = unsafe { &*manager }.lock();
+
+        match serde_json::to_string(manager.config()) {
+            Ok(json) => match CString::new(json) {
+                Ok(c_str) => c_str.into_raw(),
+                Err(_) => ptr::null_mut(),
+            },
+            Err(_) => ptr::null_mut(),
+        }
+    })
+}
+
+/// Lists every notecard slot with metadata (caller must free the returned string)
+///
+/// The result is a JSON array ordered by ID:
+///
+/// ```json
+/// [{ "id": 1, "title": "First line", "summary": "First line rest of content",
+///    "is_empty": false, "modified_at": "2024-01-01T12:00:00Z" }]
+/// ```
+///
+/// `modified_at` is `null` for cards that were never edited through the
+/// config manager. Returns null if the manager is null or serialization fails.
+#[no_mangle]
+pub extern "C" fn notecognito_list_notecards(manager: *mut FfiHandle) -> *mut c_char {
+    ffi_guard!({
+        if manager.is_null() {
+            return ptr::null_mut();
+        }
+
+        let manager = unsafe { &*manager }.lock();
+
+        match serde_json::to_string(&manager.notecard_summaries()) {
+            Ok(json) => match CString::new(json) {
+                Ok(c_str) => c_str.into_raw(),
+                Err(_) => ptr::null_mut(),
+            },
+            Err(_) => ptr::null_mut(),
+        }
+    })
+}
+
+/// Validates and applies a full configuration from JSON, then saves it
+///
+/// With `replace` set the configuration is replaced wholesale; otherwise
+/// settings are replaced and only non-empty notecards overwrite existing
+/// ones. On any failure the current configuration is left unchanged and the
+/// error message names the offending field.
+#[no_mangle]
+pub extern "C" fn notecognito_apply_config_json(
+    manager: *mut FfiHandle,
+    json: *const c_char,
+    replace: bool,
+) -> FfiResult {
+    ffi_guard!({
+        if manager.is_null() || json.is_null() {
+            return FfiResult::error(NotecognitoErrorCode::NullPointer, "Invalid parameters");
+        }
+
+        let mut manager = unsafe { &*manager }.lock();
+
+        let json_str = unsafe {
+            match CStr::from_ptr(json).to_str() {
+                Ok(s) => s,
+                Err(_) => return FfiResult::error(NotecognitoErrorCode::InvalidUtf8, "Invalid UTF-8 in JSON"),
+            }
+        };
+
+        let config: Config = match serde_json::from_str(json_str) {
+            Ok(config) => config,
+            Err(e) => return FfiResult::error(NotecognitoErrorCode::Json, &format!("Invalid configuration JSON: {}", e)),
+        };
+
+        let mode = if replace {
+            ApplyMode::Replace
+        } else {
+            ApplyMode::MergeNonEmpty
+        };
+
+        match manager.apply_config(config, mode) {
+            Ok(_) => FfiResult::success(),
+            Err(e) => FfiResult::from_error(&e),
+        }
+    })
+}
+
+/// Replaces the in-memory configuration with the config file on disk
+///
+/// If the file is unreadable or invalid the current configuration is kept
+/// and the error message gives the reason. Unsaved changes are discarded;
+/// see `notecognito_config_is_dirty`.
+#[no_mangle]
+pub extern "C" fn notecognito_reload_config(manager: *mut FfiHandle) -> FfiResult {
+    ffi_guard!({
+        if manager.is_null() {
+            return FfiResult::error(NotecognitoErrorCode::NullPointer, "Invalid manager");
+        }
+
+        let mut manager = unsafe { &*manager }.lock();
+
+        match manager.reload() {
+            Ok(_) => FfiResult::success(),
+            Err(e) => FfiResult::from_error(&e),
+        }
+    })
+}
+
+/// Returns 1 if there are unsaved changes, 0 if not, or -1 if the manager is null
+#[no_mangle]
+pub extern "C" fn notecognito_config_is_dirty(manager: *mut FfiHandle) -> c_int {
+    ffi_guard!({
+        if manager.is_null() {
+            return -1;
+        }
+
+        let manager = unsafe { &*manager }.lock();
+        manager.is_dirty() as c_int
+    })
+}
+
+/// Sets the launch on startup flag
+#[no_mangle]
+pub extern "C" fn notecognito_set_launch_on_startup(
+    manager: *mut FfiHandle,
+    enabled: bool,
+) -> FfiResult {
+    ffi_guard!({
+        if manager.is_null() {
+            return FfiResult::error(NotecognitoErrorCode::NullPointer, "Invalid manager");
+        }
+
+        let mut manager = unsafe { &*manager }.lock();
+        manager.config_mut().launch_on_startup = enabled;
+
+        match manager.save() {
+            Ok(_) => FfiResult::success(),
+            Err(e) => FfiResult::from_error(&e),
+        }
+    })
+}
+
+/// Gets the default display properties as JSON (caller must free the returned string)
+#[no_mangle]
+pub extern "C" fn notecognito_get_display_properties_json(
+    manager: *mut FfiHandle,
+) -> *mut c_char {
+    ffi_guard!({
+        if manager.is_null() {
+            return ptr::null_mut();
+        }
+
+        let manager = unsafe { &*manager }.lock();
+
+        match serde_json::to_string(&manager.config().default_display_properties) {
+            Ok(json) => match CString::new(json) {
+                Ok(c_str) => c_str.into_raw(),
+                Err(_) => ptr::null_mut(),
+            },
+            Err(_) => ptr::null_mut(),
+        }
+    })
+}
+
+/// Validates, clamps and saves new default display properties from JSON
+#[no_mangle]
+pub extern "C" fn notecognito_set_display_properties_json(
+    manager: *mut FfiHandle,
+    json: *const c_char,
+) -> FfiResult {
+    ffi_guard!({
+        if manager.is_null() || json.is_null() {
+            return FfiResult::error(NotecognitoErrorCode::NullPointer, "Invalid parameters");
+        }
+
+        let mut manager = unsafe { &*manager }.lock();
+
+        let json_str = unsafe {
+            match CStr::from_ptr(json).to_str() {
+                Ok(s) => s,
+                Err(_) => return FfiResult::error(NotecognitoErrorCode::InvalidUtf8, "Invalid UTF-8 in JSON"),
+            }
+        };
+
+        let properties: DisplayProperties = match serde_json::from_str(json_str) {
+            Ok(properties) => properties,
+            Err(e) => return FfiResult::error(NotecognitoErrorCode::Json, &format!("Invalid display properties JSON: {}", e)),
+        };
+
+        match manager.set_display_properties(properties) {
+            Ok(_) => match manager.save() {
+                Ok(_) => FfiResult::success(),
+                Err(e) => FfiResult::from_error(&e),
+            },
+            Err(e) => FfiResult::from_error(&e),
+        }
+    })
+}
+
+/// Kind of change reported to a change callback
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotecognitoChangeKind {
+    Updated = 1,
+    Cleared = 2,
+    Saved = 3,
+    Reloaded = 4,
+}
+
+impl NotecognitoChangeKind {
+    /// Splits an event into its kind and notecard ID (0 when not about a notecard)
+    fn from_event(event: &ConfigEvent) -> (Self, c_int) {
+        match event {
+            ConfigEvent::Updated(id) => (NotecognitoChangeKind::Updated, id.value() as c_int),
+            ConfigEvent::Cleared(id) => (NotecognitoChangeKind::Cleared, id.value() as c_int),
+            ConfigEvent::Saved => (NotecognitoChangeKind::Saved, 0),
+            ConfigEvent::Reloaded => (NotecognitoChangeKind::Reloaded, 0),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            NotecognitoChangeKind::Updated => "Updated",
+            NotecognitoChangeKind::Cleared => "Cleared",
+            NotecognitoChangeKind::Saved => "Saved",
+            NotecognitoChangeKind::Reloaded => "Reloaded",
+        }
+    }
+}
+
+/// Change callback; `notecard_id` is 0 for `Saved` and `Reloaded` events
+pub type NotecognitoChangeCallback =
+    extern "C" fn(event_kind: c_int, notecard_id: c_int, user_data: *mut c_void);
+
+/// Opaque caller data passed back to the callback untouched
+struct UserData(*mut c_void);
+
+// The pointer is never dereferenced on the Rust side
+unsafe impl Send for UserData {}
+unsafe impl Sync for UserData {}
+
+/// Registers a callback for notecard updates, clears, and saves
+///
+/// The callback runs synchronously on the thread that made the change,
+/// before the mutating call returns, while the manager is locked; it must
+/// not call back into any handle for the same manager. Panics inside it are
+/// caught and logged. Registering again on the same handle replaces the
+/// previous callback.
+#[no_mangle]
+pub extern "C" fn notecognito_set_change_callback(
+    manager: *mut FfiHandle,
+    callback: Option<NotecognitoChangeCallback>,
+    user_data: *mut c_void,
+) -> FfiResult {
+    ffi_guard!({
+        let callback = match callback {
+            Some(callback) if !manager.is_null() => callback,
+            _ => return FfiResult::error(NotecognitoErrorCode::NullPointer, "Invalid parameters"),
+        };
+
+        let handle = unsafe { &*manager };
+        let mut manager = handle.lock();
+        let user_data = UserData(user_data);
+
+        let listener_id = manager.add_listener(move |event| {
+            let (kind, notecard_id) = NotecognitoChangeKind::from_event(event);
+
+            let user_data = &user_data;
+            if catch_unwind(AssertUnwindSafe(|| callback(kind as c_int, notecard_id, user_data.0))).is_err() {
+                tracing::error!("Change callback panicked");
+            }
+        });
+
+        handle.replace_listener(&mut manager, Some(listener_id));
+
+        FfiResult::success()
+    })
+}
+
+/// Removes the change callback, if any
+#[no_mangle]
+pub extern "C" fn notecognito_clear_change_callback(manager: *mut FfiHandle) -> FfiResult {
+    ffi_guard!({
+        if manager.is_null() {
+            return FfiResult::error(NotecognitoErrorCode::NullPointer, "Invalid manager");
+        }
+
+        let handle = unsafe { &*manager };
+        let mut manager = handle.lock();
+        handle.replace_listener(&mut manager, None);
+
+        FfiResult::success()
+    })
+}
+
+/// Maximum number of events a queue holds before dropping the oldest
+const EVENT_QUEUE_CAPACITY: usize = 256;
+
+/// Buffer of change events for hosts that poll instead of taking callbacks
+///
+/// Keeps the manager alive until the queue is freed, even if every handle
+/// to it is freed first.
+pub struct NotecognitoEventQueue {
+    manager: Arc<Mutex<ConfigManager>>,
+    listener_id: ListenerId,
+    events: Arc<Mutex<VecDeque<ConfigEvent>>>,
+}
+
+impl Drop for NotecognitoEventQueue {
+    fn drop(&mut self) {
+        self.manager
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove_listener(self.listener_id);
+    }
+}
+
+/// Creates a queue that records every change made through the manager from now on
+///
+/// The queue holds the most recent 256 events; older ones are dropped when
+/// it is full. Returns null if the manager is null.
+#[no_mangle]
+pub extern "C" fn notecognito_event_queue_new(manager: *mut FfiHandle) -> *mut NotecognitoEventQueue {
+    ffi_guard!({
+        if manager.is_null() {
+            return ptr::null_mut();
+        }
+
+        let handle = unsafe { &*manager };
+        let events = Arc::new(Mutex::new(VecDeque::with_capacity(EVENT_QUEUE_CAPACITY)));
+
+        let sink = Arc::clone(&events);
+        let listener_id = handle.lock().add_listener(move |event| {
+            let mut events = sink.lock().unwrap_or_else(PoisonError::into_inner);
+            if events.len() == EVENT_QUEUE_CAPACITY {
+                events.pop_front();
+            }
+            events.push_back(*event);
+        });
+
+        Box::into_raw(Box::new(NotecognitoEventQueue {
+            manager: Arc::clone(&handle.manager),
+            listener_id,
+            events,
+        }))
+    })
+}
+
+/// Takes the oldest queued event
+///
+/// Returns 1 and stores the event in `*out_json` (caller must free it) when
+/// an event was dequeued, 0 when the queue is empty, or -1 on invalid
+/// parameters. Events look like `{ "kind": "Updated", "notecard_id": 3 }`,
+/// with the same kinds as the callback API and `notecard_id` 0 for `Saved`
+/// and `Reloaded`.
+#[no_mangle]
+pub extern "C" fn notecognito_event_queue_poll(
+    queue: *mut NotecognitoEventQueue,
+    out_json: *mut *mut c_char,
+) -> c_int {
+    ffi_guard!({
+        if queue.is_null() || out_json.is_null() {
+            return -1;
+        }
+
+        let queue = unsafe { &*queue };
+
+        let event = match queue.events.lock().unwrap_or_else(PoisonError::into_inner).pop_front() {
+            Some(event) => event,
+            None => return 0,
+        };
+
+        let (kind, notecard_id) = NotecognitoChangeKind::from_event(&event);
+        let json = serde_json::json!({
+            "kind": kind.name(),
+            "notecard_id": notecard_id,
+        });
+
+        match CString::new(json.to_string()) {
+            Ok(c_str) => {
+                unsafe { *out_json = c_str.into_raw() };
+                1
+            }
+            Err(_) => -1,
+        }
+    })
+}
+
+/// Stops recording events and frees the queue
+#[no_mangle]
+pub extern "C" fn notecognito_event_queue_free(queue: *mut NotecognitoEventQueue) {
+    ffi_guard!({
+        if queue.is_null() {
+            return;
+        }
+        unsafe {
+            let _ = Box::from_raw(queue);
+        }
+    })
+}
+
+/// Connection to a running Notecognito IPC server
+///
+/// Owns a current-thread runtime, so its functions may be called from any
+/// thread that isn't already running a Tokio runtime.
+pub struct NotecognitoIpcClient {
+    runtime: tokio::runtime::Runtime,
+    client: Mutex<IpcClient>,
+}
+
+impl NotecognitoIpcClient {
+    /// Connects to the server at `addr` on a runtime of the client's own,
+    /// giving the reason when it can't
+    fn connect(addr: impl tokio::net::ToSocketAddrs, timeout: std::time::Duration) -> Result<Self, String> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| format!("Failed to create runtime: {}", e))?;
+
+        // The timer needs the runtime, so it's started inside it
+        let connect = async { tokio::time::timeout(timeout, IpcClient::connect_to(addr)).await };
+        match runtime.block_on(connect) {
+            Ok(Ok(client)) => Ok(NotecognitoIpcClient {
+                runtime,
+                client: Mutex::new(client),
+            }),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => Err(NotecognitoError::ConnectionLost.to_string()),
+        }
+    }
+
+    /// Sends a request and waits for the server's reply
+    ///
+    /// A request that panicked may have left its reply unread, but the
+    /// connection stays usable rather than every later call aborting.
+    fn request(&self, message_type: IpcMessageType) -> crate::Result<IpcMessageType> {
+        let mut client = self.client.lock().unwrap_or_else(PoisonError::into_inner);
+        let response = self.runtime.block_on(client.send_message(IpcMessage::new(message_type)))?;
+        Ok(response.message_type)
+    }
+
+    /// Sends a request that is answered with `Success` or `Error`
+    fn command(&self, message_type: IpcMessageType) -> FfiResult {
+        match self.request(message_type) {
+            Ok(IpcMessageType::Success { .. }) => FfiResult::success(),
+            Ok(IpcMessageType::Error { message, .. }) => {
+                FfiResult::error(NotecognitoErrorCode::Ipc, &message)
+            }
+            Ok(_) => FfiResult::error(NotecognitoErrorCode::InvalidMessage, "Unexpected response type"),
+            Err(e) => FfiResult::from_error(&e),
+        }
+    }
+}
+
+/// Connects to the IPC server on localhost
+///
+/// Returns null if the server can't be reached within `timeout_ms`; see
+/// `notecognito_last_error` for the reason.
+#[no_mangle]
+pub extern "C" fn notecognito_ipc_connect(timeout_ms: u32) -> *mut NotecognitoIpcClient {
+    ffi_guard!({
+        let timeout = std::time::Duration::from_millis(timeout_ms as u64);
+        match NotecognitoIpcClient::connect(("127.0.0.1", crate::ipc::IPC_PORT), timeout) {
+            Ok(client) => Box::into_raw(Box::new(client)),
+            Err(e) => {
+                set_last_error(e);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Gets the server's configuration as JSON (caller must free the returned string)
+#[no_mangle]
+pub extern "C" fn notecognito_ipc_get_config_json(client: *mut NotecognitoIpcClient) -> *mut c_char {
+    ffi_guard!({
+        if client.is_null() {
+            return ptr::null_mut();
+        }
+
+        let client = unsafe { &*client };
+
+        match client.request(IpcMessageType::GetConfiguration) {
+            Ok(IpcMessageType::ConfigurationResponse { config }) => match serde_json::to_string(&config) {
+                Ok(json) => match CString::new(json) {
+                    Ok(c_str) => c_str.into_raw(),
+                    Err(_) => ptr::null_mut(),
+                },
+                Err(_) => ptr::null_mut(),
+            },
+            _ => ptr::null_mut(),
+        }
+    })
+}
+
+/// Updates a notecard through the server, which saves it
+#[no_mangle]
+pub extern "C" fn notecognito_ipc_update_notecard(
+    client: *mut NotecognitoIpcClient,
+    id: c_int,
+    content: *const c_char,
+) -> FfiResult {
+    ffi_guard!({
+        if client.is_null() || content.is_null() {
+            return FfiResult::error(NotecognitoErrorCode::NullPointer, "Invalid parameters");
+        }
+
+        let client = unsafe { &*client };
+
+        let content_str = unsafe {
+            match CStr::from_ptr(content).to_str() {
+                Ok(s) => s,
+                Err(_) => return FfiResult::error(NotecognitoErrorCode::InvalidUtf8, "Invalid UTF-8 in content"),
+            }
+        };
+
+        let notecard_id = match notecard_id_from_c(id) {
+            Some(id) => id,
+            None => return FfiResult::error(NotecognitoErrorCode::InvalidNotecardId, "Invalid notecard ID (must be 1-9)"),
+        };
+
+        client.command(IpcMessageType::UpdateNotecard {
+            notecard: Notecard::new(notecard_id, content_str.to_string()),
+        })
+    })
+}
+
+/// Asks the server's platform to show a notecard
+#[no_mangle]
+pub extern "C" fn notecognito_ipc_show_notecard(
+    client: *mut NotecognitoIpcClient,
+    id: c_int,
+) -> FfiResult {
+    ffi_guard!({
+        if client.is_null() {
+            return FfiResult::error(NotecognitoErrorCode::NullPointer, "Invalid client");
+        }
+
+        let client = unsafe { &*client };
+
+        let notecard_id = match notecard_id_from_c(id) {
+            Some(id) => id,
+            None => return FfiResult::error(NotecognitoErrorCode::InvalidNotecardId, "Invalid notecard ID (must be 1-9)"),
+        };
+
+        client.command(IpcMessageType::ShowNotecard { notecard_id })
+    })
+}
+
+/// Asks the server's platform to show every notecard with content at once
+#[no_mangle]
+pub extern "C" fn notecognito_ipc_show_all_notecards(client: *mut NotecognitoIpcClient) -> FfiResult {
+    ffi_guard!({
+        if client.is_null() {
+            return FfiResult::error(NotecognitoErrorCode::NullPointer, "Invalid client");
+        }
+
+        let client = unsafe { &*client };
+        client.command(IpcMessageType::ShowAllNotecards)
+    })
+}
+
+/// Asks the server's platform to hide every notecard
+#[no_mangle]
+pub extern "C" fn notecognito_ipc_hide_all_notecards(client: *mut NotecognitoIpcClient) -> FfiResult {
+    ffi_guard!({
+        if client.is_null() {
+            return FfiResult::error(NotecognitoErrorCode::NullPointer, "Invalid client");
+        }
+
+        let client = unsafe { &*client };
+        client.command(IpcMessageType::HideAllNotecards)
+    })
+}
+
+/// Disconnects and frees an IPC client
+#[no_mangle]
+pub extern "C" fn notecognito_ipc_free(client: *mut NotecognitoIpcClient) {
+    ffi_guard!({
+        if client.is_null() {
+            return;
+        }
+        unsafe {
+            let _ = Box::from_raw(client);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCode;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A directory of its own for a test's config file, removed when dropped
+    struct TempConfig {
+        dir: PathBuf,
+    }
+
+    impl TempConfig {
+        fn new() -> Self {
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+            let dir = std::env::temp_dir().join(format!(
+                "notecognito-ffi-test-{}-{}",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            ));
+            std::fs::create_dir_all(&dir).unwrap();
+            TempConfig { dir }
+        }
+
+        fn path(&self) -> PathBuf {
+            self.dir.join("config.json")
+        }
+
+        /// A manager backed by this directory's config file, as a host would open it
+        fn manager(&self) -> *mut FfiHandle {
+            let path = c_string(self.path().to_str().unwrap());
+            let manager = notecognito_config_manager_new_with_path(path.as_ptr());
+            assert!(!manager.is_null(), "{:?}", take_string(notecognito_last_error()));
+            manager
+        }
+    }
+
+    impl Drop for TempConfig {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn c_string(s: &str) -> CString {
+        CString::new(s).unwrap()
+    }
+
+    /// Copies and frees a string the library handed out
+    fn take_string(s: *mut c_char) -> Option<String> {
+        if s.is_null() {
+            return None;
+        }
+        let copy = unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string();
+        notecognito_free_string(s);
+        Some(copy)
+    }
+
+    /// Frees a result, panicking with its message if it failed
+    fn assert_ok(result: FfiResult) {
+        let failure = (!result.success).then(|| (result.error_code, take_error_message(&result)));
+        notecognito_result_free(result);
+        assert_eq!(failure, None);
+    }
+
+    /// Frees a failed result, returning its code and message
+    fn take_error(result: FfiResult) -> (NotecognitoErrorCode, String) {
+        assert!(!result.success, "expected an error");
+        let error = (NotecognitoErrorCode::from_c(result.error_code).unwrap(), take_error_message(&result));
+        notecognito_result_free(result);
+        error
+    }
+
+    fn take_error_message(result: &FfiResult) -> String {
+        unsafe { CStr::from_ptr(result.error_message) }.to_str().unwrap().to_string()
+    }
+
+    fn content(manager: *mut FfiHandle, id: c_int) -> Option<String> {
+        take_string(notecognito_get_notecard_content(manager, id))
+    }
+
+    fn update(manager: *mut FfiHandle, id: c_int, text: &str) -> FfiResult {
+        notecognito_update_notecard(manager, id, c_string(text).as_ptr())
+    }
+
+    #[test]
+    fn managers_with_a_path_keep_to_their_own_file() {
+        let first = TempConfig::new();
+        let second = TempConfig::new();
+        let a = first.manager();
+        let b = second.manager();
+
+        assert_ok(update(a, 1, "Only in the first"));
+
+        assert!(first.path().is_file());
+        assert!(!second.path().exists());
+        assert_eq!(content(b, 1).as_deref(), Some(""));
+
+        // A manager opened later on the same path reads what was saved
+        let reopened = first.manager();
+        assert_eq!(content(reopened, 1).as_deref(), Some("Only in the first"));
+
+        for manager in [a, b, reopened] {
+            notecognito_config_manager_free(manager);
+        }
+    }
+
+    #[test]
+    fn a_path_in_a_missing_directory_is_refused() {
+        let temp = TempConfig::new();
+        let path = c_string(temp.dir.join("missing").join("config.json").to_str().unwrap());
+
+        let manager = notecognito_config_manager_new_with_path(path.as_ptr());
+
+        assert!(manager.is_null());
+        let reason = take_string(notecognito_config_manager_last_error()).unwrap();
+        assert!(reason.starts_with("Config directory does not exist"), "{}", reason);
+        assert!(notecognito_config_manager_new_with_path(ptr::null()).is_null());
+    }
+
+    #[test]
+    fn ffi_only_codes_are_pinned() {
+        assert_eq!(NotecognitoErrorCode::Success as c_int, 0);
+        assert_eq!(NotecognitoErrorCode::NullPointer as c_int, 11);
+        assert_eq!(NotecognitoErrorCode::InvalidUtf8 as c_int, 12);
+        assert_eq!(NotecognitoErrorCode::InvalidUtf16 as c_int, 13);
+        assert_eq!(NotecognitoErrorCode::Internal as c_int, 14);
+    }
+
+    #[test]
+    fn every_error_code_has_the_same_ffi_value() {
+        for value in 1..=u16::MAX {
+            if let Some(code) = ErrorCode::from_value(value) {
+                let ffi = NotecognitoErrorCode::from_c(value as c_int).unwrap();
+                assert_eq!(ffi as c_int, code.value() as c_int, "{:?}", code);
+            }
+        }
+    }
+
+    fn display_properties(manager: *mut FfiHandle) -> serde_json::Value {
+        let json = take_string(notecognito_get_display_properties_json(manager)).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn display_properties_round_trip_through_json() {
+        let temp = TempConfig::new();
+        let manager = temp.manager();
+
+        let mut properties = display_properties(manager);
+        properties["opacity"] = 60.into();
+        properties["size"] = serde_json::json!([420, 280]);
+        properties["font_size"] = 18.into();
+        properties["font_family"] = "Georgia".into();
+        properties["auto_hide_duration"] = 15.into();
+
+        let json = c_string(&properties.to_string());
+        assert_ok(notecognito_set_display_properties_json(manager, json.as_ptr()));
+
+        assert_eq!(display_properties(manager), properties);
+        // Saved, so a manager opened later sees them too
+        let reopened = temp.manager();
+        assert_eq!(display_properties(reopened), properties);
+
+        notecognito_config_manager_free(reopened);
+        notecognito_config_manager_free(manager);
+    }
+
+    #[test]
+    fn out_of_range_display_properties_are_rejected() {
+        let temp = TempConfig::new();
+        let manager = temp.manager();
+        let before = display_properties(manager);
+
+        let mut properties = before.clone();
+        properties["opacity"] = 150.into();
+        let json = c_string(&properties.to_string());
+        let (code, message) = take_error(notecognito_set_display_properties_json(manager, json.as_ptr()));
+
+        assert_eq!(code, NotecognitoErrorCode::Validation);
+        assert!(message.contains("opacity"), "{}", message);
+        assert_eq!(display_properties(manager), before);
+
+        notecognito_config_manager_free(manager);
+    }
+
+    #[test]
+    fn a_cleared_notecard_reads_back_empty() {
+        let temp = TempConfig::new();
+        let manager = temp.manager();
+        assert_ok(update(manager, 4, "Soon gone"));
+
+        assert_ok(notecognito_clear_notecard(manager, 4));
+
+        let cleared = notecognito_get_notecard_content(manager, 4);
+        assert!(!cleared.is_null());
+        assert_eq!(take_string(cleared).as_deref(), Some(""));
+
+        notecognito_config_manager_free(manager);
+    }
+
+    #[test]
+    fn notecard_list_has_the_documented_shape() {
+        let temp = TempConfig::new();
+        let manager = temp.manager();
+        assert_ok(update(manager, 2, "  Groceries\n\nmilk   eggs\tbread"));
+
+        let json = take_string(notecognito_list_notecards(manager)).unwrap();
+        let list: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+
+        let ids: Vec<_> = list.iter().map(|entry| entry["id"].as_u64().unwrap()).collect();
+        assert_eq!(ids, (1..=9).collect::<Vec<_>>());
+        for entry in &list {
+            let mut keys: Vec<_> = entry.as_object().unwrap().keys().map(String::as_str).collect();
+            keys.sort_unstable();
+            assert_eq!(keys, ["id", "is_empty", "modified_at", "summary", "title"]);
+        }
+
+        let edited = &list[1];
+        assert_eq!(edited["title"], "Groceries");
+        assert_eq!(edited["summary"], "Groceries milk eggs bread");
+        assert_eq!(edited["is_empty"], false);
+        let modified_at = edited["modified_at"].as_str().unwrap();
+        assert!(chrono::DateTime::parse_from_rfc3339(modified_at).is_ok(), "{}", modified_at);
+
+        let untouched = &list[0];
+        assert_eq!(untouched["title"], "");
+        assert_eq!(untouched["summary"], "");
+        assert_eq!(untouched["is_empty"], true);
+        assert!(untouched["modified_at"].is_null());
+
+        notecognito_config_manager_free(manager);
+        assert!(notecognito_list_notecards(ptr::null_mut()).is_null());
+    }
+
+    fn config_json(manager: *mut FfiHandle) -> serde_json::Value {
+        serde_json::from_str(&take_string(notecognito_get_config_json(manager)).unwrap()).unwrap()
+    }
+
+    /// `config` with its notecards' content replaced, as a host would edit it
+    fn with_cards(mut config: serde_json::Value, cards: &[(u8, &str)]) -> CString {
+        for (id, text) in cards {
+            config["notecards"][id.to_string()]["content"] = (*text).into();
+        }
+        c_string(&config.to_string())
+    }
+
+    /// A manager with notecards 1 and 2 filled in
+    fn manager_with_two_cards(temp: &TempConfig) -> *mut FfiHandle {
+        let manager = temp.manager();
+        assert_ok(update(manager, 1, "Old first"));
+        assert_ok(update(manager, 2, "Old second"));
+        manager
+    }
+
+    #[test]
+    fn replacing_the_config_takes_every_card_as_given() {
+        let temp = TempConfig::new();
+        let manager = manager_with_two_cards(&temp);
+        let mut config = config_json(manager);
+        config["hide_all_hotkey"] = false.into();
+
+        let json = with_cards(config, &[(1, "New first"), (2, "")]);
+        assert_ok(notecognito_apply_config_json(manager, json.as_ptr(), true));
+
+        assert_eq!(content(manager, 1).as_deref(), Some("New first"));
+        assert_eq!(content(manager, 2).as_deref(), Some(""));
+        assert_eq!(config_json(manager)["hide_all_hotkey"], false);
+        // Saved as well
+        let reopened = temp.manager();
+        assert_eq!(content(reopened, 1).as_deref(), Some("New first"));
+
+        notecognito_config_manager_free(reopened);
+        notecognito_config_manager_free(manager);
+    }
+
+    #[test]
+    fn merging_the_config_keeps_cards_sent_empty() {
+        let temp = TempConfig::new();
+        let manager = manager_with_two_cards(&temp);
+        let mut config = config_json(manager);
+        config["hide_all_hotkey"] = false.into();
+
+        let json = with_cards(config, &[(1, "New first"), (2, "")]);
+        assert_ok(notecognito_apply_config_json(manager, json.as_ptr(), false));
+
+        assert_eq!(content(manager, 1).as_deref(), Some("New first"));
+        assert_eq!(content(manager, 2).as_deref(), Some("Old second"));
+        // Settings are taken either way
+        assert_eq!(config_json(manager)["hide_all_hotkey"], false);
+
+        notecognito_config_manager_free(manager);
+    }
+
+    #[test]
+    fn an_invalid_config_is_rejected_with_its_field_path() {
+        let temp = TempConfig::new();
+        let manager = manager_with_two_cards(&temp);
+        let before = config_json(manager);
+        let saved = std::fs::read_to_string(temp.path()).unwrap();
+
+        let mut config = before.clone();
+        config["default_display_properties"]["opacity"] = 101.into();
+        let json = with_cards(config, &[(1, "Never applied")]);
+        let (code, message) = take_error(notecognito_apply_config_json(manager, json.as_ptr(), true));
+
+        assert_eq!(code, NotecognitoErrorCode::Validation);
+        assert!(message.contains("default_display_properties.opacity"), "{}", message);
+        assert_eq!(config_json(manager), before);
+        assert_eq!(std::fs::read_to_string(temp.path()).unwrap(), saved);
+
+        notecognito_config_manager_free(manager);
+    }
+
+    #[test]
+    fn out_of_range_ids_report_invalid_notecard_id() {
+        let temp = TempConfig::new();
+        let manager = temp.manager();
+
+        // 257 would be notecard 1 if it were narrowed before checking
+        for id in [0, 10, -1, 257] {
+            let (code, _) = take_error(update(manager, id, "Nowhere"));
+            assert_eq!(code, NotecognitoErrorCode::InvalidNotecardId, "id {}", id);
+            let (code, _) = take_error(notecognito_clear_notecard(manager, id));
+            assert_eq!(code, NotecognitoErrorCode::InvalidNotecardId, "id {}", id);
+            assert_eq!(content(manager, id), None);
+        }
+
+        notecognito_config_manager_free(manager);
+    }
+
+    #[test]
+    fn null_arguments_report_null_pointer() {
+        let temp = TempConfig::new();
+        let manager = temp.manager();
+        let text = c_string("Text");
+
+        let results = [
+            notecognito_update_notecard(ptr::null_mut(), 1, text.as_ptr()),
+            notecognito_update_notecard(manager, 1, ptr::null()),
+            notecognito_clear_notecard(ptr::null_mut(), 1),
+            notecognito_update_notecard_json(manager, ptr::null()),
+            notecognito_apply_config_json(ptr::null_mut(), text.as_ptr(), true),
+            notecognito_reload_config(ptr::null_mut()),
+            notecognito_set_launch_on_startup(ptr::null_mut(), true),
+            notecognito_set_display_properties_json(manager, ptr::null()),
+            notecognito_set_change_callback(manager, None, ptr::null_mut()),
+            notecognito_clear_change_callback(ptr::null_mut()),
+        ];
+        for (index, result) in results.into_iter().enumerate() {
+            let (code, _) = take_error(result);
+            assert_eq!(code, NotecognitoErrorCode::NullPointer, "call {}", index);
+        }
+
+        notecognito_config_manager_free(manager);
+    }
+
+    #[test]
+    fn a_failed_save_reports_io() {
+        let temp = TempConfig::new();
+        let manager = temp.manager();
+        std::fs::remove_dir_all(&temp.dir).unwrap();
+
+        let (code, _) = take_error(update(manager, 1, "Can't be saved"));
+        assert_eq!(code, NotecognitoErrorCode::Io);
+        let (code, _) = take_error(notecognito_set_launch_on_startup(manager, true));
+        assert_eq!(code, NotecognitoErrorCode::Io);
+
+        notecognito_config_manager_free(manager);
+    }
+
+    /// Error messages are only tracked in debug builds
+    #[cfg(debug_assertions)]
+    fn is_tracked(message: *mut c_char) -> bool {
+        OWNED_ERROR_MESSAGES
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(&(message as usize))
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn error_results_are_tracked_until_freed_once() {
+        for index in 0..1000 {
+            let result = FfiResult::error(NotecognitoErrorCode::Config, &format!("Error {}", index));
+            let message = result.error_message;
+            assert!(is_tracked(message));
+
+            notecognito_result_free(result);
+            assert!(!is_tracked(message));
+        }
+
+        // Freeing a success, or a result a host zeroed, does nothing
+        notecognito_result_free(FfiResult::success());
+        notecognito_result_free(FfiResult { success: false, error_code: 0, error_message: ptr::null_mut() });
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn a_second_free_is_caught_instead_of_freeing_again() {
+        let result = FfiResult::error(NotecognitoErrorCode::Config, "Freed twice");
+        let copy = FfiResult { success: result.success, error_code: result.error_code, error_message: result.error_message };
+
+        notecognito_result_free(result);
+        // The debug assertion panics inside the guard, before the string is touched
+        notecognito_result_free(copy);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn error_results_survive_a_poisoned_tracking_lock() {
+        let _ = std::thread::spawn(|| {
+            let _held = OWNED_ERROR_MESSAGES.lock().unwrap_or_else(PoisonError::into_inner);
+            panic!("poisoning the error message lock");
+        })
+        .join();
+
+        let result = FfiResult::error(NotecognitoErrorCode::Config, "After the poisoning");
+        assert!(is_tracked(result.error_message));
+        notecognito_result_free(result);
+    }
+
+    /// Calls to `count_changes`; only `change_callback_counts_every_change` registers it
+    static CHANGES: AtomicUsize = AtomicUsize::new(0);
+
+    /// Only counts calls that get the registered user data back; panicking
+    /// here would abort rather than fail the test
+    extern "C" fn count_changes(_event_kind: c_int, _notecard_id: c_int, user_data: *mut c_void) {
+        if user_data as usize == 0xC0FFEE {
+            CHANGES.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn change_callback_counts_every_change() {
+        let temp = TempConfig::new();
+        let manager = temp.manager();
+        let user_data = 0xC0FFEE as *mut c_void;
+        assert_ok(notecognito_set_change_callback(manager, Some(count_changes), user_data));
+
+        // Update and save, update and save, clear and save
+        assert_ok(update(manager, 1, "One"));
+        assert_ok(update(manager, 2, "Two"));
+        assert_ok(notecognito_clear_notecard(manager, 1));
+        assert_eq!(CHANGES.load(Ordering::SeqCst), 6);
+
+        assert_ok(notecognito_reload_config(manager));
+        assert_eq!(CHANGES.load(Ordering::SeqCst), 7);
+
+        assert_ok(notecognito_clear_change_callback(manager));
+        assert_ok(update(manager, 3, "Three"));
+        assert_ok(notecognito_reload_config(manager));
+        assert_eq!(CHANGES.load(Ordering::SeqCst), 7);
+
+        notecognito_config_manager_free(manager);
+    }
+
+    /// An IPC server on a port of its own, serving a manager backed by `temp`
+    fn ipc_server(temp: &TempConfig) -> (tokio::runtime::Runtime, std::net::SocketAddr) {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let manager = ConfigManager::with_path(temp.path()).unwrap();
+        let server = crate::IpcServer::new(Arc::new(tokio::sync::Mutex::new(manager)));
+
+        let listener = runtime.block_on(tokio::net::TcpListener::bind("127.0.0.1:0")).unwrap();
+        let addr = listener.local_addr().unwrap();
+        runtime.spawn(async move { server.serve(listener).await });
+
+        (runtime, addr)
+    }
+
+    fn ipc_client(addr: std::net::SocketAddr) -> *mut NotecognitoIpcClient {
+        let client = NotecognitoIpcClient::connect(addr, std::time::Duration::from_secs(5)).unwrap();
+        Box::into_raw(Box::new(client))
+    }
+
+    #[test]
+    fn ipc_client_talks_to_an_in_process_server() {
+        let temp = TempConfig::new();
+        let (_server, addr) = ipc_server(&temp);
+        let client = ipc_client(addr);
+
+        let text = c_string("Through the server");
+        assert_ok(notecognito_ipc_update_notecard(client, 5, text.as_ptr()));
+
+        let json = take_string(notecognito_ipc_get_config_json(client)).unwrap();
+        let config: Config = serde_json::from_str(&json).unwrap();
+        assert_eq!(config.notecards[&NotecardId::new(5).unwrap()].content, "Through the server");
+        // The server saved it
+        let manager = temp.manager();
+        assert_eq!(content(manager, 5).as_deref(), Some("Through the server"));
+
+        // With no platform to show it on, the server's refusal comes through
+        let (code, _) = take_error(notecognito_ipc_show_notecard(client, 5));
+        assert_eq!(code, NotecognitoErrorCode::Ipc);
+        let (code, _) = take_error(notecognito_ipc_show_notecard(client, 12));
+        assert_eq!(code, NotecognitoErrorCode::InvalidNotecardId);
+
+        notecognito_config_manager_free(manager);
+        notecognito_ipc_free(client);
+    }
+
+    #[test]
+    fn ipc_client_survives_a_panic_while_it_was_locked() {
+        let temp = TempConfig::new();
+        let (_server, addr) = ipc_server(&temp);
+        let client = ipc_client(addr);
+
+        let shared = unsafe { &*client };
+        let _ = catch_unwind(AssertUnwindSafe(|| {
+            let _held = shared.client.lock().unwrap();
+            panic!("poisoning the client lock");
+        }));
+        assert!(shared.client.is_poisoned());
+
+        assert!(take_string(notecognito_ipc_get_config_json(client)).is_some());
+
+        notecognito_ipc_free(client);
+    }
+
+    #[test]
+    fn ipc_connect_reports_an_unreachable_server() {
+        // Bound and dropped, so nothing is listening there
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+
+        let result = NotecognitoIpcClient::connect(addr, std::time::Duration::from_secs(5));
+
+        assert_eq!(result.err(), Some(NotecognitoError::ConnectionLost.to_string()));
+    }
+
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(Some(0)).collect()
+    }
+
+    /// Copies and frees a UTF-16 string the library handed out
+    fn take_wide(s: *mut u16) -> Option<Vec<u16>> {
+        if s.is_null() {
+            return None;
+        }
+        let copy = unsafe { std::slice::from_raw_parts(s, wide_len(s) + 1) }.to_vec();
+        notecognito_free_wstring(s);
+        Some(copy)
+    }
+
+    #[test]
+    fn astral_plane_text_round_trips_through_utf16() {
+        let temp = TempConfig::new();
+        let manager = temp.manager();
+        let text = "Deploy 🚀 then 𝄞 and 😀";
+        let units = wide(text);
+        // Each of those takes a surrogate pair
+        assert_eq!(units.len(), text.chars().count() + 3 + 1);
+
+        assert_ok(notecognito_update_notecard_w(manager, 6, units.as_ptr()));
+
+        assert_eq!(take_wide(notecognito_get_notecard_content_w(manager, 6)), Some(units));
+        assert_eq!(content(manager, 6).as_deref(), Some(text));
+
+        notecognito_config_manager_free(manager);
+    }
+
+    #[test]
+    fn lone_surrogates_are_rejected() {
+        let temp = TempConfig::new();
+        let manager = temp.manager();
+        assert_ok(update(manager, 6, "Unchanged"));
+
+        let high_alone = [0x0041, 0xD83D, 0x0042, 0];
+        let low_alone = [0xDE00, 0x0041, 0];
+        for units in [&high_alone[..], &low_alone[..]] {
+            let (code, _) = take_error(notecognito_update_notecard_w(manager, 6, units.as_ptr()));
+            assert_eq!(code, NotecognitoErrorCode::InvalidUtf16);
+        }
+
+        assert_eq!(content(manager, 6).as_deref(), Some("Unchanged"));
+
+        notecognito_config_manager_free(manager);
+    }
+
+    #[test]
+    fn handles_on_eight_threads_interleave_reads_and_updates() {
+        const THREADS: c_int = 8;
+        const ROUNDS: usize = 50;
+
+        let temp = TempConfig::new();
+        let manager = temp.manager();
+
+        let threads: Vec<_> = (1..=THREADS)
+            .map(|id| {
+                // Raw pointers aren't Send; the handle is what's shared
+                let handle = notecognito_config_manager_clone_handle(manager) as usize;
+                std::thread::spawn(move || {
+                    let handle = handle as *mut FfiHandle;
+                    for round in 0..ROUNDS {
+                        let text = format!("Thread {} round {}", id, round);
+                        assert_ok(update(handle, id, &text));
+                        assert_eq!(content(handle, id), Some(text));
+
+                        // Whatever the others are doing, the whole config reads back whole
+                        let json = take_string(notecognito_get_config_json(handle)).unwrap();
+                        let config: Config = serde_json::from_str(&json).unwrap();
+                        assert_eq!(config.notecards.len(), 9);
+                    }
+                    notecognito_config_manager_free(handle);
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        for id in 1..=THREADS {
+            assert_eq!(content(manager, id), Some(format!("Thread {} round {}", id, ROUNDS - 1)));
+        }
+        assert_eq!(notecognito_config_is_dirty(manager), 0);
+
+        notecognito_config_manager_free(manager);
+    }
+
+    #[test]
+    fn version_is_a_static_c_string() {
+        let version = notecognito_version();
+
+        assert!(!version.is_null());
+        // Reading it as a CStr stops at the terminator, so the terminator is there
+        let version = unsafe { CStr::from_ptr(version) };
+        assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+        // Static, so a second call hands back the same string
+        assert_eq!(notecognito_version(), version.as_ptr());
+    }
+
+    #[test]
+    fn build_info_is_json_with_target_features_and_git_hash() {
+        let info = notecognito_build_info_json();
+        assert!(!info.is_null());
+
+        let info: serde_json::Value = serde_json::from_str(&take_string(info).unwrap()).unwrap();
+
+        assert_eq!(info["target"], env!("NOTECOGNITO_TARGET"));
+        assert!(info["features"].as_array().unwrap().contains(&"ffi".into()));
+        // Null outside a git checkout, but always there
+        assert!(info["git_hash"].is_string() || info["git_hash"].is_null(), "{}", info);
+        assert!(info.as_object().unwrap().contains_key("git_hash"));
+        assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(info["abi_version"], NOTECOGNITO_FFI_ABI_VERSION);
+    }
+
+    /// Panics inside the guard, as a bug in any extern function would
+    extern "C" fn panicking_call(value: c_int) -> FfiResult {
+        ffi_guard!({
+            if value > 0 {
+                panic!("value {} was too big", value);
+            }
+            FfiResult::success()
+        })
+    }
+
+    extern "C" fn panicking_getter() -> *mut c_char {
+        ffi_guard!({
+            panic!("no string today");
+        })
+    }
+
+    extern "C" fn panicking_status() -> c_int {
+        ffi_guard!({
+            panic!("no status today");
+        })
+    }
+
+    #[test]
+    fn a_panic_comes_back_as_an_internal_error() {
+        let (code, message) = take_error(panicking_call(3));
+
+        assert_eq!(code, NotecognitoErrorCode::Internal);
+        assert_eq!(message, "internal panic: value 3 was too big");
+        // Calls after a caught panic work as usual
+        assert_ok(panicking_call(0));
+    }
+
+    #[test]
+    fn a_panic_comes_back_as_null_or_minus_one() {
+        assert!(panicking_getter().is_null());
+        assert_eq!(panicking_status(), -1);
+    }
+
+    #[test]
+    fn a_notecard_with_every_optional_field_round_trips_through_json() {
+        use crate::{Anchor, BackgroundStyle, BlurMaterial, MonitorSelector, PixelUnits, Placement, SizeMode};
+
+        let temp = TempConfig::new();
+        let manager = temp.manager();
+        let sent_at = chrono::DateTime::parse_from_rfc3339("2001-02-03T04:05:06Z").unwrap().to_utc();
+        let notecard = Notecard {
+            modified_at: Some(sent_at),
+            display_properties: Some(DisplayProperties {
+                opacity: 70,
+                position: (-40, 25),
+                size: (500, 320),
+                size_mode: SizeMode::FitContent,
+                auto_hide_duration: 30,
+                font_family: "Menlo".to_string(),
+                font_size: 13,
+                algorithmic_spacing: true,
+                markdown: true,
+                anchor: Some(Anchor::BottomRight),
+                monitor: MonitorSelector::Index(1),
+                placement: Placement::AtCursor { offset: (12, -8) },
+                units: PixelUnits::Physical,
+                animations: false,
+                exclude_from_capture: false,
+                click_through: true,
+                announcements: false,
+                background_style: BackgroundStyle::Blur { material: BlurMaterial::Popover },
+                wrap_pages: true,
+                text_color: Some("#112233".to_string()),
+                background_color: Some("#ddeeffcc".to_string()),
+            }),
+            ..Notecard::new(NotecardId::new(7).unwrap(), "# Heading\n\n- item".to_string())
+        };
+        let sent = serde_json::to_value(&notecard).unwrap();
+
+        let json = c_string(&sent.to_string());
+        assert_ok(notecognito_update_notecard_json(manager, json.as_ptr()));
+        let json = take_string(notecognito_get_notecard_json(manager, 7)).unwrap();
+        let mut received: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        // The manager stamps the edit itself
+        let modified_at = received["modified_at"].take();
+        let modified_at = chrono::DateTime::parse_from_rfc3339(modified_at.as_str().unwrap()).unwrap();
+        assert!(modified_at > sent_at);
+        let mut expected = sent;
+        expected["modified_at"] = serde_json::Value::Null;
+        assert_eq!(received, expected);
+
+        notecognito_config_manager_free(manager);
+    }
+
+    #[test]
+    fn reload_picks_up_an_external_edit() {
+        let temp = TempConfig::new();
+        let manager = temp.manager();
+        assert_ok(update(manager, 1, "Before"));
+
+        // Another process rewrites the file
+        let mut config: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(temp.path()).unwrap()).unwrap();
+        config["notecards"]["1"]["content"] = "Edited elsewhere".into();
+        std::fs::write(temp.path(), config.to_string()).unwrap();
+        assert_eq!(content(manager, 1).as_deref(), Some("Before"));
+
+        assert_ok(notecognito_reload_config(manager));
+
+        assert_eq!(content(manager, 1).as_deref(), Some("Edited elsewhere"));
+        assert_eq!(notecognito_config_is_dirty(manager), 0);
+
+        notecognito_config_manager_free(manager);
+    }
+
+    #[test]
+    fn reload_keeps_the_config_when_the_file_is_corrupt() {
+        let temp = TempConfig::new();
+        let manager = temp.manager();
+        assert_ok(update(manager, 1, "Kept"));
+        std::fs::write(temp.path(), "{ \"notecards\": [trunc").unwrap();
+
+        let (code, message) = take_error(notecognito_reload_config(manager));
+
+        assert_eq!(code, NotecognitoErrorCode::Config);
+        assert!(message.starts_with("Configuration error: Failed to reload"), "{}", message);
+        assert_eq!(content(manager, 1).as_deref(), Some("Kept"));
+        assert_eq!(notecognito_config_is_dirty(manager), 0);
+
+        notecognito_config_manager_free(manager);
+        assert_eq!(notecognito_config_is_dirty(ptr::null_mut()), -1);
+    }
+
+    fn poll(queue: *mut NotecognitoEventQueue) -> Option<serde_json::Value> {
+        let mut json = ptr::null_mut();
+        match notecognito_event_queue_poll(queue, &mut json) {
+            1 => Some(serde_json::from_str(&take_string(json).unwrap()).unwrap()),
+            0 => None,
+            status => panic!("poll returned {}", status),
+        }
+    }
+
+    #[test]
+    fn a_full_event_queue_drops_the_oldest_events() {
+        const UPDATES: usize = 200;
+
+        let temp = TempConfig::new();
+        let manager = temp.manager();
+        let queue = notecognito_event_queue_new(manager);
+        assert!(!queue.is_null());
+
+        // Each update is an Updated and then a Saved event
+        let card = |update: usize| (update % 9 + 1) as c_int;
+        for update_index in 0..UPDATES {
+            assert_ok(update(manager, card(update_index), &format!("Update {}", update_index)));
+        }
+        // The queue outlives the handle it was made from
+        notecognito_config_manager_free(manager);
+
+        let mut events = Vec::new();
+        while let Some(event) = poll(queue) {
+            events.push(event);
+        }
+
+        assert_eq!(events.len(), EVENT_QUEUE_CAPACITY);
+        let dropped = UPDATES * 2 - EVENT_QUEUE_CAPACITY;
+        for (offset, event) in events.iter().enumerate() {
+            let index = dropped + offset;
+            let expected = if index.is_multiple_of(2) {
+                serde_json::json!({ "kind": "Updated", "notecard_id": card(index / 2) })
+            } else {
+                serde_json::json!({ "kind": "Saved", "notecard_id": 0 })
+            };
+            assert_eq!(*event, expected, "event {}", index);
+        }
+        assert_eq!(notecognito_event_queue_poll(queue, ptr::null_mut()), -1);
+
+        notecognito_event_queue_free(queue);
+    }
+
+    #[test]
+    fn build_info_reports_the_conservative_capabilities() {
+        let info: serde_json::Value = serde_json::from_str(&take_string(notecognito_build_info_json()).unwrap()).unwrap();
+
+        assert_eq!(info["capabilities"], serde_json::to_value(PlatformCapabilities::conservative()).unwrap());
+    }
+}
@@ -1,14 +1,29 @@
 use serde::{Deserialize, Serialize};
-use tokio::net::{TcpListener, TcpStream};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use crate::error::{NotecognitoError, Result};
+use std::time::Duration;
+use tokio::sync::{broadcast, Mutex};
+use crate::error::{ErrorCode, NotecognitoError, Result};
 use crate::config::{Config, ConfigManager};
-use crate::notecard::Notecard;
+use crate::notecard::{Notecard, NotecardId};
+use crate::platform::{PlatformCapabilities, PlatformEvent, PlatformInterface};
 
-const IPC_PORT: u16 = 7855;
+pub(crate) const IPC_PORT: u16 = 7855;
 const MAX_MESSAGE_SIZE: usize = 1024 * 1024; // 1MB max message size
+const MAX_CONSECUTIVE_MALFORMED: u32 = 5;
+const FRAME_READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Pushes buffered per subscriber before it falls behind
+const PUSH_CAPACITY: usize = 16;
+
+/// Machine-readable error codes carried by `Error` responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IpcErrorCode {
+    /// The frame was empty, oversized, or did not contain a valid message
+    MalformedMessage,
+    /// The message parsed but is not a request the server handles
+    InvalidMessageType,
+}
 
 /// IPC message types
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,9 +32,33 @@ pub enum IpcMessageType {
     GetConfiguration,
     UpdateNotecard { notecard: Notecard },
     SaveConfiguration { config: Config },
+    ShowNotecard { notecard_id: NotecardId },
+    /// Shows every notecard with content at once, laid out by `cascade_layout`
+    ShowAllNotecards,
+    HideAllNotecards,
+    /// Pauses or resumes hotkeys, saving the choice and pushing the new configuration
+    SetHotkeysEnabled { enabled: bool },
+    Subscribe,
+    GetPlatformInfo,
     ConfigurationResponse { config: Config },
+    PlatformInfoResponse {
+        capabilities: PlatformCapabilities,
+        /// Whether the app really launches on startup, when the platform can tell
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        launch_on_startup: Option<bool>,
+    },
+    ConfigChanged { config: Config },
+    /// Reported by a platform app and pushed on to every subscriber
+    PlatformEvent { event: PlatformEvent },
     Success { message: String },
-    Error { message: String },
+    Error {
+        message: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        code: Option<IpcErrorCode>,
+        /// Numeric code of the `NotecognitoError` behind the failure, the same value FFI reports
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error_code: Option<ErrorCode>,
+    },
 }
 
 /// IPC message wrapper
@@ -44,33 +83,75 @@ impl IpcMessage {
     }
 }
 
+/// Something pushed to subscribed connections
+#[derive(Debug, Clone)]
+enum Push {
+    /// Boxed, since a whole configuration dwarfs an event
+    Config(Box<Config>),
+    Platform(PlatformEvent),
+}
+
 /// IPC server that handles communication with the configuration UI
+#[derive(Clone)]
 pub struct IpcServer {
     config_manager: Arc<Mutex<ConfigManager>>,
+    platform: Option<Arc<Mutex<dyn PlatformInterface>>>,
+    pushes: broadcast::Sender<Push>,
 }
 
 impl IpcServer {
     /// Creates a new IPC server
     pub fn new(config_manager: Arc<Mutex<ConfigManager>>) -> Self {
-        IpcServer { config_manager }
+        IpcServer {
+            config_manager,
+            platform: None,
+            pushes: broadcast::channel(PUSH_CAPACITY).0,
+        }
+    }
+
+    /// Creates an IPC server that forwards display commands to a platform
+    pub fn with_platform(
+        config_manager: Arc<Mutex<ConfigManager>>,
+        platform: Arc<Mutex<dyn PlatformInterface>>,
+    ) -> Self {
+        IpcServer {
+            config_manager,
+            platform: Some(platform),
+            pushes: broadcast::channel(PUSH_CAPACITY).0,
+        }
     }
 
-    /// Starts the IPC server
+    /// Pushes a `ConfigChanged` message to every subscribed connection
+    pub fn notify_config_changed(&self, config: Config) {
+        // Sending only fails when nobody is subscribed
+        let _ = self.pushes.send(Push::Config(Box::new(config)));
+    }
+
+    /// Pushes a `PlatformEvent` message to every subscribed connection
+    pub fn notify_platform_event(&self, event: PlatformEvent) {
+        let _ = self.pushes.send(Push::Platform(event));
+    }
+
+    /// Starts the IPC server on the localhost port clients connect to
     pub async fn start(&self) -> Result<()> {
-        let addr = format!("127.0.0.1:{}", IPC_PORT);
-        let listener = TcpListener::bind(&addr).await?;
+        let listener = TcpListener::bind(("127.0.0.1", IPC_PORT)).await?;
+        self.serve(listener).await
+    }
 
-        tracing::info!("IPC server listening on {}", addr);
+    /// Serves every connection made to `listener`, which may be bound to
+    /// any address; tests bind port 0 to get one of their own
+    pub async fn serve(&self, listener: TcpListener) -> Result<()> {
+        tracing::info!("IPC server listening on {}", listener.local_addr()?);
 
         loop {
             let (stream, addr) = listener.accept().await?;
             tracing::debug!("New connection from {}", addr);
 
-            let config_manager = Arc::clone(&self.config_manager);
+            let server = self.clone();
 
             // Spawn a task to handle each connection
             tokio::spawn(async move {
-                if let Err(e) = handle_connection(stream, config_manager).await {
+                if let Err(e) = serve_connection(stream, &server).await {
                     tracing::error!("Error handling connection: {}", e);
                 }
             });
@@ -78,99 +159,357 @@ impl IpcServer {
     }
 }
 
-/// Handles a single client connection
-async fn handle_connection(
-    mut stream: TcpStream,
-    config_manager: Arc<Mutex<ConfigManager>>,
-) -> Result<()> {
-    let mut buffer = vec![0; MAX_MESSAGE_SIZE];
+/// Result of reading a single length-prefixed frame
+enum Frame {
+    Message(Box<IpcMessage>),
+    Malformed { id: Option<String>, reason: String },
+    Oversized(usize),
+    Closed,
+}
 
-    loop {
-        // Read message length (4 bytes)
-        let mut len_bytes = [0u8; 4];
-        match stream.read_exact(&mut len_bytes).await {
-            Ok(_) => {},
-            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
-                tracing::debug!("Client disconnected");
-                return Ok(());
-            }
-            Err(e) => return Err(e.into()),
+/// Reads one frame from the stream without trusting the declared length
+async fn read_frame<R>(reader: &mut R) -> Result<Frame>
+where
+    R: AsyncRead + Unpin,
+{
+    // Read message length (4 bytes)
+    let mut len_bytes = [0u8; 4];
+    match reader.read_exact(&mut len_bytes).await {
+        Ok(_) => {},
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            tracing::debug!("Client disconnected");
+            return Ok(Frame::Closed);
         }
+        Err(e) => return Err(e.into()),
+    }
 
-        let message_len = u32::from_le_bytes(len_bytes) as usize;
+    let message_len = u32::from_le_bytes(len_bytes) as usize;
 
-        if message_len > MAX_MESSAGE_SIZE {
-            return Err(NotecognitoError::InvalidMessage);
+    if message_len == 0 {
+        return Ok(Frame::Malformed {
+            id: None,
+            reason: "Empty frame".to_string(),
+        });
+    }
+
+    if message_len > MAX_MESSAGE_SIZE {
+        return Ok(Frame::Oversized(message_len));
+    }
+
+    // Grow the buffer only as bytes actually arrive, so a large declared
+    // length backed by a short payload never forces a full-size allocation
+    let mut buffer = Vec::new();
+    let read = tokio::time::timeout(
+        FRAME_READ_TIMEOUT,
+        (&mut *reader).take(message_len as u64).read_to_end(&mut buffer),
+    ).await;
+
+    match read {
+        Ok(Ok(_)) => {},
+        Ok(Err(e)) => return Err(e.into()),
+        Err(_) => {
+            tracing::warn!("Timed out reading {} byte frame", message_len);
+            return Ok(Frame::Closed);
         }
+    }
 
-        // Read the message
-        stream.read_exact(&mut buffer[..message_len]).await?;
+    if buffer.len() < message_len {
+        tracing::debug!(
+            "Client disconnected mid-frame ({} of {} bytes)",
+            buffer.len(),
+            message_len
+        );
+        return Ok(Frame::Closed);
+    }
 
-        // Parse the message
-        let message: IpcMessage = serde_json::from_slice(&buffer[..message_len])
-            .map_err(|_| NotecognitoError::InvalidMessage)?;
+    // Parse the message
+    match serde_json::from_slice::<IpcMessage>(&buffer) {
+        Ok(message) => Ok(Frame::Message(Box::new(message))),
+        Err(e) => Ok(Frame::Malformed {
+            id: recover_message_id(&buffer),
+            reason: e.to_string(),
+        }),
+    }
+}
+
+/// Best-effort extraction of the `id` field from an unparseable message
+fn recover_message_id(bytes: &[u8]) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_slice(bytes).ok()?;
+    match value.get("id")? {
+        serde_json::Value::String(id) => Some(id.clone()),
+        serde_json::Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+/// Serves requests on an established stream until the peer disconnects
+///
+/// Malformed frames are answered with a `MalformedMessage` error and the
+/// connection keeps reading; it is only closed after
+/// `MAX_CONSECUTIVE_MALFORMED` bad frames in a row or an oversized frame,
+/// since the framing can no longer be trusted at that point.
+///
+/// A `Subscribe` request turns the connection into a one-way stream of
+/// `ConfigChanged` and `PlatformEvent` messages for the rest of its lifetime.
+pub async fn serve_connection<S>(mut stream: S, server: &IpcServer) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut consecutive_malformed = 0;
+
+    loop {
+        let message = match read_frame(&mut stream).await? {
+            Frame::Message(message) => *message,
+            Frame::Closed => return Ok(()),
+            Frame::Oversized(len) => {
+                tracing::warn!("Rejecting oversized frame of {} bytes", len);
+                let response = malformed_response(
+                    None,
+                    format!("Message exceeds maximum size of {} bytes", MAX_MESSAGE_SIZE),
+                );
+                let _ = send_message(&mut stream, &response).await;
+                return Ok(());
+            }
+            Frame::Malformed { id, reason } => {
+                consecutive_malformed += 1;
+                tracing::warn!(
+                    "Malformed message ({}/{}): {}",
+                    consecutive_malformed,
+                    MAX_CONSECUTIVE_MALFORMED,
+                    reason
+                );
+
+                send_message(&mut stream, &malformed_response(id, reason)).await?;
+
+                if consecutive_malformed >= MAX_CONSECUTIVE_MALFORMED {
+                    tracing::warn!("Too many malformed messages, closing connection");
+                    return Ok(());
+                }
+                continue;
+            }
+        };
+
+        consecutive_malformed = 0;
 
         tracing::debug!("Received message: {:?}", message.message_type);
 
+        if let IpcMessageType::Subscribe = message.message_type {
+            let pushes = server.pushes.subscribe();
+            let response = IpcMessage::with_id(message.id, IpcMessageType::Success {
+                message: "Subscribed to configuration changes".to_string(),
+            });
+            send_message(&mut stream, &response).await?;
+            return forward_pushes(stream, pushes, server).await;
+        }
+
         // Process the message
-        let response = process_message(message, &config_manager).await?;
+        let response = process_message(message, server).await?;
 
         // Send the response
         send_message(&mut stream, &response).await?;
     }
 }
 
+/// Pushes config changes and platform events to a subscribed connection until
+/// either side goes away
+async fn forward_pushes<S>(
+    mut stream: S,
+    mut pushes: broadcast::Receiver<Push>,
+    server: &IpcServer,
+) -> Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    loop {
+        let message_type = match pushes.recv().await {
+            Ok(Push::Config(config)) => IpcMessageType::ConfigChanged { config: *config },
+            Ok(Push::Platform(event)) => IpcMessageType::PlatformEvent { event },
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                // Skipped events are gone, but the config can catch up with the latest state
+                tracing::debug!("Subscriber skipped {} pushes", skipped);
+                IpcMessageType::ConfigChanged {
+                    config: server.config_manager.lock().await.config().clone(),
+                }
+            }
+            Err(broadcast::error::RecvError::Closed) => return Ok(()),
+        };
+
+        let message = IpcMessage::new(message_type);
+        if let Err(e) = send_message(&mut stream, &message).await {
+            tracing::debug!("Subscriber disconnected: {}", e);
+            return Ok(());
+        }
+    }
+}
+
+/// Builds the error response for a request that failed
+fn error_response(error: &NotecognitoError) -> IpcMessageType {
+    IpcMessageType::Error {
+        message: error.to_string(),
+        code: None,
+        error_code: Some(error.code()),
+    }
+}
+
+/// Builds the error response for a frame that could not be parsed
+fn malformed_response(id: Option<String>, reason: String) -> IpcMessage {
+    let message_type = IpcMessageType::Error {
+        message: format!("Malformed message: {}", reason),
+        code: Some(IpcErrorCode::MalformedMessage),
+        error_code: None,
+    };
+
+    match id {
+        Some(id) => IpcMessage::with_id(id, message_type),
+        None => IpcMessage::new(message_type),
+    }
+}
+
 /// Processes an incoming IPC message
-async fn process_message(
-    message: IpcMessage,
-    config_manager: &Arc<Mutex<ConfigManager>>,
-) -> Result<IpcMessage> {
+async fn process_message(message: IpcMessage, server: &IpcServer) -> Result<IpcMessage> {
     let response_type = match message.message_type {
         IpcMessageType::GetConfiguration => {
-            let manager = config_manager.lock().await;
+            let manager = server.config_manager.lock().await;
             IpcMessageType::ConfigurationResponse {
                 config: manager.config().clone(),
             }
         }
 
         IpcMessageType::UpdateNotecard { notecard } => {
-            let mut manager = config_manager.lock().await;
-            match manager.update_notecard(notecard) {
+            let mut manager = server.config_manager.lock().await;
+            match manager.update_notecard(notecard).and_then(|_| manager.save()) {
                 Ok(_) => {
-                    manager.save()?;
+                    server.notify_config_changed(manager.config().clone());
                     IpcMessageType::Success {
                         message: "Notecard updated successfully".to_string(),
                     }
                 }
-                Err(e) => IpcMessageType::Error {
-                    message: e.to_string(),
-                },
+                Err(e) => error_response(&e),
             }
         }
 
         IpcMessageType::SaveConfiguration { config } => {
-            let mut manager = config_manager.lock().await;
+            let mut manager = server.config_manager.lock().await;
             *manager.config_mut() = config;
             match manager.save() {
+                Ok(_) => {
+                    server.notify_config_changed(manager.config().clone());
+                    IpcMessageType::Success {
+                        message: "Configuration saved successfully".to_string(),
+                    }
+                }
+                Err(e) => error_response(&e),
+            }
+        }
+
+        IpcMessageType::SetHotkeysEnabled { enabled } => {
+            let mut manager = server.config_manager.lock().await;
+            manager.config_mut().hotkeys_enabled = enabled;
+            match manager.save() {
+                Ok(_) => {
+                    server.notify_config_changed(manager.config().clone());
+                    IpcMessageType::Success {
+                        message: format!("Hotkeys {}", if enabled { "resumed" } else { "paused" }),
+                    }
+                }
+                Err(e) => error_response(&e),
+            }
+        }
+
+        IpcMessageType::ShowNotecard { notecard_id: id } => match show_notecard(id, server).await {
+            Ok(_) => IpcMessageType::Success {
+                message: format!("Notecard {} shown", id),
+            },
+            Err(e) => error_response(&e),
+        },
+
+        IpcMessageType::ShowAllNotecards => match show_all_notecards(server).await {
+            Ok(count) => IpcMessageType::Success {
+                message: format!("{} notecards shown", count),
+            },
+            Err(e) => error_response(&e),
+        },
+
+        IpcMessageType::HideAllNotecards => match attached_platform(server) {
+            Ok(platform) => match platform.lock().await.hide_all_notecards().await {
                 Ok(_) => IpcMessageType::Success {
-                    message: "Configuration saved successfully".to_string(),
-                },
-                Err(e) => IpcMessageType::Error {
-                    message: e.to_string(),
+                    message: "All notecards hidden".to_string(),
                 },
+                Err(e) => error_response(&e),
+            },
+            Err(e) => error_response(&e),
+        },
+
+        IpcMessageType::PlatformEvent { event } => {
+            server.notify_platform_event(event);
+            IpcMessageType::Success {
+                message: "Platform event forwarded".to_string(),
             }
         }
 
+        IpcMessageType::GetPlatformInfo => {
+            let (capabilities, launch_on_startup) = match &server.platform {
+                Some(platform) => {
+                    let platform = platform.lock().await;
+                    let launch_on_startup = platform.launch_on_startup_status().await.unwrap_or_else(|e| {
+                        tracing::debug!("Could not read the launch on startup status: {}", e);
+                        None
+                    });
+                    (platform.capabilities(), launch_on_startup)
+                }
+                None => (PlatformCapabilities::conservative(), None),
+            };
+            IpcMessageType::PlatformInfoResponse { capabilities, launch_on_startup }
+        }
+
         _ => IpcMessageType::Error {
             message: "Invalid message type".to_string(),
+            code: Some(IpcErrorCode::InvalidMessageType),
+            error_code: None,
         },
     };
 
     Ok(IpcMessage::with_id(message.id, response_type))
 }
 
-/// Sends a message over the TCP stream
-async fn send_message(stream: &mut TcpStream, message: &IpcMessage) -> Result<()> {
+/// The server's platform, failing when none is attached
+fn attached_platform(server: &IpcServer) -> Result<&Arc<Mutex<dyn PlatformInterface>>> {
+    server.platform.as_ref().ok_or_else(|| {
+        NotecognitoError::Platform("No platform attached to this server".to_string())
+    })
+}
+
+/// Shows a notecard through the server's platform, if one is attached
+async fn show_notecard(id: NotecardId, server: &IpcServer) -> Result<()> {
+    let platform = attached_platform(server)?;
+
+    let (content, properties) = {
+        let manager = server.config_manager.lock().await;
+        let notecard = manager.get_notecard(id)
+            .filter(|notecard| !notecard.is_empty())
+            .ok_or_else(|| NotecognitoError::Config(format!("Notecard {} is empty", id)))?;
+        (notecard.content.clone(), manager.config().display_properties_for(id).clone())
+    };
+
+    let mut platform = platform.lock().await;
+    platform.show_notecard(id, &content, &properties).await
+}
+
+/// Shows every notecard with content through the server's platform, returning
+/// how many there were
+async fn show_all_notecards(server: &IpcServer) -> Result<usize> {
+    let platform = attached_platform(server)?;
+    let notecards = server.config_manager.lock().await.config().notecards_with_content();
+
+    platform.lock().await.show_all_notecards(&notecards).await?;
+    Ok(notecards.len())
+}
+
+/// Sends a length-prefixed message over the stream
+async fn send_message<W>(stream: &mut W, message: &IpcMessage) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
     let json = serde_json::to_vec(message)?;
     let len = json.len() as u32;
 
@@ -192,13 +531,24 @@ pub struct IpcClient {
 impl IpcClient {
     /// Connects to the IPC server
     pub async fn connect() -> Result<Self> {
-        let addr = format!("127.0.0.1:{}", IPC_PORT);
-        let stream = TcpStream::connect(&addr).await
+        Self::connect_to(("127.0.0.1", IPC_PORT)).await
+    }
+
+    /// Connects to an IPC server listening on `addr`
+    pub async fn connect_to(addr: impl ToSocketAddrs) -> Result<Self> {
+        let stream = TcpStream::connect(addr).await
             .map_err(|_| NotecognitoError::ConnectionLost)?;
 
         Ok(IpcClient { stream })
     }
 
+    /// Connects to the IPC server, giving up after `timeout`
+    pub async fn connect_timeout(timeout: Duration) -> Result<Self> {
+        tokio::time::timeout(timeout, Self::connect())
+            .await
+            .map_err(|_| NotecognitoError::ConnectionLost)?
+    }
+
     /// Sends a message and waits for a response
     pub async fn send_message(&mut self, message: IpcMessage) -> Result<IpcMessage> {
         // Send the message
@@ -223,4 +573,314 @@ impl IpcClient {
 
         Ok(response)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::DuplexStream;
+
+    /// A frame as it goes on the wire, and what the server should make of it
+    #[derive(Debug, Clone)]
+    enum TestFrame {
+        Valid,
+        /// A zero length prefix
+        Empty,
+        /// Bytes that are never JSON, since they aren't UTF-8
+        Garbage(Vec<u8>),
+        /// Declares more than `MAX_MESSAGE_SIZE` and sends nothing after it
+        Oversized(u32),
+        /// Declares `declared` bytes and sends only `sent`, then the stream ends
+        Truncated { declared: u32, sent: usize },
+        /// Ends the stream partway through the length prefix
+        TruncatedPrefix(usize),
+    }
+
+    impl TestFrame {
+        fn encode(&self, out: &mut Vec<u8>) {
+            match self {
+                TestFrame::Valid => {
+                    let message = IpcMessage::with_id("ok".to_string(), IpcMessageType::GetConfiguration);
+                    let json = serde_json::to_vec(&message).unwrap();
+                    out.extend_from_slice(&(json.len() as u32).to_le_bytes());
+                    out.extend_from_slice(&json);
+                }
+                TestFrame::Empty => out.extend_from_slice(&0u32.to_le_bytes()),
+                TestFrame::Garbage(bytes) => {
+                    out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+                    out.extend_from_slice(bytes);
+                }
+                TestFrame::Oversized(len) => out.extend_from_slice(&len.to_le_bytes()),
+                TestFrame::Truncated { declared, sent } => {
+                    out.extend_from_slice(&declared.to_le_bytes());
+                    out.resize(out.len() + sent, b'x');
+                }
+                TestFrame::TruncatedPrefix(len) => out.extend_from_slice(&[0x10, 0, 0][..*len]),
+            }
+        }
+    }
+
+    /// xorshift64, so failures reproduce from the seed alone
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next() % n
+        }
+    }
+
+    fn random_frames(rng: &mut Rng) -> Vec<TestFrame> {
+        let count = 1 + rng.below(20) as usize;
+        let mut frames: Vec<_> = (0..count)
+            .map(|_| match rng.below(10) {
+                0..=3 => TestFrame::Valid,
+                4 => TestFrame::Empty,
+                5 => TestFrame::Oversized(MAX_MESSAGE_SIZE as u32 + 1 + rng.below(u32::MAX as u64 - MAX_MESSAGE_SIZE as u64) as u32),
+                _ => {
+                    let len = 1 + rng.below(64) as usize;
+                    let mut bytes: Vec<u8> = (0..len).map(|_| rng.next() as u8).collect();
+                    bytes[0] = 0xFF;
+                    TestFrame::Garbage(bytes)
+                }
+            })
+            .collect();
+
+        // Sometimes the stream ends in the middle of a last frame
+        match rng.below(4) {
+            0 => {
+                let declared = 1 + rng.below(MAX_MESSAGE_SIZE as u64) as u32;
+                let sent = rng.below(declared.min(256) as u64) as usize;
+                frames.push(TestFrame::Truncated { declared, sent });
+            }
+            1 => frames.push(TestFrame::TruncatedPrefix(1 + rng.below(3) as usize)),
+            _ => {}
+        }
+        frames
+    }
+
+    /// How many replies the server sends before it stops reading, by the
+    /// rules `serve_connection` documents
+    fn expected_replies(frames: &[TestFrame]) -> usize {
+        let mut replies = 0;
+        let mut consecutive_malformed = 0;
+        for frame in frames {
+            match frame {
+                TestFrame::Valid => {
+                    replies += 1;
+                    consecutive_malformed = 0;
+                }
+                TestFrame::Empty | TestFrame::Garbage(_) => {
+                    replies += 1;
+                    consecutive_malformed += 1;
+                    if consecutive_malformed >= MAX_CONSECUTIVE_MALFORMED {
+                        return replies;
+                    }
+                }
+                TestFrame::Oversized(_) => return replies + 1,
+                TestFrame::Truncated { .. } | TestFrame::TruncatedPrefix(_) => return replies,
+            }
+        }
+        replies
+    }
+
+    fn server() -> IpcServer {
+        let path = std::env::temp_dir().join(format!("notecognito-ipc-test-{}.json", std::process::id()));
+        IpcServer::new(Arc::new(Mutex::new(ConfigManager::with_path(path).unwrap())))
+    }
+
+    /// Writes `bytes` and ends the stream, then collects every reply until the
+    /// server closes the connection
+    async fn serve(bytes: Vec<u8>) -> Vec<IpcMessage> {
+        serve_with(server(), bytes).await
+    }
+
+    async fn serve_with(server: IpcServer, bytes: Vec<u8>) -> Vec<IpcMessage> {
+        let (mut client, stream): (DuplexStream, DuplexStream) = tokio::io::duplex(1 << 20);
+        let connection = tokio::spawn(async move { serve_connection(stream, &server).await });
+
+        client.write_all(&bytes).await.unwrap();
+        client.shutdown().await.unwrap();
+
+        let mut replies = Vec::new();
+        loop {
+            match read_frame(&mut client).await.unwrap() {
+                Frame::Message(message) => replies.push(*message),
+                Frame::Closed => break,
+                Frame::Malformed { reason, .. } => panic!("Server sent a malformed reply: {}", reason),
+                Frame::Oversized(len) => panic!("Server sent a {} byte reply", len),
+            }
+        }
+
+        connection.await.unwrap().unwrap();
+        replies
+    }
+
+    fn is_malformed_error(message: &IpcMessage) -> bool {
+        matches!(
+            message.message_type,
+            IpcMessageType::Error { code: Some(IpcErrorCode::MalformedMessage), .. }
+        )
+    }
+
+    async fn read_one(bytes: &[u8]) -> Frame {
+        let mut reader = bytes;
+        read_frame(&mut reader).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn read_frame_rejects_an_empty_frame() {
+        assert!(matches!(read_one(&0u32.to_le_bytes()).await, Frame::Malformed { id: None, .. }));
+    }
+
+    #[tokio::test]
+    async fn read_frame_rejects_a_length_over_the_limit_without_reading_it() {
+        let len = MAX_MESSAGE_SIZE as u32 + 1;
+        assert!(matches!(read_one(&len.to_le_bytes()).await, Frame::Oversized(n) if n == len as usize));
+    }
+
+    #[tokio::test]
+    async fn read_frame_reads_a_frame_at_the_limit() {
+        // A JSON string padded out to exactly the limit parses as far as the type check
+        let mut body = vec![b' '; MAX_MESSAGE_SIZE];
+        body[0] = b'1';
+        let mut bytes = (MAX_MESSAGE_SIZE as u32).to_le_bytes().to_vec();
+        bytes.extend_from_slice(&body);
+
+        assert!(matches!(read_one(&bytes).await, Frame::Malformed { .. }));
+    }
+
+    #[tokio::test]
+    async fn read_frame_treats_a_short_body_as_closed() {
+        let mut bytes = 100u32.to_le_bytes().to_vec();
+        bytes.extend_from_slice(b"{\"id\":");
+
+        assert!(matches!(read_one(&bytes).await, Frame::Closed));
+    }
+
+    #[tokio::test]
+    async fn read_frame_treats_a_short_prefix_as_closed() {
+        for len in 0..4 {
+            assert!(matches!(read_one(&[1, 0, 0, 0][..len]).await, Frame::Closed));
+        }
+    }
+
+    #[tokio::test]
+    async fn read_frame_recovers_the_id_of_a_bad_message() {
+        let body = br#"{"id":"abc","type":"NoSuchMessage"}"#;
+        let mut bytes = (body.len() as u32).to_le_bytes().to_vec();
+        bytes.extend_from_slice(body);
+
+        assert!(matches!(read_one(&bytes).await, Frame::Malformed { id: Some(id), .. } if id == "abc"));
+    }
+
+    #[tokio::test]
+    async fn connection_closes_after_too_many_malformed_frames() {
+        let mut bytes = Vec::new();
+        for _ in 0..MAX_CONSECUTIVE_MALFORMED + 2 {
+            TestFrame::Empty.encode(&mut bytes);
+        }
+        TestFrame::Valid.encode(&mut bytes);
+
+        let replies = serve(bytes).await;
+
+        assert_eq!(replies.len(), MAX_CONSECUTIVE_MALFORMED as usize);
+        assert!(replies.iter().all(is_malformed_error));
+    }
+
+    #[tokio::test]
+    async fn valid_message_resets_the_malformed_count() {
+        let mut bytes = Vec::new();
+        for _ in 0..2 {
+            for _ in 0..MAX_CONSECUTIVE_MALFORMED - 1 {
+                TestFrame::Garbage(vec![0xFF]).encode(&mut bytes);
+            }
+            TestFrame::Valid.encode(&mut bytes);
+        }
+
+        let replies = serve(bytes).await;
+
+        assert_eq!(replies.len(), 2 * MAX_CONSECUTIVE_MALFORMED as usize);
+        assert!(matches!(replies.last().unwrap().message_type, IpcMessageType::ConfigurationResponse { .. }));
+    }
+
+    #[tokio::test]
+    async fn oversized_frame_is_answered_then_closes_the_connection() {
+        let mut bytes = Vec::new();
+        TestFrame::Oversized(u32::MAX).encode(&mut bytes);
+        TestFrame::Valid.encode(&mut bytes);
+
+        let replies = serve(bytes).await;
+
+        assert_eq!(replies.len(), 1);
+        assert!(is_malformed_error(&replies[0]));
+    }
+
+    #[tokio::test]
+    async fn random_streams_get_the_replies_the_framing_rules_promise() {
+        for seed in 1..=200u64 {
+            let mut rng = Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            let frames = random_frames(&mut rng);
+            let mut bytes = Vec::new();
+            for frame in &frames {
+                frame.encode(&mut bytes);
+            }
+
+            let replies = serve(bytes).await;
+
+            assert_eq!(replies.len(), expected_replies(&frames), "seed {}: {:?}", seed, frames);
+            for (reply, frame) in replies.iter().zip(&frames) {
+                match frame {
+                    TestFrame::Valid => assert_eq!(reply.id, "ok", "seed {}", seed),
+                    _ => assert!(is_malformed_error(reply), "seed {}: {:?}", seed, reply),
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn a_failed_save_is_answered_and_the_connection_kept() {
+        let missing = std::env::temp_dir()
+            .join(format!("notecognito-ipc-missing-{}", std::process::id()))
+            .join("config.json");
+        let server = IpcServer::new(Arc::new(Mutex::new(ConfigManager::with_path(missing).unwrap())));
+
+        let mut bytes = Vec::new();
+        let notecard = Notecard::new(NotecardId::new(1).unwrap(), "Can't be saved".to_string());
+        for message_type in [IpcMessageType::UpdateNotecard { notecard }, IpcMessageType::GetConfiguration] {
+            send_message(&mut bytes, &IpcMessage::new(message_type)).await.unwrap();
+        }
+
+        let replies = serve_with(server, bytes).await;
+
+        assert_eq!(replies.len(), 2);
+        assert!(
+            matches!(replies[0].message_type, IpcMessageType::Error { error_code: Some(ErrorCode::Io), .. }),
+            "{:?}",
+            replies[0].message_type
+        );
+        assert!(matches!(replies[1].message_type, IpcMessageType::ConfigurationResponse { .. }));
+    }
+
+    #[tokio::test]
+    async fn platform_info_without_a_platform_is_conservative() {
+        let mut bytes = Vec::new();
+        send_message(&mut bytes, &IpcMessage::new(IpcMessageType::GetPlatformInfo)).await.unwrap();
+
+        let replies = serve(bytes).await;
+
+        match &replies[..] {
+            [IpcMessage { message_type: IpcMessageType::PlatformInfoResponse { capabilities, launch_on_startup }, .. }] => {
+                assert_eq!(*capabilities, PlatformCapabilities::conservative());
+                assert_eq!(*launch_on_startup, None);
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+}
@@ -0,0 +1,872 @@
+use serde::{Deserialize, Serialize};
+use crate::config::DisplayProperties;
+use crate::notecard::NotecardId;
+
+/// Space between tiled notecards, and between them and the work area's edges
+const TILE_GAP: i32 = 16;
+
+/// How far each cascaded notecard sits right of and below the one before
+const CASCADE_STEP: i32 = 32;
+
+/// A rectangle as (x, y, width, height)
+pub type Rect = (i32, i32, u32, u32);
+
+/// Geometry of a single display
+///
+/// Coordinates are in the platform's virtual screen space with y growing
+/// downwards. On Windows and macOS the primary screen's top-left corner is the
+/// origin, so secondary monitors left of or above it have negative origins.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScreenInfo {
+    /// Position in the platform's monitor enumeration order
+    pub index: usize,
+    /// Full bounds of the screen
+    pub frame: Rect,
+    /// Bounds minus the taskbar, dock and menu bar
+    pub work_area: Rect,
+    /// Physical pixels per logical pixel
+    pub scale_factor: f64,
+    /// Whether this is the primary screen
+    pub is_primary: bool,
+}
+
+impl ScreenInfo {
+    /// Whether a point in virtual screen space lies within the screen's frame
+    pub fn contains(&self, point: (i32, i32)) -> bool {
+        let (x, y, width, height) = self.frame;
+        (point.0 as i64) >= x as i64
+            && (point.0 as i64) < x as i64 + width as i64
+            && (point.1 as i64) >= y as i64
+            && (point.1 as i64) < y as i64 + height as i64
+    }
+}
+
+/// Unit of the positions and sizes in `DisplayProperties`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PixelUnits {
+    /// Points that are scaled by the screen's `scale_factor`, so a notecard
+    /// looks the same size on every display
+    #[default]
+    Logical,
+    /// Device pixels used as-is, as configurations written before units
+    /// existed were interpreted
+    Physical,
+}
+
+impl PixelUnits {
+    /// Units assumed for configurations that don't say
+    pub(crate) fn legacy() -> Self {
+        PixelUnits::Physical
+    }
+}
+
+/// A window's bounds in device pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhysicalRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Point of the work area a notecard is pinned to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Anchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    CenterLeft,
+    Center,
+    CenterRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+/// Where a notecard opens
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Placement {
+    /// At `position`, relative to `anchor` when one is set
+    #[default]
+    Fixed,
+    /// With its top-left corner at the mouse cursor moved by `offset`, on the
+    /// screen under the cursor; fixed where the platform can't report the cursor
+    AtCursor { offset: (i32, i32) },
+}
+
+/// How a notecard window is sized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizeMode {
+    /// Exactly `size`
+    #[default]
+    Fixed,
+    /// Just big enough for the content, no wider than `size`'s width and no
+    /// bigger than the work area, as `fit_content_size` works out
+    FitContent,
+}
+
+/// Screen a notecard is placed on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MonitorSelector {
+    /// The screen containing the mouse cursor, falling back to the primary
+    /// one where the platform doesn't report the cursor
+    #[default]
+    Cursor,
+    /// The primary screen
+    Primary,
+    /// The screen at this `ScreenInfo::index`, falling back to the primary one
+    Index(usize),
+}
+
+impl MonitorSelector {
+    /// Picks the selected screen, falling back to the primary and then the first one
+    pub fn select<'a>(&self, screens: &'a [ScreenInfo]) -> Option<&'a ScreenInfo> {
+        self.select_at(screens, None)
+    }
+
+    /// Like `select`, with `cursor` in virtual screen space for `Cursor`
+    pub fn select_at<'a>(&self, screens: &'a [ScreenInfo], cursor: Option<(i32, i32)>) -> Option<&'a ScreenInfo> {
+        let primary = || screens.iter().find(|s| s.is_primary).or_else(|| screens.first());
+
+        match self {
+            MonitorSelector::Cursor => cursor
+                .and_then(|cursor| screens.iter().find(|s| s.contains(cursor)))
+                .or_else(primary),
+            MonitorSelector::Primary => primary(),
+            MonitorSelector::Index(index) => {
+                screens.iter().find(|s| s.index == *index).or_else(primary)
+            }
+        }
+    }
+}
+
+/// Resolves the top-left corner of a notecard window
+///
+/// Without an anchor, `position` is relative to the selected screen's origin,
+/// which keeps absolute positions working on the primary screen. With an
+/// anchor, `position` is an offset that moves the window inwards from the
+/// anchored edges of the work area (and is added as-is on centered axes).
+/// With no screens to place against, `position` is used unchanged.
+pub fn place_window(properties: &DisplayProperties, screens: &[ScreenInfo]) -> (i32, i32) {
+    place_window_at(properties, screens, None)
+}
+
+/// Like `place_window`, with the cursor position for `MonitorSelector::Cursor`
+/// and `Placement::AtCursor`
+///
+/// On the screen under the cursor, a position without an anchor is also
+/// clamped into the work area, since it was likely chosen on another screen.
+pub fn place_window_at(properties: &DisplayProperties, screens: &[ScreenInfo], cursor: Option<(i32, i32)>) -> (i32, i32) {
+    if let (Placement::AtCursor { offset }, Some(cursor)) = (properties.placement, cursor) {
+        return (cursor.0.saturating_add(offset.0), cursor.1.saturating_add(offset.1));
+    }
+
+    let Some(screen) = properties.monitor.select_at(screens, cursor) else {
+        return properties.position;
+    };
+
+    let (offset_x, offset_y) = properties.position;
+    let (area_x, area_y, area_width, area_height) = screen.work_area;
+    let (width, height) = (properties.size.0 as i32, properties.size.1 as i32);
+    let free_x = area_width as i32 - width;
+    let free_y = area_height as i32 - height;
+
+    let Some(anchor) = properties.anchor else {
+        let (x, y) = (screen.frame.0 + offset_x, screen.frame.1 + offset_y);
+        if properties.monitor != MonitorSelector::Cursor {
+            return (x, y);
+        }
+
+        // A window bigger than the work area keeps its top-left corner inside
+        return (
+            x.min(area_x + free_x).max(area_x),
+            y.min(area_y + free_y).max(area_y),
+        );
+    };
+
+    let x = match anchor {
+        Anchor::TopLeft | Anchor::CenterLeft | Anchor::BottomLeft => area_x + offset_x,
+        Anchor::TopCenter | Anchor::Center | Anchor::BottomCenter => area_x + free_x / 2 + offset_x,
+        Anchor::TopRight | Anchor::CenterRight | Anchor::BottomRight => area_x + free_x - offset_x,
+    };
+
+    let y = match anchor {
+        Anchor::TopLeft | Anchor::TopCenter | Anchor::TopRight => area_y + offset_y,
+        Anchor::CenterLeft | Anchor::Center | Anchor::CenterRight => area_y + free_y / 2 + offset_y,
+        Anchor::BottomLeft | Anchor::BottomCenter | Anchor::BottomRight => area_y + free_y - offset_y,
+    };
+
+    (x, y)
+}
+
+/// Like `place_window_at`, but returns the window's whole bounds, moved and
+/// if need be shrunk to lie within the selected screen's work area
+///
+/// Positions saved on a bigger or differently arranged display would
+/// otherwise open the window partly or entirely off screen, as would a
+/// window shown at a cursor near the edge.
+pub fn place_window_within(properties: &DisplayProperties, screens: &[ScreenInfo], cursor: Option<(i32, i32)>) -> Rect {
+    let (x, y) = place_window_at(properties, screens, cursor);
+    let rect = (x, y, properties.size.0, properties.size.1);
+
+    match placement_screen(properties, screens, cursor) {
+        Some(screen) => fit_to_screen(rect, screen),
+        None => rect,
+    }
+}
+
+/// The screen a window ends up on; a window at the cursor goes on the
+/// cursor's screen whatever `monitor` says
+fn placement_screen<'a>(
+    properties: &DisplayProperties,
+    screens: &'a [ScreenInfo],
+    cursor: Option<(i32, i32)>,
+) -> Option<&'a ScreenInfo> {
+    let monitor = match properties.placement {
+        Placement::AtCursor { .. } if cursor.is_some() => MonitorSelector::Cursor,
+        _ => properties.monitor,
+    };
+    monitor.select_at(screens, cursor)
+}
+
+/// Size of a window holding text laid out at `text`, with `padding` on every
+/// side, no wider than `max_width` and no bigger than `work_area`
+///
+/// Like `DisplayProperties::clamp`, it's never under `MIN_DIMENSION` either
+/// way, unless the limits are. An empty work area doesn't limit the size.
+pub fn fit_content_size(text: (u32, u32), padding: u32, max_width: u32, work_area: Rect) -> (u32, u32) {
+    let padded = |length: u32| length.saturating_add(padding.saturating_mul(2));
+    let (max_width, max_height) = match work_area {
+        (_, _, width, height) if width > 0 && height > 0 => (max_width.min(width), height),
+        _ => (max_width, u32::MAX),
+    };
+    let min = DisplayProperties::MIN_DIMENSION;
+
+    (
+        padded(text.0).min(max_width).max(min.min(max_width)),
+        padded(text.1).min(max_height).max(min.min(max_height)),
+    )
+}
+
+/// Width text may wrap to in a window no wider than `max_width` or
+/// `work_area`, with `padding` on each side; the inverse of the width
+/// `fit_content_size` pads it back out to
+fn text_wrap_width(max_width: u32, padding: u32, work_area: Rect) -> u32 {
+    let window_width = match work_area.2 {
+        0 => max_width,
+        area_width => max_width.min(area_width),
+    };
+    window_width.saturating_sub(padding.saturating_mul(2))
+}
+
+/// Moves and, where it's larger, shrinks `rect` so it lies within `area`
+///
+/// A rectangle wider or taller than the area takes the area's full width or
+/// height. An empty area leaves `rect` as it is.
+pub fn fit_to_area(rect: Rect, area: Rect) -> Rect {
+    let (x, y, width, height) = rect;
+    let (area_x, area_y, area_width, area_height) = area;
+    if area_width == 0 || area_height == 0 {
+        return rect;
+    }
+
+    let width = width.min(area_width);
+    let height = height.min(area_height);
+
+    // In i64 so origins near the ends of i32 can't overflow
+    let clamp = |origin: i32, start: i32, free: u32| {
+        (origin as i64).clamp(start as i64, start as i64 + free as i64) as i32
+    };
+
+    (
+        clamp(x, area_x, area_width - width),
+        clamp(y, area_y, area_height - height),
+        width,
+        height,
+    )
+}
+
+/// `fit_to_area` against a screen's work area, logging when the window had to move
+fn fit_to_screen(rect: Rect, screen: &ScreenInfo) -> Rect {
+    let fitted = fit_to_area(rect, screen.work_area);
+    if fitted != rect {
+        tracing::info!(
+            "Window {:?} doesn't fit the work area {:?} of screen {}, using {:?}",
+            rect,
+            screen.work_area,
+            screen.index,
+            fitted
+        );
+    }
+    fitted
+}
+
+/// Lays notecards of the given sizes out together on `screen`, for showing
+/// them all at once, and returns each one's top-left corner in virtual
+/// screen space, in the order given
+///
+/// The notecards are tiled in a grid of as few columns as lets every one fit
+/// its cell, filled row by row. When they're too big to tile, they cascade
+/// from the work area's top-left corner instead, starting over a little to
+/// the right whenever the next one would run off the bottom or right edge, so
+/// each card's top-left corner stays visible. Either way every card is then
+/// kept inside the work area as `fit_to_area` keeps it.
+pub fn cascade_layout(cards: &[(NotecardId, (u32, u32))], screen: &ScreenInfo) -> Vec<(NotecardId, (i32, i32))> {
+    let (area_x, area_y, area_width, area_height) = screen.work_area;
+    let count = cards.len() as i64;
+    let gap = TILE_GAP as i64;
+
+    // In i64 so huge sizes and work areas can't overflow
+    let tiled = (1..=count).find_map(|columns| {
+        let rows = (count + columns - 1) / columns;
+        let cell_width = (area_width as i64 - gap * (columns + 1)) / columns;
+        let cell_height = (area_height as i64 - gap * (rows + 1)) / rows;
+        let fits = cards
+            .iter()
+            .all(|(_, (width, height))| *width as i64 <= cell_width && *height as i64 <= cell_height);
+        fits.then_some((columns, cell_width, cell_height))
+    });
+
+    let mut cascade = (0i64, 0i64);
+    let mut restarts = 0i64;
+    let positions = cards.iter().enumerate().map(|(i, (id, (width, height)))| {
+        let (x, y) = match tiled {
+            Some((columns, cell_width, cell_height)) => {
+                let (column, row) = (i as i64 % columns, i as i64 / columns);
+                (
+                    area_x as i64 + gap + column * (cell_width + gap),
+                    area_y as i64 + gap + row * (cell_height + gap),
+                )
+            }
+            None => {
+                let step = CASCADE_STEP as i64;
+                let mut x = area_x as i64 + gap + restarts * step + cascade.0;
+                let mut y = area_y as i64 + gap + cascade.1;
+                let overflows = x + *width as i64 > area_x as i64 + area_width as i64
+                    || y + *height as i64 > area_y as i64 + area_height as i64;
+                if overflows && cascade != (0, 0) {
+                    restarts += 1;
+                    cascade = (0, 0);
+                    x = area_x as i64 + gap + restarts * step;
+                    y = area_y as i64 + gap;
+                }
+                cascade = (cascade.0 + step, cascade.1 + step);
+                (x, y)
+            }
+        };
+
+        let clamp = |value: i64| value.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+        let (x, y, _, _) = fit_to_area((clamp(x), clamp(y), *width, *height), screen.work_area);
+        (*id, (x, y))
+    });
+
+    positions.collect()
+}
+
+/// Finds the screen a window at `top_left` is on and its position relative to
+/// that screen, the inverse of `place_window` for `MonitorSelector::Index`
+/// without an anchor
+///
+/// The screen holding the window's center wins, then the one holding its
+/// top-left corner, then the primary screen. Returns `None` without screens.
+pub fn locate_window(screens: &[ScreenInfo], top_left: (i32, i32), size: (u32, u32)) -> Option<(usize, (i32, i32))> {
+    let center = (
+        top_left.0.saturating_add((size.0 / 2) as i32),
+        top_left.1.saturating_add((size.1 / 2) as i32),
+    );
+
+    let screen = screens
+        .iter()
+        .find(|s| s.contains(center))
+        .or_else(|| screens.iter().find(|s| s.contains(top_left)))
+        .or_else(|| MonitorSelector::Primary.select(screens))?;
+
+    Some((screen.index, (top_left.0 - screen.frame.0, top_left.1 - screen.frame.1)))
+}
+
+/// Converts the configured position and size to device pixels for a screen
+/// with `scale_factor`
+///
+/// The position stays relative to whatever it was configured against; use
+/// `place_window_physical` to get the final window bounds.
+pub fn to_physical(properties: &DisplayProperties, scale_factor: f64) -> PhysicalRect {
+    let scale = properties.scale_for(scale_factor);
+    let scale_length = |length: u32| (length as f64 * scale).round() as u32;
+    let scale_offset = |offset: i32| (offset as f64 * scale).round() as i32;
+
+    PhysicalRect {
+        x: scale_offset(properties.position.0),
+        y: scale_offset(properties.position.1),
+        width: scale_length(properties.size.0),
+        height: scale_length(properties.size.1),
+    }
+}
+
+/// Resolves a notecard window's bounds in device pixels
+///
+/// For platforms whose screen coordinates are device pixels, like Windows
+/// with per-monitor DPI awareness. The position and size are scaled by the
+/// selected screen's `scale_factor` before placing, so anchoring uses the
+/// window's real size. Like `place_window_within`, the result is kept inside
+/// the screen's work area.
+pub fn place_window_physical(properties: &DisplayProperties, screens: &[ScreenInfo]) -> PhysicalRect {
+    place_window_physical_at(properties, screens, None)
+}
+
+/// Like `place_window_physical`, with the cursor position in device pixels
+/// for `MonitorSelector::Cursor` and `Placement::AtCursor`
+///
+/// The window is scaled for the screen it ends up on, so on a mixed-DPI
+/// desktop it keeps the same apparent size on every monitor.
+pub fn place_window_physical_at(
+    properties: &DisplayProperties,
+    screens: &[ScreenInfo],
+    cursor: Option<(i32, i32)>,
+) -> PhysicalRect {
+    let screen = placement_screen(properties, screens, cursor);
+    let scale_factor = screen.map_or(1.0, |s| s.scale_factor);
+    let rect = to_physical(properties, scale_factor);
+
+    let scale = properties.scale_for(scale_factor);
+    let placement = match properties.placement {
+        Placement::AtCursor { offset } => Placement::AtCursor {
+            offset: ((offset.0 as f64 * scale).round() as i32, (offset.1 as f64 * scale).round() as i32),
+        },
+        placement => placement,
+    };
+
+    let scaled = DisplayProperties {
+        position: (rect.x, rect.y),
+        size: (rect.width, rect.height),
+        placement,
+        ..properties.clone()
+    };
+    let (x, y) = place_window_at(&scaled, screens, cursor);
+
+    let Some(screen) = screen else {
+        return PhysicalRect { x, y, ..rect };
+    };
+    let (x, y, width, height) = fit_to_screen((x, y, rect.width, rect.height), screen);
+    PhysicalRect { x, y, width, height }
+}
+
+/// Like `place_window_physical_at`, sized to the content with
+/// `SizeMode::FitContent`
+///
+/// `measure` gets the width the text may wrap to and the scale factor of the
+/// screen the window ends up on, and returns the laid-out text's size, all in
+/// device pixels. The window's size comes from `fit_content_size` with
+/// `padding` device pixels on each side, and is settled before the window is
+/// anchored, so a card pinned to the bottom or right edge stays on screen.
+pub fn place_fitted_window_physical_at(
+    properties: &DisplayProperties,
+    screens: &[ScreenInfo],
+    cursor: Option<(i32, i32)>,
+    padding: u32,
+    measure: impl FnOnce(u32, f64) -> (u32, u32),
+) -> PhysicalRect {
+    if properties.size_mode != SizeMode::FitContent {
+        return place_window_physical_at(properties, screens, cursor);
+    }
+
+    let screen = placement_screen(properties, screens, cursor);
+    let scale_factor = screen.map_or(1.0, |s| s.scale_factor);
+    let work_area = screen.map_or((0, 0, 0, 0), |s| s.work_area);
+    let scale = properties.scale_for(scale_factor);
+
+    let max_width = (properties.size.0 as f64 * scale).round() as u32;
+    let text = measure(text_wrap_width(max_width, padding, work_area), scale_factor);
+    let (width, height) = fit_content_size(text, padding, max_width, work_area);
+
+    // Back in configured units, rounded up so scaling again can't cut off the last line
+    let unscale = |length: u32| (length as f64 / scale).ceil() as u32;
+    let fitted = DisplayProperties {
+        size: (unscale(width), unscale(height)),
+        ..properties.clone()
+    };
+    place_window_physical_at(&fitted, screens, cursor)
+}
+
+/// Converts a point in a bottom-left space, such as Cocoa's global one whose
+/// y grows upwards from the bottom of the primary screen, into the top-left
+/// space of `ScreenInfo`
+///
+/// Screens above the primary one end up with negative y, screens below it
+/// with y past the primary's height.
+pub fn from_bottom_left_point(point: (f64, f64), primary_height: f64) -> (i32, i32) {
+    (point.0.floor() as i32, (primary_height - point.1).floor() as i32)
+}
+
+/// Converts a rectangle in a bottom-left space, as its bottom-left origin
+/// and size, into a top-left `Rect`
+pub fn from_bottom_left_rect(origin: (f64, f64), size: (f64, f64), primary_height: f64) -> Rect {
+    (
+        origin.0 as i32,
+        (primary_height - origin.1 - size.1) as i32,
+        size.0 as u32,
+        size.1 as u32,
+    )
+}
+
+/// The bottom-left origin of a window `height` tall whose top-left corner is
+/// at `position`, the inverse of `from_bottom_left_rect`
+pub fn to_bottom_left_origin(position: (i32, i32), height: u32, primary_height: f64) -> (f64, f64) {
+    (position.0 as f64, primary_height - position.1 as f64 - height as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 1920x1080 primary screen, a 2x screen to its left and reaching above
+    /// it, and a screen directly above it
+    fn screens() -> Vec<ScreenInfo> {
+        vec![
+            ScreenInfo {
+                index: 0,
+                frame: (0, 0, 1920, 1080),
+                work_area: (0, 0, 1920, 1040),
+                scale_factor: 1.0,
+                is_primary: true,
+            },
+            ScreenInfo {
+                index: 1,
+                frame: (-2560, -200, 2560, 1440),
+                work_area: (-2560, -200, 2560, 1400),
+                scale_factor: 2.0,
+                is_primary: false,
+            },
+            ScreenInfo {
+                index: 2,
+                frame: (0, -1080, 1920, 1080),
+                work_area: (0, -1080, 1920, 1080),
+                scale_factor: 1.0,
+                is_primary: false,
+            },
+        ]
+    }
+
+    fn properties(monitor: MonitorSelector, position: (i32, i32)) -> DisplayProperties {
+        DisplayProperties { monitor, position, size: (400, 200), ..DisplayProperties::default() }
+    }
+
+    #[test]
+    fn position_is_relative_to_a_negative_origin() {
+        let screens = screens();
+
+        assert_eq!(place_window(&properties(MonitorSelector::Index(1), (100, 100)), &screens), (-2460, -100));
+        assert_eq!(place_window(&properties(MonitorSelector::Index(2), (100, 100)), &screens), (100, -980));
+    }
+
+    #[test]
+    fn locate_window_undoes_placement_on_a_negative_origin() {
+        let screens = screens();
+        let top_left = place_window(&properties(MonitorSelector::Index(1), (100, 100)), &screens);
+
+        assert_eq!(locate_window(&screens, top_left, (400, 200)), Some((1, (100, 100))));
+    }
+
+    #[test]
+    fn cursor_screen_with_negative_origin_is_picked_and_clamped() {
+        let screens = screens();
+        let cursor = Some((-1000, 300));
+
+        assert_eq!(
+            place_window_at(&properties(MonitorSelector::Cursor, (100, 100)), &screens, cursor),
+            (-2460, -100)
+        );
+        // Past the bottom-right of the work area, pulled back in
+        assert_eq!(
+            place_window_at(&properties(MonitorSelector::Cursor, (2500, 1400)), &screens, cursor),
+            (-400, 1000)
+        );
+    }
+
+    #[test]
+    fn anchors_measure_from_a_negative_work_area() {
+        let screens = screens();
+        let anchored = |anchor, position| DisplayProperties {
+            anchor: Some(anchor),
+            ..properties(MonitorSelector::Index(1), position)
+        };
+
+        assert_eq!(place_window(&anchored(Anchor::TopLeft, (10, 20)), &screens), (-2550, -180));
+        assert_eq!(place_window(&anchored(Anchor::BottomRight, (10, 20)), &screens), (-410, 980));
+        assert_eq!(place_window(&anchored(Anchor::Center, (0, 0)), &screens), (-1480, 400));
+    }
+
+    #[test]
+    fn at_cursor_follows_a_negative_cursor() {
+        let screens = screens();
+        let at_cursor = DisplayProperties {
+            placement: Placement::AtCursor { offset: (10, 10) },
+            ..properties(MonitorSelector::Primary, (0, 0))
+        };
+
+        assert_eq!(place_window_at(&at_cursor, &screens, Some((-30, -150))), (-20, -140));
+        // On the screen under the cursor, not the primary one, and kept inside it
+        assert_eq!(place_window_within(&at_cursor, &screens, Some((-30, -150))), (-400, -140, 400, 200));
+    }
+
+    #[test]
+    fn within_keeps_a_window_on_the_screen_above() {
+        let screens = screens();
+
+        assert_eq!(
+            place_window_within(&properties(MonitorSelector::Index(2), (100, 2000)), &screens, None),
+            (100, -200, 400, 200)
+        );
+    }
+
+    #[test]
+    fn physical_placement_scales_on_a_negative_origin() {
+        let screens = screens();
+
+        assert_eq!(
+            place_window_physical(&properties(MonitorSelector::Index(1), (100, 100)), &screens),
+            PhysicalRect { x: -2360, y: 0, width: 800, height: 400 }
+        );
+    }
+
+    #[test]
+    fn physical_at_cursor_scales_the_offset_and_stays_inside() {
+        let screens = screens();
+        let at_cursor = DisplayProperties {
+            placement: Placement::AtCursor { offset: (10, 10) },
+            ..properties(MonitorSelector::Primary, (0, 0))
+        };
+
+        assert_eq!(
+            place_window_physical_at(&at_cursor, &screens, Some((-100, -100))),
+            PhysicalRect { x: -800, y: -80, width: 800, height: 400 }
+        );
+    }
+
+    #[test]
+    fn a_config_saved_before_units_keeps_its_device_pixels() {
+        let mut json = serde_json::to_value(DisplayProperties {
+            position: (100, 50),
+            size: (400, 200),
+            ..DisplayProperties::default()
+        })
+        .unwrap();
+        json.as_object_mut().unwrap().remove("units");
+
+        let legacy: DisplayProperties = serde_json::from_value(json).unwrap();
+        assert_eq!(legacy.units, PixelUnits::Physical);
+        assert_eq!(to_physical(&legacy, 2.0), PhysicalRect { x: 100, y: 50, width: 400, height: 200 });
+
+        let logical = DisplayProperties { units: PixelUnits::Logical, ..legacy };
+        assert_eq!(to_physical(&logical, 2.0), PhysicalRect { x: 200, y: 100, width: 800, height: 400 });
+    }
+
+    #[test]
+    fn fitted_window_is_anchored_on_a_negative_origin() {
+        let screens = screens();
+        let fitted = DisplayProperties {
+            size_mode: SizeMode::FitContent,
+            anchor: Some(Anchor::BottomRight),
+            ..properties(MonitorSelector::Index(1), (0, 0))
+        };
+
+        let mut measured = None;
+        let rect = place_fitted_window_physical_at(&fitted, &screens, None, 10, |wrap_width, scale_factor| {
+            measured = Some((wrap_width, scale_factor));
+            (300, 100)
+        });
+
+        assert_eq!(measured, Some((780, 2.0)));
+        assert_eq!(rect, PhysicalRect { x: -320, y: 1080, width: 320, height: 120 });
+    }
+
+    fn primary() -> ScreenInfo {
+        screens().remove(0)
+    }
+
+    fn cards(count: u8, size: (u32, u32)) -> Vec<(NotecardId, (u32, u32))> {
+        (1..=count).map(|value| (NotecardId::new(value).unwrap(), size)).collect()
+    }
+
+    fn corners(layout: Vec<(NotecardId, (i32, i32))>) -> Vec<(i32, i32)> {
+        layout.into_iter().map(|(_, corner)| corner).collect()
+    }
+
+    #[test]
+    fn cascade_of_nothing_is_empty() {
+        assert!(cascade_layout(&[], &primary()).is_empty());
+    }
+
+    #[test]
+    fn cards_that_fit_one_column_are_stacked() {
+        assert_eq!(
+            corners(cascade_layout(&cards(4, (400, 200)), &primary())),
+            [(16, 16), (16, 272), (16, 528), (16, 784)]
+        );
+    }
+
+    #[test]
+    fn taller_cards_tile_in_as_few_columns_as_fit() {
+        assert_eq!(
+            corners(cascade_layout(&cards(6, (400, 300)), &primary())),
+            [(16, 16), (968, 16), (16, 357), (968, 357), (16, 698), (968, 698)]
+        );
+    }
+
+    #[test]
+    fn cards_too_big_to_tile_cascade_and_start_over() {
+        assert_eq!(
+            corners(cascade_layout(&cards(5, (1000, 900)), &primary())),
+            [(16, 16), (48, 48), (80, 80), (112, 112), (48, 16)]
+        );
+    }
+
+    #[test]
+    fn cascade_keeps_cards_bigger_than_the_work_area_at_its_corner() {
+        assert_eq!(corners(cascade_layout(&cards(1, (3000, 2000)), &primary())), [(0, 0)]);
+    }
+
+    #[test]
+    fn cascade_follows_a_negative_work_area() {
+        let left = screens().remove(1);
+
+        assert_eq!(
+            corners(cascade_layout(&cards(2, (400, 200)), &left)),
+            [(-2544, -184), (-2544, 508)]
+        );
+    }
+
+    #[test]
+    fn cascade_keeps_the_ids_in_order() {
+        let given = [(NotecardId::new(7).unwrap(), (400, 200)), (NotecardId::new(2).unwrap(), (400, 200))];
+        let ids: Vec<_> = cascade_layout(&given, &primary()).into_iter().map(|(id, _)| id.value()).collect();
+
+        assert_eq!(ids, [7, 2]);
+    }
+
+    const WORK_AREA: Rect = (0, 0, 1920, 1040);
+
+    #[test]
+    fn fitted_size_is_the_text_plus_padding() {
+        assert_eq!(fit_content_size((300, 100), 10, 800, WORK_AREA), (320, 120));
+    }
+
+    #[test]
+    fn fitted_width_stops_at_the_configured_width() {
+        assert_eq!(fit_content_size((900, 100), 10, 800, WORK_AREA), (800, 120));
+    }
+
+    #[test]
+    fn fitted_size_stops_at_the_work_area() {
+        assert_eq!(fit_content_size((3000, 2000), 10, 4000, WORK_AREA), (1920, 1040));
+    }
+
+    #[test]
+    fn fitted_size_is_never_under_the_minimum() {
+        let min = DisplayProperties::MIN_DIMENSION;
+
+        assert_eq!(fit_content_size((0, 0), 0, 800, WORK_AREA), (min, min));
+        // Unless the limits are smaller still
+        assert_eq!(fit_content_size((0, 0), 0, 20, (0, 0, 1920, 30)), (20, 30));
+    }
+
+    #[test]
+    fn empty_work_area_only_limits_the_width() {
+        assert_eq!(fit_content_size((300, 5000), 10, 800, (0, 0, 0, 0)), (320, 5020));
+    }
+
+    #[test]
+    fn fitted_size_saturates_instead_of_overflowing() {
+        assert_eq!(fit_content_size((u32::MAX, u32::MAX), u32::MAX, u32::MAX, (0, 0, 0, 0)), (u32::MAX, u32::MAX));
+    }
+
+    #[test]
+    fn text_wraps_inside_the_padding_of_the_narrower_limit() {
+        assert_eq!(text_wrap_width(800, 10, WORK_AREA), 780);
+        assert_eq!(text_wrap_width(4000, 10, WORK_AREA), 1900);
+        assert_eq!(text_wrap_width(800, 10, (0, 0, 0, 0)), 780);
+        assert_eq!(text_wrap_width(15, 10, WORK_AREA), 0);
+    }
+
+    #[test]
+    fn fixed_size_cards_are_not_measured() {
+        let screens = screens();
+        let fixed = properties(MonitorSelector::Primary, (100, 100));
+
+        let rect = place_fitted_window_physical_at(&fixed, &screens, None, 10, |_, _| panic!("measured"));
+
+        assert_eq!(rect, place_window_physical(&fixed, &screens));
+    }
+
+    #[test]
+    fn fitted_size_round_trips_through_the_scale_without_cutting_text() {
+        let screens = vec![ScreenInfo { scale_factor: 1.5, ..primary() }];
+        let fitted = DisplayProperties { size_mode: SizeMode::FitContent, ..properties(MonitorSelector::Primary, (0, 0)) };
+
+        // 301 device pixels is 200.67 logical, which must come back as at least 301
+        let rect = place_fitted_window_physical_at(&fitted, &screens, None, 0, |_, _| (301, 101));
+
+        assert!(rect.width >= 301 && rect.width <= 302, "{:?}", rect);
+        assert!(rect.height >= 101 && rect.height <= 102, "{:?}", rect);
+    }
+
+    /// Cocoa's frames for a 1440x900 primary screen with one screen above
+    /// it, one below it and one to its left, as (origin, size) with y up
+    const PRIMARY_HEIGHT: f64 = 900.0;
+    const COCOA_PRIMARY: ((f64, f64), (f64, f64)) = ((0.0, 0.0), (1440.0, 900.0));
+    const COCOA_ABOVE: ((f64, f64), (f64, f64)) = ((0.0, 900.0), (1920.0, 1080.0));
+    const COCOA_BELOW: ((f64, f64), (f64, f64)) = ((-240.0, -1080.0), (1920.0, 1080.0));
+    const COCOA_LEFT: ((f64, f64), (f64, f64)) = ((-2560.0, -300.0), (2560.0, 1440.0));
+
+    #[test]
+    fn bottom_left_frames_flip_around_the_primary_screen() {
+        let flip = |(origin, size)| from_bottom_left_rect(origin, size, PRIMARY_HEIGHT);
+
+        assert_eq!(flip(COCOA_PRIMARY), (0, 0, 1440, 900));
+        assert_eq!(flip(COCOA_ABOVE), (0, -1080, 1920, 1080));
+        assert_eq!(flip(COCOA_BELOW), (-240, 900, 1920, 1080));
+        assert_eq!(flip(COCOA_LEFT), (-2560, -240, 2560, 1440));
+    }
+
+    #[test]
+    fn bottom_left_points_flip_onto_the_right_screen() {
+        let screens: Vec<_> = [COCOA_PRIMARY, COCOA_ABOVE, COCOA_BELOW, COCOA_LEFT]
+            .into_iter()
+            .enumerate()
+            .map(|(index, (origin, size))| {
+                let frame = from_bottom_left_rect(origin, size, PRIMARY_HEIGHT);
+                ScreenInfo { index, frame, work_area: frame, scale_factor: 1.0, is_primary: index == 0 }
+            })
+            .collect();
+        let screen_at = |point| {
+            let point = from_bottom_left_point(point, PRIMARY_HEIGHT);
+            screens.iter().find(|screen| screen.contains(point)).map(|screen| screen.index)
+        };
+
+        assert_eq!(from_bottom_left_point((100.0, 800.0), PRIMARY_HEIGHT), (100, 100));
+        assert_eq!(screen_at((100.0, 800.0)), Some(0));
+        assert_eq!(from_bottom_left_point((100.0, 1000.0), PRIMARY_HEIGHT), (100, -100));
+        assert_eq!(screen_at((100.0, 1000.0)), Some(1));
+        assert_eq!(from_bottom_left_point((100.0, -10.0), PRIMARY_HEIGHT), (100, 910));
+        assert_eq!(screen_at((100.0, -10.0)), Some(2));
+        assert_eq!(from_bottom_left_point((-10.0, 450.0), PRIMARY_HEIGHT), (-10, 450));
+        assert_eq!(screen_at((-10.0, 450.0)), Some(3));
+        // Fractional points round towards the pixel they're in
+        assert_eq!(from_bottom_left_point((-0.5, 899.5), PRIMARY_HEIGHT), (-1, 0));
+    }
+
+    #[test]
+    fn window_origins_flip_back_to_where_they_were_placed() {
+        for (origin, size) in [COCOA_PRIMARY, COCOA_ABOVE, COCOA_BELOW, COCOA_LEFT] {
+            let (x, y, _, height) = from_bottom_left_rect(origin, size, PRIMARY_HEIGHT);
+            assert_eq!(to_bottom_left_origin((x, y), height, PRIMARY_HEIGHT), origin);
+        }
+
+        // A 400x200 window 50 points below the top of the screen above
+        assert_eq!(to_bottom_left_origin((10, -1030), 200, PRIMARY_HEIGHT), (10.0, 1730.0));
+    }
+}
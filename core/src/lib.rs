@@ -2,16 +2,33 @@ pub mod config;
 pub mod notecard;
 pub mod ipc;
 pub mod platform;
+pub mod layout;
+pub mod markdown;
 pub mod error;
+pub mod permission;
+pub mod spacing;
+pub mod pages;
+pub mod color;
 
 #[cfg(feature = "ffi")]
 pub mod ffi;
 
-pub use config::{Config, ConfigManager, DisplayProperties};
-pub use notecard::{Notecard, NotecardId};
-pub use ipc::{IpcServer, IpcMessage, IpcMessageType};
-pub use platform::{PlatformInterface, HotkeyModifier};
-pub use error::{NotecognitoError, Result};
+pub use config::{ApplyMode, BackgroundStyle, BlurMaterial, Config, ConfigEvent, ConfigManager, DisplayProperties, ListenerId, StartupMethod};
+pub use notecard::{ContentLimits, Notecard, NotecardId, NotecardSummary, ValidationIssue};
+pub use ipc::{IpcServer, IpcMessage, IpcMessageType, IpcErrorCode};
+pub use platform::{
+    AppIdentity, PlatformInterface, PlatformCapabilities, HotkeyAction, HotkeyBinding, HotkeyChanges, HotkeyDebouncer, HotkeyKey, HotkeyModifier,
+    DismissReason, NotificationKind, PlatformEvent, ToggleOutcome, PLATFORM_EVENT_CAPACITY, notify_config_recovered,
+};
+pub use layout::{
+    cascade_layout, fit_content_size, fit_to_area, from_bottom_left_point, from_bottom_left_rect, locate_window, place_fitted_window_physical_at, place_window, place_window_at, place_window_physical, place_window_physical_at, place_window_within, to_bottom_left_origin, to_physical, Anchor, MonitorSelector, PhysicalRect, PixelUnits, Placement, ScreenInfo, SizeMode,
+};
+pub use markdown::{attribute_runs, heading_scale, parse_markdown, AttributeRun, Segment, SegmentStyle};
+pub use error::{ErrorCategory, ErrorCode, NotecognitoError, Result};
+pub use permission::{PermissionChanged, PermissionMonitor, PermissionState};
+pub use spacing::{apply_spacing, LineSpacing, SpacedLayout, SpacingParams};
+pub use pages::{split_pages, turn_page, PAGE_BREAK};
+pub use color::{parse_hex_color, Rgba};
 
 // Re-export commonly used items
 pub mod prelude {
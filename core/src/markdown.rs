@@ -0,0 +1,311 @@
+use serde::{Deserialize, Serialize};
+
+/// Shown in place of `-`, `*` and `+` list markers
+const BULLET: &str = "•";
+
+/// How a piece of a markdown notecard is drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentStyle {
+    pub bold: bool,
+    pub italic: bool,
+    /// Inline code, drawn monospaced
+    pub code: bool,
+    /// Heading level, 1 to 6, for text on a heading line
+    pub heading: Option<u8>,
+}
+
+/// A piece of a markdown notecard with the markers taken out
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Segment {
+    pub text: String,
+    pub style: SegmentStyle,
+}
+
+/// A stretch of the rendered text with one style, in UTF-16 code units as
+/// Cocoa and Win32 text APIs count them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttributeRun {
+    pub start: usize,
+    pub len: usize,
+    pub style: SegmentStyle,
+}
+
+/// Splits a notecard's content into styled segments
+///
+/// Understands the markdown that reads well on a small card: `#` headings,
+/// `-`, `*` and `+` bullets (drawn as `•`), `**bold**` or `__bold__`,
+/// `*italic*` or `_italic_`, and `` `code` ``. Markers without a partner on
+/// the same line, and `_` inside words as in `snake_case`, stay literal text.
+/// Line breaks are kept, so the rendered text has as many lines as the content.
+pub fn parse_markdown(content: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+
+    for (i, line) in content.split('\n').enumerate() {
+        if i > 0 {
+            push(&mut segments, "\n", SegmentStyle::default());
+        }
+
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let rest = &line[indent.len()..];
+
+        if let Some((level, text)) = heading(rest) {
+            let style = SegmentStyle { heading: Some(level), ..Default::default() };
+            parse_inline(&mut segments, text, style);
+        } else if let Some(text) = ["- ", "* ", "+ "].iter().find_map(|marker| rest.strip_prefix(marker)) {
+            push(&mut segments, &format!("{}{} ", indent, BULLET), SegmentStyle::default());
+            parse_inline(&mut segments, text, SegmentStyle::default());
+        } else {
+            parse_inline(&mut segments, line, SegmentStyle::default());
+        }
+    }
+
+    segments
+}
+
+/// Joins segments into the text to show and the style runs over it
+///
+/// Neighbouring segments with the same style share one run.
+pub fn attribute_runs(segments: &[Segment]) -> (String, Vec<AttributeRun>) {
+    let mut text = String::new();
+    let mut runs: Vec<AttributeRun> = Vec::new();
+    let mut start = 0;
+
+    for segment in segments {
+        let len = segment.text.encode_utf16().count();
+        text.push_str(&segment.text);
+
+        match runs.last_mut() {
+            Some(run) if run.style == segment.style => run.len += len,
+            _ if len > 0 => runs.push(AttributeRun { start, len, style: segment.style }),
+            _ => {}
+        }
+        start += len;
+    }
+
+    (text, runs)
+}
+
+/// Font size multiple for a heading level
+pub fn heading_scale(level: u8) -> f64 {
+    match level {
+        1 => 1.6,
+        2 => 1.35,
+        3 => 1.2,
+        _ => 1.1,
+    }
+}
+
+/// The level and text of a `#` heading line
+fn heading(line: &str) -> Option<(u8, &str)> {
+    let level = line.len() - line.trim_start_matches('#').len();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let text = line[level..].strip_prefix(' ')?;
+    Some((level as u8, text.trim_start()))
+}
+
+/// Appends `text` in `style`, merging it into the last segment when the
+/// style is the same
+fn push(segments: &mut Vec<Segment>, text: &str, style: SegmentStyle) {
+    if text.is_empty() {
+        return;
+    }
+    match segments.last_mut() {
+        Some(last) if last.style == style => last.text.push_str(text),
+        _ => segments.push(Segment { text: text.to_string(), style }),
+    }
+}
+
+/// Parses emphasis and code spans on one line, on top of `base`
+fn parse_inline(segments: &mut Vec<Segment>, line: &str, base: SegmentStyle) {
+    let mut style = base;
+    let mut literal_start = 0;
+    let mut i = 0;
+
+    while i < line.len() {
+        let rest = &line[i..];
+        let before = line[..i].chars().next_back();
+
+        let (marker_len, toggled) = if let Some(code) = rest.strip_prefix('`') {
+            // Code spans are literal up to the closing backtick
+            match code.find('`') {
+                Some(end) => {
+                    push(segments, &line[literal_start..i], style);
+                    push(segments, &code[..end], SegmentStyle { code: true, ..style });
+                    i += end + 2;
+                    literal_start = i;
+                    continue;
+                }
+                None => (0, style),
+            }
+        } else if rest.starts_with("**") || rest.starts_with("__") {
+            let marker = &rest[..2];
+            if style.bold || closes_later(&rest[2..], marker) {
+                (2, SegmentStyle { bold: !style.bold, ..style })
+            } else {
+                (0, style)
+            }
+        } else if rest.starts_with('*') || rest.starts_with('_') {
+            let marker = &rest[..1];
+            let after = rest[1..].chars().next();
+            let in_word = marker == "_"
+                && if style.italic {
+                    after.is_some_and(char::is_alphanumeric)
+                } else {
+                    before.is_some_and(char::is_alphanumeric)
+                };
+
+            if !in_word && (style.italic || closes_later(&rest[1..], marker)) {
+                (1, SegmentStyle { italic: !style.italic, ..style })
+            } else {
+                (0, style)
+            }
+        } else {
+            (0, style)
+        };
+
+        if marker_len == 0 {
+            i += rest.chars().next().map_or(1, char::len_utf8);
+            continue;
+        }
+
+        push(segments, &line[literal_start..i], style);
+        style = toggled;
+        i += marker_len;
+        literal_start = i;
+    }
+
+    push(segments, &line[literal_start..], style);
+}
+
+/// Whether an opening marker has a partner later on the line, with text between
+fn closes_later(rest: &str, marker: &str) -> bool {
+    rest.find(marker).is_some_and(|end| end > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAIN: SegmentStyle = SegmentStyle { bold: false, italic: false, code: false, heading: None };
+    const BOLD: SegmentStyle = SegmentStyle { bold: true, ..PLAIN };
+    const ITALIC: SegmentStyle = SegmentStyle { italic: true, ..PLAIN };
+    const CODE: SegmentStyle = SegmentStyle { code: true, ..PLAIN };
+
+    fn parsed(content: &str) -> Vec<(String, SegmentStyle)> {
+        parse_markdown(content).into_iter().map(|segment| (segment.text, segment.style)).collect()
+    }
+
+    fn segments(expected: &[(&str, SegmentStyle)]) -> Vec<(String, SegmentStyle)> {
+        expected.iter().map(|(text, style)| (text.to_string(), *style)).collect()
+    }
+
+    #[test]
+    fn plain_text_is_one_segment() {
+        assert_eq!(parsed("Just text"), segments(&[("Just text", PLAIN)]));
+        assert!(parsed("").is_empty());
+    }
+
+    #[test]
+    fn emphasis_markers_are_taken_out() {
+        assert_eq!(
+            parsed("**bold** and *italic*"),
+            segments(&[("bold", BOLD), (" and ", PLAIN), ("italic", ITALIC)])
+        );
+        assert_eq!(parsed("__bold__ _italic_"), segments(&[("bold", BOLD), (" ", PLAIN), ("italic", ITALIC)]));
+    }
+
+    #[test]
+    fn emphasis_nests() {
+        let both = SegmentStyle { bold: true, italic: true, ..PLAIN };
+
+        assert_eq!(
+            parsed("**bold *both* bold**"),
+            segments(&[("bold ", BOLD), ("both", both), (" bold", BOLD)])
+        );
+    }
+
+    #[test]
+    fn code_spans_are_literal() {
+        assert_eq!(parsed("run `a *b* c` now"), segments(&[("run ", PLAIN), ("a *b* c", CODE), (" now", PLAIN)]));
+    }
+
+    #[test]
+    fn unpartnered_markers_stay_literal() {
+        for line in ["2 * 3 = 6", "**open", "a ` tick", "snake_case_name", "**", "*"] {
+            assert_eq!(parsed(line), segments(&[(line, PLAIN)]), "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn markers_do_not_pair_across_lines() {
+        assert_eq!(parsed("*one\ntwo*"), segments(&[("*one\ntwo*", PLAIN)]));
+    }
+
+    #[test]
+    fn headings_carry_their_level() {
+        let h2 = SegmentStyle { heading: Some(2), ..PLAIN };
+
+        assert_eq!(
+            parsed("## Title **x**\nbody"),
+            segments(&[("Title ", h2), ("x", SegmentStyle { bold: true, ..h2 }), ("\nbody", PLAIN)])
+        );
+        assert_eq!(parsed("######   six"), segments(&[("six", SegmentStyle { heading: Some(6), ..PLAIN })]));
+    }
+
+    #[test]
+    fn not_quite_headings_stay_literal() {
+        for line in ["#NoSpace", "####### seven", " x # y"] {
+            assert_eq!(parsed(line), segments(&[(line, PLAIN)]), "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn bullets_become_dots_and_keep_their_indent() {
+        assert_eq!(
+            parsed("- one\n  * two\n+ *three*"),
+            segments(&[("• one\n  • two\n• ", PLAIN), ("three", ITALIC)])
+        );
+    }
+
+    #[test]
+    fn line_count_is_kept() {
+        let (text, _) = attribute_runs(&parse_markdown("# a\n\n- b\n**c**\n"));
+
+        assert_eq!(text, "a\n\n• b\nc\n");
+    }
+
+    #[test]
+    fn runs_count_utf16_code_units() {
+        let (text, runs) = attribute_runs(&parse_markdown("é😀 **x**"));
+
+        assert_eq!(text, "é😀 x");
+        assert_eq!(
+            runs,
+            [
+                AttributeRun { start: 0, len: 4, style: PLAIN },
+                AttributeRun { start: 4, len: 1, style: BOLD },
+            ]
+        );
+    }
+
+    #[test]
+    fn runs_merge_neighbours_with_the_same_style() {
+        let split = [
+            Segment { text: "a".to_string(), style: PLAIN },
+            Segment { text: String::new(), style: BOLD },
+            Segment { text: "b".to_string(), style: PLAIN },
+        ];
+
+        assert_eq!(attribute_runs(&split).1, [AttributeRun { start: 0, len: 2, style: PLAIN }]);
+    }
+
+    #[test]
+    fn heading_scale_shrinks_with_the_level() {
+        let scales: Vec<_> = (1..=6).map(heading_scale).collect();
+
+        assert!(scales.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert!(scales.iter().all(|scale| *scale > 1.0));
+    }
+}
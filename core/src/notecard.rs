@@ -1,14 +1,22 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use crate::config::DisplayProperties;
 use crate::error::{NotecognitoError, Result};
 
+/// Maximum number of characters in a notecard title
+const MAX_TITLE_LENGTH: usize = 40;
+/// Maximum number of characters in a notecard summary
+const MAX_SUMMARY_LENGTH: usize = 100;
+
 /// Represents a notecard ID (1-9)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u8")]
 pub struct NotecardId(u8);
 
 impl NotecardId {
     /// Creates a new NotecardId, validating that it's between 1 and 9
     pub fn new(id: u8) -> Result<Self> {
-        if id >= 1 && id <= 9 {
+        if (1..=9).contains(&id) {
             Ok(NotecardId(id))
         } else {
             Err(NotecognitoError::InvalidNotecardId(id))
@@ -42,12 +50,93 @@ pub struct Notecard {
     pub id: NotecardId,
     /// The text content to display (supports multi-line)
     pub content: String,
+    /// When the content was last changed through the config manager
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<DateTime<Utc>>,
+    /// Replaces `Config::default_display_properties` for this card, e.g. once
+    /// it has been dragged somewhere
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_properties: Option<DisplayProperties>,
+}
+
+/// Limits enforced on notecard content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContentLimits {
+    /// Maximum number of characters
+    pub max_length: usize,
+    /// Maximum number of lines, if limited
+    pub max_lines: Option<usize>,
+}
+
+impl Default for ContentLimits {
+    fn default() -> Self {
+        ContentLimits {
+            max_length: 10000,
+            max_lines: None,
+        }
+    }
+}
+
+/// A single rule violation found while validating a value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    /// Dotted path of the offending field
+    pub path: String,
+    pub message: String,
+}
+
+impl ContentLimits {
+    /// Checks content against every limit, reporting all violations
+    pub fn check(&self, content: &str) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        let length = content.chars().count();
+        if length > self.max_length {
+            issues.push(ValidationIssue {
+                path: "content".to_string(),
+                message: format!(
+                    "exceeds maximum length of {} characters (got {})",
+                    self.max_length, length
+                ),
+            });
+        }
+
+        if let Some(max_lines) = self.max_lines {
+            let lines = content.lines().count();
+            if lines > max_lines {
+                issues.push(ValidationIssue {
+                    path: "content".to_string(),
+                    message: format!("exceeds maximum of {} lines (got {})", max_lines, lines),
+                });
+            }
+        }
+
+        issues
+    }
+}
+
+/// Lightweight description of a notecard for listings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotecardSummary {
+    pub id: NotecardId,
+    /// First non-blank line of the content
+    pub title: String,
+    /// Content collapsed onto a single line and truncated
+    pub summary: String,
+    pub is_empty: bool,
+    pub modified_at: Option<DateTime<Utc>>,
 }
 
 impl Notecard {
     /// Creates a new notecard with the given ID and content
     pub fn new(id: NotecardId, content: String) -> Self {
-        Notecard { id, content }
+        Notecard {
+            id,
+            content,
+            modified_at: None,
+            display_properties: None,
+        }
     }
 
     /// Creates an empty notecard with the given ID
@@ -55,21 +144,56 @@ impl Notecard {
         Notecard {
             id,
             content: String::new(),
+            modified_at: None,
+            display_properties: None,
+        }
+    }
+
+    /// Returns true if the notecard has no displayable content
+    pub fn is_empty(&self) -> bool {
+        self.content.trim().is_empty()
+    }
+
+    /// Builds a listing summary of the notecard
+    pub fn summary(&self) -> NotecardSummary {
+        let title = self.content
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or("");
+
+        let collapsed = self.content.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        NotecardSummary {
+            id: self.id,
+            title: truncate_chars(title, MAX_TITLE_LENGTH),
+            summary: truncate_chars(&collapsed, MAX_SUMMARY_LENGTH),
+            is_empty: self.is_empty(),
+            modified_at: self.modified_at,
         }
     }
 
-    /// Validates the notecard content
+    /// Validates the notecard content against the default limits
     pub fn validate(&self) -> Result<()> {
-        // Add any content validation rules here
-        // For now, we'll just ensure the content isn't too long
-        const MAX_CONTENT_LENGTH: usize = 10000;
-
-        if self.content.len() > MAX_CONTENT_LENGTH {
-            return Err(NotecognitoError::Config(
-                format!("Notecard content exceeds maximum length of {} characters", MAX_CONTENT_LENGTH)
-            ));
+        self.validate_with(&ContentLimits::default())
+    }
+
+    /// Validates the notecard content, reporting the first limit it exceeds
+    pub fn validate_with(&self, limits: &ContentLimits) -> Result<()> {
+        match limits.check(&self.content).into_iter().next() {
+            Some(issue) => Err(NotecognitoError::validation(issue.path, issue.message)),
+            None => Ok(()),
         }
+    }
+}
 
-        Ok(())
+/// Truncates to at most `max` characters, marking the cut with an ellipsis
+fn truncate_chars(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
     }
-}
\ No newline at end of file
+
+    let mut truncated: String = text.chars().take(max.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
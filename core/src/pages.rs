@@ -0,0 +1,50 @@
+/// A line on its own that splits a notecard into pages
+pub const PAGE_BREAK: &str = "---";
+
+/// Splits a notecard's content into pages at `PAGE_BREAK` lines
+///
+/// Blank lines around a break are dropped along with it, and so are pages
+/// with nothing on them, such as after a break at the very end. Content
+/// without a break, or with nothing but breaks, is a single page as it is.
+pub fn split_pages(content: &str) -> Vec<&str> {
+    let mut pages = Vec::new();
+    let mut page_start = 0;
+    let mut line_start = 0;
+
+    for line in content.split_inclusive('\n') {
+        if line.trim() == PAGE_BREAK {
+            pages.push(&content[page_start..line_start]);
+            page_start = line_start + line.len();
+        }
+        line_start += line.len();
+    }
+    pages.push(&content[page_start..]);
+
+    let pages: Vec<&str> = pages
+        .into_iter()
+        .map(|page| page.trim_matches(|c| c == '\n' || c == '\r'))
+        .filter(|page| !page.trim().is_empty())
+        .collect();
+
+    if pages.is_empty() {
+        vec![content]
+    } else {
+        pages
+    }
+}
+
+/// The page a notecard of `count` pages on `page` turns to
+///
+/// Forwards past the last page gives `None`, for the card to be hidden,
+/// unless `wrap` goes round to the first. Backwards from the first page gives
+/// `None`, leaving the card where it is, unless `wrap` goes round to the last.
+pub fn turn_page(page: usize, count: usize, forward: bool, wrap: bool) -> Option<usize> {
+    let last = count.checked_sub(1)?;
+    match (forward, wrap) {
+        (true, _) if page < last => Some(page + 1),
+        (true, true) => Some(0),
+        (false, _) if page > 0 => Some(page.min(count) - 1),
+        (false, true) => Some(last),
+        _ => None,
+    }
+}
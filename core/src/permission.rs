@@ -0,0 +1,241 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use crate::config::ListenerId;
+use crate::error::Result;
+use crate::platform::PlatformInterface;
+
+/// Time between checks while permission is denied
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Whether the platform lets the app use global hotkeys
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PermissionState {
+    /// Not checked yet
+    #[default]
+    Unknown,
+    Granted,
+    Denied,
+}
+
+/// A change of `PermissionState` seen by a `PermissionMonitor`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PermissionChanged {
+    pub previous: PermissionState,
+    pub current: PermissionState,
+}
+
+type PermissionListener = Box<dyn Fn(&PermissionChanged) + Send + Sync>;
+
+/// Tracks `PlatformInterface::check_permissions` so permission granted after
+/// startup is noticed without a relaunch
+pub struct PermissionMonitor {
+    state: PermissionState,
+    poll_interval: Duration,
+    listeners: Vec<(ListenerId, PermissionListener)>,
+    next_listener_id: ListenerId,
+}
+
+impl Default for PermissionMonitor {
+    fn default() -> Self {
+        Self::with_poll_interval(DEFAULT_POLL_INTERVAL)
+    }
+}
+
+impl PermissionMonitor {
+    /// Creates a monitor in the `Unknown` state that polls every 2 seconds
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a monitor that waits `poll_interval` between checks while denied
+    pub fn with_poll_interval(poll_interval: Duration) -> Self {
+        PermissionMonitor {
+            state: PermissionState::Unknown,
+            poll_interval,
+            listeners: Vec::new(),
+            next_listener_id: 0,
+        }
+    }
+
+    /// Gets the state found by the last check
+    pub fn state(&self) -> PermissionState {
+        self.state
+    }
+
+    /// Registers a listener called synchronously after every state change
+    pub fn add_listener<F>(&mut self, listener: F) -> ListenerId
+    where
+        F: Fn(&PermissionChanged) + Send + Sync + 'static,
+    {
+        let id = self.next_listener_id;
+        self.next_listener_id += 1;
+        self.listeners.push((id, Box::new(listener)));
+        id
+    }
+
+    /// Removes a listener, returning whether it was registered
+    pub fn remove_listener(&mut self, id: ListenerId) -> bool {
+        let before = self.listeners.len();
+        self.listeners.retain(|(listener_id, _)| *listener_id != id);
+        self.listeners.len() != before
+    }
+
+    /// Asks the platform once, notifying listeners if the state changed
+    pub async fn check<P>(&mut self, platform: &P) -> Result<PermissionState>
+    where
+        P: PlatformInterface + ?Sized,
+    {
+        let current = if platform.check_permissions().await? {
+            PermissionState::Granted
+        } else {
+            PermissionState::Denied
+        };
+        self.transition(current);
+        Ok(current)
+    }
+
+    /// Checks until permission is granted, polling while it's denied
+    ///
+    /// The platform is only locked for each check. A failed check is logged and
+    /// retried after the poll interval without changing the state.
+    pub async fn watch<P>(&mut self, platform: &Mutex<P>) -> Result<()>
+    where
+        P: PlatformInterface + ?Sized,
+    {
+        loop {
+            let checked = {
+                let platform = platform.lock().await;
+                self.check(&*platform).await
+            };
+
+            match checked {
+                Ok(PermissionState::Granted) => return Ok(()),
+                Ok(_) => {}
+                Err(e) => tracing::debug!("Permission check failed: {}", e),
+            }
+
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    fn transition(&mut self, current: PermissionState) {
+        if current == self.state {
+            return;
+        }
+
+        let change = PermissionChanged {
+            previous: self.state,
+            current,
+        };
+        self.state = current;
+        tracing::info!("Permission state changed from {:?} to {:?}", change.previous, change.current);
+
+        for (_, listener) in &self.listeners {
+            listener(&change);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "test-util"))]
+mod tests {
+    use super::*;
+    use crate::platform::mock::MockPlatform;
+    use std::sync::{Arc, Mutex as StdMutex};
+
+    /// A monitor whose listener records every change it's told about
+    fn recording_monitor() -> (PermissionMonitor, Arc<StdMutex<Vec<PermissionChanged>>>) {
+        let changes = Arc::new(StdMutex::new(Vec::new()));
+        let mut monitor = PermissionMonitor::with_poll_interval(Duration::from_millis(1));
+        let recorded = Arc::clone(&changes);
+        monitor.add_listener(move |change| recorded.lock().unwrap().push(*change));
+        (monitor, changes)
+    }
+
+    fn change(previous: PermissionState, current: PermissionState) -> PermissionChanged {
+        PermissionChanged { previous, current }
+    }
+
+    #[tokio::test]
+    async fn check_reports_what_the_platform_says() {
+        let (mut monitor, changes) = recording_monitor();
+        let mut platform = MockPlatform::new();
+        assert_eq!(monitor.state(), PermissionState::Unknown);
+
+        platform.set_permissions_granted(false);
+        assert_eq!(monitor.check(&platform).await.unwrap(), PermissionState::Denied);
+        platform.set_permissions_granted(true);
+        assert_eq!(monitor.check(&platform).await.unwrap(), PermissionState::Granted);
+
+        assert_eq!(monitor.state(), PermissionState::Granted);
+        assert_eq!(
+            *changes.lock().unwrap(),
+            [
+                change(PermissionState::Unknown, PermissionState::Denied),
+                change(PermissionState::Denied, PermissionState::Granted),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn unchanged_state_notifies_nobody() {
+        let (mut monitor, changes) = recording_monitor();
+        let platform = MockPlatform::new();
+
+        monitor.check(&platform).await.unwrap();
+        monitor.check(&platform).await.unwrap();
+
+        assert_eq!(changes.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn removed_listener_is_not_called() {
+        let mut monitor = PermissionMonitor::new();
+        let called = Arc::new(StdMutex::new(false));
+        let flag = Arc::clone(&called);
+        let id = monitor.add_listener(move |_| *flag.lock().unwrap() = true);
+
+        assert!(monitor.remove_listener(id));
+        assert!(!monitor.remove_listener(id));
+        monitor.check(&MockPlatform::new()).await.unwrap();
+
+        assert!(!*called.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn watch_returns_at_once_when_granted() {
+        let (mut monitor, _) = recording_monitor();
+        let platform = Mutex::new(MockPlatform::new());
+
+        tokio::time::timeout(Duration::from_secs(5), monitor.watch(&platform)).await.unwrap().unwrap();
+
+        assert_eq!(monitor.state(), PermissionState::Granted);
+    }
+
+    #[tokio::test]
+    async fn watch_polls_until_permission_is_granted() {
+        let (mut monitor, changes) = recording_monitor();
+        let mut denied = MockPlatform::new();
+        denied.set_permissions_granted(false);
+        let platform = Mutex::new(denied);
+
+        let grant = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            platform.lock().await.set_permissions_granted(true);
+        };
+        let (watched, _) = tokio::time::timeout(Duration::from_secs(5), async {
+            tokio::join!(monitor.watch(&platform), grant)
+        })
+        .await
+        .unwrap();
+        watched.unwrap();
+
+        assert_eq!(
+            *changes.lock().unwrap(),
+            [
+                change(PermissionState::Unknown, PermissionState::Denied),
+                change(PermissionState::Denied, PermissionState::Granted),
+            ]
+        );
+    }
+}
@@ -1,17 +1,35 @@
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use crate::error::Result;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use crate::error::{NotecognitoError, Result};
 use crate::notecard::NotecardId;
 use crate::config::DisplayProperties;
+use crate::layout::{cascade_layout, MonitorSelector, Placement, ScreenInfo};
+
+mod keycodes;
+#[cfg(feature = "test-util")]
+pub mod mock;
 
 /// Hotkey modifier keys
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+///
+/// Every variant exists on every platform so a config written on one OS loads
+/// on another; whether the current platform has the key is checked when the
+/// hotkey is registered (see `check_supported`).
+///
+/// Variants are declared in the order macOS lists modifier glyphs, which is
+/// also the order hotkey strings are written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum HotkeyModifier {
     Control,
     Alt,
     Shift,
-    #[cfg(target_os = "macos")]
+    /// The ⌘ key, macOS only
     Command,
-    #[cfg(target_os = "windows")]
+    /// The ⊞ key, Windows only
     Windows,
 }
 
@@ -37,42 +55,699 @@ impl HotkeyModifier {
                 #[cfg(not(target_os = "macos"))]
                 return "Shift";
             }
-            #[cfg(target_os = "macos")]
             HotkeyModifier::Command => "⌘ Command",
-            #[cfg(target_os = "windows")]
             HotkeyModifier::Windows => "⊞ Win",
         }
     }
+
+    /// Whether the current platform has this modifier key
+    pub fn is_supported(&self) -> bool {
+        match self {
+            HotkeyModifier::Control | HotkeyModifier::Alt | HotkeyModifier::Shift => true,
+            HotkeyModifier::Command => cfg!(target_os = "macos"),
+            HotkeyModifier::Windows => cfg!(target_os = "windows"),
+        }
+    }
+
+    /// Fails if the current platform lacks any modifier of a hotkey combination
+    pub fn check_supported(modifiers: &[HotkeyModifier]) -> Result<()> {
+        match modifiers.iter().find(|modifier| !modifier.is_supported()) {
+            Some(modifier) => Err(NotecognitoError::Platform(format!(
+                "The {:?} modifier isn't available on {}",
+                modifier,
+                current_platform()
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Gets the platform-independent name used in stored hotkey strings
+    pub fn canonical_name(&self) -> &'static str {
+        match self {
+            HotkeyModifier::Control => "Ctrl",
+            HotkeyModifier::Alt => "Alt",
+            HotkeyModifier::Shift => "Shift",
+            HotkeyModifier::Command => "Cmd",
+            HotkeyModifier::Windows => "Win",
+        }
+    }
+
+    /// Gets the glyph macOS menus show for the modifier
+    pub fn glyph(&self) -> char {
+        match self {
+            HotkeyModifier::Control => '⌃',
+            HotkeyModifier::Alt => '⌥',
+            HotkeyModifier::Shift => '⇧',
+            HotkeyModifier::Command => '⌘',
+            HotkeyModifier::Windows => '⊞',
+        }
+    }
+
+    /// Parses a modifier combination such as "ctrl+shift"; an empty string means none
+    pub fn parse_list(s: &str) -> Result<Vec<HotkeyModifier>> {
+        if s.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut modifiers = Vec::new();
+        for token in hotkey_tokens(s)? {
+            push_modifier(&mut modifiers, token.parse()?)?;
+        }
+
+        modifiers.sort();
+        Ok(modifiers)
+    }
+
+    /// Formats a modifier combination in the stored form, e.g. "Ctrl+Shift"
+    pub fn canonical_list(modifiers: &[HotkeyModifier]) -> String {
+        let mut modifiers = modifiers.to_vec();
+        modifiers.sort();
+        modifiers.dedup();
+
+        modifiers
+            .iter()
+            .map(HotkeyModifier::canonical_name)
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+
+    /// Whether holding `held` triggers a hotkey that needs `required`
+    ///
+    /// `strict` asks for exactly the required set, otherwise holding extra
+    /// modifiers still matches. Order and repeats don't matter.
+    pub fn matches(held: &[HotkeyModifier], required: &[HotkeyModifier], strict: bool) -> bool {
+        let covers = |outer: &[HotkeyModifier], inner: &[HotkeyModifier]| inner.iter().all(|m| outer.contains(m));
+        covers(held, required) && (!strict || covers(required, held))
+    }
+}
+
+/// Glyphs on macOS ("⌘"), short names elsewhere ("Ctrl")
+impl fmt::Display for HotkeyModifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if cfg!(target_os = "macos") {
+            write!(f, "{}", self.glyph())
+        } else {
+            write!(f, "{}", self.canonical_name())
+        }
+    }
+}
+
+/// Case-insensitive; accepts names, common aliases and glyphs
+impl FromStr for HotkeyModifier {
+    type Err = NotecognitoError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "ctrl" | "control" | "⌃" => Ok(HotkeyModifier::Control),
+            "alt" | "opt" | "option" | "⌥" => Ok(HotkeyModifier::Alt),
+            "shift" | "⇧" => Ok(HotkeyModifier::Shift),
+            "cmd" | "command" | "meta" | "⌘" => Ok(HotkeyModifier::Command),
+            "win" | "windows" | "super" | "⊞" => Ok(HotkeyModifier::Windows),
+            _ => Err(NotecognitoError::Config(format!("Unknown hotkey modifier '{}'", s.trim()))),
+        }
+    }
+}
+
+/// Key pressed together with the modifiers of a hotkey
+///
+/// The keycode tables every platform crate maps through live in
+/// `platform::keycodes`, so the platforms agree on which keys exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HotkeyKey {
+    /// A key of the digit row (0-9)
+    Digit(u8),
+    /// A letter key (A-Z), stored uppercase
+    Letter(char),
+    /// A function key (F1-F24)
+    Function(u8),
+    /// A digit key of the numeric keypad (0-9)
+    Numpad(u8),
+}
+
+impl HotkeyKey {
+    /// The digit key that shows a notecard
+    pub fn for_notecard(id: NotecardId) -> Self {
+        HotkeyKey::Digit(id.value())
+    }
+
+    /// The digit key that hides every notecard; 0 is the one digit no notecard uses
+    pub fn hide_all() -> Self {
+        HotkeyKey::Digit(0)
+    }
+
+    /// Gets the Windows virtual-key code, if the key exists there
+    pub fn to_windows_vk(&self) -> Option<u16> {
+        keycodes::windows_vk(*self)
+    }
+
+    /// Gets the macOS virtual keycode (`kVK_*`), if the key exists there
+    pub fn to_macos_keycode(&self) -> Option<u16> {
+        keycodes::macos_keycode(*self)
+    }
+
+    /// Finds the key a macOS virtual keycode belongs to
+    pub fn from_macos_keycode(keycode: u16) -> Option<Self> {
+        keycodes::from_macos_keycode(keycode)
+    }
+
+    /// Gets the X11 keysym the key produces without modifiers
+    pub fn to_x11_keysym(&self) -> Option<u32> {
+        keycodes::x11_keysym(*self)
+    }
+}
+
+impl fmt::Display for HotkeyKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HotkeyKey::Digit(digit) => write!(f, "{}", digit),
+            HotkeyKey::Letter(letter) => write!(f, "{}", letter),
+            HotkeyKey::Function(number) => write!(f, "F{}", number),
+            HotkeyKey::Numpad(digit) => write!(f, "Num{}", digit),
+        }
+    }
+}
+
+/// Case-insensitive; accepts "3", "a", "F5" and "Num5" (or "numpad5", "kp5")
+impl FromStr for HotkeyKey {
+    type Err = NotecognitoError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim().to_lowercase();
+        let single_digit = |digits: &str| match digits.as_bytes() {
+            [digit @ b'0'..=b'9'] => Some(digit - b'0'),
+            _ => None,
+        };
+
+        let key = if let Some(digit) = single_digit(&name) {
+            Some(HotkeyKey::Digit(digit))
+        } else if let [letter @ b'a'..=b'z'] = name.as_bytes() {
+            Some(HotkeyKey::Letter(letter.to_ascii_uppercase() as char))
+        } else if let Some(digits) = ["numpad", "num", "kp"]
+            .iter()
+            .find_map(|prefix| name.strip_prefix(prefix))
+        {
+            single_digit(digits).map(HotkeyKey::Numpad)
+        } else if let Some(number) = name.strip_prefix('f') {
+            number
+                .parse::<u8>()
+                .ok()
+                .filter(|n| (1..=keycodes::MAX_FUNCTION_KEY).contains(n) && !number.starts_with(['0', '+']))
+                .map(HotkeyKey::Function)
+        } else {
+            None
+        };
+
+        key.ok_or_else(|| NotecognitoError::Config(format!("Unknown hotkey key '{}'", s.trim())))
+    }
+}
+
+/// A modifier combination plus a key, stored as a string like "Ctrl+Shift+3"
+///
+/// `parse` accepts any casing, the aliases of `HotkeyModifier::from_str` and
+/// the glyph form "⌘⇧5". `to_string` is for display and uses glyphs on macOS;
+/// `canonical` is the platform-independent form that gets stored.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct HotkeyBinding {
+    pub modifiers: Vec<HotkeyModifier>,
+    pub key: HotkeyKey,
+}
+
+impl HotkeyBinding {
+    /// Creates a binding, ordering the modifiers and dropping repeats
+    pub fn new(modifiers: &[HotkeyModifier], key: HotkeyKey) -> Self {
+        let mut modifiers = modifiers.to_vec();
+        modifiers.sort();
+        modifiers.dedup();
+        HotkeyBinding { modifiers, key }
+    }
+
+    /// Parses a binding such as "ctrl+shift+3"
+    pub fn parse(s: &str) -> Result<Self> {
+        let tokens = hotkey_tokens(s)?;
+        let (key, modifier_tokens) = tokens
+            .split_last()
+            .ok_or_else(|| NotecognitoError::Config("Empty hotkey".to_string()))?;
+
+        let mut modifiers = Vec::new();
+        for token in modifier_tokens {
+            push_modifier(&mut modifiers, token.parse()?)?;
+        }
+        modifiers.sort();
+
+        Ok(HotkeyBinding {
+            modifiers,
+            key: key.parse()?,
+        })
+    }
+
+    /// Formats the binding in the stored form, e.g. "Ctrl+Shift+3"
+    pub fn canonical(&self) -> String {
+        if self.modifiers.is_empty() {
+            return self.key.to_string();
+        }
+        format!("{}+{}", HotkeyModifier::canonical_list(&self.modifiers), self.key)
+    }
+}
+
+/// "⌃⇧3" on macOS, "Ctrl+Shift+3" elsewhere
+impl fmt::Display for HotkeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if cfg!(target_os = "macos") { "" } else { "+" };
+        for modifier in &self.modifiers {
+            write!(f, "{}{}", modifier, separator)?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+impl FromStr for HotkeyBinding {
+    type Err = NotecognitoError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        HotkeyBinding::parse(s)
+    }
+}
+
+impl TryFrom<String> for HotkeyBinding {
+    type Error = NotecognitoError;
+
+    fn try_from(value: String) -> Result<Self> {
+        HotkeyBinding::parse(&value)
+    }
+}
+
+impl From<HotkeyBinding> for String {
+    fn from(binding: HotkeyBinding) -> Self {
+        binding.canonical()
+    }
+}
+
+/// Splits a hotkey string at `+`, also splitting off leading modifier glyphs
+/// so that "⌘⇧5" reads the same as "⌘+⇧+5"
+fn hotkey_tokens(s: &str) -> Result<Vec<&str>> {
+    let is_glyph = |c: char| matches!(c, '⌃' | '⌥' | '⇧' | '⌘' | '⊞');
+    let mut tokens = Vec::new();
+
+    for part in s.split('+') {
+        let mut rest = part.trim();
+        if rest.is_empty() {
+            return Err(NotecognitoError::Config(format!("Invalid hotkey '{}': empty key name", s)));
+        }
+
+        while let Some(glyph) = rest.chars().next().filter(|c| is_glyph(*c)) {
+            let (token, tail) = rest.split_at(glyph.len_utf8());
+            tokens.push(token);
+            rest = tail.trim_start();
+        }
+
+        if !rest.is_empty() {
+            tokens.push(rest);
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Adds a parsed modifier, rejecting one that's already in the combination
+fn push_modifier(modifiers: &mut Vec<HotkeyModifier>, modifier: HotkeyModifier) -> Result<()> {
+    if modifiers.contains(&modifier) {
+        return Err(NotecognitoError::Config(format!(
+            "Duplicate hotkey modifier '{}'",
+            modifier.canonical_name()
+        )));
+    }
+    modifiers.push(modifier);
+    Ok(())
+}
+
+/// What a global hotkey does when pressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HotkeyAction {
+    ToggleNotecard(NotecardId),
+    HideAll,
+}
+
+/// What it takes to go from the registered hotkeys to the wanted ones
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HotkeyChanges {
+    /// Registered hotkeys that are no longer wanted
+    pub unregister: Vec<HotkeyAction>,
+    /// Hotkeys to register, including ones whose combination changed
+    pub register: Vec<(HotkeyAction, HotkeyBinding)>,
+}
+
+impl HotkeyChanges {
+    /// Compares the registered hotkeys with the wanted ones
+    ///
+    /// Hotkeys bound the same way in both are left alone, so a config change
+    /// that doesn't touch them never re-registers them.
+    pub fn between(
+        registered: &HashMap<HotkeyAction, HotkeyBinding>,
+        wanted: &HashMap<HotkeyAction, HotkeyBinding>,
+    ) -> Self {
+        let mut unregister: Vec<_> = registered
+            .keys()
+            .filter(|action| !wanted.contains_key(action))
+            .copied()
+            .collect();
+        let mut register: Vec<_> = wanted
+            .iter()
+            .filter(|(action, binding)| registered.get(action) != Some(binding))
+            .map(|(action, binding)| (*action, binding.clone()))
+            .collect();
+
+        // Map order is arbitrary; a fixed order keeps the logs readable
+        unregister.sort_by_key(|action| action.sort_key());
+        register.sort_by_key(|(action, _)| action.sort_key());
+
+        HotkeyChanges { unregister, register }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.unregister.is_empty() && self.register.is_empty()
+    }
+}
+
+impl HotkeyAction {
+    /// Notecards in ID order, then hide-all
+    fn sort_key(&self) -> u8 {
+        match self {
+            HotkeyAction::ToggleNotecard(id) => id.value(),
+            HotkeyAction::HideAll => u8::MAX,
+        }
+    }
+}
+
+/// Drops hotkey presses that follow one for the same action too closely, so
+/// one physical press is one toggle however many events the OS delivers
+///
+/// The window slides: each dropped press starts it again, so a key held down
+/// and repeating acts once, however long it's held.
+#[derive(Debug, Clone)]
+pub struct HotkeyDebouncer {
+    window: Duration,
+    last: Option<(HotkeyAction, Instant)>,
+}
+
+impl HotkeyDebouncer {
+    /// Short enough that deliberate double presses still both count
+    pub const DEFAULT_WINDOW: Duration = Duration::from_millis(200);
+
+    pub fn new(window: Duration) -> Self {
+        HotkeyDebouncer { window, last: None }
+    }
+
+    /// Whether a press of `action` made at `at` should be acted on; presses
+    /// of other actions in between don't count as repeats
+    pub fn accept(&mut self, action: HotkeyAction, at: Instant) -> bool {
+        let repeat = self
+            .last
+            .is_some_and(|(last, last_at)| last == action && at.saturating_duration_since(last_at) < self.window);
+        self.last = Some((action, at));
+        !repeat
+    }
+}
+
+impl Default for HotkeyDebouncer {
+    fn default() -> Self {
+        HotkeyDebouncer::new(Self::DEFAULT_WINDOW)
+    }
+}
+
+/// Events buffered between a window manager and the app before it falls behind
+pub const PLATFORM_EVENT_CAPACITY: usize = 64;
+
+/// Why a notecard left the screen other than through `hide_notecard`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DismissReason {
+    Click,
+    Escape,
+    /// The auto-hide timer ran out
+    AutoHide,
+    /// Taken down along with every other card by `hide_all_notecards`
+    HideAll,
+}
+
+/// Something that happened on the platform side which the rest of the app
+/// can't learn through `PlatformInterface`
+///
+/// Window managers send these on a `tokio::sync::mpsc` channel; the apps
+/// forward them to IPC subscribers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum PlatformEvent {
+    /// A notecard window was closed by the user or its auto-hide timer
+    NotecardDismissed { id: NotecardId, reason: DismissReason },
+    /// The user dragged a notecard window; `position` is its top-left corner
+    /// relative to screen `monitor`, as `DisplayProperties::position` is
+    /// without an anchor
+    NotecardMoved { id: NotecardId, monitor: usize, position: (i32, i32) },
+    /// The OS stopped delivering a notecard's hotkey
+    HotkeyRegistrationLost { id: NotecardId },
+    /// Monitors were added, removed or rearranged
+    DisplayChanged,
+}
+
+/// What `PlatformInterface::toggle_notecard` did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ToggleOutcome {
+    Shown,
+    Hidden,
+    /// Went on to another page of a card split into pages; `page` counts from 0
+    PageTurned { page: usize },
+}
+
+/// The application in front of the user
+///
+/// `Default` is the unknown application: an empty name and no identifier.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppIdentity {
+    /// User-visible name, e.g. "Safari"
+    pub name: String,
+    /// Bundle identifier on macOS, executable path on Windows
+    pub identifier: Option<String>,
+    /// Whether the application covers its whole screen
+    pub is_fullscreen: bool,
+}
+
+impl AppIdentity {
+    /// Whether any pattern equals the name or identifier, ignoring case;
+    /// the unknown application matches nothing
+    pub fn matches_any(&self, patterns: &[String]) -> bool {
+        let matches = |value: &str| {
+            !value.is_empty() && patterns.iter().any(|pattern| pattern.eq_ignore_ascii_case(value))
+        };
+
+        matches(&self.name) || self.identifier.as_deref().is_some_and(matches)
+    }
+}
+
+/// How urgent a notification is; platforms pick the icon or sound from it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NotificationKind {
+    Info,
+    Warning,
+    Error,
+}
+
+/// Optional display features of the running platform
+///
+/// The config UI uses this to hide controls that would have no effect.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlatformCapabilities {
+    /// Same value as `current_platform()`
+    pub platform_name: String,
+    /// Version of the operating system, when the platform can tell
+    pub os_version: Option<String>,
+    /// Translucent blurred window backgrounds
+    pub blur: bool,
+    /// Rounded window corners
+    pub rounded_corners: bool,
+    /// Scale factors that differ per screen
+    pub per_monitor_dpi: bool,
+    /// Notecard content can pull in the clipboard through placeholders
+    pub clipboard_placeholders: bool,
+    /// Notecard windows can be hidden from screenshots and screen sharing
+    pub screen_capture_exclusion: bool,
+}
+
+impl PlatformCapabilities {
+    /// Capabilities assumed without detection: none of the optional features
+    pub fn conservative() -> Self {
+        PlatformCapabilities {
+            platform_name: current_platform().to_string(),
+            os_version: None,
+            blur: false,
+            rounded_corners: false,
+            per_monitor_dpi: false,
+            clipboard_placeholders: false,
+            screen_capture_exclusion: false,
+        }
+    }
 }
 
 /// Platform-specific interface that must be implemented for each OS
+///
+/// Methods are async so implementations can await their own managers instead
+/// of blocking a runtime thread; `async_trait` keeps the trait object-safe for
+/// `IpcServer::with_platform`.
+#[async_trait]
 pub trait PlatformInterface: Send + Sync {
     /// Registers a global hotkey for a notecard
-    fn register_hotkey(&mut self, id: NotecardId, modifiers: &[HotkeyModifier]) -> Result<()>;
+    async fn register_hotkey(&mut self, id: NotecardId, modifiers: &[HotkeyModifier]) -> Result<()>;
 
     /// Unregisters a global hotkey for a notecard
-    fn unregister_hotkey(&mut self, id: NotecardId) -> Result<()>;
+    async fn unregister_hotkey(&mut self, id: NotecardId) -> Result<()>;
 
     /// Shows a notecard overlay window
-    fn show_notecard(&mut self, id: NotecardId, content: &str, properties: &DisplayProperties) -> Result<()>;
+    async fn show_notecard(&mut self, id: NotecardId, content: &str, properties: &DisplayProperties) -> Result<()>;
 
     /// Hides a notecard overlay window
-    fn hide_notecard(&mut self, id: NotecardId) -> Result<()>;
+    async fn hide_notecard(&mut self, id: NotecardId) -> Result<()>;
+
+    /// Hides the notecard if it's on screen and shows it otherwise
+    async fn toggle_notecard(
+        &mut self,
+        id: NotecardId,
+        content: &str,
+        properties: &DisplayProperties,
+    ) -> Result<ToggleOutcome> {
+        if self.is_notecard_visible(id).await? {
+            self.hide_notecard(id).await?;
+            Ok(ToggleOutcome::Hidden)
+        } else {
+            self.show_notecard(id, content, properties).await?;
+            Ok(ToggleOutcome::Shown)
+        }
+    }
+
+    /// Hides every notecard overlay window, including ones still being created;
+    /// does nothing if none are on screen
+    async fn hide_all_notecards(&mut self) -> Result<()> {
+        for id in self.visible_notecards().await? {
+            self.hide_notecard(id).await?;
+        }
+        Ok(())
+    }
+
+    /// Shows every given notecard at once, laid out together on the primary
+    /// screen by `cascade_layout` rather than at their configured positions,
+    /// which are left as they are; each is shown as configured without screens
+    ///
+    /// Positions are worked out in the coordinates `screens` reports and
+    /// placed with the cards' configured sizes, so a platform whose screens
+    /// count device pixels while the sizes are logical should scale them first.
+    async fn show_all_notecards(&mut self, notecards: &[(NotecardId, String, DisplayProperties)]) -> Result<()> {
+        let screens = self.screens().await?;
+        let Some(screen) = MonitorSelector::Primary.select(&screens) else {
+            for (id, content, properties) in notecards {
+                self.show_notecard(*id, content, properties).await?;
+            }
+            return Ok(());
+        };
+
+        let sizes: Vec<_> = notecards.iter().map(|(id, _, properties)| (*id, properties.size)).collect();
+        let positions = cascade_layout(&sizes, screen);
+
+        for ((id, content, properties), (_, (x, y))) in notecards.iter().zip(positions) {
+            // Relative to the screen, as positions without an anchor are
+            let laid_out = DisplayProperties {
+                position: (x - screen.frame.0, y - screen.frame.1),
+                anchor: None,
+                monitor: MonitorSelector::Index(screen.index),
+                placement: Placement::Fixed,
+                ..properties.clone()
+            };
+            self.show_notecard(*id, content, &laid_out).await?;
+        }
+        Ok(())
+    }
+
+    /// Updates the content and geometry of a notecard overlay window in place;
+    /// does nothing if the notecard isn't on screen
+    async fn update_notecard(&mut self, id: NotecardId, content: &str, properties: &DisplayProperties) -> Result<()>;
+
+    /// Moves a notecard overlay window so its top-left corner is at (x, y) in
+    /// virtual screen coordinates; fails with `NotVisible` if it isn't on screen
+    async fn set_notecard_position(&mut self, id: NotecardId, x: i32, y: i32) -> Result<()>;
+
+    /// Resizes a notecard overlay window, keeping its top-left corner in place;
+    /// fails with `NotVisible` if it isn't on screen
+    async fn set_notecard_size(&mut self, id: NotecardId, width: u32, height: u32) -> Result<()>;
+
+    /// Checks if a notecard overlay window is currently on screen
+    async fn is_notecard_visible(&self, id: NotecardId) -> Result<bool>;
+
+    /// Gets the notecards whose overlay windows are currently on screen, in id order
+    async fn visible_notecards(&self) -> Result<Vec<NotecardId>>;
+
+    /// Gets the geometry of every connected screen
+    async fn screens(&self) -> Result<Vec<ScreenInfo>>;
 
     /// Sets the app to launch on startup
-    fn set_launch_on_startup(&mut self, enabled: bool) -> Result<()>;
+    async fn set_launch_on_startup(&mut self, enabled: bool) -> Result<()>;
+
+    /// Whether the app is actually set to launch on startup, which can differ
+    /// from the stored setting; `None` when the platform can't tell
+    async fn launch_on_startup_status(&self) -> Result<Option<bool>> {
+        Ok(None)
+    }
+
+    /// Reads the clipboard as text; `None` when it holds something else or the
+    /// platform has no clipboard access
+    async fn read_clipboard_text(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// Replaces the clipboard contents with text
+    async fn write_clipboard_text(&mut self, text: &str) -> Result<()> {
+        let _ = text;
+        Err(NotecognitoError::Platform(format!(
+            "Clipboard access isn't available on {}",
+            current_platform()
+        )))
+    }
+
+    /// Gets the application in front of the user; the default reports it as unknown
+    async fn frontmost_application(&self) -> Result<AppIdentity> {
+        Ok(AppIdentity::default())
+    }
+
+    /// Tells the user something without interrupting them, e.g. a toast or
+    /// notification center banner; does nothing on platforms without one
+    async fn show_notification(&self, title: &str, body: &str, kind: NotificationKind) -> Result<()> {
+        let _ = (title, body, kind);
+        Ok(())
+    }
+
+    /// Describes the optional features this platform supports
+    fn capabilities(&self) -> PlatformCapabilities {
+        PlatformCapabilities::conservative()
+    }
 
     /// Initializes the platform-specific components
-    fn initialize(&mut self) -> Result<()>;
+    async fn initialize(&mut self) -> Result<()>;
 
     /// Cleans up platform-specific resources
-    fn cleanup(&mut self) -> Result<()>;
+    async fn cleanup(&mut self) -> Result<()>;
 
     /// Checks if the required permissions are granted (e.g., accessibility on macOS)
-    fn check_permissions(&self) -> Result<bool>;
+    async fn check_permissions(&self) -> Result<bool>;
 
     /// Requests the required permissions from the user
-    fn request_permissions(&self) -> Result<()>;
+    async fn request_permissions(&self) -> Result<()>;
+}
+
+/// Tells the user their config couldn't be read and was set aside at `set_aside`
+pub async fn notify_config_recovered<P: PlatformInterface + ?Sized>(platform: &P, set_aside: &Path) -> Result<()> {
+    let body = format!(
+        "Your settings couldn't be read, so the defaults are in use. The old file was kept at {}.",
+        set_aside.display()
+    );
+    platform
+        .show_notification("Notecognito settings were reset", &body, NotificationKind::Warning)
+        .await
 }
 
 /// Platform detection helper
@@ -85,4 +760,262 @@ pub fn current_platform() -> &'static str {
     return "linux";
     #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
     return "unknown";
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use HotkeyModifier::*;
+
+    fn binding(s: &str) -> HotkeyBinding {
+        HotkeyBinding::parse(s).unwrap_or_else(|e| panic!("{:?} didn't parse: {}", s, e))
+    }
+
+    #[test]
+    fn parses_a_stored_binding() {
+        assert_eq!(binding("Ctrl+Shift+3"), HotkeyBinding::new(&[Control, Shift], HotkeyKey::Digit(3)));
+    }
+
+    #[test]
+    fn parsing_ignores_case_spacing_and_order() {
+        let expected = HotkeyBinding::new(&[Alt, Command], HotkeyKey::Letter('A'));
+
+        assert_eq!(binding("cmd+option+a"), expected);
+        assert_eq!(binding(" Option + CMD + A "), expected);
+        assert_eq!(binding("meta+alt+a"), expected);
+    }
+
+    #[test]
+    fn parses_glyphs_with_or_without_separators() {
+        let expected = HotkeyBinding::new(&[Shift, Command], HotkeyKey::Digit(5));
+
+        assert_eq!(binding("⌘⇧5"), expected);
+        assert_eq!(binding("⌘+⇧+5"), expected);
+        assert_eq!(binding("⌘ ⇧ 5"), expected);
+    }
+
+    #[test]
+    fn parses_every_kind_of_key() {
+        assert_eq!(binding("F5").key, HotkeyKey::Function(5));
+        assert_eq!(binding("ctrl+f24").key, HotkeyKey::Function(24));
+        for numpad in ["Num7", "numpad7", "KP7"] {
+            assert_eq!(binding(numpad).key, HotkeyKey::Numpad(7));
+        }
+        assert_eq!(binding("win+z").key, HotkeyKey::Letter('Z'));
+    }
+
+    #[test]
+    fn rejects_malformed_bindings() {
+        for s in [
+            "", "+", "ctrl+", "ctrl++1", "ctrl+shift", "ctrl+ctrl+1", "hyper+1", "ctrl+10", "F0", "F05", "F25",
+            "F+1", "Num10", "ctrl+é",
+        ] {
+            assert!(HotkeyBinding::parse(s).is_err(), "{:?} parsed", s);
+        }
+    }
+
+    #[test]
+    fn canonical_form_is_sorted_and_round_trips() {
+        for (input, canonical) in [
+            ("shift+ctrl+3", "Ctrl+Shift+3"),
+            ("⌘⇧⌥⌃f12", "Ctrl+Alt+Shift+Cmd+F12"),
+            ("super+kp0", "Win+Num0"),
+            ("q", "Q"),
+        ] {
+            assert_eq!(binding(input).canonical(), canonical);
+            assert_eq!(binding(canonical), binding(input));
+        }
+    }
+
+    #[test]
+    fn display_uses_the_platform_form() {
+        let shown = binding("ctrl+shift+3").to_string();
+
+        if cfg!(target_os = "macos") {
+            assert_eq!(shown, "⌃⇧3");
+        } else {
+            assert_eq!(shown, "Ctrl+Shift+3");
+        }
+    }
+
+    #[test]
+    fn binding_is_stored_as_its_canonical_string() {
+        let json = serde_json::to_string(&binding("shift+ctrl+9")).unwrap();
+
+        assert_eq!(json, "\"Ctrl+Shift+9\"");
+        assert_eq!(serde_json::from_str::<HotkeyBinding>(&json).unwrap(), binding("ctrl+shift+9"));
+        assert!(serde_json::from_str::<HotkeyBinding>("\"ctrl+nope\"").is_err());
+    }
+
+    #[test]
+    fn modifier_lists_parse_and_format() {
+        assert_eq!(HotkeyModifier::parse_list("").unwrap(), []);
+        assert_eq!(HotkeyModifier::parse_list("shift+control").unwrap(), [Control, Shift]);
+        assert!(HotkeyModifier::parse_list("shift+shift").is_err());
+        assert_eq!(HotkeyModifier::canonical_list(&[Windows, Control, Control]), "Ctrl+Win");
+    }
+
+    /// The modifiers whose bit is set in `mask`
+    fn modifier_set(mask: u32) -> Vec<HotkeyModifier> {
+        [Control, Alt, Shift, Command, Windows]
+            .into_iter()
+            .enumerate()
+            .filter(|(bit, _)| mask & (1 << bit) != 0)
+            .map(|(_, modifier)| modifier)
+            .collect()
+    }
+
+    #[test]
+    fn strict_modifiers_match_exactly_and_lenient_ones_allow_extras() {
+        for held in 0..32 {
+            for required in 0..32 {
+                let (held_set, required_set) = (modifier_set(held), modifier_set(required));
+                let superset = held & required == required;
+
+                assert_eq!(
+                    HotkeyModifier::matches(&held_set, &required_set, true),
+                    held == required,
+                    "strict, held {:?}, required {:?}", held_set, required_set
+                );
+                assert_eq!(
+                    HotkeyModifier::matches(&held_set, &required_set, false),
+                    superset,
+                    "lenient, held {:?}, required {:?}", held_set, required_set
+                );
+            }
+        }
+
+        assert!(HotkeyModifier::matches(&[Shift, Control, Shift], &[Control, Shift], true));
+    }
+
+    fn toggle(value: u8) -> HotkeyAction {
+        HotkeyAction::ToggleNotecard(NotecardId::new(value).unwrap())
+    }
+
+    /// Presses of `action` at the given milliseconds after a fixed start,
+    /// and whether each was accepted
+    fn presses(debouncer: &mut HotkeyDebouncer, start: Instant, at: &[(HotkeyAction, u64)]) -> Vec<bool> {
+        at.iter()
+            .map(|(action, ms)| debouncer.accept(*action, start + Duration::from_millis(*ms)))
+            .collect()
+    }
+
+    #[test]
+    fn debouncer_drops_a_repeat_inside_the_window() {
+        let mut debouncer = HotkeyDebouncer::default();
+
+        assert_eq!(presses(&mut debouncer, Instant::now(), &[(toggle(1), 0), (toggle(1), 50)]), [true, false]);
+    }
+
+    #[test]
+    fn debouncer_accepts_a_press_once_the_window_has_passed() {
+        let mut debouncer = HotkeyDebouncer::default();
+
+        assert_eq!(
+            presses(&mut debouncer, Instant::now(), &[(toggle(1), 0), (toggle(1), 199), (toggle(1), 400)]),
+            [true, false, true]
+        );
+    }
+
+    #[test]
+    fn debouncer_window_slides_while_a_key_repeats() {
+        let mut debouncer = HotkeyDebouncer::default();
+        let held: Vec<_> = (0..20).map(|i| (toggle(1), i * 150)).collect();
+
+        let accepted = presses(&mut debouncer, Instant::now(), &held);
+
+        assert_eq!(accepted.iter().filter(|accepted| **accepted).count(), 1);
+    }
+
+    #[test]
+    fn debouncer_never_drops_a_different_action() {
+        let mut debouncer = HotkeyDebouncer::default();
+
+        assert_eq!(
+            presses(
+                &mut debouncer,
+                Instant::now(),
+                &[(toggle(1), 0), (toggle(2), 10), (toggle(1), 20), (HotkeyAction::HideAll, 30)]
+            ),
+            [true, true, true, true]
+        );
+    }
+
+    #[test]
+    fn debouncer_treats_an_earlier_timestamp_as_a_repeat() {
+        let mut debouncer = HotkeyDebouncer::default();
+        let start = Instant::now() + Duration::from_secs(1);
+
+        assert_eq!(presses(&mut debouncer, start, &[(toggle(3), 100), (toggle(3), 0)]), [true, false]);
+    }
+
+    #[test]
+    fn debouncer_with_no_window_accepts_everything() {
+        let mut debouncer = HotkeyDebouncer::new(Duration::ZERO);
+
+        assert_eq!(presses(&mut debouncer, Instant::now(), &[(toggle(1), 0), (toggle(1), 0)]), [true, true]);
+    }
+
+    #[test]
+    fn a_config_with_every_modifier_round_trips_on_any_platform() {
+        let all = [Control, Alt, Shift, Command, Windows];
+        // Stops compiling when a variant is added without being listed above
+        for modifier in all {
+            match modifier {
+                Control | Alt | Shift | Command | Windows => {}
+            }
+        }
+        let config = crate::Config { hotkey_modifiers: all.to_vec(), ..Default::default() };
+
+        let mut json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["hotkey_modifiers"], "Ctrl+Alt+Shift+Cmd+Win");
+        let loaded: crate::Config = serde_json::from_value(json.clone()).unwrap();
+
+        // Loading keeps modifiers this platform lacks; only registering refuses them
+        assert_eq!(loaded.hotkey_modifiers, all);
+        // As does the array form earlier versions wrote
+        json["hotkey_modifiers"] = serde_json::json!(["Control", "Alt", "Shift", "Command", "Windows"]);
+        let legacy: crate::Config = serde_json::from_value(json).unwrap();
+        assert_eq!(legacy.hotkey_modifiers, all);
+        assert!(loaded.validate().is_ok());
+        // No platform has both ⌘ and ⊞
+        assert!(!(Command.is_supported() && Windows.is_supported()));
+        assert!(HotkeyModifier::check_supported(&loaded.hotkey_modifiers).is_err());
+    }
+
+    #[test]
+    fn conservative_capabilities_promise_nothing_optional() {
+        let capabilities = PlatformCapabilities::conservative();
+
+        assert_eq!(capabilities.platform_name, current_platform());
+        assert_eq!(capabilities.os_version, None);
+        let PlatformCapabilities {
+            platform_name: _,
+            os_version: _,
+            blur,
+            rounded_corners,
+            per_monitor_dpi,
+            clipboard_placeholders,
+            screen_capture_exclusion,
+        } = capabilities.clone();
+        assert_eq!([blur, rounded_corners, per_monitor_dpi, clipboard_placeholders, screen_capture_exclusion], [false; 5]);
+
+        // The config UI reads these by name
+        let json = serde_json::to_value(&capabilities).unwrap();
+        let mut keys: Vec<_> = json.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            [
+                "blur",
+                "clipboard_placeholders",
+                "os_version",
+                "per_monitor_dpi",
+                "platform_name",
+                "rounded_corners",
+                "screen_capture_exclusion",
+            ]
+        );
+        assert_eq!(serde_json::from_value::<PlatformCapabilities>(json).unwrap(), capabilities);
+    }
+}
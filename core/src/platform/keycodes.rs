@@ -0,0 +1,179 @@
+//! Keycode tables mapping `HotkeyKey` to each platform's key identifiers
+
+use super::HotkeyKey;
+
+/// Highest function key any platform has
+pub(crate) const MAX_FUNCTION_KEY: u8 = 24;
+
+/// `VK_0`; `VK_1`-`VK_9` follow consecutively
+const WINDOWS_VK_0: u16 = 0x30;
+/// `VK_A`; letters follow consecutively
+const WINDOWS_VK_A: u16 = 0x41;
+/// `VK_F1`; `VK_F2`-`VK_F24` follow consecutively
+const WINDOWS_VK_F1: u16 = 0x70;
+/// `VK_NUMPAD0`; `VK_NUMPAD1`-`VK_NUMPAD9` follow consecutively
+const WINDOWS_VK_NUMPAD0: u16 = 0x60;
+
+/// `kVK_ANSI_0` through `kVK_ANSI_9`
+const MACOS_DIGITS: [u16; 10] = [29, 18, 19, 20, 21, 23, 22, 26, 28, 25];
+/// `kVK_ANSI_A` through `kVK_ANSI_Z`
+const MACOS_LETTERS: [u16; 26] = [
+    0, 11, 8, 2, 14, 3, 5, 4, 34, 38, 40, 37, 46, 45, 31, 35, 12, 15, 1, 17, 32, 9, 13, 7, 16, 6,
+];
+/// `kVK_F1` through `kVK_F20`; Mac keyboards stop at F20
+const MACOS_FUNCTION_KEYS: [u16; 20] = [
+    122, 120, 99, 118, 96, 97, 98, 100, 101, 109, 103, 111, 105, 107, 113, 106, 64, 79, 80, 90,
+];
+/// `kVK_ANSI_Keypad0` through `kVK_ANSI_Keypad9`
+const MACOS_NUMPAD: [u16; 10] = [82, 83, 84, 85, 86, 87, 88, 89, 91, 92];
+
+/// `XK_0`; `XK_1`-`XK_9` follow consecutively
+const XK_0: u32 = 0x30;
+/// `XK_a`; grabs use the unshifted lowercase keysym
+const XK_A: u32 = 0x61;
+/// `XK_F1`; `XK_F2`-`XK_F35` follow consecutively
+const XK_F1: u32 = 0xffbe;
+/// `XK_KP_0`; `XK_KP_1`-`XK_KP_9` follow consecutively
+const XK_KP_0: u32 = 0xffb0;
+
+/// Position of a letter in the alphabet, if it's an ASCII letter
+fn letter_index(letter: char) -> Option<usize> {
+    letter
+        .is_ascii_alphabetic()
+        .then(|| (letter.to_ascii_uppercase() as u8 - b'A') as usize)
+}
+
+pub(crate) fn windows_vk(key: HotkeyKey) -> Option<u16> {
+    match key {
+        HotkeyKey::Digit(digit @ 0..=9) => Some(WINDOWS_VK_0 + digit as u16),
+        HotkeyKey::Letter(letter) => letter_index(letter).map(|i| WINDOWS_VK_A + i as u16),
+        HotkeyKey::Function(number @ 1..=MAX_FUNCTION_KEY) => Some(WINDOWS_VK_F1 + number as u16 - 1),
+        HotkeyKey::Numpad(digit @ 0..=9) => Some(WINDOWS_VK_NUMPAD0 + digit as u16),
+        _ => None,
+    }
+}
+
+pub(crate) fn macos_keycode(key: HotkeyKey) -> Option<u16> {
+    match key {
+        HotkeyKey::Digit(digit) => MACOS_DIGITS.get(digit as usize).copied(),
+        HotkeyKey::Letter(letter) => letter_index(letter).map(|i| MACOS_LETTERS[i]),
+        HotkeyKey::Function(number) => {
+            (number as usize).checked_sub(1).and_then(|i| MACOS_FUNCTION_KEYS.get(i)).copied()
+        }
+        HotkeyKey::Numpad(digit) => MACOS_NUMPAD.get(digit as usize).copied(),
+    }
+}
+
+pub(crate) fn from_macos_keycode(keycode: u16) -> Option<HotkeyKey> {
+    let position = |table: &[u16]| table.iter().position(|&code| code == keycode);
+
+    if let Some(digit) = position(&MACOS_DIGITS) {
+        Some(HotkeyKey::Digit(digit as u8))
+    } else if let Some(index) = position(&MACOS_LETTERS) {
+        Some(HotkeyKey::Letter((b'A' + index as u8) as char))
+    } else if let Some(index) = position(&MACOS_FUNCTION_KEYS) {
+        Some(HotkeyKey::Function(index as u8 + 1))
+    } else {
+        position(&MACOS_NUMPAD).map(|digit| HotkeyKey::Numpad(digit as u8))
+    }
+}
+
+pub(crate) fn x11_keysym(key: HotkeyKey) -> Option<u32> {
+    match key {
+        HotkeyKey::Digit(digit @ 0..=9) => Some(XK_0 + digit as u32),
+        HotkeyKey::Letter(letter) => letter_index(letter).map(|i| XK_A + i as u32),
+        HotkeyKey::Function(number @ 1..=MAX_FUNCTION_KEY) => Some(XK_F1 + number as u32 - 1),
+        HotkeyKey::Numpad(digit @ 0..=9) => Some(XK_KP_0 + digit as u32),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// Every key some platform has
+    fn all_keys() -> Vec<HotkeyKey> {
+        (0..=9)
+            .map(HotkeyKey::Digit)
+            .chain((b'A'..=b'Z').map(|letter| HotkeyKey::Letter(letter as char)))
+            .chain((1..=MAX_FUNCTION_KEY).map(HotkeyKey::Function))
+            .chain((0..=9).map(HotkeyKey::Numpad))
+            .collect()
+    }
+
+    #[test]
+    fn windows_codes_match_the_vk_constants() {
+        assert_eq!(windows_vk(HotkeyKey::Digit(0)), Some(0x30));
+        assert_eq!(windows_vk(HotkeyKey::Digit(9)), Some(0x39));
+        assert_eq!(windows_vk(HotkeyKey::Letter('A')), Some(0x41));
+        assert_eq!(windows_vk(HotkeyKey::Letter('z')), Some(0x5A));
+        assert_eq!(windows_vk(HotkeyKey::Function(1)), Some(0x70));
+        assert_eq!(windows_vk(HotkeyKey::Function(24)), Some(0x87));
+        assert_eq!(windows_vk(HotkeyKey::Numpad(9)), Some(0x69));
+    }
+
+    #[test]
+    fn macos_codes_match_the_kvk_constants() {
+        assert_eq!(macos_keycode(HotkeyKey::Digit(0)), Some(29));
+        assert_eq!(macos_keycode(HotkeyKey::Digit(1)), Some(18));
+        assert_eq!(macos_keycode(HotkeyKey::Letter('A')), Some(0));
+        assert_eq!(macos_keycode(HotkeyKey::Letter('q')), Some(12));
+        assert_eq!(macos_keycode(HotkeyKey::Function(1)), Some(122));
+        assert_eq!(macos_keycode(HotkeyKey::Function(20)), Some(90));
+        assert_eq!(macos_keycode(HotkeyKey::Numpad(9)), Some(92));
+    }
+
+    #[test]
+    fn x11_keysyms_match_the_xk_constants() {
+        assert_eq!(x11_keysym(HotkeyKey::Digit(5)), Some(0x35));
+        assert_eq!(x11_keysym(HotkeyKey::Letter('A')), Some(0x61));
+        assert_eq!(x11_keysym(HotkeyKey::Function(12)), Some(0xffc9));
+        assert_eq!(x11_keysym(HotkeyKey::Numpad(0)), Some(0xffb0));
+    }
+
+    #[test]
+    fn keys_out_of_range_have_no_code() {
+        for key in [
+            HotkeyKey::Digit(10),
+            HotkeyKey::Letter('é'),
+            HotkeyKey::Letter('1'),
+            HotkeyKey::Function(0),
+            HotkeyKey::Function(MAX_FUNCTION_KEY + 1),
+            HotkeyKey::Numpad(10),
+        ] {
+            assert_eq!(windows_vk(key), None, "{:?}", key);
+            assert_eq!(macos_keycode(key), None, "{:?}", key);
+            assert_eq!(x11_keysym(key), None, "{:?}", key);
+        }
+        // Mac keyboards stop at F20
+        assert_eq!(macos_keycode(HotkeyKey::Function(21)), None);
+    }
+
+    #[test]
+    fn every_key_has_a_distinct_code_on_windows_and_x11() {
+        let keys = all_keys();
+        let windows: HashSet<_> = keys.iter().map(|key| windows_vk(*key).unwrap()).collect();
+        let x11: HashSet<_> = keys.iter().map(|key| x11_keysym(*key).unwrap()).collect();
+
+        assert_eq!(windows.len(), keys.len());
+        assert_eq!(x11.len(), keys.len());
+    }
+
+    #[test]
+    fn macos_codes_are_distinct_and_map_back() {
+        let mapped: Vec<_> = all_keys()
+            .into_iter()
+            .filter_map(|key| macos_keycode(key).map(|code| (key, code)))
+            .collect();
+        let codes: HashSet<_> = mapped.iter().map(|(_, code)| *code).collect();
+
+        assert_eq!(codes.len(), mapped.len());
+        for (key, code) in mapped {
+            assert_eq!(from_macos_keycode(code), Some(key), "keycode {}", code);
+        }
+        // kVK_Return
+        assert_eq!(from_macos_keycode(36), None);
+    }
+}
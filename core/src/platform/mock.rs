@@ -0,0 +1,866 @@
+//! In-memory `PlatformInterface` for exercising core logic without OS windows or hotkeys
+
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use crate::config::DisplayProperties;
+use crate::error::{NotecognitoError, Result};
+use crate::layout::ScreenInfo;
+use crate::notecard::NotecardId;
+use super::{AppIdentity, DismissReason, HotkeyModifier, NotificationKind, PlatformEvent, PlatformInterface};
+
+/// A call made through `PlatformInterface`
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockCall {
+    RegisterHotkey { id: NotecardId, modifiers: Vec<HotkeyModifier> },
+    UnregisterHotkey(NotecardId),
+    ShowNotecard { id: NotecardId, content: String },
+    UpdateNotecard { id: NotecardId, content: String },
+    HideNotecard(NotecardId),
+    HideAllNotecards,
+    SetNotecardPosition { id: NotecardId, x: i32, y: i32 },
+    SetNotecardSize { id: NotecardId, width: u32, height: u32 },
+    WriteClipboardText(String),
+    SetLaunchOnStartup(bool),
+    Initialize,
+    Cleanup,
+}
+
+/// Operations that can be made to fail with `MockPlatform::fail_next`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MockOperation {
+    RegisterHotkey,
+    UnregisterHotkey,
+    ShowNotecard,
+    UpdateNotecard,
+    HideNotecard,
+    HideAllNotecards,
+    SetNotecardPosition,
+    SetNotecardSize,
+    WriteClipboardText,
+    SetLaunchOnStartup,
+    Initialize,
+}
+
+/// A notecard the mock is currently displaying
+#[derive(Debug, Clone)]
+pub struct ShownNotecard {
+    pub content: String,
+    pub properties: DisplayProperties,
+    /// Top-left corner set by `set_notecard_position`, if the window was moved
+    pub origin: Option<(i32, i32)>,
+}
+
+/// A notification the mock was asked to show
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockNotification {
+    pub title: String,
+    pub body: String,
+    pub kind: NotificationKind,
+}
+
+/// Records every call and keeps the state a real platform would have
+///
+/// Calls are recorded even when they fail, and a failed call leaves the
+/// state untouched.
+#[derive(Debug)]
+pub struct MockPlatform {
+    calls: Vec<MockCall>,
+    hotkeys: HashMap<NotecardId, Vec<HotkeyModifier>>,
+    shown: HashMap<NotecardId, ShownNotecard>,
+    screens: Vec<ScreenInfo>,
+    frontmost: AppIdentity,
+    clipboard: Option<String>,
+    launch_on_startup: bool,
+    initialized: bool,
+    permissions_granted: bool,
+    /// Behind a lock because `show_notification` only gets `&self`
+    notifications: Mutex<Vec<MockNotification>>,
+    events: Option<tokio::sync::mpsc::Sender<PlatformEvent>>,
+    failures: HashSet<MockOperation>,
+}
+
+impl Default for MockPlatform {
+    fn default() -> Self {
+        MockPlatform {
+            calls: Vec::new(),
+            hotkeys: HashMap::new(),
+            shown: HashMap::new(),
+            screens: vec![ScreenInfo {
+                index: 0,
+                frame: (0, 0, 1920, 1080),
+                work_area: (0, 0, 1920, 1040),
+                scale_factor: 1.0,
+                is_primary: true,
+            }],
+            frontmost: AppIdentity::default(),
+            clipboard: None,
+            launch_on_startup: false,
+            initialized: false,
+            permissions_granted: true,
+            notifications: Mutex::new(Vec::new()),
+            events: None,
+            failures: HashSet::new(),
+        }
+    }
+}
+
+impl MockPlatform {
+    /// Creates a mock with a single 1920x1080 primary screen and permissions granted
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes the next call of `operation` fail with a platform error
+    pub fn fail_next(&mut self, operation: MockOperation) {
+        self.failures.insert(operation);
+    }
+
+    /// Makes the next `register_hotkey` fail, as if the combination were taken
+    pub fn fail_next_register(&mut self) {
+        self.fail_next(MockOperation::RegisterHotkey);
+    }
+
+    /// Makes the next `show_notecard` fail
+    pub fn fail_next_show(&mut self) {
+        self.fail_next(MockOperation::ShowNotecard);
+    }
+
+    /// Every call made so far, oldest first
+    pub fn calls(&self) -> &[MockCall] {
+        &self.calls
+    }
+
+    /// Forgets the recorded calls, keeping the state
+    pub fn clear_calls(&mut self) {
+        self.calls.clear();
+    }
+
+    /// Modifiers of the hotkey registered for a notecard
+    pub fn hotkey(&self, id: NotecardId) -> Option<&[HotkeyModifier]> {
+        self.hotkeys.get(&id).map(Vec::as_slice)
+    }
+
+    /// Number of registered hotkeys
+    pub fn hotkey_count(&self) -> usize {
+        self.hotkeys.len()
+    }
+
+    /// The notecard as currently displayed
+    pub fn shown(&self, id: NotecardId) -> Option<&ShownNotecard> {
+        self.shown.get(&id)
+    }
+
+    /// Whether launch on startup is enabled
+    pub fn launch_on_startup(&self) -> bool {
+        self.launch_on_startup
+    }
+
+    /// Whether `initialize` has been called without a later `cleanup`
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    /// Every notification shown so far, oldest first
+    pub fn notifications(&self) -> Vec<MockNotification> {
+        self.notifications.lock().unwrap().clone()
+    }
+
+    /// Replaces the screens reported by `screens`
+    pub fn set_screens(&mut self, screens: Vec<ScreenInfo>) {
+        self.screens = screens;
+    }
+
+    /// Text on the clipboard, `None` if it holds no text
+    pub fn clipboard(&self) -> Option<&str> {
+        self.clipboard.as_deref()
+    }
+
+    /// Replaces the clipboard, `None` standing in for non-text contents
+    pub fn set_clipboard(&mut self, text: Option<String>) {
+        self.clipboard = text;
+    }
+
+    /// Sets the application `frontmost_application` reports
+    pub fn set_frontmost_application(&mut self, app: AppIdentity) {
+        self.frontmost = app;
+    }
+
+    /// Sets the channel `dismiss` and `hide_all_notecards` report
+    /// `NotecardDismissed` events on
+    pub fn set_event_sender(&mut self, events: tokio::sync::mpsc::Sender<PlatformEvent>) {
+        self.events = Some(events);
+    }
+
+    /// Closes a shown notecard the way a click, ESC or timer would on a real
+    /// platform: without a recorded call, but with a `NotecardDismissed` event
+    pub fn dismiss(&mut self, id: NotecardId, reason: DismissReason) {
+        if self.shown.remove(&id).is_none() {
+            return;
+        }
+
+        if let Some(events) = &self.events {
+            let _ = events.try_send(PlatformEvent::NotecardDismissed { id, reason });
+        }
+    }
+
+    /// Sets what `check_permissions` reports and whether `request_permissions` succeeds
+    pub fn set_permissions_granted(&mut self, granted: bool) {
+        self.permissions_granted = granted;
+    }
+
+    /// Records a call and consumes a pending failure for its operation
+    fn record(&mut self, call: MockCall, operation: MockOperation) -> Result<()> {
+        self.calls.push(call);
+
+        if self.failures.remove(&operation) {
+            return Err(NotecognitoError::Platform(format!("Mock failure: {:?}", operation)));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PlatformInterface for MockPlatform {
+    async fn register_hotkey(&mut self, id: NotecardId, modifiers: &[HotkeyModifier]) -> Result<()> {
+        let call = MockCall::RegisterHotkey { id, modifiers: modifiers.to_vec() };
+        self.record(call, MockOperation::RegisterHotkey)?;
+        HotkeyModifier::check_supported(modifiers)?;
+        self.hotkeys.insert(id, modifiers.to_vec());
+        Ok(())
+    }
+
+    async fn unregister_hotkey(&mut self, id: NotecardId) -> Result<()> {
+        self.record(MockCall::UnregisterHotkey(id), MockOperation::UnregisterHotkey)?;
+        self.hotkeys.remove(&id);
+        Ok(())
+    }
+
+    async fn show_notecard(&mut self, id: NotecardId, content: &str, properties: &DisplayProperties) -> Result<()> {
+        let call = MockCall::ShowNotecard { id, content: content.to_string() };
+        self.record(call, MockOperation::ShowNotecard)?;
+        self.shown.insert(id, ShownNotecard {
+            content: content.to_string(),
+            properties: properties.clone(),
+            origin: None,
+        });
+        Ok(())
+    }
+
+    async fn hide_notecard(&mut self, id: NotecardId) -> Result<()> {
+        self.record(MockCall::HideNotecard(id), MockOperation::HideNotecard)?;
+        self.shown.remove(&id);
+        Ok(())
+    }
+
+    async fn hide_all_notecards(&mut self) -> Result<()> {
+        self.record(MockCall::HideAllNotecards, MockOperation::HideAllNotecards)?;
+        let mut ids: Vec<_> = self.shown.drain().map(|(id, _)| id).collect();
+        ids.sort_by_key(|id| id.value());
+        if let Some(events) = &self.events {
+            for id in ids {
+                let _ = events.try_send(PlatformEvent::NotecardDismissed { id, reason: DismissReason::HideAll });
+            }
+        }
+        Ok(())
+    }
+
+    async fn update_notecard(&mut self, id: NotecardId, content: &str, properties: &DisplayProperties) -> Result<()> {
+        let call = MockCall::UpdateNotecard { id, content: content.to_string() };
+        self.record(call, MockOperation::UpdateNotecard)?;
+        if let Some(shown) = self.shown.get_mut(&id) {
+            shown.content = content.to_string();
+            shown.properties = properties.clone();
+        }
+        Ok(())
+    }
+
+    async fn set_notecard_position(&mut self, id: NotecardId, x: i32, y: i32) -> Result<()> {
+        self.record(MockCall::SetNotecardPosition { id, x, y }, MockOperation::SetNotecardPosition)?;
+        let shown = self.shown.get_mut(&id).ok_or(NotecognitoError::NotVisible(id))?;
+        shown.origin = Some((x, y));
+        Ok(())
+    }
+
+    async fn set_notecard_size(&mut self, id: NotecardId, width: u32, height: u32) -> Result<()> {
+        let call = MockCall::SetNotecardSize { id, width, height };
+        self.record(call, MockOperation::SetNotecardSize)?;
+        let shown = self.shown.get_mut(&id).ok_or(NotecognitoError::NotVisible(id))?;
+        shown.properties.size = (width, height);
+        Ok(())
+    }
+
+    async fn is_notecard_visible(&self, id: NotecardId) -> Result<bool> {
+        Ok(self.shown.contains_key(&id))
+    }
+
+    async fn visible_notecards(&self) -> Result<Vec<NotecardId>> {
+        let mut ids: Vec<_> = self.shown.keys().copied().collect();
+        ids.sort_by_key(|id| id.value());
+        Ok(ids)
+    }
+
+    async fn screens(&self) -> Result<Vec<ScreenInfo>> {
+        Ok(self.screens.clone())
+    }
+
+    async fn read_clipboard_text(&self) -> Result<Option<String>> {
+        Ok(self.clipboard.clone())
+    }
+
+    async fn write_clipboard_text(&mut self, text: &str) -> Result<()> {
+        self.record(MockCall::WriteClipboardText(text.to_string()), MockOperation::WriteClipboardText)?;
+        self.clipboard = Some(text.to_string());
+        Ok(())
+    }
+
+    async fn frontmost_application(&self) -> Result<AppIdentity> {
+        Ok(self.frontmost.clone())
+    }
+
+    async fn show_notification(&self, title: &str, body: &str, kind: NotificationKind) -> Result<()> {
+        self.notifications.lock().unwrap().push(MockNotification {
+            title: title.to_string(),
+            body: body.to_string(),
+            kind,
+        });
+        Ok(())
+    }
+
+    async fn set_launch_on_startup(&mut self, enabled: bool) -> Result<()> {
+        self.record(MockCall::SetLaunchOnStartup(enabled), MockOperation::SetLaunchOnStartup)?;
+        self.launch_on_startup = enabled;
+        Ok(())
+    }
+
+    async fn launch_on_startup_status(&self) -> Result<Option<bool>> {
+        Ok(Some(self.launch_on_startup))
+    }
+
+    async fn initialize(&mut self) -> Result<()> {
+        self.record(MockCall::Initialize, MockOperation::Initialize)?;
+        self.initialized = true;
+        Ok(())
+    }
+
+    async fn cleanup(&mut self) -> Result<()> {
+        self.calls.push(MockCall::Cleanup);
+        self.hotkeys.clear();
+        self.shown.clear();
+        self.initialized = false;
+        Ok(())
+    }
+
+    async fn check_permissions(&self) -> Result<bool> {
+        Ok(self.permissions_granted)
+    }
+
+    async fn request_permissions(&self) -> Result<()> {
+        if self.permissions_granted {
+            Ok(())
+        } else {
+            Err(NotecognitoError::PermissionDenied("Mock permissions not granted".to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigManager;
+    use crate::ipc::{serve_connection, IpcMessage, IpcMessageType, IpcServer};
+    use crate::notecard::Notecard;
+    use crate::platform::{HotkeyAction, HotkeyBinding, HotkeyChanges, HotkeyKey, ToggleOutcome};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+    use tokio::sync::mpsc;
+
+    fn id(value: u8) -> NotecardId {
+        NotecardId::new(value).unwrap()
+    }
+
+    /// A config manager whose file is never written, as none of these save
+    fn config_manager(cards: &[(u8, &str)]) -> ConfigManager {
+        let path = std::env::temp_dir().join(format!("notecognito-mock-test-{}.json", std::process::id()));
+        let mut manager = ConfigManager::with_path(path).unwrap();
+        for (value, content) in cards {
+            manager.update_notecard(Notecard::new(id(*value), content.to_string())).unwrap();
+        }
+        manager
+    }
+
+    /// Sends one request over a connection served by `server` and returns the reply
+    async fn request(server: IpcServer, message_type: IpcMessageType) -> IpcMessageType {
+        let (mut client, stream): (DuplexStream, DuplexStream) = tokio::io::duplex(64 * 1024);
+        let connection = tokio::spawn(async move { serve_connection(stream, &server).await });
+
+        let json = serde_json::to_vec(&IpcMessage::with_id("1".to_string(), message_type)).unwrap();
+        client.write_all(&(json.len() as u32).to_le_bytes()).await.unwrap();
+        client.write_all(&json).await.unwrap();
+
+        let reply = read_reply(&mut client).await;
+        drop(client);
+        connection.await.unwrap().unwrap();
+
+        assert_eq!(reply.id, "1");
+        reply.message_type
+    }
+
+    async fn read_reply(client: &mut DuplexStream) -> IpcMessage {
+        let mut len = [0u8; 4];
+        client.read_exact(&mut len).await.unwrap();
+        let mut reply = vec![0u8; u32::from_le_bytes(len) as usize];
+        client.read_exact(&mut reply).await.unwrap();
+        serde_json::from_slice(&reply).unwrap()
+    }
+
+    fn served(manager: ConfigManager, platform: &Arc<tokio::sync::Mutex<MockPlatform>>) -> IpcServer {
+        let platform: Arc<tokio::sync::Mutex<dyn PlatformInterface>> = platform.clone();
+        IpcServer::with_platform(Arc::new(tokio::sync::Mutex::new(manager)), platform)
+    }
+
+    #[tokio::test]
+    async fn ipc_show_notecard_forwards_content_and_properties() {
+        let mut manager = config_manager(&[(3, "Third card")]);
+        let properties = DisplayProperties { size: (420, 240), ..DisplayProperties::default() };
+        manager.set_notecard_display_properties(id(3), properties).unwrap();
+        let platform = Arc::new(tokio::sync::Mutex::new(MockPlatform::new()));
+
+        let reply = request(served(manager, &platform), IpcMessageType::ShowNotecard { notecard_id: id(3) }).await;
+
+        assert!(matches!(reply, IpcMessageType::Success { .. }), "{:?}", reply);
+        let platform = platform.lock().await;
+        assert_eq!(
+            platform.calls(),
+            [MockCall::ShowNotecard { id: id(3), content: "Third card".to_string() }]
+        );
+        assert_eq!(platform.shown(id(3)).unwrap().properties.size, (420, 240));
+    }
+
+    #[tokio::test]
+    async fn ipc_show_notecard_refuses_an_empty_card() {
+        let platform = Arc::new(tokio::sync::Mutex::new(MockPlatform::new()));
+
+        let reply = request(served(config_manager(&[]), &platform), IpcMessageType::ShowNotecard { notecard_id: id(2) }).await;
+
+        assert!(matches!(reply, IpcMessageType::Error { error_code: Some(_), .. }), "{:?}", reply);
+        assert!(platform.lock().await.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn ipc_show_notecard_reports_a_platform_failure() {
+        let platform = Arc::new(tokio::sync::Mutex::new(MockPlatform::new()));
+        platform.lock().await.fail_next_show();
+
+        let reply = request(served(config_manager(&[(1, "First")]), &platform), IpcMessageType::ShowNotecard { notecard_id: id(1) }).await;
+
+        assert!(matches!(reply, IpcMessageType::Error { .. }), "{:?}", reply);
+        assert!(platform.lock().await.shown(id(1)).is_none());
+    }
+
+    #[tokio::test]
+    async fn ipc_show_notecard_without_a_platform_fails() {
+        let server = IpcServer::new(Arc::new(tokio::sync::Mutex::new(config_manager(&[(1, "First")]))));
+
+        let reply = request(server, IpcMessageType::ShowNotecard { notecard_id: id(1) }).await;
+
+        assert!(matches!(reply, IpcMessageType::Error { .. }), "{:?}", reply);
+    }
+
+    #[tokio::test]
+    async fn ipc_hide_all_notecards_reaches_the_platform() {
+        let platform = Arc::new(tokio::sync::Mutex::new(MockPlatform::new()));
+        let properties = DisplayProperties::default();
+        platform.lock().await.show_notecard(id(1), "First", &properties).await.unwrap();
+
+        let reply = request(served(config_manager(&[]), &platform), IpcMessageType::HideAllNotecards).await;
+
+        assert!(matches!(reply, IpcMessageType::Success { .. }), "{:?}", reply);
+        assert!(platform.lock().await.visible_notecards().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn registering_again_replaces_the_modifiers() {
+        let mut platform = MockPlatform::new();
+        platform.register_hotkey(id(1), &[HotkeyModifier::Control]).await.unwrap();
+        platform.register_hotkey(id(1), &[HotkeyModifier::Alt, HotkeyModifier::Shift]).await.unwrap();
+
+        assert_eq!(platform.hotkey(id(1)), Some(&[HotkeyModifier::Alt, HotkeyModifier::Shift][..]));
+        assert_eq!(platform.hotkey_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_registration_keeps_the_previous_hotkey() {
+        let mut platform = MockPlatform::new();
+        platform.register_hotkey(id(1), &[HotkeyModifier::Control]).await.unwrap();
+
+        platform.fail_next_register();
+        assert!(platform.register_hotkey(id(1), &[HotkeyModifier::Alt]).await.is_err());
+        assert_eq!(platform.hotkey(id(1)), Some(&[HotkeyModifier::Control][..]));
+
+        // Only the next call fails; retrying goes through
+        platform.register_hotkey(id(1), &[HotkeyModifier::Alt]).await.unwrap();
+        assert_eq!(platform.hotkey(id(1)), Some(&[HotkeyModifier::Alt][..]));
+    }
+
+    #[tokio::test]
+    async fn unsupported_modifier_is_rejected_without_registering() {
+        let mut platform = MockPlatform::new();
+        let unsupported = if cfg!(target_os = "windows") { HotkeyModifier::Command } else { HotkeyModifier::Windows };
+
+        assert!(platform.register_hotkey(id(1), &[unsupported]).await.is_err());
+        assert_eq!(platform.hotkey_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn unregister_then_register_moves_a_hotkey() {
+        let mut platform = MockPlatform::new();
+        platform.register_hotkey(id(1), &[HotkeyModifier::Control]).await.unwrap();
+
+        platform.unregister_hotkey(id(1)).await.unwrap();
+        platform.register_hotkey(id(2), &[HotkeyModifier::Control]).await.unwrap();
+
+        assert_eq!(platform.hotkey(id(1)), None);
+        assert_eq!(platform.hotkey(id(2)), Some(&[HotkeyModifier::Control][..]));
+        assert_eq!(
+            platform.calls(),
+            [
+                MockCall::RegisterHotkey { id: id(1), modifiers: vec![HotkeyModifier::Control] },
+                MockCall::UnregisterHotkey(id(1)),
+                MockCall::RegisterHotkey { id: id(2), modifiers: vec![HotkeyModifier::Control] },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn cleanup_drops_every_hotkey() {
+        let mut platform = MockPlatform::new();
+        platform.register_hotkey(id(1), &[HotkeyModifier::Control]).await.unwrap();
+        platform.register_hotkey(id(2), &[HotkeyModifier::Control]).await.unwrap();
+
+        platform.cleanup().await.unwrap();
+
+        assert_eq!(platform.hotkey_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn toggle_shows_then_hides() {
+        let mut platform = MockPlatform::new();
+        let properties = DisplayProperties::default();
+
+        assert_eq!(platform.toggle_notecard(id(1), "First", &properties).await.unwrap(), ToggleOutcome::Shown);
+        assert!(platform.is_notecard_visible(id(1)).await.unwrap());
+
+        assert_eq!(platform.toggle_notecard(id(1), "First", &properties).await.unwrap(), ToggleOutcome::Hidden);
+        assert!(!platform.is_notecard_visible(id(1)).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn toggle_after_a_dismissal_shows_again() {
+        let mut platform = MockPlatform::new();
+        let properties = DisplayProperties::default();
+        platform.toggle_notecard(id(1), "First", &properties).await.unwrap();
+
+        platform.dismiss(id(1), DismissReason::Click);
+
+        assert_eq!(platform.toggle_notecard(id(1), "First", &properties).await.unwrap(), ToggleOutcome::Shown);
+    }
+
+    #[tokio::test]
+    async fn failed_toggle_leaves_the_card_hidden() {
+        let mut platform = MockPlatform::new();
+        platform.fail_next_show();
+
+        assert!(platform.toggle_notecard(id(1), "First", &DisplayProperties::default()).await.is_err());
+        assert!(!platform.is_notecard_visible(id(1)).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn hide_all_reports_each_card_dismissed() {
+        let (events, mut received) = mpsc::channel(8);
+        let mut platform = MockPlatform::new();
+        platform.set_event_sender(events);
+        let properties = DisplayProperties::default();
+        platform.show_notecard(id(4), "Fourth", &properties).await.unwrap();
+        platform.show_notecard(id(2), "Second", &properties).await.unwrap();
+
+        platform.hide_all_notecards().await.unwrap();
+
+        assert!(platform.visible_notecards().await.unwrap().is_empty());
+        for expected in [2, 4] {
+            assert_eq!(
+                received.try_recv().unwrap(),
+                PlatformEvent::NotecardDismissed { id: id(expected), reason: DismissReason::HideAll }
+            );
+        }
+        assert!(received.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn hide_all_with_nothing_shown_reports_nothing() {
+        let (events, mut received) = mpsc::channel(8);
+        let mut platform = MockPlatform::new();
+        platform.set_event_sender(events);
+
+        platform.hide_all_notecards().await.unwrap();
+
+        assert!(received.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn failed_hide_all_keeps_the_cards() {
+        let mut platform = MockPlatform::new();
+        platform.show_notecard(id(1), "First", &DisplayProperties::default()).await.unwrap();
+        platform.fail_next(MockOperation::HideAllNotecards);
+
+        assert!(platform.hide_all_notecards().await.is_err());
+        assert_eq!(platform.visible_notecards().await.unwrap(), [id(1)]);
+    }
+
+    fn bindings(entries: &[(HotkeyAction, &[HotkeyModifier])]) -> HashMap<HotkeyAction, HotkeyBinding> {
+        entries
+            .iter()
+            .map(|(action, modifiers)| {
+                let key = match action {
+                    HotkeyAction::ToggleNotecard(id) => HotkeyKey::for_notecard(*id),
+                    HotkeyAction::HideAll => HotkeyKey::hide_all(),
+                };
+                (*action, HotkeyBinding::new(modifiers, key))
+            })
+            .collect()
+    }
+
+    /// Applies the changes as the Windows and macOS `sync_bindings` do,
+    /// keeping `registered` to what actually got registered; the mock has no
+    /// hide-all hotkey, so that one always succeeds
+    async fn sync_bindings(
+        platform: &mut MockPlatform,
+        registered: &mut HashMap<HotkeyAction, HotkeyBinding>,
+        wanted: &HashMap<HotkeyAction, HotkeyBinding>,
+    ) -> HotkeyChanges {
+        let changes = HotkeyChanges::between(registered, wanted);
+
+        for action in &changes.unregister {
+            if let HotkeyAction::ToggleNotecard(id) = action {
+                platform.unregister_hotkey(*id).await.unwrap();
+            }
+            registered.remove(action);
+        }
+
+        for (action, binding) in &changes.register {
+            let result = match action {
+                HotkeyAction::ToggleNotecard(id) => platform.register_hotkey(*id, &binding.modifiers).await,
+                HotkeyAction::HideAll => Ok(()),
+            };
+            if result.is_ok() {
+                registered.insert(*action, binding.clone());
+            }
+        }
+
+        changes
+    }
+
+    const CTRL_SHIFT: &[HotkeyModifier] = &[HotkeyModifier::Control, HotkeyModifier::Shift];
+    const ALT_SHIFT: &[HotkeyModifier] = &[HotkeyModifier::Alt, HotkeyModifier::Shift];
+
+    #[tokio::test]
+    async fn sync_registers_added_hotkeys_in_order() {
+        let mut platform = MockPlatform::new();
+        let mut registered = HashMap::new();
+        let toggle = |value| HotkeyAction::ToggleNotecard(id(value));
+        let wanted = bindings(&[(HotkeyAction::HideAll, CTRL_SHIFT), (toggle(5), CTRL_SHIFT), (toggle(2), CTRL_SHIFT)]);
+
+        let changes = sync_bindings(&mut platform, &mut registered, &wanted).await;
+
+        assert!(changes.unregister.is_empty());
+        let order: Vec<_> = changes.register.iter().map(|(action, _)| *action).collect();
+        assert_eq!(order, [toggle(2), toggle(5), HotkeyAction::HideAll]);
+        assert_eq!(platform.hotkey_count(), 2);
+        assert_eq!(registered, wanted);
+    }
+
+    #[tokio::test]
+    async fn sync_unregisters_removed_hotkeys_only() {
+        let mut platform = MockPlatform::new();
+        let mut registered = HashMap::new();
+        let toggle = |value| HotkeyAction::ToggleNotecard(id(value));
+        sync_bindings(&mut platform, &mut registered, &bindings(&[(toggle(1), CTRL_SHIFT), (toggle(2), CTRL_SHIFT)])).await;
+        platform.clear_calls();
+
+        let changes = sync_bindings(&mut platform, &mut registered, &bindings(&[(toggle(1), CTRL_SHIFT)])).await;
+
+        assert_eq!(changes, HotkeyChanges { unregister: vec![toggle(2)], register: Vec::new() });
+        assert_eq!(platform.calls(), [MockCall::UnregisterHotkey(id(2))]);
+        assert_eq!(platform.hotkey(id(1)), Some(CTRL_SHIFT));
+        assert_eq!(platform.hotkey(id(2)), None);
+    }
+
+    #[tokio::test]
+    async fn sync_leaves_unchanged_hotkeys_alone() {
+        let mut platform = MockPlatform::new();
+        let mut registered = HashMap::new();
+        let wanted = bindings(&[(HotkeyAction::ToggleNotecard(id(1)), CTRL_SHIFT), (HotkeyAction::HideAll, CTRL_SHIFT)]);
+        sync_bindings(&mut platform, &mut registered, &wanted).await;
+        platform.clear_calls();
+
+        let changes = sync_bindings(&mut platform, &mut registered, &wanted).await;
+
+        assert!(changes.is_empty());
+        assert!(platform.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn sync_re_registers_a_hotkey_whose_modifiers_changed() {
+        let mut platform = MockPlatform::new();
+        let mut registered = HashMap::new();
+        let toggle = |value| HotkeyAction::ToggleNotecard(id(value));
+        sync_bindings(&mut platform, &mut registered, &bindings(&[(toggle(1), CTRL_SHIFT), (toggle(2), CTRL_SHIFT)])).await;
+        platform.clear_calls();
+
+        let wanted = bindings(&[(toggle(1), ALT_SHIFT), (toggle(2), CTRL_SHIFT)]);
+        let changes = sync_bindings(&mut platform, &mut registered, &wanted).await;
+
+        assert!(changes.unregister.is_empty());
+        assert_eq!(changes.register, [(toggle(1), wanted[&toggle(1)].clone())]);
+        assert_eq!(platform.calls(), [MockCall::RegisterHotkey { id: id(1), modifiers: ALT_SHIFT.to_vec() }]);
+        assert_eq!(platform.hotkey(id(1)), Some(ALT_SHIFT));
+    }
+
+    #[tokio::test]
+    async fn sync_retries_a_hotkey_that_failed_to_register() {
+        let mut platform = MockPlatform::new();
+        let mut registered = HashMap::new();
+        let wanted = bindings(&[(HotkeyAction::ToggleNotecard(id(1)), CTRL_SHIFT)]);
+
+        platform.fail_next_register();
+        sync_bindings(&mut platform, &mut registered, &wanted).await;
+        assert_eq!(platform.hotkey_count(), 0);
+
+        let changes = sync_bindings(&mut platform, &mut registered, &wanted).await;
+        assert_eq!(changes.register.len(), 1);
+        assert_eq!(platform.hotkey(id(1)), Some(CTRL_SHIFT));
+    }
+
+    #[tokio::test]
+    async fn dismissal_takes_the_card_off_screen_and_reports_it_once() {
+        let (events, mut received) = mpsc::channel(8);
+        let mut platform = MockPlatform::new();
+        platform.set_event_sender(events);
+        let properties = DisplayProperties::default();
+        platform.show_notecard(id(1), "First", &properties).await.unwrap();
+        platform.show_notecard(id(2), "Second", &properties).await.unwrap();
+
+        platform.dismiss(id(1), DismissReason::Escape);
+        platform.dismiss(id(1), DismissReason::Click);
+
+        assert_eq!(platform.visible_notecards().await.unwrap(), [id(2)]);
+        assert!(platform.shown(id(1)).is_none());
+        assert_eq!(
+            received.try_recv().unwrap(),
+            PlatformEvent::NotecardDismissed { id: id(1), reason: DismissReason::Escape }
+        );
+        assert!(received.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn hidden_card_is_never_reported_dismissed() {
+        let (events, mut received) = mpsc::channel(8);
+        let mut platform = MockPlatform::new();
+        platform.set_event_sender(events);
+        platform.show_notecard(id(1), "First", &DisplayProperties::default()).await.unwrap();
+
+        platform.hide_notecard(id(1)).await.unwrap();
+        platform.dismiss(id(1), DismissReason::AutoHide);
+        platform.dismiss(id(3), DismissReason::Click);
+
+        assert!(received.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn card_dismissed_then_shown_again_is_tracked_afresh() {
+        let (events, mut received) = mpsc::channel(8);
+        let mut platform = MockPlatform::new();
+        platform.set_event_sender(events);
+        let properties = DisplayProperties::default();
+        platform.show_notecard(id(1), "First", &properties).await.unwrap();
+        platform.set_notecard_position(id(1), 10, 20).await.unwrap();
+
+        platform.dismiss(id(1), DismissReason::Click);
+        platform.show_notecard(id(1), "First again", &properties).await.unwrap();
+
+        let shown = platform.shown(id(1)).unwrap();
+        assert_eq!(shown.content, "First again");
+        assert_eq!(shown.origin, None);
+        assert!(platform.set_notecard_position(id(2), 0, 0).await.is_err());
+
+        platform.hide_all_notecards().await.unwrap();
+        let reasons: Vec<_> = std::iter::from_fn(|| received.try_recv().ok()).collect();
+        assert_eq!(
+            reasons,
+            [
+                PlatformEvent::NotecardDismissed { id: id(1), reason: DismissReason::Click },
+                PlatformEvent::NotecardDismissed { id: id(1), reason: DismissReason::HideAll },
+            ]
+        );
+    }
+
+    #[test]
+    fn platform_events_keep_their_wire_format() {
+        let event = PlatformEvent::NotecardDismissed { id: id(4), reason: DismissReason::HideAll };
+        let json = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(json, serde_json::json!({ "type": "NotecardDismissed", "id": 4, "reason": "HideAll" }));
+        assert_eq!(serde_json::from_value::<PlatformEvent>(json).unwrap(), event);
+
+        let moved = PlatformEvent::NotecardMoved { id: id(2), monitor: 1, position: (-5, 7) };
+        let json = serde_json::to_value(&moved).unwrap();
+        assert_eq!(json, serde_json::json!({ "type": "NotecardMoved", "id": 2, "monitor": 1, "position": [-5, 7] }));
+    }
+
+    #[tokio::test]
+    async fn dismissals_reach_ipc_subscribers() {
+        let platform = Arc::new(tokio::sync::Mutex::new(MockPlatform::new()));
+        let server = served(config_manager(&[]), &platform);
+        let (mut client, stream) = tokio::io::duplex(64 * 1024);
+        let serving = server.clone();
+        tokio::spawn(async move { serve_connection(stream, &serving).await });
+
+        let json = serde_json::to_vec(&IpcMessage::with_id("sub".to_string(), IpcMessageType::Subscribe)).unwrap();
+        client.write_all(&(json.len() as u32).to_le_bytes()).await.unwrap();
+        client.write_all(&json).await.unwrap();
+        let subscribed = read_reply(&mut client).await;
+        assert!(matches!(subscribed.message_type, IpcMessageType::Success { .. }));
+
+        let event = PlatformEvent::NotecardDismissed { id: id(5), reason: DismissReason::Click };
+        server.notify_platform_event(event.clone());
+
+        match read_reply(&mut client).await.message_type {
+            IpcMessageType::PlatformEvent { event: pushed } => assert_eq!(pushed, event),
+            other => panic!("Expected a platform event, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn a_corrupt_config_is_set_aside_and_the_user_told() {
+        let dir = std::env::temp_dir().join(format!("notecognito-mock-recover-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        std::fs::write(&path, "{ \"notecards\": ").unwrap();
+
+        let (manager, set_aside) = ConfigManager::with_path_or_recover(&path).unwrap();
+        let set_aside = set_aside.expect("a corrupt config should be set aside");
+        assert!(manager.config().notecards.values().all(|card| card.content.is_empty()));
+        assert_eq!(std::fs::read_to_string(&set_aside).unwrap(), "{ \"notecards\": ");
+        assert!(!path.exists());
+
+        let platform = MockPlatform::new();
+        crate::platform::notify_config_recovered(&platform, &set_aside).await.unwrap();
+        let notifications = platform.notifications();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].kind, NotificationKind::Warning);
+        assert!(notifications[0].body.contains(&set_aside.display().to_string()));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
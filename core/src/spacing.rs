@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+
+/// Tuning for `apply_spacing`; gaps are multiples of the font size
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpacingParams {
+    /// Font size in points
+    pub font_size: f64,
+    /// Line height multiple for short cards
+    pub line_height: f64,
+    /// Line height multiple once a card counts as dense
+    pub dense_line_height: f64,
+    /// Non-blank lines from which a card counts as dense
+    pub dense_lines: usize,
+    /// Characters from which a card counts as dense, for long wrapped lines
+    pub dense_chars: usize,
+    /// Extra space above a paragraph that follows a blank line
+    pub paragraph_gap: f64,
+    /// Extra space below each list item
+    pub list_item_gap: f64,
+    /// Extra space between consecutive lines of dense prose
+    pub dense_line_gap: f64,
+}
+
+impl SpacingParams {
+    /// The default tuning for text set at `font_size` points
+    pub fn for_font_size(font_size: f64) -> Self {
+        SpacingParams {
+            font_size,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for SpacingParams {
+    fn default() -> Self {
+        SpacingParams {
+            font_size: 16.0,
+            line_height: 1.15,
+            dense_line_height: 1.4,
+            dense_lines: 12,
+            dense_chars: 480,
+            paragraph_gap: 0.6,
+            list_item_gap: 0.3,
+            dense_line_gap: 0.25,
+        }
+    }
+}
+
+/// Spacing for one line of the content, in points
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LineSpacing {
+    pub space_before: f64,
+    pub space_after: f64,
+}
+
+/// How to space out a notecard's text; the text itself is left as it is
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpacedLayout {
+    /// Multiple of the font's natural line height, for every line
+    pub line_height_multiple: f64,
+    /// Whether the content counted as dense
+    pub dense: bool,
+    /// One entry per line of the content, as split on `\n`
+    pub lines: Vec<LineSpacing>,
+}
+
+/// Works out line and paragraph spacing from how dense `content` is
+///
+/// Dense cards, with many lines or much text, get taller lines and a small
+/// gap at every line break. Paragraphs that follow a blank line get extra
+/// space above them, and list items (`-`, `*`, `•` or `1.` and `1)` bullets)
+/// extra space below, so a long card reads in blocks rather than as a wall.
+pub fn apply_spacing(content: &str, params: &SpacingParams) -> SpacedLayout {
+    let lines: Vec<&str> = content.split('\n').collect();
+    let non_blank = lines.iter().filter(|line| !line.trim().is_empty()).count();
+    let dense = non_blank >= params.dense_lines || content.chars().count() >= params.dense_chars;
+
+    let points = |multiple: f64| multiple * params.font_size;
+    let mut spacing = vec![LineSpacing::default(); lines.len()];
+
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let previous = i.checked_sub(1).map(|i| lines[i]);
+        match previous {
+            Some(previous) if previous.trim().is_empty() => spacing[i].space_before = points(params.paragraph_gap),
+            Some(previous) if dense && !is_list_item(previous) && !is_list_item(line) => {
+                spacing[i].space_before = points(params.dense_line_gap)
+            }
+            _ => {}
+        }
+
+        if is_list_item(line) {
+            spacing[i].space_after = points(params.list_item_gap);
+        }
+    }
+
+    SpacedLayout {
+        line_height_multiple: if dense { params.dense_line_height } else { params.line_height },
+        dense,
+        lines: spacing,
+    }
+}
+
+/// Whether a line starts with a bullet or a number followed by `.` or `)`
+fn is_list_item(line: &str) -> bool {
+    let line = line.trim_start();
+    if ["- ", "* ", "• "].iter().any(|bullet| line.starts_with(bullet)) {
+        return true;
+    }
+
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    digits > 0 && (line[digits..].starts_with(". ") || line[digits..].starts_with(") "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> SpacingParams {
+        SpacingParams::for_font_size(10.0)
+    }
+
+    fn before(layout: &SpacedLayout) -> Vec<f64> {
+        layout.lines.iter().map(|line| line.space_before).collect()
+    }
+
+    fn after(layout: &SpacedLayout) -> Vec<f64> {
+        layout.lines.iter().map(|line| line.space_after).collect()
+    }
+
+    #[test]
+    fn short_card_keeps_plain_spacing() {
+        let layout = apply_spacing("One line\nand another", &params());
+
+        assert!(!layout.dense);
+        assert_eq!(layout.line_height_multiple, params().line_height);
+        assert_eq!(layout.lines, [LineSpacing::default(); 2]);
+    }
+
+    #[test]
+    fn one_entry_per_line_including_blank_ones() {
+        assert_eq!(apply_spacing("", &params()).lines.len(), 1);
+        assert_eq!(apply_spacing("a\n\n\nb\n", &params()).lines.len(), 5);
+    }
+
+    #[test]
+    fn paragraph_after_a_blank_line_gets_space_above() {
+        let params = params();
+        let gap = params.paragraph_gap * params.font_size;
+
+        let layout = apply_spacing("First\n\nSecond\n  \nThird", &params);
+
+        assert_eq!(before(&layout), [0.0, 0.0, gap, 0.0, gap]);
+    }
+
+    #[test]
+    fn list_items_get_space_below() {
+        let params = params();
+        let gap = params.list_item_gap * params.font_size;
+
+        let layout = apply_spacing("Items:\n- one\n* two\n• three\n12. four\n3) five\n-not a bullet\n4.not either", &params);
+
+        assert_eq!(after(&layout), [0.0, gap, gap, gap, gap, gap, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn many_lines_make_a_card_dense() {
+        let params = params();
+        let content = vec!["line"; params.dense_lines].join("\n");
+
+        let layout = apply_spacing(&content, &params);
+
+        assert!(layout.dense);
+        assert_eq!(layout.line_height_multiple, params.dense_line_height);
+        let gap = params.dense_line_gap * params.font_size;
+        assert_eq!(before(&layout)[0], 0.0);
+        assert!(before(&layout)[1..].iter().all(|space| *space == gap));
+    }
+
+    #[test]
+    fn blank_lines_do_not_count_towards_density() {
+        let params = params();
+        let content = vec!["line"; params.dense_lines - 1].join("\n\n");
+
+        assert!(!apply_spacing(&content, &params).dense);
+    }
+
+    #[test]
+    fn long_text_makes_a_card_dense() {
+        let params = params();
+
+        assert!(apply_spacing(&"é".repeat(params.dense_chars), &params).dense);
+        assert!(!apply_spacing(&"é".repeat(params.dense_chars - 1), &params).dense);
+    }
+
+    #[test]
+    fn dense_gap_skips_list_items() {
+        let params = SpacingParams { dense_lines: 1, ..params() };
+        let gap = params.dense_line_gap * params.font_size;
+
+        let layout = apply_spacing("Prose\nmore prose\n- item\nafter the list", &params);
+
+        assert_eq!(before(&layout), [0.0, gap, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,29 @@
+#!/bin/bash
+
+# Build script for Notecognito Linux
+
+echo "Building Notecognito for Linux..."
+
+# Check if Rust is installed
+if ! command -v cargo &> /dev/null; then
+    echo "Error: Cargo not found. Please install Rust from https://rustup.rs/"
+    exit 1
+fi
+
+# fontconfig resolves the configured font family at runtime
+if ! command -v fc-match &> /dev/null; then
+    echo "Warning: fc-match not found. Install fontconfig so notecards can find fonts."
+fi
+
+# Build in release mode
+echo "Building release version..."
+cargo build --release
+
+if [ $? -ne 0 ]; then
+    echo "Error: Build failed!"
+    exit 1
+fi
+
+echo ""
+echo "Build successful!"
+echo "  Executable: target/release/notecognito"
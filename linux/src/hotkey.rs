@@ -0,0 +1,251 @@
+use anyhow::{anyhow, Context, Result};
+use notecognito_core::{HotkeyAction, HotkeyBinding, HotkeyChanges, HotkeyKey, HotkeyModifier, NotecardId};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{ConnectionExt as _, GrabMode, Keycode, ModMask, Window};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+/// Caps Lock and Num Lock, which don't change the meaning of a hotkey
+const LOCK_MODIFIERS: u16 = (1 << 1) | (1 << 4);
+
+/// Every combination of the lock modifiers
+const IGNORED_MODIFIERS: [u16; 4] = [0, 1 << 1, 1 << 4, LOCK_MODIFIERS];
+
+/// Global hotkeys grabbed on the X11 root window
+pub struct HotkeyManager {
+    conn: Arc<RustConnection>,
+    root: Window,
+    registered_hotkeys: Arc<Mutex<HashMap<HotkeyAction, (Keycode, u16)>>>,
+    /// The combinations behind `registered_hotkeys`, for `sync_bindings` to diff
+    bindings: HashMap<HotkeyAction, HotkeyBinding>,
+    event_thread: Option<thread::JoinHandle<()>>,
+}
+
+impl HotkeyManager {
+    pub fn new() -> Result<Self> {
+        let (conn, screen_num) = x11rb::connect(None)
+            .context("Failed to connect to the X server")?;
+        let root = conn.setup().roots[screen_num].root;
+
+        Ok(HotkeyManager {
+            conn: Arc::new(conn),
+            root,
+            registered_hotkeys: Arc::new(Mutex::new(HashMap::new())),
+            bindings: HashMap::new(),
+            event_thread: None,
+        })
+    }
+
+    pub fn register_hotkey(
+        &mut self,
+        notecard_id: NotecardId,
+        modifiers: &[HotkeyModifier],
+        key: HotkeyKey,
+    ) -> Result<()> {
+        self.register(HotkeyAction::ToggleNotecard(notecard_id), modifiers, key)?;
+        tracing::info!(
+            "Registered hotkey {} for notecard {}",
+            HotkeyBinding::new(modifiers, key),
+            notecard_id.value()
+        );
+        Ok(())
+    }
+
+    /// Registers the panic hotkey, the notecard modifiers with 0
+    pub fn register_hide_all_hotkey(&mut self, modifiers: &[HotkeyModifier]) -> Result<()> {
+        let key = HotkeyKey::hide_all();
+        self.register(HotkeyAction::HideAll, modifiers, key)?;
+        tracing::info!("Registered hotkey {} to hide all notecards", HotkeyBinding::new(modifiers, key));
+        Ok(())
+    }
+
+    /// Fails on a modifier X11 hotkeys don't have, such as Command from a
+    /// configuration saved on macOS, rather than grabbing the combination
+    /// without it
+    fn register(&mut self, action: HotkeyAction, modifiers: &[HotkeyModifier], key: HotkeyKey) -> Result<()> {
+        HotkeyModifier::check_supported(modifiers)?;
+
+        // Convert modifiers to X11 format
+        let mut x11_modifiers = 0u16;
+
+        for modifier in modifiers {
+            x11_modifiers |= u16::from(match modifier {
+                HotkeyModifier::Control => ModMask::CONTROL,
+                HotkeyModifier::Alt => ModMask::M1,
+                HotkeyModifier::Shift => ModMask::SHIFT,
+                HotkeyModifier::Command | HotkeyModifier::Windows => {
+                    unreachable!("rejected by check_supported above")
+                }
+            });
+        }
+
+        let keysym = key
+            .to_x11_keysym()
+            .ok_or_else(|| anyhow!("Key {} has no X11 keysym", key))?;
+        let keycode = self.keycode_for_keysym(keysym)?;
+
+        // A combination already bound to this action is replaced
+        self.unregister(action)?;
+
+        // Grab once per lock-key combination so Caps/Num Lock don't block the hotkey
+        for ignored in IGNORED_MODIFIERS {
+            let grabbed = self
+                .conn
+                .grab_key(
+                    false,
+                    self.root,
+                    ModMask::from(x11_modifiers | ignored),
+                    keycode,
+                    GrabMode::ASYNC,
+                    GrabMode::ASYNC,
+                )?
+                .check();
+            if grabbed.is_err() {
+                // Don't leave the lock-key combinations already grabbed behind
+                let _ = self.ungrab(keycode, x11_modifiers);
+                self.conn.flush()?;
+                return Err(anyhow!(
+                    "Failed to register hotkey {} (already in use by another application)",
+                    HotkeyBinding::new(modifiers, key)
+                ));
+            }
+        }
+        self.conn.flush()?;
+
+        self.registered_hotkeys
+            .lock()
+            .unwrap()
+            .insert(action, (keycode, x11_modifiers));
+        self.bindings.insert(action, HotkeyBinding::new(modifiers, key));
+
+        Ok(())
+    }
+
+    pub fn unregister_hotkey(&mut self, notecard_id: NotecardId) -> Result<()> {
+        self.unregister(HotkeyAction::ToggleNotecard(notecard_id))
+    }
+
+    fn unregister(&mut self, action: HotkeyAction) -> Result<()> {
+        self.bindings.remove(&action);
+        let removed = self.registered_hotkeys.lock().unwrap().remove(&action);
+        if let Some((keycode, modifiers)) = removed {
+            self.ungrab(keycode, modifiers)?;
+            self.conn.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn unregister_all(&mut self) -> Result<()> {
+        self.bindings.clear();
+        let hotkeys: Vec<_> = self.registered_hotkeys.lock().unwrap().drain().collect();
+        for (_, (keycode, modifiers)) in hotkeys {
+            let _ = self.ungrab(keycode, modifiers);
+        }
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// Grabs and releases hotkeys until exactly `wanted` is bound, returning
+    /// the ones that couldn't be grabbed
+    ///
+    /// Unchanged hotkeys stay grabbed, so running this again never drops a
+    /// press. A hotkey that fails is logged and skipped so it doesn't cost
+    /// the others, and is tried again on the next sync.
+    pub fn sync_bindings(
+        &mut self,
+        wanted: &HashMap<HotkeyAction, HotkeyBinding>,
+    ) -> Vec<(HotkeyAction, HotkeyBinding)> {
+        let changes = HotkeyChanges::between(&self.bindings, wanted);
+        let mut failed = Vec::new();
+
+        for action in changes.unregister {
+            match self.unregister(action) {
+                Ok(()) => tracing::info!("Unregistered hotkey for {:?}", action),
+                Err(e) => tracing::warn!("Could not unregister the hotkey for {:?}: {}", action, e),
+            }
+        }
+
+        for (action, binding) in changes.register {
+            let result = match action {
+                HotkeyAction::ToggleNotecard(id) => self.register_hotkey(id, &binding.modifiers, binding.key),
+                HotkeyAction::HideAll => self.register_hide_all_hotkey(&binding.modifiers),
+            };
+            if let Err(e) = result {
+                tracing::warn!("Could not register hotkey {} for {:?}: {}", binding, action, e);
+                failed.push((action, binding));
+            }
+        }
+
+        failed
+    }
+
+    pub fn start_event_loop<F>(&mut self, callback: F) -> Result<()>
+    where
+        F: Fn(HotkeyAction) + Send + 'static,
+    {
+        let conn = Arc::clone(&self.conn);
+        let registered_hotkeys = Arc::clone(&self.registered_hotkeys);
+
+        let handle = thread::spawn(move || loop {
+            let event = match conn.wait_for_event() {
+                Ok(event) => event,
+                Err(e) => {
+                    tracing::error!("Lost connection to the X server: {}", e);
+                    break;
+                }
+            };
+
+            if let Event::KeyPress(press) = event {
+                let state = u16::from(press.state) & !LOCK_MODIFIERS;
+                let action = registered_hotkeys
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .find(|(_, (keycode, modifiers))| {
+                        *keycode == press.detail && *modifiers == state
+                    })
+                    .map(|(action, _)| *action);
+
+                if let Some(action) = action {
+                    callback(action);
+                }
+            }
+        });
+
+        self.event_thread = Some(handle);
+        Ok(())
+    }
+
+    fn ungrab(&self, keycode: Keycode, modifiers: u16) -> Result<()> {
+        for ignored in IGNORED_MODIFIERS {
+            self.conn.ungrab_key(keycode, self.root, ModMask::from(modifiers | ignored))?;
+        }
+        Ok(())
+    }
+
+    /// Finds the keycode that produces `keysym` in the current keyboard layout
+    fn keycode_for_keysym(&self, keysym: u32) -> Result<Keycode> {
+        let setup = self.conn.setup();
+        let min_keycode = setup.min_keycode;
+        let count = setup.max_keycode - min_keycode + 1;
+
+        let mapping = self.conn.get_keyboard_mapping(min_keycode, count)?.reply()?;
+        let per_keycode = mapping.keysyms_per_keycode as usize;
+
+        mapping
+            .keysyms
+            .chunks(per_keycode.max(1))
+            .position(|keysyms| keysyms.contains(&keysym))
+            .map(|index| min_keycode + index as u8)
+            .ok_or_else(|| anyhow!("No key produces keysym {:#x}", keysym))
+    }
+}
+
+impl Drop for HotkeyManager {
+    fn drop(&mut self) {
+        let _ = self.unregister_all();
+    }
+}
@@ -0,0 +1,155 @@
+use anyhow::{anyhow, Result};
+use notecognito_core::{Config, IpcMessage, IpcMessageType, Notecard, PlatformEvent};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+
+const IPC_HOST: &str = "127.0.0.1";
+const IPC_PORT: u16 = 7855;
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024; // 1MB
+
+pub struct IpcClient {
+    stream: Option<Arc<Mutex<TcpStream>>>,
+}
+
+// Mirrors the client on the other platforms; not every request is used here yet
+#[allow(dead_code)]
+impl IpcClient {
+    pub fn new() -> Self {
+        IpcClient { stream: None }
+    }
+
+    pub async fn connect(&mut self) -> Result<()> {
+        let addr = format!("{}:{}", IPC_HOST, IPC_PORT);
+        let stream = TcpStream::connect(&addr).await?;
+        self.stream = Some(Arc::new(Mutex::new(stream)));
+        tracing::info!("Connected to IPC server at {}", addr);
+        Ok(())
+    }
+
+    pub async fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    pub async fn get_configuration(&mut self) -> Result<Config> {
+        let message = IpcMessage::new(IpcMessageType::GetConfiguration);
+        let response = self.send_message(message).await?;
+
+        match response.message_type {
+            IpcMessageType::ConfigurationResponse { config } => Ok(config),
+            IpcMessageType::Error { message, .. } => Err(anyhow!("Server error: {}", message)),
+            _ => Err(anyhow!("Unexpected response type")),
+        }
+    }
+
+    pub async fn update_notecard(&mut self, notecard: Notecard) -> Result<()> {
+        let message = IpcMessage::new(IpcMessageType::UpdateNotecard { notecard });
+        let response = self.send_message(message).await?;
+
+        match response.message_type {
+            IpcMessageType::Success { .. } => Ok(()),
+            IpcMessageType::Error { message, .. } => Err(anyhow!("Server error: {}", message)),
+            _ => Err(anyhow!("Unexpected response type")),
+        }
+    }
+
+    pub async fn save_configuration(&mut self, config: Config) -> Result<()> {
+        let message = IpcMessage::new(IpcMessageType::SaveConfiguration { config });
+        let response = self.send_message(message).await?;
+
+        match response.message_type {
+            IpcMessageType::Success { .. } => Ok(()),
+            IpcMessageType::Error { message, .. } => Err(anyhow!("Server error: {}", message)),
+            _ => Err(anyhow!("Unexpected response type")),
+        }
+    }
+
+    /// Reports an event for the server to push to its subscribers
+    pub async fn report_platform_event(&mut self, event: PlatformEvent) -> Result<()> {
+        let message = IpcMessage::new(IpcMessageType::PlatformEvent { event });
+        let response = self.send_message(message).await?;
+
+        match response.message_type {
+            IpcMessageType::Success { .. } => Ok(()),
+            IpcMessageType::Error { message, .. } => Err(anyhow!("Server error: {}", message)),
+            _ => Err(anyhow!("Unexpected response type")),
+        }
+    }
+
+    /// Subscribes to configuration changes; afterwards the connection only
+    /// carries the server's pushes, read with `next_config_change`
+    pub async fn subscribe(&mut self) -> Result<()> {
+        let message = IpcMessage::new(IpcMessageType::Subscribe);
+        let response = self.send_message(message).await?;
+
+        match response.message_type {
+            IpcMessageType::Success { .. } => Ok(()),
+            IpcMessageType::Error { message, .. } => Err(anyhow!("Server error: {}", message)),
+            _ => Err(anyhow!("Unexpected response type")),
+        }
+    }
+
+    /// Waits for the next configuration pushed to a subscribed connection
+    pub async fn next_config_change(&mut self) -> Result<Config> {
+        let stream = self.stream.as_ref()
+            .ok_or_else(|| anyhow!("Not connected to IPC server"))?;
+
+        let mut stream = stream.lock().await;
+
+        loop {
+            let message = read_message(&mut stream).await?;
+
+            match message.message_type {
+                IpcMessageType::ConfigChanged { config } => return Ok(config),
+                // Pushed to every subscriber, including the app that reported it
+                IpcMessageType::PlatformEvent { .. } => continue,
+                _ => return Err(anyhow!("Unexpected message type")),
+            }
+        }
+    }
+
+    async fn send_message(&mut self, message: IpcMessage) -> Result<IpcMessage> {
+        let stream = self.stream.as_ref()
+            .ok_or_else(|| anyhow!("Not connected to IPC server"))?;
+
+        let mut stream = stream.lock().await;
+
+        // Serialize message
+        let json = serde_json::to_vec(&message)?;
+        let len = json.len() as u32;
+
+        // Send length prefix
+        stream.write_all(&len.to_le_bytes()).await?;
+
+        // Send message
+        stream.write_all(&json).await?;
+        stream.flush().await?;
+
+        read_message(&mut stream).await
+    }
+
+    pub async fn disconnect(&mut self) {
+        self.stream = None;
+    }
+}
+
+/// Reads one length-prefixed message from the server
+async fn read_message(stream: &mut TcpStream) -> Result<IpcMessage> {
+    // Read message length
+    let mut len_bytes = [0u8; 4];
+    stream.read_exact(&mut len_bytes).await?;
+    let message_len = u32::from_le_bytes(len_bytes) as usize;
+
+    if message_len > MAX_MESSAGE_SIZE {
+        return Err(anyhow!("Response too large"));
+    }
+
+    // Read message
+    let mut buffer = vec![0; message_len];
+    stream.read_exact(&mut buffer).await?;
+
+    // Parse message
+    let message: IpcMessage = serde_json::from_slice(&buffer)?;
+    Ok(message)
+}
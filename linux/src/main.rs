@@ -0,0 +1,340 @@
+use anyhow::{Context, Result};
+use ksni::TrayMethods;
+use notecognito_core::{
+    AppIdentity, ConfigManager, HotkeyAction, NotecardId, PlatformEvent, PlatformInterface,
+    PLATFORM_EVENT_CAPACITY,
+};
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::net::{SocketAddr, UnixListener};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+
+mod hotkey;
+mod ipc_client;
+mod notecard_window;
+mod platform_impl;
+mod tray;
+
+use hotkey::HotkeyManager;
+use ipc_client::IpcClient;
+use notecard_window::NotecardWindowManager;
+use platform_impl::LinuxPlatform;
+use tray::NotecognitoTray;
+
+const APP_NAME: &str = "Notecognito";
+
+struct App {
+    config_manager: Arc<Mutex<ConfigManager>>,
+    ipc_client: Arc<Mutex<IpcClient>>,
+    hotkey_manager: Arc<Mutex<HotkeyManager>>,
+    platform: Arc<Mutex<LinuxPlatform>>,
+    /// Taken by `run`, which forwards the events to core
+    platform_events: Option<mpsc::Receiver<PlatformEvent>>,
+    tray: Option<ksni::Handle<NotecognitoTray>>,
+}
+
+impl App {
+    async fn new() -> Result<Self> {
+        // Initialize logging
+        tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::INFO)
+            .init();
+
+        tracing::info!("Starting Notecognito for Linux");
+
+        // Create config manager
+        let config_manager = ConfigManager::new()
+            .context("Failed to create config manager")?;
+        let config_manager = Arc::new(Mutex::new(config_manager));
+
+        // Create IPC client
+        let ipc_client = IpcClient::new();
+        let ipc_client = Arc::new(Mutex::new(ipc_client));
+
+        // Create managers
+        let hotkey_manager = Arc::new(Mutex::new(HotkeyManager::new()?));
+        let (events_tx, platform_events) = mpsc::channel(PLATFORM_EVENT_CAPACITY);
+        let window_manager = Arc::new(Mutex::new(NotecardWindowManager::new(events_tx)?));
+
+        // Create platform implementation
+        let platform = LinuxPlatform::new(
+            Arc::clone(&hotkey_manager),
+            window_manager,
+        );
+        let platform = Arc::new(Mutex::new(platform));
+
+        Ok(App {
+            config_manager,
+            ipc_client,
+            hotkey_manager,
+            platform,
+            platform_events: Some(platform_events),
+            tray: None,
+        })
+    }
+
+    async fn initialize(&mut self) -> Result<()> {
+        // Try to connect to IPC server
+        match self.connect_to_core().await {
+            Ok(_) => tracing::info!("Connected to core service"),
+            Err(e) => {
+                tracing::warn!("Could not connect to core service: {}", e);
+                tracing::info!("Running in standalone mode");
+            }
+        }
+
+        // Initialize platform
+        {
+            let mut platform = self.platform.lock().await;
+            platform.initialize().await?;
+            platform.check_permissions().await?;
+        }
+
+        // Load configuration and setup hotkeys
+        self.load_configuration().await?;
+
+        // Create system tray; not every desktop has a StatusNotifierItem host
+        let tray = NotecognitoTray {
+            platform: Arc::clone(&self.platform),
+            runtime: tokio::runtime::Handle::current(),
+        };
+        match tray.spawn().await {
+            Ok(handle) => self.tray = Some(handle),
+            Err(e) => tracing::warn!("Could not create tray icon: {}", e),
+        }
+
+        Ok(())
+    }
+
+    async fn connect_to_core(&self) -> Result<()> {
+        let mut client = self.ipc_client.lock().await;
+        client.connect().await?;
+
+        // Get configuration from core
+        let config = client.get_configuration().await?;
+
+        // Update local config
+        let mut manager = self.config_manager.lock().await;
+        *manager.config_mut() = config;
+
+        Ok(())
+    }
+
+    async fn load_configuration(&self) -> Result<()> {
+        // A hotkey another app holds is logged and skipped; the app runs all the same
+        sync_hotkeys(&self.config_manager, &self.hotkey_manager).await;
+
+        let manager = self.config_manager.lock().await;
+        let config = manager.config();
+
+        // Set launch on startup
+        if config.launch_on_startup {
+            let mut platform = self.platform.lock().await;
+            platform.set_launch_on_startup(true).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn run(&mut self) -> Result<()> {
+        let config_manager = Arc::clone(&self.config_manager);
+        let platform = Arc::clone(&self.platform);
+        let runtime = tokio::runtime::Handle::current();
+
+        {
+            let mut hotkey_manager = self.hotkey_manager.lock().await;
+
+            hotkey_manager.start_event_loop(move |action| {
+                let config_manager = Arc::clone(&config_manager);
+                let platform = Arc::clone(&platform);
+
+                match action {
+                    HotkeyAction::ToggleNotecard(notecard_id) => {
+                        runtime.spawn(async move {
+                            if let Err(e) = toggle_notecard(notecard_id, config_manager, platform).await {
+                                tracing::error!("Failed to toggle notecard: {}", e);
+                            }
+                        });
+                    }
+                    HotkeyAction::HideAll => {
+                        runtime.spawn(hide_all_notecards(platform));
+                    }
+                }
+            })?;
+        }
+
+        // Let core's subscribers hear about notecards closed by click or timer
+        if let Some(events) = self.platform_events.take() {
+            tokio::spawn(forward_platform_events(events, Arc::clone(&self.ipc_client)));
+        }
+
+        // Keep visible notecards in sync with edits made in the config UI
+        let config_manager = Arc::clone(&self.config_manager);
+        let platform = Arc::clone(&self.platform);
+        let hotkey_manager = Arc::clone(&self.hotkey_manager);
+        tokio::spawn(async move {
+            if let Err(e) = watch_config_changes(config_manager, platform, hotkey_manager).await {
+                tracing::warn!("Stopped watching for configuration changes: {}", e);
+            }
+        });
+
+        // Hotkeys and clicks are handled on their own threads; quitting goes
+        // through the tray menu or a signal
+        tokio::signal::ctrl_c().await?;
+
+        let mut platform = self.platform.lock().await;
+        platform.cleanup().await?;
+
+        Ok(())
+    }
+}
+
+/// Applies configuration pushed by core: refreshes notecards that are on
+/// screen and brings the hotkeys in line with it
+async fn watch_config_changes(
+    config_manager: Arc<Mutex<ConfigManager>>,
+    platform: Arc<Mutex<LinuxPlatform>>,
+    hotkey_manager: Arc<Mutex<HotkeyManager>>,
+) -> Result<()> {
+    // Subscribing turns the connection into a push stream, so it gets its own client
+    let mut client = IpcClient::new();
+    client.connect().await?;
+    client.subscribe().await?;
+
+    loop {
+        let config = client.next_config_change().await?;
+
+        {
+            let mut platform = platform.lock().await;
+
+            for id in platform.visible_notecards().await? {
+                match config.notecards.get(&id).filter(|notecard| !notecard.is_empty()) {
+                    Some(notecard) => {
+                        let properties = config.display_properties_for(id);
+                        platform.update_notecard(id, &notecard.content, properties).await?
+                    }
+                    None => platform.hide_notecard(id).await?,
+                }
+            }
+        }
+
+        *config_manager.lock().await.config_mut() = config;
+        sync_hotkeys(&config_manager, &hotkey_manager).await;
+    }
+}
+
+/// Grabs and releases hotkeys until they match the configuration: one per
+/// notecard with content, plus hide-all when it's on, and none at all while
+/// hotkeys are paused
+///
+/// Combinations another app already holds are logged; the rest still work.
+async fn sync_hotkeys(config_manager: &Mutex<ConfigManager>, hotkey_manager: &Mutex<HotkeyManager>) {
+    let bindings = config_manager.lock().await.config().active_hotkey_bindings();
+    let failed = hotkey_manager.lock().await.sync_bindings(&bindings);
+    if !failed.is_empty() {
+        tracing::warn!("{} of {} hotkeys could not be registered", failed.len(), bindings.len());
+    }
+}
+
+async fn toggle_notecard(
+    notecard_id: NotecardId,
+    config_manager: Arc<Mutex<ConfigManager>>,
+    platform: Arc<Mutex<LinuxPlatform>>,
+) -> Result<()> {
+    let (content, properties, blocked_applications) = {
+        let manager = config_manager.lock().await;
+        let config = manager.config();
+        let content = manager
+            .get_notecard(notecard_id)
+            .map(|notecard| notecard.content.clone())
+            .unwrap_or_default();
+        (
+            content,
+            config.display_properties_for(notecard_id).clone(),
+            config.blocked_applications.clone(),
+        )
+    };
+
+    let mut platform = platform.lock().await;
+
+    if !platform.is_notecard_visible(notecard_id).await? {
+        // A card cleared while on screen can still be hidden, but never shown
+        if content.is_empty() {
+            return Ok(());
+        }
+
+        if !blocked_applications.is_empty() {
+            // Not knowing what's in front never keeps a notecard from showing
+            let app = platform.frontmost_application().await.unwrap_or_else(|e| {
+                tracing::debug!("Could not get the frontmost application: {}", e);
+                AppIdentity::default()
+            });
+
+            if app.matches_any(&blocked_applications) {
+                tracing::debug!("Not showing notecard {} in front of {}", notecard_id.value(), app.name);
+                return Ok(());
+            }
+        }
+    }
+
+    let outcome = platform.toggle_notecard(notecard_id, &content, &properties).await?;
+    tracing::debug!("Notecard {} toggled: {:?}", notecard_id.value(), outcome);
+
+    Ok(())
+}
+
+/// Reports platform events to core, which pushes them to its subscribers
+async fn forward_platform_events(mut events: mpsc::Receiver<PlatformEvent>, ipc_client: Arc<Mutex<IpcClient>>) {
+    while let Some(event) = events.recv().await {
+        tracing::debug!("Platform event: {:?}", event);
+
+        let mut client = ipc_client.lock().await;
+        if !client.is_connected().await {
+            continue;
+        }
+        if let Err(e) = client.report_platform_event(event).await {
+            tracing::debug!("Could not report platform event: {}", e);
+        }
+    }
+}
+
+/// Takes every notecard off screen, for the panic hotkey and the tray menu
+async fn hide_all_notecards(platform: Arc<Mutex<LinuxPlatform>>) {
+    let mut platform = platform.lock().await;
+    if let Err(e) = platform.hide_all_notecards().await {
+        tracing::error!("Failed to hide notecards: {}", e);
+    }
+}
+
+/// Launches the Electron configuration UI, preferring a copy next to this executable
+fn launch_config_ui() {
+    let config_path = std::env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(|p| p.join("notecognito-config")))
+        .filter(|p| p.exists())
+        .unwrap_or_else(|| "notecognito-config".into());
+
+    if let Err(e) = std::process::Command::new(&config_path).spawn() {
+        tracing::error!("Failed to launch config UI: {}", e);
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Check if already running; the abstract socket is released when the process exits
+    let addr = SocketAddr::from_abstract_name(b"notecognito-linux")?;
+    let _instance_lock = match UnixListener::bind_addr(&addr) {
+        Ok(listener) => listener,
+        Err(_) => {
+            eprintln!("Notecognito is already running");
+            return Ok(());
+        }
+    };
+
+    // Create and run app
+    let mut app = App::new().await?;
+    app.initialize().await?;
+    app.run().await?;
+
+    Ok(())
+}
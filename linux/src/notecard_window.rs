@@ -0,0 +1,590 @@
+use anyhow::{anyhow, Context, Result};
+use fontdue::{Font, FontSettings};
+use notecognito_core::{place_window, DismissReason, DisplayProperties, NotecardId, PlatformEvent, ScreenInfo};
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::randr::ConnectionExt as _;
+use x11rb::protocol::xproto::{
+    AtomEnum, ChangeWindowAttributesAux, ColormapAlloc, ConfigureWindowAux, ConnectionExt as _, CreateWindowAux, EventMask,
+    ImageFormat, Visualid, VisualClass, Window, WindowClass,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+/// Padding between the window edge and the text, in pixels
+const PADDING: u32 = 10;
+/// Background color (dark gray, like the other platforms)
+const BACKGROUND: u8 = 0x20;
+/// Font files tried when fontconfig can't resolve the configured family
+const FALLBACK_FONTS: [&str; 3] = [
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+];
+
+type WindowMap = Arc<Mutex<HashMap<NotecardId, Window>>>;
+
+/// Visual used for notecard windows
+struct NotecardVisual {
+    id: Visualid,
+    depth: u8,
+    /// Whether the visual has an alpha channel (needs a compositor to show through)
+    argb: bool,
+}
+
+/// Override-redirect overlay windows, one per visible notecard
+pub struct NotecardWindowManager {
+    conn: Arc<RustConnection>,
+    screen_num: usize,
+    windows: WindowMap,
+    /// What each window was last painted with, so a resize can repaint it
+    painted: HashMap<NotecardId, (String, DisplayProperties)>,
+    fonts: HashMap<String, Arc<Font>>,
+    /// Where dismissals by click or auto-hide timer are reported
+    events: mpsc::Sender<PlatformEvent>,
+}
+
+impl NotecardWindowManager {
+    pub fn new(events: mpsc::Sender<PlatformEvent>) -> Result<Self> {
+        let (conn, screen_num) = x11rb::connect(None)
+            .context("Failed to connect to the X server")?;
+        let conn = Arc::new(conn);
+        let windows: WindowMap = Arc::new(Mutex::new(HashMap::new()));
+
+        // Close notecards on click
+        let event_conn = Arc::clone(&conn);
+        let event_windows = Arc::clone(&windows);
+        let click_events = events.clone();
+        thread::spawn(move || loop {
+            match event_conn.wait_for_event() {
+                Ok(Event::ButtonPress(press)) => {
+                    let mut windows = event_windows.lock().unwrap();
+                    let notecard_id = windows
+                        .iter()
+                        .find(|(_, window)| **window == press.event)
+                        .map(|(id, _)| *id);
+                    if let Some(id) = notecard_id {
+                        windows.remove(&id);
+                    }
+                    drop(windows);
+                    let _ = destroy_window(&event_conn, press.event);
+
+                    if let Some(id) = notecard_id {
+                        send_dismissed(&click_events, id, DismissReason::Click);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::error!("Lost connection to the X server: {}", e);
+                    break;
+                }
+            }
+        });
+
+        Ok(NotecardWindowManager {
+            conn,
+            screen_num,
+            windows,
+            painted: HashMap::new(),
+            fonts: HashMap::new(),
+            events,
+        })
+    }
+
+    pub fn show_notecard(
+        &mut self,
+        notecard_id: NotecardId,
+        content: &str,
+        properties: &DisplayProperties,
+    ) -> Result<()> {
+        // Hide existing window if any
+        self.hide_notecard(notecard_id)?;
+
+        let font = self.font(&properties.font_family)?;
+        let window = self.create_notecard_window(content, properties, &font)?;
+
+        self.windows.lock().unwrap().insert(notecard_id, window);
+        self.painted.insert(notecard_id, (content.to_string(), properties.clone()));
+
+        // Set auto-hide timer if configured
+        if properties.auto_hide_duration > 0 {
+            let conn = Arc::clone(&self.conn);
+            let windows = Arc::clone(&self.windows);
+            let events = self.events.clone();
+            let duration = Duration::from_secs(properties.auto_hide_duration as u64);
+
+            thread::spawn(move || {
+                thread::sleep(duration);
+
+                // Only close the window this timer was started for
+                let mut windows = windows.lock().unwrap();
+                if windows.get(&notecard_id) == Some(&window) {
+                    windows.remove(&notecard_id);
+                    drop(windows);
+                    let _ = destroy_window(&conn, window);
+                    send_dismissed(&events, notecard_id, DismissReason::AutoHide);
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn hide_notecard(&mut self, notecard_id: NotecardId) -> Result<()> {
+        self.painted.remove(&notecard_id);
+        let window = self.windows.lock().unwrap().remove(&notecard_id);
+        if let Some(window) = window {
+            destroy_window(&self.conn, window)?;
+        }
+        Ok(())
+    }
+
+    /// Repaints an open window with new content and geometry without recreating it
+    pub fn update_notecard(
+        &mut self,
+        notecard_id: NotecardId,
+        content: &str,
+        properties: &DisplayProperties,
+    ) -> Result<()> {
+        let window = match self.windows.lock().unwrap().get(&notecard_id) {
+            Some(window) => *window,
+            None => return Ok(()),
+        };
+
+        let font = self.font(&properties.font_family)?;
+        let (width, height) = (properties.size.0.max(1), properties.size.1.max(1));
+        let position = place_window(properties, &self.screens().unwrap_or_default());
+
+        self.conn.configure_window(
+            window,
+            &ConfigureWindowAux::new()
+                .x(position.0)
+                .y(position.1)
+                .width(width)
+                .height(height),
+        )?;
+        self.paint_window(window, content, properties, &font)?;
+        self.painted.insert(notecard_id, (content.to_string(), properties.clone()));
+
+        // Repaint from the new background pixmap
+        self.conn.clear_area(false, window, 0, 0, 0, 0)?;
+        self.conn.flush()?;
+
+        Ok(())
+    }
+
+    /// Moves an open window without repainting it
+    pub fn set_notecard_position(&mut self, notecard_id: NotecardId, x: i32, y: i32) -> Result<()> {
+        let window = self.open_window(notecard_id)?;
+        self.conn.configure_window(window, &ConfigureWindowAux::new().x(x).y(y))?;
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// Resizes an open window in place and repaints its content at the new size
+    pub fn set_notecard_size(&mut self, notecard_id: NotecardId, width: u32, height: u32) -> Result<()> {
+        let window = self.open_window(notecard_id)?;
+        let (content, mut properties) = self
+            .painted
+            .get(&notecard_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("Notecard {} has no painted content", notecard_id.value()))?;
+        properties.size = (width, height);
+
+        let font = self.font(&properties.font_family)?;
+        self.conn.configure_window(
+            window,
+            &ConfigureWindowAux::new().width(width.max(1)).height(height.max(1)),
+        )?;
+        self.paint_window(window, &content, &properties, &font)?;
+        self.painted.insert(notecard_id, (content, properties));
+
+        self.conn.clear_area(false, window, 0, 0, 0, 0)?;
+        self.conn.flush()?;
+
+        Ok(())
+    }
+
+    /// Lists RandR monitors, with the work area taken from `_NET_WORKAREA` when the
+    /// window manager publishes one
+    pub fn screens(&self) -> Result<Vec<ScreenInfo>> {
+        let root = self.conn.setup().roots[self.screen_num].root;
+        let monitors = self.conn.randr_get_monitors(root, true)?.reply()?.monitors;
+        let work_area = self.desktop_work_area(root);
+
+        Ok(monitors
+            .iter()
+            .enumerate()
+            .map(|(index, monitor)| {
+                let frame = (
+                    monitor.x as i32,
+                    monitor.y as i32,
+                    monitor.width as u32,
+                    monitor.height as u32,
+                );
+
+                ScreenInfo {
+                    index,
+                    frame,
+                    work_area: work_area
+                        .and_then(|area| intersect(frame, area))
+                        .unwrap_or(frame),
+                    // X11 has no per-monitor scaling
+                    scale_factor: 1.0,
+                    is_primary: monitor.primary,
+                }
+            })
+            .collect())
+    }
+
+    /// Work area of the first desktop, which spans every monitor
+    fn desktop_work_area(&self, root: Window) -> Option<(i32, i32, u32, u32)> {
+        let atom = self.conn.intern_atom(true, b"_NET_WORKAREA").ok()?.reply().ok()?.atom;
+        let reply = self.conn
+            .get_property(false, root, atom, AtomEnum::CARDINAL, 0, 4)
+            .ok()?
+            .reply()
+            .ok()?;
+
+        let values: Vec<u32> = reply.value32()?.collect();
+        match values[..] {
+            [x, y, width, height] => Some((x as i32, y as i32, width, height)),
+            _ => None,
+        }
+    }
+
+    /// Windows closed by a click or the auto-hide timer are already untracked
+    pub fn is_notecard_visible(&self, notecard_id: NotecardId) -> bool {
+        self.windows.lock().unwrap().contains_key(&notecard_id)
+    }
+
+    pub fn visible_notecards(&self) -> Vec<NotecardId> {
+        let mut ids: Vec<_> = self.windows.lock().unwrap().keys().copied().collect();
+        ids.sort_by_key(|id| id.value());
+        ids
+    }
+
+    pub fn hide_all(&mut self) -> Result<()> {
+        self.painted.clear();
+        let windows: Vec<_> = self.windows.lock().unwrap().drain().collect();
+        for (notecard_id, window) in windows {
+            let _ = destroy_window(&self.conn, window);
+            send_dismissed(&self.events, notecard_id, DismissReason::HideAll);
+        }
+        Ok(())
+    }
+
+    fn open_window(&self, notecard_id: NotecardId) -> Result<Window> {
+        self.windows
+            .lock()
+            .unwrap()
+            .get(&notecard_id)
+            .copied()
+            .ok_or_else(|| anyhow!("Notecard {} has no open window", notecard_id.value()))
+    }
+
+    fn create_notecard_window(
+        &self,
+        content: &str,
+        properties: &DisplayProperties,
+        font: &Font,
+    ) -> Result<Window> {
+        let screen = &self.conn.setup().roots[self.screen_num];
+        let visual = self.find_visual();
+        let (width, height) = (properties.size.0.max(1), properties.size.1.max(1));
+        let position = place_window(properties, &self.screens().unwrap_or_default());
+
+        // A matching colormap is required for visuals other than the root's
+        let colormap = self.conn.generate_id()?;
+        self.conn.create_colormap(ColormapAlloc::NONE, colormap, screen.root, visual.id)?;
+
+        let window = self.conn.generate_id()?;
+        let aux = CreateWindowAux::new()
+            .override_redirect(1)
+            .background_pixel(0)
+            .border_pixel(0)
+            .colormap(colormap)
+            .event_mask(EventMask::BUTTON_PRESS);
+
+        self.conn.create_window(
+            visual.depth,
+            window,
+            screen.root,
+            position.0 as i16,
+            position.1 as i16,
+            width as u16,
+            height as u16,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            visual.id,
+            &aux,
+        )?;
+        self.conn.free_colormap(colormap)?;
+
+        self.paint_window(window, content, properties, font)?;
+
+        self.conn.map_window(window)?;
+        self.conn.flush()?;
+
+        Ok(window)
+    }
+
+    /// Renders into a pixmap and uses it as the window background, so the
+    /// server repaints exposures without a round trip
+    fn paint_window(
+        &self,
+        window: Window,
+        content: &str,
+        properties: &DisplayProperties,
+        font: &Font,
+    ) -> Result<()> {
+        let visual = self.find_visual();
+        let (width, height) = (properties.size.0.max(1), properties.size.1.max(1));
+
+        let alpha = if visual.argb {
+            ((properties.opacity as u32 * 255) / 100) as u8
+        } else {
+            0xFF
+        };
+        let pixels = render_notecard(content, properties, font, width, height, alpha);
+
+        let pixmap = self.conn.generate_id()?;
+        self.conn.create_pixmap(visual.depth, pixmap, window, width as u16, height as u16)?;
+
+        let gc = self.conn.generate_id()?;
+        self.conn.create_gc(gc, pixmap, &Default::default())?;
+        self.put_image(pixmap, gc, visual.depth, width, &pixels)?;
+        self.conn.free_gc(gc)?;
+
+        self.conn.change_window_attributes(
+            window,
+            &ChangeWindowAttributesAux::new().background_pixmap(pixmap),
+        )?;
+        self.conn.free_pixmap(pixmap)?;
+
+        Ok(())
+    }
+
+    /// Uploads BGRA pixels in bands that fit the server's request size limit
+    fn put_image(
+        &self,
+        pixmap: u32,
+        gc: u32,
+        depth: u8,
+        width: u32,
+        pixels: &[u8],
+    ) -> Result<()> {
+        let row_bytes = width as usize * 4;
+        let max_rows = ((self.conn.maximum_request_bytes() - 32) / row_bytes).max(1);
+
+        for (band, rows) in pixels.chunks(row_bytes * max_rows).enumerate() {
+            self.conn.put_image(
+                ImageFormat::Z_PIXMAP,
+                pixmap,
+                gc,
+                width as u16,
+                (rows.len() / row_bytes) as u16,
+                0,
+                (band * max_rows) as i16,
+                0,
+                depth,
+                rows,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Prefers a 32-bit ARGB visual so opacity works under a compositor
+    fn find_visual(&self) -> NotecardVisual {
+        let screen = &self.conn.setup().roots[self.screen_num];
+
+        for depth in &screen.allowed_depths {
+            if depth.depth != 32 {
+                continue;
+            }
+            if let Some(visual) = depth.visuals.iter().find(|v| v.class == VisualClass::TRUE_COLOR) {
+                return NotecardVisual { id: visual.visual_id, depth: 32, argb: true };
+            }
+        }
+
+        NotecardVisual { id: screen.root_visual, depth: screen.root_depth, argb: false }
+    }
+
+    /// Loads (and caches) the font for a family name via fontconfig
+    fn font(&mut self, family: &str) -> Result<Arc<Font>> {
+        if let Some(font) = self.fonts.get(family) {
+            return Ok(Arc::clone(font));
+        }
+
+        let pattern = match family {
+            "System" | "" => "sans-serif",
+            name => name,
+        };
+
+        let matched = Command::new("fc-match")
+            .args(["--format=%{file}", pattern])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned());
+
+        let candidates = matched.iter().map(String::as_str).chain(FALLBACK_FONTS);
+        for path in candidates {
+            if let Ok(data) = std::fs::read(path) {
+                let font = Font::from_bytes(data, FontSettings::default())
+                    .map_err(|e| anyhow!("Failed to load font {}: {}", path, e))?;
+                let font = Arc::new(font);
+                self.fonts.insert(family.to_string(), Arc::clone(&font));
+                return Ok(font);
+            }
+        }
+
+        Err(anyhow!("No usable font found for \"{}\" (is fontconfig installed?)", family))
+    }
+}
+
+impl Drop for NotecardWindowManager {
+    fn drop(&mut self) {
+        let _ = self.hide_all();
+    }
+}
+
+/// Overlap of two (x, y, width, height) rectangles
+fn intersect(a: (i32, i32, u32, u32), b: (i32, i32, u32, u32)) -> Option<(i32, i32, u32, u32)> {
+    let left = a.0.max(b.0);
+    let top = a.1.max(b.1);
+    let right = (a.0 + a.2 as i32).min(b.0 + b.2 as i32);
+    let bottom = (a.1 + a.3 as i32).min(b.1 + b.3 as i32);
+
+    if right <= left || bottom <= top {
+        return None;
+    }
+
+    Some((left, top, (right - left) as u32, (bottom - top) as u32))
+}
+
+/// Reports a window closed other than by `hide`; a full channel drops the event
+fn send_dismissed(events: &mpsc::Sender<PlatformEvent>, id: NotecardId, reason: DismissReason) {
+    if let Err(e) = events.try_send(PlatformEvent::NotecardDismissed { id, reason }) {
+        tracing::debug!("Dropped dismissal of notecard {}: {}", id.value(), e);
+    }
+}
+
+fn destroy_window(conn: &RustConnection, window: Window) -> Result<()> {
+    conn.destroy_window(window)?;
+    conn.flush()?;
+    Ok(())
+}
+
+/// Draws white word-wrapped text on the dark background as premultiplied BGRA
+fn render_notecard(
+    content: &str,
+    properties: &DisplayProperties,
+    font: &Font,
+    width: u32,
+    height: u32,
+    alpha: u8,
+) -> Vec<u8> {
+    let mut coverage = vec![0u8; (width * height) as usize];
+    let px = properties.font_size as f32;
+    let max_width = width.saturating_sub(PADDING * 2) as f32;
+
+    let line_height = font
+        .horizontal_line_metrics(px)
+        .map(|metrics| metrics.new_line_size)
+        .unwrap_or(px * 1.2);
+    let ascent = font
+        .horizontal_line_metrics(px)
+        .map(|metrics| metrics.ascent)
+        .unwrap_or(px);
+
+    let mut baseline = PADDING as f32 + ascent;
+    for line in wrap_text(&content.replace('\t', "    "), font, px, max_width) {
+        if baseline - ascent > height as f32 {
+            break;
+        }
+
+        let mut x = PADDING as f32;
+        for ch in line.chars() {
+            let (metrics, bitmap) = font.rasterize(ch, px);
+            let left = x as i32 + metrics.xmin;
+            let top = baseline as i32 - metrics.height as i32 - metrics.ymin;
+
+            for row in 0..metrics.height {
+                for col in 0..metrics.width {
+                    let (px_x, px_y) = (left + col as i32, top + row as i32);
+                    if px_x < 0 || px_y < 0 || px_x >= width as i32 || px_y >= height as i32 {
+                        continue;
+                    }
+                    let index = (px_y as u32 * width + px_x as u32) as usize;
+                    coverage[index] = coverage[index].max(bitmap[row * metrics.width + col]);
+                }
+            }
+
+            x += metrics.advance_width;
+        }
+
+        baseline += line_height;
+    }
+
+    // Blend text over the background, then premultiply by the window alpha
+    let mut pixels = Vec::with_capacity(coverage.len() * 4);
+    for cov in coverage {
+        let value = BACKGROUND as u32 + (0xFF - BACKGROUND as u32) * cov as u32 / 0xFF;
+        let value = (value * alpha as u32 / 0xFF) as u8;
+        pixels.extend_from_slice(&[value, value, value, alpha]);
+    }
+    pixels
+}
+
+/// Greedy word wrap; words wider than a line are broken between characters
+fn wrap_text(text: &str, font: &Font, px: f32, max_width: f32) -> Vec<String> {
+    let advance = |ch: char| font.metrics(ch, px).advance_width;
+    let space = advance(' ');
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        let mut line_width = 0.0;
+
+        for word in paragraph.split(' ') {
+            let word_width: f32 = word.chars().map(advance).sum();
+            let needed = if line.is_empty() { word_width } else { line_width + space + word_width };
+
+            if needed <= max_width {
+                if !line.is_empty() {
+                    line.push(' ');
+                    line_width += space;
+                }
+                line.push_str(word);
+                line_width += word_width;
+                continue;
+            }
+
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+                line_width = 0.0;
+            }
+
+            for ch in word.chars() {
+                let width = advance(ch);
+                if line_width + width > max_width && !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0.0;
+                }
+                line.push(ch);
+                line_width += width;
+            }
+        }
+
+        lines.push(line);
+    }
+
+    lines
+}
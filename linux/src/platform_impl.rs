@@ -0,0 +1,217 @@
+use async_trait::async_trait;
+use notecognito_core::{
+    DisplayProperties, HotkeyKey, HotkeyModifier, NotecardId, PlatformInterface, ScreenInfo,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::hotkey::HotkeyManager;
+use crate::notecard_window::NotecardWindowManager;
+
+const AUTOSTART_FILE: &str = "notecognito.desktop";
+
+pub struct LinuxPlatform {
+    hotkey_manager: Arc<Mutex<HotkeyManager>>,
+    window_manager: Arc<Mutex<NotecardWindowManager>>,
+    initialized: bool,
+}
+
+impl LinuxPlatform {
+    pub fn new(
+        hotkey_manager: Arc<Mutex<HotkeyManager>>,
+        window_manager: Arc<Mutex<NotecardWindowManager>>,
+    ) -> Self {
+        LinuxPlatform {
+            hotkey_manager,
+            window_manager,
+            initialized: false,
+        }
+    }
+}
+
+/// Returns true when running in a Wayland session, where X11 key grabs only
+/// see input aimed at XWayland windows
+pub fn is_wayland_session() -> bool {
+    std::env::var("XDG_SESSION_TYPE").map(|t| t == "wayland").unwrap_or(false)
+        || std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+/// Path of the XDG autostart entry
+fn autostart_path() -> notecognito_core::Result<PathBuf> {
+    let config_dir = dirs::config_dir().ok_or_else(|| {
+        notecognito_core::NotecognitoError::Platform("Could not determine config directory".to_string())
+    })?;
+    Ok(config_dir.join("autostart").join(AUTOSTART_FILE))
+}
+
+#[async_trait]
+impl PlatformInterface for LinuxPlatform {
+    async fn register_hotkey(
+        &mut self,
+        id: NotecardId,
+        modifiers: &[HotkeyModifier],
+    ) -> notecognito_core::Result<()> {
+        HotkeyModifier::check_supported(modifiers)?;
+
+        let mut manager = self.hotkey_manager.lock().await;
+        manager
+            .register_hotkey(id, modifiers, HotkeyKey::for_notecard(id))
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn unregister_hotkey(&mut self, id: NotecardId) -> notecognito_core::Result<()> {
+        let mut manager = self.hotkey_manager.lock().await;
+        manager
+            .unregister_hotkey(id)
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn show_notecard(
+        &mut self,
+        id: NotecardId,
+        content: &str,
+        properties: &DisplayProperties,
+    ) -> notecognito_core::Result<()> {
+        let mut manager = self.window_manager.lock().await;
+        manager
+            .show_notecard(id, content, properties)
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn hide_notecard(&mut self, id: NotecardId) -> notecognito_core::Result<()> {
+        let mut manager = self.window_manager.lock().await;
+        manager
+            .hide_notecard(id)
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn hide_all_notecards(&mut self) -> notecognito_core::Result<()> {
+        let mut manager = self.window_manager.lock().await;
+        manager
+            .hide_all()
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn update_notecard(
+        &mut self,
+        id: NotecardId,
+        content: &str,
+        properties: &DisplayProperties,
+    ) -> notecognito_core::Result<()> {
+        let mut manager = self.window_manager.lock().await;
+        manager
+            .update_notecard(id, content, properties)
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn set_notecard_position(
+        &mut self,
+        id: NotecardId,
+        x: i32,
+        y: i32,
+    ) -> notecognito_core::Result<()> {
+        let mut manager = self.window_manager.lock().await;
+        if !manager.is_notecard_visible(id) {
+            return Err(notecognito_core::NotecognitoError::NotVisible(id));
+        }
+        manager
+            .set_notecard_position(id, x, y)
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn set_notecard_size(
+        &mut self,
+        id: NotecardId,
+        width: u32,
+        height: u32,
+    ) -> notecognito_core::Result<()> {
+        let mut manager = self.window_manager.lock().await;
+        if !manager.is_notecard_visible(id) {
+            return Err(notecognito_core::NotecognitoError::NotVisible(id));
+        }
+        manager
+            .set_notecard_size(id, width, height)
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn is_notecard_visible(&self, id: NotecardId) -> notecognito_core::Result<bool> {
+        let manager = self.window_manager.lock().await;
+        Ok(manager.is_notecard_visible(id))
+    }
+
+    async fn visible_notecards(&self) -> notecognito_core::Result<Vec<NotecardId>> {
+        let manager = self.window_manager.lock().await;
+        Ok(manager.visible_notecards())
+    }
+
+    async fn screens(&self) -> notecognito_core::Result<Vec<ScreenInfo>> {
+        let manager = self.window_manager.lock().await;
+        manager
+            .screens()
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn set_launch_on_startup(&mut self, enabled: bool) -> notecognito_core::Result<()> {
+        let path = autostart_path()?;
+
+        if enabled {
+            let exe_path = std::env::current_exe()?;
+            let entry = format!(
+                "[Desktop Entry]\n\
+                 Type=Application\n\
+                 Name=Notecognito\n\
+                 Comment=Quick notecard display\n\
+                 Exec=\"{}\"\n\
+                 Terminal=false\n\
+                 X-GNOME-Autostart-enabled=true\n",
+                exe_path.display()
+            );
+
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(&path, entry)?;
+        } else {
+            match std::fs::remove_file(&path) {
+                Ok(_) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn initialize(&mut self) -> notecognito_core::Result<()> {
+        if self.initialized {
+            return Ok(());
+        }
+
+        self.initialized = true;
+        Ok(())
+    }
+
+    async fn cleanup(&mut self) -> notecognito_core::Result<()> {
+        let mut manager = self.hotkey_manager.lock().await;
+        manager
+            .unregister_all()
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn check_permissions(&self) -> notecognito_core::Result<bool> {
+        // X11 needs no special permissions, but Wayland has no global hotkey API
+        if is_wayland_session() {
+            return Err(notecognito_core::NotecognitoError::Platform(
+                "Global hotkeys are not supported on Wayland; log in with an Xorg session".to_string(),
+            ));
+        }
+
+        Ok(true)
+    }
+
+    async fn request_permissions(&self) -> notecognito_core::Result<()> {
+        // Nothing can be granted on Wayland; report the same error as the check
+        self.check_permissions().await.map(|_| ())
+    }
+}
@@ -0,0 +1,53 @@
+use ksni::menu::StandardItem;
+use ksni::{MenuItem, Tray};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::platform_impl::LinuxPlatform;
+
+/// StatusNotifierItem tray entry with the same menu as the other platforms
+pub struct NotecognitoTray {
+    pub platform: Arc<Mutex<LinuxPlatform>>,
+    pub runtime: tokio::runtime::Handle,
+}
+
+impl Tray for NotecognitoTray {
+    fn id(&self) -> String {
+        "notecognito".into()
+    }
+
+    fn title(&self) -> String {
+        crate::APP_NAME.into()
+    }
+
+    fn icon_name(&self) -> String {
+        "accessories-text-editor".into()
+    }
+
+    fn menu(&self) -> Vec<MenuItem<Self>> {
+        vec![
+            StandardItem {
+                label: "Configure".into(),
+                activate: Box::new(|_| crate::launch_config_ui()),
+                ..Default::default()
+            }
+            .into(),
+            StandardItem {
+                label: "Hide All Notecards".into(),
+                activate: Box::new(|tray: &mut Self| {
+                    tray.runtime.spawn(crate::hide_all_notecards(Arc::clone(&tray.platform)));
+                }),
+                ..Default::default()
+            }
+            .into(),
+            MenuItem::Separator,
+            StandardItem {
+                label: "Quit".into(),
+                icon_name: "application-exit".into(),
+                activate: Box::new(|_| std::process::exit(0)),
+                ..Default::default()
+            }
+            .into(),
+        ]
+    }
+}
@@ -0,0 +1,79 @@
+use notecognito_core::{attribute_runs, parse_markdown, NotecardId};
+use objc2::msg_send;
+use objc2::runtime::AnyObject;
+use objc2_app_kit::{
+    NSAccessibilityAnnouncementKey, NSAccessibilityAnnouncementRequestedNotification, NSAccessibilityPriorityKey,
+    NSApplication,
+};
+use objc2_foundation::{MainThreadMarker, NSDictionary, NSMutableDictionary, NSNumber, NSString};
+
+/// Most characters of a card read out when it appears; VoiceOver can still
+/// read the rest from the card itself
+const MAX_ANNOUNCED_CHARS: usize = 200;
+
+// NSAccessibilityPriorityLevel
+const PRIORITY_MEDIUM: isize = 50;
+const PRIORITY_HIGH: isize = 90;
+
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {
+    fn NSAccessibilityPostNotificationWithUserInfo(
+        element: &AnyObject,
+        notification: &NSString,
+        user_info: &NSDictionary<NSString, AnyObject>,
+    );
+}
+
+/// Accessibility label of a notecard's window
+pub fn label(id: NotecardId) -> String {
+    format!("Notecard {}", id.value())
+}
+
+/// What VoiceOver says when a notecard appears: its label, then its text as
+/// drawn, with markdown markers taken out and line breaks read as spaces
+///
+/// Long cards are cut at `MAX_ANNOUNCED_CHARS`, so a wall of text doesn't
+/// hold up whatever the user does next.
+pub fn shown_announcement(id: NotecardId, content: &str, markdown: bool) -> String {
+    let shown = if markdown {
+        attribute_runs(&parse_markdown(content)).0
+    } else {
+        content.to_string()
+    };
+    let text = shown.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if text.is_empty() {
+        return format!("{}, empty", label(id));
+    }
+
+    let mut announced: String = text.chars().take(MAX_ANNOUNCED_CHARS).collect();
+    if announced.len() < text.len() {
+        announced.push('…');
+    }
+    format!("{}: {}", label(id), announced)
+}
+
+/// What VoiceOver says when a notecard goes away
+pub fn dismissed_announcement(id: NotecardId) -> String {
+    format!("{} dismissed", label(id))
+}
+
+/// Asks VoiceOver to speak `text`; nothing happens when it isn't running
+///
+/// With `interrupt`, the announcement cuts off whatever VoiceOver is saying,
+/// such as the dismissal of the card a new one replaces.
+pub fn announce(mtm: MainThreadMarker, text: &str, interrupt: bool) {
+    let priority = if interrupt { PRIORITY_HIGH } else { PRIORITY_MEDIUM };
+
+    unsafe {
+        let user_info = NSMutableDictionary::<NSString, AnyObject>::new();
+        let text: &AnyObject = &NSString::from_str(text);
+        let priority: &AnyObject = &NSNumber::new_isize(priority);
+        let _: () = msg_send![&user_info, setObject: text, forKey: NSAccessibilityAnnouncementKey];
+        let _: () = msg_send![&user_info, setObject: priority, forKey: NSAccessibilityPriorityKey];
+
+        // Posted on the app, since the cards never become the focused window
+        let app: &AnyObject = &NSApplication::sharedApplication(mtm);
+        NSAccessibilityPostNotificationWithUserInfo(app, NSAccessibilityAnnouncementRequestedNotification, &user_info);
+    }
+}
@@ -1,7 +1,59 @@
+use dispatch::Queue;
+use notecognito_core::{
+    Config, ConfigManager, HotkeyAction, HotkeyBinding, HotkeyKey, HotkeyModifier, NotecardId, NotecardSummary,
+    NotificationKind, PlatformInterface,
+};
 use objc2::rc::Retained;
-use objc2::{declare_class, msg_send, mutability, msg_send_id, ClassType, DeclaredClass};
-use objc2_app_kit::{NSApplication, NSApplicationDelegate};
-use objc2_foundation::{MainThreadMarker, NSNotification, NSObject, NSObjectProtocol};
+use objc2::runtime::{AnyObject, Sel};
+use objc2::{declare_class, msg_send, msg_send_id, mutability, sel, ClassType, DeclaredClass};
+use objc2::Message;
+use objc2_app_kit::{
+    NSApplication, NSApplicationDelegate, NSApplicationDidChangeScreenParametersNotification, NSBezierPath, NSColor,
+    NSCompositingOperation, NSControlStateValueOff, NSControlStateValueOn, NSEventModifierFlags, NSGraphicsContext,
+    NSImage, NSMenu, NSMenuItem, NSStatusBar, NSStatusItem, NSWorkspace, NSWorkspaceDidWakeNotification,
+};
+use objc2_foundation::{
+    CGFloat, CGPoint, CGRect, CGSize, MainThreadMarker, NSBundle, NSData, NSDistributedNotificationCenter, NSNotification,
+    NSNotificationCenter, NSObject, NSObjectProtocol, NSString,
+};
+use std::cell::{Cell, OnceCell, RefCell};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch, Mutex};
+
+use crate::fallback_editor::{FallbackEditor, NotecardEdits};
+use crate::menu::{self, IconState, MenuAction, MenuEntry, MenuState};
+use crate::notecard_window::NotecardWindowManager;
+use crate::platform_impl::MacOSPlatform;
+
+/// How long quitting waits for the connection to core to close
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// State the menu actions work with
+pub struct AppDelegateIvars {
+    config_manager: Arc<Mutex<ConfigManager>>,
+    window_manager: Arc<Mutex<NotecardWindowManager>>,
+    /// Lays the notecards out together for Show All
+    platform: Arc<Mutex<MacOSPlatform>>,
+    /// Received by the task `App::run` starts, which owns the hotkeys
+    pause_requests: mpsc::UnboundedSender<()>,
+    /// Handed to the fallback editor; `App::run` saves what it sends
+    edits: mpsc::UnboundedSender<NotecardEdits>,
+    /// Set on quit, for the task that keeps the connection to core
+    shutdown: watch::Sender<bool>,
+    /// Shown by Configure when the configuration app can't be found
+    fallback_editor: RefCell<Option<Retained<FallbackEditor>>>,
+    /// Set once by `install_status_item`
+    status_item: OnceCell<Retained<NSStatusItem>>,
+    /// Set with the status item, unless no icon could be loaded
+    icons: OnceCell<StatusIcons>,
+    menu_state: Cell<MenuState>,
+    /// Listed in the Notecards and Copy Notecard submenus
+    notecards: RefCell<Vec<NotecardSummary>>,
+    /// Shown as the key equivalents of the items doing the same
+    hotkeys: RefCell<HashMap<HotkeyAction, HotkeyBinding>>,
+}
 
 declare_class!(
     pub struct AppDelegate;
@@ -13,7 +65,7 @@ declare_class!(
     }
 
     impl DeclaredClass for AppDelegate {
-        type Ivars = ();
+        type Ivars = AppDelegateIvars;
     }
 
     unsafe impl NSObjectProtocol for AppDelegate {}
@@ -29,6 +81,22 @@ declare_class!(
             // Don't terminate when windows close (menu bar app)
             false
         }
+
+        #[method(applicationWillTerminate:)]
+        fn application_will_terminate(&self, _notification: &NSNotification) {
+            crate::single_instance::release();
+            crate::notecard_window::close_all_windows_now();
+
+            // The process exits when this returns, so the tasks watching for
+            // shutdown get a moment to stop the hotkeys, save and close their
+            // connections; each drops its receiver when done
+            let shutdown = &self.ivars().shutdown;
+            shutdown.send_replace(true);
+            let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+            while shutdown.receiver_count() > 0 && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        }
     }
 
     // Custom methods
@@ -36,7 +104,135 @@ declare_class!(
         #[method(configure:)]
         fn configure(&self, _sender: &NSObject) {
             tracing::info!("Configure menu item clicked");
-            crate::launch_config_ui();
+            let config_manager = Arc::clone(&self.ivars().config_manager);
+            tokio::spawn(async move {
+                // `open` waits on Launch Services, which mustn't hold up the menu
+                let searched = match tokio::task::spawn_blocking(crate::launch_config_ui).await {
+                    Ok(Ok(())) => return,
+                    Ok(Err(searched)) => searched,
+                    Err(e) => {
+                        tracing::error!("Configuration app launch panicked: {}", e);
+                        Vec::new()
+                    }
+                };
+
+                crate::notification::show(
+                    "Configuration App Not Found",
+                    &format!(
+                        "Looked in {}. Edit your notecards here instead, or reinstall Notecognito Config.",
+                        searched.join(", ")
+                    ),
+                    NotificationKind::Warning,
+                );
+
+                let config = config_manager.lock().await.config().clone();
+                on_main(move |delegate| delegate.show_fallback_editor(&config));
+            });
+        }
+
+        #[method(showAll:)]
+        fn show_all(&self, _sender: &NSObject) {
+            tracing::info!("Show All menu item clicked");
+            let config_manager = Arc::clone(&self.ivars().config_manager);
+            let platform = Arc::clone(&self.ivars().platform);
+            tokio::spawn(async move {
+                let notecards = config_manager.lock().await.config().notecards_with_content();
+                if let Err(e) = platform.lock().await.show_all_notecards(&notecards).await {
+                    tracing::error!("Failed to show notecards: {}", e);
+                }
+            });
+        }
+
+        #[method(hideAll:)]
+        fn hide_all(&self, _sender: &NSObject) {
+            tracing::info!("Hide All menu item clicked");
+            let window_manager = Arc::clone(&self.ivars().window_manager);
+            tokio::spawn(async move {
+                if let Err(e) = window_manager.lock().await.hide_all().await {
+                    tracing::error!("Failed to hide notecards: {}", e);
+                }
+            });
+        }
+
+        #[method(pauseHotkeys:)]
+        fn pause_hotkeys(&self, _sender: &NSObject) {
+            tracing::info!("Pause Hotkeys menu item clicked");
+            if self.ivars().pause_requests.send(()).is_err() {
+                tracing::warn!("Hotkeys can't be paused before the app is running");
+            }
+        }
+
+        // The key equivalent stands in for the hotkey while the menu is open,
+        // so this toggles exactly as a hotkey press does
+        #[method(toggleNotecard:)]
+        fn toggle_notecard(&self, sender: &NSMenuItem) {
+            let tag = unsafe { sender.tag() };
+            let Some(id) = u8::try_from(tag).ok().and_then(|value| NotecardId::new(value).ok()) else {
+                tracing::warn!("Notecard menu item has no notecard: {}", tag);
+                return;
+            };
+
+            tracing::info!("Notecard menu item clicked for notecard {}", id.value());
+            let config_manager = Arc::clone(&self.ivars().config_manager);
+            let platform = Arc::clone(&self.ivars().platform);
+            tokio::spawn(async move {
+                if let Err(e) = crate::toggle_notecard(id, config_manager, platform).await {
+                    tracing::error!("Failed to toggle notecard: {}", e);
+                }
+            });
+        }
+
+        #[method(copyNotecard:)]
+        fn copy_notecard(&self, sender: &NSMenuItem) {
+            // The item's tag is the notecard id
+            let tag = unsafe { sender.tag() };
+            let Some(id) = u8::try_from(tag).ok().and_then(|value| NotecardId::new(value).ok()) else {
+                tracing::warn!("Copy menu item has no notecard: {}", tag);
+                return;
+            };
+
+            tracing::info!("Copy menu item clicked for notecard {}", id.value());
+            let config_manager = Arc::clone(&self.ivars().config_manager);
+            tokio::spawn(async move {
+                let content = config_manager.lock().await.get_notecard(id).map(|notecard| notecard.content.clone());
+                match content {
+                    Some(content) => {
+                        if let Err(e) = crate::clipboard::write_text(&content) {
+                            tracing::error!("Failed to copy notecard {}: {}", id.value(), e);
+                        }
+                    }
+                    None => tracing::warn!("Notecard {} no longer exists", id.value()),
+                }
+            });
+        }
+
+        #[method(anotherLaunch:)]
+        fn another_launch(&self, _notification: &NSNotification) {
+            tracing::info!("Notecognito was launched again, showing the menu");
+            let Some(mtm) = MainThreadMarker::new() else {
+                return;
+            };
+            if let Some(button) = self.ivars().status_item.get().and_then(|item| unsafe { item.button(mtm) }) {
+                unsafe { button.performClick(None) };
+            }
+        }
+
+        #[method(screenParametersChanged:)]
+        fn screen_parameters_changed(&self, _notification: &NSNotification) {
+            tracing::debug!("Screen configuration changed");
+            if let Some(mtm) = MainThreadMarker::new() {
+                crate::notecard_window::refit_windows(mtm);
+            }
+        }
+
+        // The event tap occasionally comes back from sleep switched off
+        #[method(didWake:)]
+        fn did_wake(&self, _notification: &NSNotification) {
+            tracing::debug!("System woke from sleep, checking the event tap");
+            let platform = Arc::clone(&self.ivars().platform);
+            tokio::spawn(async move {
+                platform.lock().await.ensure_event_tap_enabled().await;
+            });
         }
 
         #[method(about:)]
@@ -51,7 +247,421 @@ declare_class!(
 );
 
 impl AppDelegate {
-    pub fn new(mtm: MainThreadMarker) -> Retained<Self> {
-        unsafe { msg_send_id![mtm.alloc::<Self>(), init] }
+    pub fn new(
+        mtm: MainThreadMarker,
+        config_manager: Arc<Mutex<ConfigManager>>,
+        window_manager: Arc<Mutex<NotecardWindowManager>>,
+        platform: Arc<Mutex<MacOSPlatform>>,
+        pause_requests: mpsc::UnboundedSender<()>,
+        edits: mpsc::UnboundedSender<NotecardEdits>,
+        shutdown: watch::Sender<bool>,
+    ) -> Retained<Self> {
+        let this = mtm.alloc::<Self>().set_ivars(AppDelegateIvars {
+            config_manager,
+            window_manager,
+            platform,
+            pause_requests,
+            edits,
+            shutdown,
+            fallback_editor: RefCell::new(None),
+            status_item: OnceCell::new(),
+            icons: OnceCell::new(),
+            menu_state: Cell::new(MenuState::default()),
+            notecards: RefCell::new(Vec::new()),
+            hotkeys: RefCell::new(HashMap::new()),
+        });
+        unsafe { msg_send_id![super(this), init] }
+    }
+
+    /// Creates the menu bar item with its icon and menu; the delegate must
+    /// already be the application's, since the menu items target it
+    pub fn install_status_item(&self, mtm: MainThreadMarker) {
+        tracing::debug!("Creating menu bar item...");
+
+        unsafe {
+            // Variable length
+            let status_item = NSStatusBar::systemStatusBar().statusItemWithLength(-1.0);
+
+            match load_icon() {
+                Some(icon) => {
+                    let _ = self.ivars().icons.set(StatusIcons::new(icon));
+                }
+                // Fallback to text, set by `refresh_button`
+                None => tracing::warn!("Menu bar icon not found, showing a title instead"),
+            }
+
+            if self.ivars().status_item.set(status_item).is_err() {
+                tracing::warn!("Menu bar item already created");
+                return;
+            }
+        }
+
+        self.rebuild_menu(mtm);
+        tracing::info!("Menu bar item created successfully");
+    }
+
+    /// Opens the menu whenever a second launch announces itself, so it's
+    /// clear which copy is running
+    pub fn listen_for_other_launches(&self) {
+        let observer: &AnyObject = self;
+        unsafe {
+            NSDistributedNotificationCenter::defaultCenter().addObserver_selector_name_object(
+                observer,
+                sel!(anotherLaunch:),
+                Some(&NSString::from_str(crate::single_instance::ACTIVATE_NOTIFICATION)),
+                None,
+            );
+        }
+    }
+
+    /// Watches for displays being plugged, unplugged or rearranged, and for
+    /// the system waking from sleep
+    pub fn observe_system_changes(&self) {
+        let observer: &AnyObject = self;
+        unsafe {
+            NSNotificationCenter::defaultCenter().addObserver_selector_name_object(
+                observer,
+                sel!(screenParametersChanged:),
+                Some(NSApplicationDidChangeScreenParametersNotification),
+                None,
+            );
+            // Wake is only posted to the workspace's own center
+            NSWorkspace::sharedWorkspace().notificationCenter().addObserver_selector_name_object(
+                observer,
+                sel!(didWake:),
+                Some(NSWorkspaceDidWakeNotification),
+                None,
+            );
+        }
+    }
+
+    /// Reads what the menu shows from the configuration, once it's loaded
+    pub async fn sync_menu_with_config(&self, capture_supported: bool) {
+        let (hotkeys_paused, capture_excluded, notecards, hotkeys) = {
+            let manager = self.ivars().config_manager.lock().await;
+            let config = manager.config();
+            (
+                !config.hotkeys_enabled,
+                config.default_display_properties.exclude_from_capture,
+                manager.notecard_summaries(),
+                config.hotkey_bindings(),
+            )
+        };
+
+        *self.ivars().notecards.borrow_mut() = notecards;
+        *self.ivars().hotkeys.borrow_mut() = hotkeys;
+        self.update_menu(|state| {
+            state.hotkeys_paused = hotkeys_paused;
+            state.capture_excluded = capture_excluded;
+            state.capture_supported = capture_supported;
+        });
+    }
+
+    /// Changes the state the menu and icon reflect and redraws them
+    pub fn update_menu(&self, change: impl FnOnce(&mut MenuState)) {
+        let mut state = self.ivars().menu_state.get();
+        change(&mut state);
+        self.ivars().menu_state.set(state);
+
+        if let Some(mtm) = MainThreadMarker::new() {
+            self.rebuild_menu(mtm);
+        }
+    }
+
+    /// Replaces the notecards the submenus list and the hotkeys shown beside them
+    pub fn set_notecards(&self, notecards: Vec<NotecardSummary>, hotkeys: HashMap<HotkeyAction, HotkeyBinding>) {
+        *self.ivars().notecards.borrow_mut() = notecards;
+        *self.ivars().hotkeys.borrow_mut() = hotkeys;
+        self.update_menu(|_| {});
+    }
+
+    /// Opens the fallback editor, filled from `config` unless it's already open
+    pub fn show_fallback_editor(&self, config: &Config) {
+        let Some(mtm) = MainThreadMarker::new() else {
+            return;
+        };
+
+        let mut editor = self.ivars().fallback_editor.borrow_mut();
+        // An open editor may hold unsaved typing
+        if !editor.as_ref().is_some_and(|editor| editor.is_visible()) {
+            *editor = Some(FallbackEditor::new(mtm, config, self.ivars().edits.clone()));
+        }
+        if let Some(editor) = editor.as_ref() {
+            editor.show(mtm);
+        }
+    }
+
+    /// Shows or clears the badge that marks missing accessibility access
+    pub fn set_accessibility_badge(&self, missing: bool) {
+        self.update_menu(|state| state.accessibility_missing = missing);
+    }
+
+    /// Replaces the status item's menu with one built from the current state,
+    /// and the icon and tooltip with the ones for it
+    fn rebuild_menu(&self, mtm: MainThreadMarker) {
+        let Some(status_item) = self.ivars().status_item.get() else {
+            return;
+        };
+        let state = self.ivars().menu_state.get();
+
+        let entries = menu::build(&state, &self.ivars().notecards.borrow(), &self.ivars().hotkeys.borrow());
+        let menu = self.menu_from(mtm, entries);
+        unsafe { status_item.setMenu(Some(&menu)) };
+
+        self.refresh_button(mtm, &state);
+    }
+
+    /// Builds an `NSMenu` of `entries`, with items targeting the delegate
+    fn menu_from(&self, mtm: MainThreadMarker, entries: Vec<MenuEntry>) -> Retained<NSMenu> {
+        unsafe {
+            let menu = NSMenu::new(mtm);
+            for entry in entries {
+                let item = match entry {
+                    MenuEntry::Separator => NSMenuItem::separatorItem(mtm),
+                    MenuEntry::Submenu { title, entries } => {
+                        let item = NSMenuItem::new(mtm);
+                        item.setTitle(&NSString::from_str(&title));
+                        item.setSubmenu(Some(&self.menu_from(mtm, entries)));
+                        item
+                    }
+                    MenuEntry::Item { title, action, checked, key } => {
+                        let item = NSMenuItem::new(mtm);
+                        item.setTitle(&NSString::from_str(&title));
+                        item.setState(if checked { NSControlStateValueOn } else { NSControlStateValueOff });
+
+                        match action {
+                            // Quit goes to the application rather than the delegate
+                            Some(MenuAction::Quit) => {
+                                item.setAction(Some(sel!(terminate:)));
+                                item.setKeyEquivalent(&NSString::from_str("q"));
+                                item.setKeyEquivalentModifierMask(NSEventModifierFlags::NSEventModifierFlagCommand);
+                            }
+                            Some(action) => {
+                                if let MenuAction::ToggleNotecard(id) | MenuAction::CopyNotecard(id) = action {
+                                    item.setTag(id.value() as isize);
+                                }
+                                item.setAction(Some(selector(action)));
+                                item.setTarget(Some(self));
+                            }
+                            // Without an action it's greyed out
+                            None => {}
+                        }
+
+                        if let Some((equivalent, modifiers)) = key.as_ref().and_then(key_equivalent) {
+                            item.setKeyEquivalent(&NSString::from_str(&equivalent));
+                            item.setKeyEquivalentModifierMask(modifiers);
+                        }
+                        item
+                    }
+                };
+                menu.addItem(&item);
+            }
+            menu
+        }
+    }
+
+    /// Shows the icon variant and tooltip for `state`, or a title without an icon
+    fn refresh_button(&self, mtm: MainThreadMarker, state: &MenuState) {
+        let Some(button) = self.ivars().status_item.get().and_then(|item| unsafe { item.button(mtm) }) else {
+            return;
+        };
+
+        unsafe {
+            button.setToolTip(Some(&NSString::from_str(menu::tooltip(state))));
+
+            match self.ivars().icons.get() {
+                Some(icons) => {
+                    button.setImage(Some(icons.get(state.icon())));
+                    button.setTitle(&NSString::from_str(""));
+                }
+                None => {
+                    let title = if state.icon() == IconState::Attention { "!" } else { "N" };
+                    button.setTitle(&NSString::from_str(title));
+                    button.setAppearsDisabled(state.icon() == IconState::Paused);
+                }
+            }
+        }
+    }
+}
+
+/// The menu bar icon in each `IconState`, generated from the one template image
+struct StatusIcons {
+    normal: Retained<NSImage>,
+    paused: Retained<NSImage>,
+    attention: Retained<NSImage>,
+}
+
+impl StatusIcons {
+    /// Opacity of the paused icon
+    const PAUSED_ALPHA: CGFloat = 0.5;
+
+    fn new(icon: Retained<NSImage>) -> Self {
+        StatusIcons {
+            paused: icon_variant(&icon, Self::PAUSED_ALPHA, false),
+            attention: icon_variant(&icon, 1.0, true),
+            normal: icon,
+        }
+    }
+
+    fn get(&self, state: IconState) -> &NSImage {
+        match state {
+            IconState::Normal => &self.normal,
+            IconState::Paused => &self.paused,
+            IconState::Attention => &self.attention,
+        }
+    }
+}
+
+/// Draws `icon` at `alpha`, with a dot in its top-right corner if `badge`
+///
+/// The result stays a template image, so the menu bar still tints it for
+/// light and dark mode; the drawing happens whenever the image is rendered.
+fn icon_variant(icon: &NSImage, alpha: CGFloat, badge: bool) -> Retained<NSImage> {
+    use block2::ConcreteBlock;
+    use objc2::runtime::Bool;
+
+    let icon = icon.retain();
+    let size = unsafe { icon.size() };
+
+    let draw = ConcreteBlock::new(move |rect: CGRect| -> Bool {
+        unsafe {
+            let whole = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(0.0, 0.0));
+            icon.drawInRect_fromRect_operation_fraction(rect, whole, NSCompositingOperation::SourceOver, alpha);
+
+            if badge {
+                let diameter = rect.size.width.min(rect.size.height) * 0.45;
+                let dot = CGRect::new(
+                    CGPoint::new(rect.size.width - diameter, rect.size.height - diameter),
+                    CGSize::new(diameter, diameter),
+                );
+
+                // Cut a gap around the dot so it reads apart from the glyph
+                let gap = 1.5;
+                let ring = CGRect::new(
+                    CGPoint::new(dot.origin.x - gap, dot.origin.y - gap),
+                    CGSize::new(diameter + 2.0 * gap, diameter + 2.0 * gap),
+                );
+                if let Some(context) = NSGraphicsContext::currentContext() {
+                    context.setCompositingOperation(NSCompositingOperation::Clear);
+                    NSBezierPath::bezierPathWithOvalInRect(ring).fill();
+                    context.setCompositingOperation(NSCompositingOperation::SourceOver);
+                }
+
+                NSColor::blackColor().setFill();
+                NSBezierPath::bezierPathWithOvalInRect(dot).fill();
+            }
+        }
+        Bool::YES
+    });
+    let draw = draw.copy();
+
+    unsafe {
+        let image = NSImage::imageWithSize_flipped_drawingHandler(size, false, &draw);
+        let _: () = msg_send![&image, setTemplate: true];
+        image
+    }
+}
+
+/// Runs `f` with the application's delegate on the main thread; callable from any thread
+pub fn on_main<F>(f: F)
+where
+    F: FnOnce(&AppDelegate) + Send + 'static,
+{
+    Queue::main().exec_async(move || {
+        let Some(mtm) = MainThreadMarker::new() else {
+            return;
+        };
+
+        let delegate = unsafe { NSApplication::sharedApplication(mtm).delegate() };
+        let Some(delegate) = delegate else {
+            return;
+        };
+
+        let is_ours: bool = unsafe { msg_send![&delegate, isKindOfClass: AppDelegate::class()] };
+        if is_ours {
+            // Checked above, so the cast is sound
+            let delegate = unsafe { Retained::cast::<AppDelegate>(delegate) };
+            f(&delegate);
+        }
+    });
+}
+
+/// Selector a menu action sends to the delegate
+fn selector(action: MenuAction) -> Sel {
+    match action {
+        MenuAction::Configure => sel!(configure:),
+        MenuAction::ShowAll => sel!(showAll:),
+        MenuAction::HideAll => sel!(hideAll:),
+        MenuAction::PauseHotkeys => sel!(pauseHotkeys:),
+        MenuAction::ToggleNotecard(_) => sel!(toggleNotecard:),
+        MenuAction::CopyNotecard(_) => sel!(copyNotecard:),
+        MenuAction::About => sel!(about:),
+        MenuAction::Quit => sel!(terminate:),
+    }
+}
+
+/// The key equivalent and modifier mask a menu item shows for a hotkey; `None`
+/// for a binding with a modifier macOS doesn't have
+///
+/// AppKit matches a Shift equivalent against the shifted character, so ⇧
+/// with a digit shows right but may not fire from the menu on every layout.
+fn key_equivalent(binding: &HotkeyBinding) -> Option<(String, NSEventModifierFlags)> {
+    // NSF1FunctionKey; the function keys follow it in order
+    const F1_FUNCTION_KEY: u32 = 0xF704;
+
+    let mut modifiers = NSEventModifierFlags::empty();
+    for modifier in &binding.modifiers {
+        modifiers |= match modifier {
+            HotkeyModifier::Control => NSEventModifierFlags::NSEventModifierFlagControl,
+            HotkeyModifier::Alt => NSEventModifierFlags::NSEventModifierFlagOption,
+            HotkeyModifier::Shift => NSEventModifierFlags::NSEventModifierFlagShift,
+            HotkeyModifier::Command => NSEventModifierFlags::NSEventModifierFlagCommand,
+            HotkeyModifier::Windows => return None,
+        };
+    }
+
+    let equivalent = match binding.key {
+        HotkeyKey::Digit(digit) => digit.to_string(),
+        HotkeyKey::Letter(letter) => letter.to_ascii_lowercase().to_string(),
+        HotkeyKey::Function(number) => char::from_u32(F1_FUNCTION_KEY + u32::from(number) - 1)?.to_string(),
+        HotkeyKey::Numpad(digit) => {
+            modifiers |= NSEventModifierFlags::NSEventModifierFlagNumericPad;
+            digit.to_string()
+        }
+    };
+
+    Some((equivalent, modifiers))
+}
+
+/// Loads the menu bar icon from the bundle, or the copy embedded in the binary
+fn load_icon() -> Option<Retained<NSImage>> {
+    unsafe {
+        // 1. Try from app bundle resources
+        let bundle = NSBundle::mainBundle();
+        if let Some(path) = bundle.pathForResource_ofType(
+            Some(&NSString::from_str("icon")),
+            Some(&NSString::from_str("png")),
+        ) {
+            if let Some(image) = NSImage::initWithContentsOfFile(NSImage::alloc(), &path) {
+                // Set template image for proper menu bar styling
+                let _: () = msg_send![&image, setTemplate: true];
+                return Some(image);
+            }
+        }
+
+        // 2. Try embedded icon data
+        let icon_data = include_bytes!("../assets/icon.png");
+        let data = NSData::dataWithBytes_length(
+            icon_data.as_ptr() as *mut std::ffi::c_void,
+            icon_data.len(),
+        );
+
+        if let Some(image) = NSImage::initWithData(NSImage::alloc(), &data) {
+            // Set template image
+            let _: () = msg_send![&image, setTemplate: true];
+            return Some(image);
+        }
+
+        None
     }
-}
\ No newline at end of file
+}
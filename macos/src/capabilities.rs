@@ -0,0 +1,25 @@
+use notecognito_core::PlatformCapabilities;
+use objc2_foundation::NSProcessInfo;
+
+/// Last major release where `NSWindowSharingNone` keeps windows out of captures;
+/// ScreenCaptureKit ignores it from macOS 15 on
+const LAST_CAPTURE_EXCLUSION_MAJOR: isize = 14;
+
+/// Detects which optional display features this version of macOS has
+pub fn detect() -> PlatformCapabilities {
+    let version = unsafe { NSProcessInfo::processInfo().operatingSystemVersion() };
+
+    PlatformCapabilities {
+        os_version: Some(format!(
+            "{}.{}.{}",
+            version.majorVersion, version.minorVersion, version.patchVersion
+        )),
+        // NSVisualEffectView and layer corner radii predate every supported release
+        blur: true,
+        rounded_corners: true,
+        // Every NSScreen reports its own backingScaleFactor
+        per_monitor_dpi: true,
+        screen_capture_exclusion: version.majorVersion <= LAST_CAPTURE_EXCLUSION_MAJOR,
+        ..PlatformCapabilities::conservative()
+    }
+}
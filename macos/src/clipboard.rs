@@ -0,0 +1,26 @@
+use anyhow::{anyhow, Result};
+use objc2_app_kit::{NSPasteboard, NSPasteboardTypeString};
+use objc2_foundation::NSString;
+
+/// Reads the general pasteboard as text; `None` when it holds something else
+pub fn read_text() -> Option<String> {
+    unsafe {
+        NSPasteboard::generalPasteboard()
+            .stringForType(NSPasteboardTypeString)
+            .map(|text| text.to_string())
+    }
+}
+
+/// Replaces the general pasteboard's contents with text
+pub fn write_text(text: &str) -> Result<()> {
+    unsafe {
+        let pasteboard = NSPasteboard::generalPasteboard();
+        pasteboard.clearContents();
+
+        if pasteboard.setString_forType(&NSString::from_str(text), NSPasteboardTypeString) {
+            Ok(())
+        } else {
+            Err(anyhow!("Failed to write to the pasteboard"))
+        }
+    }
+}
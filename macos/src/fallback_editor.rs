@@ -0,0 +1,182 @@
+use notecognito_core::{Config, NotecardId};
+use objc2::rc::Retained;
+use objc2::runtime::AnyObject;
+use objc2::{declare_class, msg_send_id, mutability, sel, ClassType, DeclaredClass};
+use objc2_app_kit::{
+    NSApplication, NSAutoresizingMaskOptions, NSBackingStoreType, NSBorderType, NSButton, NSPanel,
+    NSScrollView, NSTextField, NSTextView, NSWindowStyleMask,
+};
+use objc2_foundation::{CGFloat, CGPoint, CGRect, CGSize, MainThreadMarker, NSObject, NSString};
+use tokio::sync::mpsc;
+
+/// New content for notecards, as typed into the editor
+pub type NotecardEdits = Vec<(NotecardId, String)>;
+
+const WIDTH: CGFloat = 520.0;
+const MARGIN: CGFloat = 12.0;
+const LABEL_HEIGHT: CGFloat = 16.0;
+const TEXT_HEIGHT: CGFloat = 44.0;
+const ROW_HEIGHT: CGFloat = LABEL_HEIGHT + TEXT_HEIGHT + 8.0;
+const BUTTON_BAR_HEIGHT: CGFloat = 48.0;
+
+pub struct FallbackEditorIvars {
+    panel: Retained<NSPanel>,
+    text_views: Vec<(NotecardId, Retained<NSTextView>)>,
+    /// Received by the task `App::run` starts, which saves the edits
+    edits: mpsc::UnboundedSender<NotecardEdits>,
+}
+
+declare_class!(
+    /// A plain panel for editing the nine notecards, shown when the
+    /// configuration app can't be found so Configure always does something
+    pub struct FallbackEditor;
+
+    unsafe impl ClassType for FallbackEditor {
+        type Super = NSObject;
+        type Mutability = mutability::MainThreadOnly;
+        const NAME: &'static str = "NotecognitoFallbackEditor";
+    }
+
+    impl DeclaredClass for FallbackEditor {
+        type Ivars = FallbackEditorIvars;
+    }
+
+    unsafe impl FallbackEditor {
+        #[method(save:)]
+        fn save(&self, _sender: &NSObject) {
+            let edits: NotecardEdits = self
+                .ivars()
+                .text_views
+                .iter()
+                .map(|(id, text_view)| (*id, unsafe { text_view.string() }.to_string()))
+                .collect();
+
+            if self.ivars().edits.send(edits).is_err() {
+                tracing::warn!("Notecards can't be saved before the app is running");
+                return;
+            }
+            self.ivars().panel.close();
+        }
+
+        #[method(cancel:)]
+        fn cancel(&self, _sender: &NSObject) {
+            self.ivars().panel.close();
+        }
+    }
+);
+
+impl FallbackEditor {
+    /// Builds the panel with the notecards in `config`; it's hidden until `show`
+    pub fn new(mtm: MainThreadMarker, config: &Config, edits: mpsc::UnboundedSender<NotecardEdits>) -> Retained<Self> {
+        let ids: Vec<_> = (1..=9).filter_map(|i| NotecardId::new(i).ok()).collect();
+        let height = MARGIN + ids.len() as CGFloat * ROW_HEIGHT + BUTTON_BAR_HEIGHT;
+
+        unsafe {
+            let panel = NSPanel::initWithContentRect_styleMask_backing_defer(
+                mtm.alloc(),
+                CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(WIDTH, height)),
+                NSWindowStyleMask::Titled | NSWindowStyleMask::Closable,
+                NSBackingStoreType::NSBackingStoreBuffered,
+                false,
+            );
+            panel.setTitle(&NSString::from_str("Notecognito Notecards"));
+            // Kept for reopening; the editor owns it
+            panel.setReleasedWhenClosed(false);
+            // The app is never active for long, so the panel mustn't vanish with it
+            panel.setHidesOnDeactivate(false);
+            panel.center();
+
+            let content = panel.contentView().expect("a new panel has a content view");
+
+            // Rows run top to bottom, in a view whose origin is the bottom left
+            let mut text_views = Vec::new();
+            for (row, id) in ids.into_iter().enumerate() {
+                let top = height - MARGIN - row as CGFloat * ROW_HEIGHT;
+
+                let label = NSTextField::labelWithString(&NSString::from_str(&format!("Notecard {}", id.value())), mtm);
+                label.setFrame(CGRect::new(
+                    CGPoint::new(MARGIN, top - LABEL_HEIGHT),
+                    CGSize::new(WIDTH - 2.0 * MARGIN, LABEL_HEIGHT),
+                ));
+                content.addSubview(&label);
+
+                let frame = CGRect::new(
+                    CGPoint::new(MARGIN, top - LABEL_HEIGHT - TEXT_HEIGHT - 2.0),
+                    CGSize::new(WIDTH - 2.0 * MARGIN, TEXT_HEIGHT),
+                );
+                let text = config.notecards.get(&id).map(|notecard| notecard.content.as_str()).unwrap_or("");
+                let (scroll_view, text_view) = text_area(mtm, frame, text);
+                content.addSubview(&scroll_view);
+                text_views.push((id, text_view));
+            }
+
+            let this = mtm.alloc::<Self>().set_ivars(FallbackEditorIvars {
+                panel: panel.clone(),
+                text_views,
+                edits,
+            });
+            let this: Retained<Self> = msg_send_id![super(this), init];
+
+            let target: &AnyObject = &this;
+            let save = NSButton::buttonWithTitle_target_action(
+                &NSString::from_str("Save"),
+                Some(target),
+                Some(sel!(save:)),
+                mtm,
+            );
+            save.setKeyEquivalent(&NSString::from_str("\r"));
+            save.setFrame(CGRect::new(CGPoint::new(WIDTH - MARGIN - 96.0, MARGIN), CGSize::new(96.0, 28.0)));
+            content.addSubview(&save);
+
+            let cancel = NSButton::buttonWithTitle_target_action(
+                &NSString::from_str("Cancel"),
+                Some(target),
+                Some(sel!(cancel:)),
+                mtm,
+            );
+            cancel.setKeyEquivalent(&NSString::from_str("\u{1b}"));
+            cancel.setFrame(CGRect::new(CGPoint::new(WIDTH - MARGIN - 200.0, MARGIN), CGSize::new(96.0, 28.0)));
+            content.addSubview(&cancel);
+
+            this
+        }
+    }
+
+    /// Brings the panel to the front, taking focus from the app in front
+    pub fn show(&self, mtm: MainThreadMarker) {
+        unsafe {
+            NSApplication::sharedApplication(mtm).activateIgnoringOtherApps(true);
+            self.ivars().panel.makeKeyAndOrderFront(None);
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.ivars().panel.isVisible()
+    }
+}
+
+/// An editable, bordered text view that scrolls once it outgrows `frame`
+unsafe fn text_area(mtm: MainThreadMarker, frame: CGRect, text: &str) -> (Retained<NSScrollView>, Retained<NSTextView>) {
+    let scroll_view = NSScrollView::initWithFrame(mtm.alloc(), frame);
+    scroll_view.setHasVerticalScroller(true);
+    scroll_view.setHasHorizontalScroller(false);
+    scroll_view.setAutohidesScrollers(true);
+    scroll_view.setBorderType(NSBorderType::NSBezelBorder);
+
+    let content_size = scroll_view.contentSize();
+    let text_view = NSTextView::initWithFrame(mtm.alloc(), CGRect::new(CGPoint::new(0.0, 0.0), content_size));
+    text_view.setRichText(false);
+    text_view.setMinSize(CGSize::new(0.0, content_size.height));
+    text_view.setMaxSize(CGSize::new(CGFloat::MAX, CGFloat::MAX));
+    text_view.setVerticallyResizable(true);
+    text_view.setHorizontallyResizable(false);
+    text_view.setAutoresizingMask(NSAutoresizingMaskOptions::NSViewWidthSizable);
+    if let Some(container) = text_view.textContainer() {
+        container.setWidthTracksTextView(true);
+        container.setContainerSize(CGSize::new(content_size.width, CGFloat::MAX));
+    }
+    text_view.setString(&NSString::from_str(text));
+    scroll_view.setDocumentView(Some(&text_view));
+
+    (scroll_view, text_view)
+}
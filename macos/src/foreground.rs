@@ -0,0 +1,20 @@
+use notecognito_core::AppIdentity;
+use objc2_app_kit::{NSMenu, NSWorkspace};
+use objc2_foundation::MainThreadMarker;
+
+/// Identifies the frontmost application; `None` when no application is active
+///
+/// Other apps' windows can't be inspected without screen recording access, so
+/// a hidden menu bar stands in for fullscreen: it's hidden for native
+/// fullscreen spaces and for games that take over the display.
+pub fn frontmost_application(mtm: MainThreadMarker) -> Option<AppIdentity> {
+    unsafe {
+        let app = NSWorkspace::sharedWorkspace().frontmostApplication()?;
+
+        Some(AppIdentity {
+            name: app.localizedName().map(|name| name.to_string()).unwrap_or_default(),
+            identifier: app.bundleIdentifier().map(|id| id.to_string()),
+            is_fullscreen: !NSMenu::menuBarVisible(mtm),
+        })
+    }
+}
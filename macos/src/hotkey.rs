@@ -1,77 +1,307 @@
 use anyhow::{anyhow, Result};
-use core_foundation::runloop::{CFRunLoop, kCFRunLoopCommonModes};
+use core_foundation::base::TCFType;
+use core_foundation::date::CFDate;
+use core_foundation::mach_port::{CFMachPort, CFMachPortRef};
+use core_foundation::runloop::{CFRunLoop, CFRunLoopTimer, CFRunLoopTimerRef, kCFRunLoopCommonModes};
 use core_graphics::event::{
     CGEvent, CGEventFlags, CGEventTap, CGEventTapLocation, CGEventTapOptions,
     CGEventTapPlacement, CGEventType, EventField,
 };
-use notecognito_core::{HotkeyModifier, NotecardId};
+use notecognito_core::{HotkeyAction, HotkeyBinding, HotkeyChanges, HotkeyKey, HotkeyModifier, NotecardId};
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{mpsc, Arc};
 use std::sync::Mutex;
 use std::thread;
+use std::time::Duration;
 use once_cell::sync::Lazy;
+use tokio::sync::Notify;
 
-// Global state for the event tap callback
+/// How long `start_monitoring` waits for the event tap thread to create its tap
+const TAP_START_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often the event tap thread checks that macOS hasn't left its tap off
+/// without saying so
+const TAP_WATCHDOG_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Times macOS has disabled the event tap since launch, for the logs
+static TAP_DISABLED_COUNT: AtomicU32 = AtomicU32::new(0);
+
+thread_local! {
+    // The tap served by this thread's run loop, for its callback and watchdog
+    // to turn back on; only ever set on the event tap thread
+    static TAP_PORT: RefCell<Option<CFMachPort>> = const { RefCell::new(None) };
+}
+
+// Global state for the event tap and Carbon callbacks
 static HOTKEY_STATE: Lazy<Arc<Mutex<HotkeyState>>> = Lazy::new(|| {
     Arc::new(Mutex::new(HotkeyState {
         hotkeys: HashMap::new(),
+        carbon_hotkeys: HashMap::new(),
         callback: None,
+        paused: false,
+        strict_modifiers: true,
     }))
 });
 
 struct HotkeyState {
-    hotkeys: HashMap<NotecardId, Vec<HotkeyModifier>>,
-    callback: Option<Arc<dyn Fn(NotecardId) + Send + Sync>>,
+    hotkeys: HashMap<HotkeyAction, (HotkeyKey, Vec<HotkeyModifier>)>,
+    /// Hotkeys registered with `RegisterEventHotKey`, empty for the event tap
+    carbon_hotkeys: HashMap<HotkeyAction, carbon::HotkeyRef>,
+    callback: Option<Arc<dyn Fn(HotkeyAction) + Send + Sync>>,
+    /// Set while hotkeys are handed back to other apps
+    paused: bool,
+    /// Event tap hotkeys need exactly their modifiers, not just at least them
+    strict_modifiers: bool,
+}
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGEventTapIsEnabled(tap: CFMachPortRef) -> bool;
+    fn CGEventTapEnable(tap: CFMachPortRef, enable: bool);
+}
+
+/// The running event tap's port and the run loop of the thread serving it
+struct EventTap {
+    port: CFMachPort,
+    run_loop: CFRunLoop,
+}
+
+// Enabling a tap and stopping a run loop both work from any thread
+unsafe impl Send for EventTap {}
+
+/// How key presses are matched against the registered hotkeys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HotkeyBackend {
+    /// Carbon's `RegisterEventHotKey`, which needs no permission
+    #[default]
+    Carbon,
+    /// A `CGEventTap` that sees and consumes every matching key down;
+    /// needs accessibility access and stops if macOS disables the tap
+    EventTap,
+}
+
+impl HotkeyBackend {
+    /// Whether hotkeys only work once the user grants accessibility access
+    pub fn needs_accessibility(self) -> bool {
+        self == HotkeyBackend::EventTap
+    }
 }
 
 pub struct HotkeyManager {
+    backend: HotkeyBackend,
     monitoring: Arc<Mutex<bool>>,
     event_tap_thread: Option<thread::JoinHandle<()>>,
+    /// Set once the event tap thread has created its tap
+    event_tap: Option<EventTap>,
+    /// Notified when the event tap's run loop ends, so monitoring can be restarted
+    stopped: Arc<Notify>,
+    /// Handler for Carbon hotkey presses, installed by `start_monitoring`
+    carbon_handler: Option<carbon::EventHandlerRef>,
 }
 
 unsafe impl Send for HotkeyManager {}
 unsafe impl Sync for HotkeyManager {}
 
 impl HotkeyManager {
-    pub fn new() -> Self {
+    pub fn new(backend: HotkeyBackend) -> Self {
+        tracing::info!("Using the {:?} hotkey backend", backend);
+
         HotkeyManager {
+            backend,
             monitoring: Arc::new(Mutex::new(false)),
             event_tap_thread: None,
+            event_tap: None,
+            stopped: Arc::new(Notify::new()),
+            carbon_handler: None,
         }
     }
 
+    pub fn backend(&self) -> HotkeyBackend {
+        self.backend
+    }
+
+    /// Whether hotkey presses are currently being delivered
+    pub fn is_monitoring(&self) -> bool {
+        *self.monitoring.lock().unwrap()
+    }
+
+    /// Notified once each time the event tap stops, which happens when macOS
+    /// disables it, for instance after accessibility access is revoked
+    ///
+    /// Carbon hotkeys never stop, so this is never notified for them.
+    pub fn stopped(&self) -> Arc<Notify> {
+        Arc::clone(&self.stopped)
+    }
+
     pub fn register_hotkey(
         &mut self,
         notecard_id: NotecardId,
         modifiers: &[HotkeyModifier],
+        key: HotkeyKey,
     ) -> Result<()> {
-        let mut state = HOTKEY_STATE.lock().unwrap();
-        state.hotkeys.insert(notecard_id, modifiers.to_vec());
-
+        self.register(HotkeyAction::ToggleNotecard(notecard_id), modifiers, key)?;
         tracing::info!(
-            "Registered hotkey for notecard {} with modifiers: {:?}",
-            notecard_id.value(),
-            modifiers
+            "Registered hotkey {} for notecard {}",
+            HotkeyBinding::new(modifiers, key),
+            notecard_id.value()
         );
+        Ok(())
+    }
 
+    /// Registers the panic hotkey, the notecard modifiers with 0
+    pub fn register_hide_all_hotkey(&mut self, modifiers: &[HotkeyModifier]) -> Result<()> {
+        let key = HotkeyKey::hide_all();
+        self.register(HotkeyAction::HideAll, modifiers, key)?;
+        tracing::info!("Registered hotkey {} to hide all notecards", HotkeyBinding::new(modifiers, key));
         Ok(())
     }
 
-    pub fn unregister_hotkey(&mut self, notecard_id: NotecardId) -> Result<()> {
+    /// Fails on a modifier macOS doesn't have, such as Windows from a
+    /// configuration saved on Windows, rather than binding the combination
+    /// without it
+    fn register(&mut self, action: HotkeyAction, modifiers: &[HotkeyModifier], key: HotkeyKey) -> Result<()> {
+        HotkeyModifier::check_supported(modifiers)?;
+
+        let keycode = key
+            .to_macos_keycode()
+            .ok_or_else(|| anyhow!("Key {} has no macOS keycode", key))?;
+
         let mut state = HOTKEY_STATE.lock().unwrap();
-        state.hotkeys.remove(&notecard_id);
+
+        // While paused only the binding is kept; resuming registers it
+        if self.backend == HotkeyBackend::Carbon && !state.paused {
+            // Re-registering replaces the old combination
+            if let Some(old) = state.carbon_hotkeys.remove(&action) {
+                old.unregister();
+            }
+
+            let hotkey = carbon::HotkeyRef::register(action, keycode, modifiers).map_err(|status| {
+                anyhow!("Failed to register hotkey {}: OSStatus {}", HotkeyBinding::new(modifiers, key), status)
+            })?;
+            state.carbon_hotkeys.insert(action, hotkey);
+        }
+
+        state.hotkeys.insert(action, (key, modifiers.to_vec()));
         Ok(())
     }
 
+    pub fn unregister_hotkey(&mut self, notecard_id: NotecardId) -> Result<()> {
+        self.unregister(HotkeyAction::ToggleNotecard(notecard_id));
+        Ok(())
+    }
+
+    fn unregister(&mut self, action: HotkeyAction) {
+        let mut state = HOTKEY_STATE.lock().unwrap();
+        state.hotkeys.remove(&action);
+        if let Some(hotkey) = state.carbon_hotkeys.remove(&action) {
+            hotkey.unregister();
+        }
+    }
+
+    /// The bindings currently registered, paused or not
+    pub fn bindings(&self) -> HashMap<HotkeyAction, HotkeyBinding> {
+        HOTKEY_STATE
+            .lock()
+            .unwrap()
+            .hotkeys
+            .iter()
+            .map(|(action, (key, modifiers))| (*action, HotkeyBinding::new(modifiers, *key)))
+            .collect()
+    }
+
+    /// Registers and unregisters hotkeys until exactly `wanted` is bound
+    ///
+    /// Unchanged hotkeys are left registered and the event tap keeps running,
+    /// since it reads the bindings on every key press. A hotkey that fails to
+    /// register is logged and skipped so it doesn't cost the others.
+    pub fn sync_bindings(&mut self, wanted: &HashMap<HotkeyAction, HotkeyBinding>) {
+        let changes = HotkeyChanges::between(&self.bindings(), wanted);
+        if changes.is_empty() {
+            return;
+        }
+
+        for action in changes.unregister {
+            self.unregister(action);
+            tracing::info!("Unregistered hotkey for {:?}", action);
+        }
+
+        for (action, binding) in changes.register {
+            let result = match action {
+                HotkeyAction::ToggleNotecard(id) => self.register_hotkey(id, &binding.modifiers, binding.key),
+                HotkeyAction::HideAll => self.register_hide_all_hotkey(&binding.modifiers),
+            };
+            if let Err(e) = result {
+                // Don't leave the old combination bound in its place
+                self.unregister(action);
+                tracing::warn!("Could not register hotkey {} for {:?}: {}", binding, action, e);
+            }
+        }
+    }
+
     pub fn unregister_all(&mut self) -> Result<()> {
         let mut state = HOTKEY_STATE.lock().unwrap();
         state.hotkeys.clear();
+        for (_, hotkey) in state.carbon_hotkeys.drain() {
+            hotkey.unregister();
+        }
+        Ok(())
+    }
+
+    /// Whether hotkeys are currently handed back to other apps
+    pub fn is_paused(&self) -> bool {
+        HOTKEY_STATE.lock().unwrap().paused
+    }
+
+    /// Pauses or resumes every hotkey, keeping the bindings
+    ///
+    /// The event tap lets matching key presses through while paused, and
+    /// Carbon hotkeys are unregistered so the system delivers them elsewhere.
+    pub fn set_paused(&mut self, paused: bool) -> Result<()> {
+        let mut state = HOTKEY_STATE.lock().unwrap();
+        if state.paused == paused {
+            return Ok(());
+        }
+        state.paused = paused;
+
+        if self.backend == HotkeyBackend::Carbon {
+            if paused {
+                for (_, hotkey) in state.carbon_hotkeys.drain() {
+                    hotkey.unregister();
+                }
+            } else {
+                let bindings: Vec<_> = state
+                    .hotkeys
+                    .iter()
+                    .map(|(action, (key, modifiers))| (*action, *key, modifiers.clone()))
+                    .collect();
+                for (action, key, modifiers) in bindings {
+                    let Some(keycode) = key.to_macos_keycode() else {
+                        continue;
+                    };
+                    match carbon::HotkeyRef::register(action, keycode, &modifiers) {
+                        Ok(hotkey) => {
+                            state.carbon_hotkeys.insert(action, hotkey);
+                        }
+                        Err(status) => tracing::warn!(
+                            "Failed to re-register hotkey {}: OSStatus {}",
+                            HotkeyBinding::new(&modifiers, key),
+                            status
+                        ),
+                    }
+                }
+            }
+        }
+
+        tracing::info!("Hotkeys {}", if paused { "paused" } else { "resumed" });
         Ok(())
     }
 
     pub fn start_monitoring<F>(&mut self, callback: F) -> Result<()>
     where
-        F: Fn(NotecardId) + Send + Sync + 'static,
+        F: Fn(HotkeyAction) + Send + Sync + 'static,
     {
         // Check if already monitoring
         {
@@ -81,33 +311,68 @@ impl HotkeyManager {
             }
         }
 
-        // Check accessibility permissions first
-        if !Self::check_accessibility_permissions() {
-            return Err(anyhow!("Accessibility permissions not granted. Please grant permissions in System Preferences > Security & Privacy > Privacy > Accessibility"));
-        }
-
         // Store callback in global state
         {
             let mut state = HOTKEY_STATE.lock().unwrap();
             state.callback = Some(Arc::new(callback));
         }
 
+        if self.backend == HotkeyBackend::Carbon {
+            // Presses are delivered by the main run loop, so no thread is needed
+            let handler = carbon::install_handler()
+                .map_err(|status| anyhow!("Failed to install hotkey handler: OSStatus {}", status))?;
+            self.carbon_handler = Some(handler);
+            *self.monitoring.lock().unwrap() = true;
+            return Ok(());
+        }
+
+        // Check accessibility permissions first
+        if !Self::check_accessibility_permissions() {
+            return Err(anyhow!("Accessibility permissions not granted. Please grant permissions in System Preferences > Security & Privacy > Privacy > Accessibility"));
+        }
+
         let monitoring = Arc::clone(&self.monitoring);
+        let stopped = Arc::clone(&self.stopped);
+        let (started_tx, started) = mpsc::channel();
 
         // Start event tap in a separate thread
-        let handle = thread::spawn(move || {
-            if let Err(e) = Self::run_event_tap(monitoring) {
-                tracing::error!("Event tap error: {}", e);
+        let handle = thread::spawn(move || Self::run_event_tap(monitoring, started_tx, stopped));
+
+        // Wait for the tap, so a failure to create it is an error here rather
+        // than a thread that quietly exits
+        match started.recv_timeout(TAP_START_TIMEOUT) {
+            Ok(Ok(event_tap)) => {
+                self.event_tap_thread = Some(handle);
+                self.event_tap = Some(event_tap);
+                Ok(())
+            }
+            Ok(Err(e)) => {
+                let _ = handle.join();
+                Err(e)
             }
-        });
+            // It may still come up; calling this again then finds it monitoring
+            Err(_) => {
+                self.event_tap_thread = Some(handle);
+                Err(anyhow!("Event tap did not start within {:?}", TAP_START_TIMEOUT))
+            }
+        }
+    }
 
-        self.event_tap_thread = Some(handle);
+    /// Checks accessibility trust without prompting the user
+    pub fn accessibility_trusted() -> bool {
+        use core_foundation::base::Boolean;
 
-        Ok(())
+        #[link(name = "ApplicationServices", kind = "framework")]
+        extern "C" {
+            fn AXIsProcessTrusted() -> Boolean;
+        }
+
+        unsafe { AXIsProcessTrusted() != 0 }
     }
 
-    fn check_accessibility_permissions() -> bool {
-        use core_foundation::base::{Boolean, TCFType};
+    /// Checks accessibility trust, asking the user to grant it if it's missing
+    pub fn check_accessibility_permissions() -> bool {
+        use core_foundation::base::Boolean;
         use core_foundation::dictionary::CFDictionary;
         use core_foundation::string::CFString;
 
@@ -127,7 +392,35 @@ impl HotkeyManager {
         }
     }
 
-    fn run_event_tap(monitoring: Arc<Mutex<bool>>) -> Result<()> {
+    /// Turns the event tap back on if macOS left it off, as it occasionally
+    /// does across sleep; does nothing for Carbon hotkeys
+    ///
+    /// A tap that won't come back on has its run loop stopped, so it's
+    /// recreated like any other tap that stops.
+    pub fn ensure_event_tap_enabled(&self) {
+        let Some(event_tap) = &self.event_tap else {
+            return;
+        };
+        if !self.is_monitoring() {
+            return;
+        }
+
+        if unsafe { CGEventTapIsEnabled(event_tap.port.as_concrete_TypeRef()) } {
+            tracing::debug!("Event tap still enabled");
+            return;
+        }
+        if reenable_tap(&event_tap.port) {
+            tracing::info!("Event tap was disabled; re-enabled it");
+            return;
+        }
+
+        tracing::info!("Event tap was disabled and couldn't be re-enabled; recreating it");
+        event_tap.run_loop.stop();
+    }
+
+    /// Runs the event tap until macOS disables it, reporting on `started`
+    /// whether it was created and notifying `stopped` once it ends
+    fn run_event_tap(monitoring: Arc<Mutex<bool>>, started: mpsc::Sender<Result<EventTap>>, stopped: Arc<Notify>) {
         tracing::debug!("Creating event tap...");
 
         // Create event tap with proper error handling
@@ -139,17 +432,21 @@ impl HotkeyManager {
             |_proxy, event_type, event| {
                 // Only process KeyDown events
                 match event_type {
+                    // macOS turns the tap off after a slow callback, and for
+                    // good once access is revoked
+                    CGEventType::TapDisabledByTimeout | CGEventType::TapDisabledByUserInput => {
+                        recover_disabled_tap(&format!("{:?}", event_type));
+                    }
                     CGEventType::KeyDown => {
                         // Check if this matches any registered hotkey
-                        if let Some(notecard_id) = Self::check_hotkey(&event) {
-                            tracing::debug!("Hotkey matched for notecard {}", notecard_id.value());
-
-                            // Call the callback
-                            if let Ok(state) = HOTKEY_STATE.lock() {
-                                if let Some(ref cb) = state.callback {
-                                    cb(notecard_id);
-                                }
+                        if let Some((action, callback)) = Self::check_hotkey(&event) {
+                            // A held key repeats; the repeats are swallowed
+                            // like the press, but only the press acts
+                            if event.get_integer_value_field(EventField::KEYBOARD_EVENT_AUTOREPEAT) != 0 {
+                                return None;
                             }
+                            tracing::debug!("Hotkey matched: {:?}", action);
+                            callback(action);
 
                             // Consume the event (prevent it from propagating)
                             return None;
@@ -167,7 +464,10 @@ impl HotkeyManager {
             Ok(tap) => tap,
             Err(e) => {
                 tracing::error!("Failed to create event tap: {:?}", e);
-                return Err(anyhow!("Failed to create event tap. Make sure accessibility permissions are granted."));
+                let _ = started.send(Err(anyhow!(
+                    "Failed to create event tap. Make sure accessibility permissions are granted."
+                )));
+                return;
             }
         };
 
@@ -183,13 +483,36 @@ impl HotkeyManager {
         tracing::info!("Event tap created and enabled, starting run loop");
 
         // Run the current thread's run loop
-        let tap_source = tap.mach_port.create_runloop_source(0)
-            .map_err(|_| anyhow!("Failed to create run loop source"))?;
+        let tap_source = match tap.mach_port.create_runloop_source(0) {
+            Ok(source) => source,
+            Err(_) => {
+                *monitoring.lock().unwrap() = false;
+                let _ = started.send(Err(anyhow!("Failed to create run loop source")));
+                return;
+            }
+        };
 
         let run_loop = CFRunLoop::get_current();
+        TAP_PORT.with(|port| *port.borrow_mut() = Some(tap.mach_port.clone()));
+
+        // Catches a tap turned off without the callback being told
+        let interval = TAP_WATCHDOG_INTERVAL.as_secs_f64();
+        let watchdog = CFRunLoopTimer::new(
+            CFDate::now().abs_time() + interval,
+            interval,
+            0,
+            0,
+            check_tap_enabled,
+            std::ptr::null_mut(),
+        );
         unsafe {
             run_loop.add_source(&tap_source, kCFRunLoopCommonModes);
+            run_loop.add_timer(&watchdog, kCFRunLoopCommonModes);
         }
+        let _ = started.send(Ok(EventTap {
+            port: tap.mach_port.clone(),
+            run_loop: run_loop.clone(),
+        }));
 
         // Run the loop
         CFRunLoop::run_current();
@@ -199,77 +522,62 @@ impl HotkeyManager {
             let mut mon = monitoring.lock().unwrap();
             *mon = false;
         }
+        TAP_PORT.with(|port| port.borrow_mut().take());
 
         tracing::info!("Event tap run loop ended");
-
-        Ok(())
+        stopped.notify_one();
     }
 
-    fn check_hotkey(event: &CGEvent) -> Option<NotecardId> {
+    /// The action a key down triggers and the callback to tell, looked up
+    /// under one short hold of the lock; the callback is called after it's
+    /// released, so the tap never waits on anything the callback does
+    fn check_hotkey(event: &CGEvent) -> Option<(HotkeyAction, Arc<dyn Fn(HotkeyAction) + Send + Sync>)> {
         let keycode = event.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE);
         let flags = event.get_flags();
 
-        // Map keycodes 18-26 to numbers 1-9
-        let number = match keycode {
-            18 => 1, // 1
-            19 => 2, // 2
-            20 => 3, // 3
-            21 => 4, // 4
-            23 => 5, // 5
-            22 => 6, // 6
-            26 => 7, // 7
-            28 => 8, // 8
-            25 => 9, // 9
-            _ => return None,
-        };
+        let pressed = HotkeyKey::from_macos_keycode(u16::try_from(keycode).ok()?)?;
 
-        // Try to create notecard ID
-        let notecard_id = match NotecardId::new(number) {
-            Ok(id) => id,
-            Err(_) => return None,
-        };
-
-        // Check if this notecard has registered hotkeys
         let state = match HOTKEY_STATE.lock() {
             Ok(state) => state,
             Err(_) => return None,
         };
 
-        if let Some(required_modifiers) = state.hotkeys.get(&notecard_id) {
-            // Check if all required modifiers are pressed
-            if Self::check_modifiers(&flags, required_modifiers) {
-                tracing::debug!("Hotkey match found for notecard {}", notecard_id.value());
-                return Some(notecard_id);
-            }
+        // Paused hotkeys pass through to the app in front
+        if state.paused {
+            return None;
         }
 
-        None
-    }
+        // Find the action bound to this key whose modifiers are held
+        let action = state
+            .hotkeys
+            .iter()
+            .find(|(_, (key, required_modifiers))| {
+                *key == pressed && HotkeyModifier::matches(&held_modifiers(flags), required_modifiers, state.strict_modifiers)
+            })
+            .map(|(action, _)| *action)?;
 
-    fn check_modifiers(flags: &CGEventFlags, required: &[HotkeyModifier]) -> bool {
-        for modifier in required {
-            let pressed = match modifier {
-                HotkeyModifier::Control => flags.contains(CGEventFlags::CGEventFlagControl),
-                HotkeyModifier::Alt => flags.contains(CGEventFlags::CGEventFlagAlternate),
-                HotkeyModifier::Shift => flags.contains(CGEventFlags::CGEventFlagShift),
-                #[cfg(target_os = "macos")]
-                HotkeyModifier::Command => flags.contains(CGEventFlags::CGEventFlagCommand),
-            };
-
-            if !pressed {
-                return false;
-            }
-        }
+        Some((action, state.callback.clone()?))
+    }
 
-        true
+    /// Whether event tap hotkeys fire only on exactly their modifiers;
+    /// Carbon hotkeys always do
+    pub fn set_strict_modifiers(&mut self, strict: bool) {
+        HOTKEY_STATE.lock().unwrap().strict_modifiers = strict;
     }
 
     pub fn stop_monitoring(&mut self) {
         let mut monitoring = self.monitoring.lock().unwrap();
         *monitoring = false;
 
-        // Stop the run loop
-        CFRunLoop::get_current().stop();
+        if let Some(handler) = self.carbon_handler.take() {
+            handler.remove();
+            return;
+        }
+
+        // Stop the tap thread's run loop, which ends the thread
+        if let Some(event_tap) = self.event_tap.take() {
+            event_tap.run_loop.stop();
+        }
 
         // Wait for thread to finish
         if let Some(handle) = self.event_tap_thread.take() {
@@ -281,5 +589,291 @@ impl HotkeyManager {
 impl Drop for HotkeyManager {
     fn drop(&mut self) {
         self.stop_monitoring();
+
+        // Carbon hotkeys stay registered with the system until removed
+        let _ = self.unregister_all();
+    }
+}
+
+/// The hotkey modifiers held in `flags`
+///
+/// Only Control, Option, Shift and Command count; caps lock, the numeric pad
+/// and other flags are ignored, so a hotkey needing the Windows key never
+/// matches.
+fn held_modifiers(flags: CGEventFlags) -> Vec<HotkeyModifier> {
+    [
+        (CGEventFlags::CGEventFlagControl, HotkeyModifier::Control),
+        (CGEventFlags::CGEventFlagAlternate, HotkeyModifier::Alt),
+        (CGEventFlags::CGEventFlagShift, HotkeyModifier::Shift),
+        (CGEventFlags::CGEventFlagCommand, HotkeyModifier::Command),
+    ]
+    .into_iter()
+    .filter(|(flag, _)| flags.contains(*flag))
+    .map(|(_, modifier)| modifier)
+    .collect()
+}
+
+/// Turns the event tap back on after macOS disabled it, `cause` saying how
+/// that was noticed; runs on the event tap thread
+///
+/// When access was revoked, or the tap won't come back on, its run loop is
+/// stopped instead, and the app waits for access and starts a new tap.
+fn recover_disabled_tap(cause: &str) {
+    let count = TAP_DISABLED_COUNT.fetch_add(1, Ordering::Relaxed) + 1;
+    let port = TAP_PORT.with(|port| port.borrow().clone());
+
+    let recovered = HotkeyManager::accessibility_trusted() && port.as_ref().is_some_and(reenable_tap);
+    if recovered {
+        tracing::warn!("Event tap disabled by the system ({}, {} times so far); re-enabled it", cause, count);
+    } else {
+        tracing::warn!("Event tap disabled by the system ({}, {} times so far); stopping it", cause, count);
+        CFRunLoop::get_current().stop();
+    }
+}
+
+/// Turns a tap on; returns whether it's on afterwards
+fn reenable_tap(port: &CFMachPort) -> bool {
+    let port = port.as_concrete_TypeRef();
+    unsafe {
+        CGEventTapEnable(port, true);
+        CGEventTapIsEnabled(port)
+    }
+}
+
+/// Watchdog timer callout on the event tap thread: recovers the tap if it's
+/// off, in case macOS disabled it without the callback hearing
+extern "C" fn check_tap_enabled(_timer: CFRunLoopTimerRef, _info: *mut c_void) {
+    let disabled = TAP_PORT.with(|port| {
+        port.borrow()
+            .as_ref()
+            .is_some_and(|port| !unsafe { CGEventTapIsEnabled(port.as_concrete_TypeRef()) })
+    });
+    if disabled {
+        recover_disabled_tap("found by the watchdog");
+    }
+}
+
+/// Handles a Carbon hotkey press delivered by the main run loop
+extern "C" fn carbon_hotkey_pressed(
+    _next: carbon::EventHandlerCallRef,
+    event: carbon::EventRef,
+    _user_data: *mut c_void,
+) -> carbon::OSStatus {
+    let Some(action) = carbon::pressed_action(event) else {
+        return carbon::EVENT_NOT_HANDLED_ERR;
+    };
+
+    // Release the lock before calling out
+    let callback = HOTKEY_STATE.lock().ok().and_then(|state| state.callback.clone());
+    if let Some(callback) = callback {
+        tracing::debug!("Hotkey matched: {:?}", action);
+        callback(action);
+    }
+
+    carbon::NO_ERR
+}
+
+/// The parts of the Carbon Event Manager used for hotkeys
+mod carbon {
+    use notecognito_core::{HotkeyAction, HotkeyModifier, NotecardId};
+    use std::ffi::c_void;
+    use std::ptr;
+
+    pub type OSStatus = i32;
+    pub type EventRef = *mut c_void;
+    pub type EventHandlerCallRef = *mut c_void;
+    type EventTargetRef = *mut c_void;
+    type EventHandlerUPP = extern "C" fn(EventHandlerCallRef, EventRef, *mut c_void) -> OSStatus;
+
+    pub const NO_ERR: OSStatus = 0;
+    pub const EVENT_NOT_HANDLED_ERR: OSStatus = -9874;
+
+    const fn four_char_code(code: &[u8; 4]) -> u32 {
+        u32::from_be_bytes(*code)
+    }
+
+    const EVENT_CLASS_KEYBOARD: u32 = four_char_code(b"keyb");
+    const EVENT_HOT_KEY_PRESSED: u32 = 5;
+    const EVENT_PARAM_DIRECT_OBJECT: u32 = four_char_code(b"----");
+    const TYPE_EVENT_HOT_KEY_ID: u32 = four_char_code(b"hkid");
+    /// Marks hotkeys as ours in `EventHotKeyID`
+    const SIGNATURE: u32 = four_char_code(b"ntcg");
+
+    const CMD_KEY: u32 = 1 << 8;
+    const SHIFT_KEY: u32 = 1 << 9;
+    const OPTION_KEY: u32 = 1 << 11;
+    const CONTROL_KEY: u32 = 1 << 12;
+
+    #[repr(C)]
+    struct EventTypeSpec {
+        event_class: u32,
+        event_kind: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct EventHotKeyID {
+        signature: u32,
+        id: u32,
+    }
+
+    #[link(name = "Carbon", kind = "framework")]
+    extern "C" {
+        fn GetApplicationEventTarget() -> EventTargetRef;
+        fn InstallEventHandler(
+            target: EventTargetRef,
+            handler: EventHandlerUPP,
+            num_types: u32,
+            types: *const EventTypeSpec,
+            user_data: *mut c_void,
+            out_ref: *mut *mut c_void,
+        ) -> OSStatus;
+        fn RemoveEventHandler(handler: *mut c_void) -> OSStatus;
+        fn RegisterEventHotKey(
+            key_code: u32,
+            modifiers: u32,
+            id: EventHotKeyID,
+            target: EventTargetRef,
+            options: u32,
+            out_ref: *mut *mut c_void,
+        ) -> OSStatus;
+        fn UnregisterEventHotKey(hotkey: *mut c_void) -> OSStatus;
+        fn GetEventParameter(
+            event: EventRef,
+            name: u32,
+            desired_type: u32,
+            actual_type: *mut u32,
+            buffer_size: usize,
+            actual_size: *mut usize,
+            data: *mut c_void,
+        ) -> OSStatus;
+    }
+
+    /// A hotkey registered with `RegisterEventHotKey`
+    pub struct HotkeyRef(*mut c_void);
+
+    // Only handed to Carbon, which doesn't tie it to a thread
+    unsafe impl Send for HotkeyRef {}
+
+    impl HotkeyRef {
+        pub fn register(action: HotkeyAction, keycode: u16, modifiers: &[HotkeyModifier]) -> Result<Self, OSStatus> {
+            let id = EventHotKeyID { signature: SIGNATURE, id: encode(action) };
+            let mut hotkey = ptr::null_mut();
+
+            let status = unsafe {
+                RegisterEventHotKey(
+                    keycode as u32,
+                    modifier_mask(modifiers),
+                    id,
+                    GetApplicationEventTarget(),
+                    0,
+                    &mut hotkey,
+                )
+            };
+
+            if status == NO_ERR {
+                Ok(HotkeyRef(hotkey))
+            } else {
+                Err(status)
+            }
+        }
+
+        pub fn unregister(self) {
+            let status = unsafe { UnregisterEventHotKey(self.0) };
+            if status != NO_ERR {
+                tracing::warn!("UnregisterEventHotKey failed: OSStatus {}", status);
+            }
+        }
+    }
+
+    /// The installed hotkey press handler
+    pub struct EventHandlerRef(*mut c_void);
+
+    impl EventHandlerRef {
+        pub fn remove(self) {
+            unsafe {
+                RemoveEventHandler(self.0);
+            }
+        }
+    }
+
+    /// Routes hotkey presses on the application target to `carbon_hotkey_pressed`
+    pub fn install_handler() -> Result<EventHandlerRef, OSStatus> {
+        let spec = EventTypeSpec {
+            event_class: EVENT_CLASS_KEYBOARD,
+            event_kind: EVENT_HOT_KEY_PRESSED,
+        };
+        let mut handler = ptr::null_mut();
+
+        let status = unsafe {
+            InstallEventHandler(
+                GetApplicationEventTarget(),
+                super::carbon_hotkey_pressed,
+                1,
+                &spec,
+                ptr::null_mut(),
+                &mut handler,
+            )
+        };
+
+        if status == NO_ERR {
+            Ok(EventHandlerRef(handler))
+        } else {
+            Err(status)
+        }
+    }
+
+    /// Reads which of our hotkeys a press event is for
+    pub fn pressed_action(event: EventRef) -> Option<HotkeyAction> {
+        let mut id = EventHotKeyID::default();
+
+        let status = unsafe {
+            GetEventParameter(
+                event,
+                EVENT_PARAM_DIRECT_OBJECT,
+                TYPE_EVENT_HOT_KEY_ID,
+                ptr::null_mut(),
+                std::mem::size_of::<EventHotKeyID>(),
+                ptr::null_mut(),
+                &mut id as *mut EventHotKeyID as *mut c_void,
+            )
+        };
+
+        if status != NO_ERR || id.signature != SIGNATURE {
+            return None;
+        }
+
+        decode(id.id)
+    }
+
+    /// Notecards use their ID and hide-all uses 0
+    fn encode(action: HotkeyAction) -> u32 {
+        match action {
+            HotkeyAction::ToggleNotecard(id) => id.value() as u32,
+            HotkeyAction::HideAll => 0,
+        }
+    }
+
+    fn decode(id: u32) -> Option<HotkeyAction> {
+        match id {
+            0 => Some(HotkeyAction::HideAll),
+            id => {
+                let id = NotecardId::new(u8::try_from(id).ok()?).ok()?;
+                Some(HotkeyAction::ToggleNotecard(id))
+            }
+        }
+    }
+
+    fn modifier_mask(modifiers: &[HotkeyModifier]) -> u32 {
+        modifiers.iter().fold(0, |mask, modifier| {
+            mask | match modifier {
+                HotkeyModifier::Control => CONTROL_KEY,
+                HotkeyModifier::Alt => OPTION_KEY,
+                HotkeyModifier::Shift => SHIFT_KEY,
+                HotkeyModifier::Command => CMD_KEY,
+                // Only bindings `HotkeyManager::register` accepted get here
+                HotkeyModifier::Windows => unreachable!("rejected by check_supported in register"),
+            }
+        })
     }
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
 use anyhow::{anyhow, Result};
-use notecognito_core::{Config, IpcMessage, IpcMessageType, Notecard};
+use notecognito_core::{Config, IpcMessage, IpcMessageType, Notecard, PlatformEvent};
 use serde_json;
 use std::sync::Arc;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
@@ -37,7 +37,7 @@ impl IpcClient {
 
         match response.message_type {
             IpcMessageType::ConfigurationResponse { config } => Ok(config),
-            IpcMessageType::Error { message } => Err(anyhow!("Server error: {}", message)),
+            IpcMessageType::Error { message, .. } => Err(anyhow!("Server error: {}", message)),
             _ => Err(anyhow!("Unexpected response type")),
         }
     }
@@ -48,7 +48,7 @@ impl IpcClient {
 
         match response.message_type {
             IpcMessageType::Success { .. } => Ok(()),
-            IpcMessageType::Error { message } => Err(anyhow!("Server error: {}", message)),
+            IpcMessageType::Error { message, .. } => Err(anyhow!("Server error: {}", message)),
             _ => Err(anyhow!("Unexpected response type")),
         }
     }
@@ -59,11 +59,67 @@ impl IpcClient {
 
         match response.message_type {
             IpcMessageType::Success { .. } => Ok(()),
-            IpcMessageType::Error { message } => Err(anyhow!("Server error: {}", message)),
+            IpcMessageType::Error { message, .. } => Err(anyhow!("Server error: {}", message)),
             _ => Err(anyhow!("Unexpected response type")),
         }
     }
 
+    /// Reports an event for the server to push to its subscribers
+    pub async fn report_platform_event(&mut self, event: PlatformEvent) -> Result<()> {
+        let message = IpcMessage::new(IpcMessageType::PlatformEvent { event });
+        let response = self.send_message(message).await?;
+
+        match response.message_type {
+            IpcMessageType::Success { .. } => Ok(()),
+            IpcMessageType::Error { message, .. } => Err(anyhow!("Server error: {}", message)),
+            _ => Err(anyhow!("Unexpected response type")),
+        }
+    }
+
+    /// Pauses or resumes hotkeys in core's configuration, which pushes the change
+    pub async fn set_hotkeys_enabled(&mut self, enabled: bool) -> Result<()> {
+        let message = IpcMessage::new(IpcMessageType::SetHotkeysEnabled { enabled });
+        let response = self.send_message(message).await?;
+
+        match response.message_type {
+            IpcMessageType::Success { .. } => Ok(()),
+            IpcMessageType::Error { message, .. } => Err(anyhow!("Server error: {}", message)),
+            _ => Err(anyhow!("Unexpected response type")),
+        }
+    }
+
+    /// Subscribes to configuration changes; afterwards the connection only
+    /// carries the server's pushes, read with `next_config_change`
+    pub async fn subscribe(&mut self) -> Result<()> {
+        let message = IpcMessage::new(IpcMessageType::Subscribe);
+        let response = self.send_message(message).await?;
+
+        match response.message_type {
+            IpcMessageType::Success { .. } => Ok(()),
+            IpcMessageType::Error { message, .. } => Err(anyhow!("Server error: {}", message)),
+            _ => Err(anyhow!("Unexpected response type")),
+        }
+    }
+
+    /// Waits for the next configuration pushed to a subscribed connection
+    pub async fn next_config_change(&mut self) -> Result<Config> {
+        let stream = self.stream.as_ref()
+            .ok_or_else(|| anyhow!("Not connected to IPC server"))?;
+
+        let mut stream = stream.lock().await;
+
+        loop {
+            let message = read_message(&mut stream).await?;
+
+            match message.message_type {
+                IpcMessageType::ConfigChanged { config } => return Ok(config),
+                // Pushed to every subscriber, including the app that reported it
+                IpcMessageType::PlatformEvent { .. } => continue,
+                _ => return Err(anyhow!("Unexpected message type")),
+            }
+        }
+    }
+
     async fn send_message(&mut self, message: IpcMessage) -> Result<IpcMessage> {
         let stream = self.stream.as_ref()
             .ok_or_else(|| anyhow!("Not connected to IPC server"))?;
@@ -81,25 +137,30 @@ impl IpcClient {
         stream.write_all(&json).await?;
         stream.flush().await?;
 
-        // Read response length
-        let mut len_bytes = [0u8; 4];
-        stream.read_exact(&mut len_bytes).await?;
-        let message_len = u32::from_le_bytes(len_bytes) as usize;
-
-        if message_len > MAX_MESSAGE_SIZE {
-            return Err(anyhow!("Response too large"));
-        }
-
-        // Read response
-        let mut buffer = vec![0; message_len];
-        stream.read_exact(&mut buffer).await?;
-
-        // Parse response
-        let response: IpcMessage = serde_json::from_slice(&buffer)?;
-        Ok(response)
+        read_message(&mut stream).await
     }
 
     pub async fn disconnect(&mut self) {
         self.stream = None;
     }
-}
\ No newline at end of file
+}
+
+/// Reads one length-prefixed message from the server
+async fn read_message(stream: &mut TcpStream) -> Result<IpcMessage> {
+    // Read message length
+    let mut len_bytes = [0u8; 4];
+    stream.read_exact(&mut len_bytes).await?;
+    let message_len = u32::from_le_bytes(len_bytes) as usize;
+
+    if message_len > MAX_MESSAGE_SIZE {
+        return Err(anyhow!("Response too large"));
+    }
+
+    // Read message
+    let mut buffer = vec![0; message_len];
+    stream.read_exact(&mut buffer).await?;
+
+    // Parse message
+    let message: IpcMessage = serde_json::from_slice(&buffer)?;
+    Ok(message)
+}
@@ -0,0 +1,180 @@
+use objc2::rc::Retained;
+use objc2::runtime::{AnyClass, AnyObject};
+use objc2::{msg_send, msg_send_id};
+use objc2_foundation::{NSError, NSProcessInfo};
+
+/// First major release with `SMAppService`
+const SM_APP_SERVICE_MAJOR: isize = 13;
+
+// SMAppServiceStatus
+const STATUS_NOT_REGISTERED: isize = 0;
+const STATUS_ENABLED: isize = 1;
+const STATUS_REQUIRES_APPROVAL: isize = 2;
+
+// Loads ServiceManagement so `SMAppService` can be looked up by name
+#[link(name = "ServiceManagement", kind = "framework")]
+extern "C" {}
+
+/// Whether the app is registered to launch at login and allowed to; `None`
+/// before macOS 13, where the legacy login item list can't be asked reliably
+pub fn is_enabled() -> Option<bool> {
+    let service = main_app_service()?;
+    let status: isize = unsafe { msg_send![&service, status] };
+    Some(status == STATUS_ENABLED)
+}
+
+/// Registers or unregisters the app as a login item, doing nothing when it's
+/// already in the requested state
+///
+/// Fails with the system's description of the problem, or an explanation when
+/// the user still has to approve the login item.
+pub fn set_enabled(enabled: bool) -> Result<(), String> {
+    let Some(service) = main_app_service() else {
+        return legacy::set_enabled(enabled);
+    };
+
+    let status: isize = unsafe { msg_send![&service, status] };
+    match (enabled, status) {
+        (true, STATUS_ENABLED) | (false, STATUS_NOT_REGISTERED) => return Ok(()),
+        // Registered already; registering again fails, and only the user can approve it
+        (true, STATUS_REQUIRES_APPROVAL) => {
+            return Err("Notecognito is waiting for approval in System Settings > General > Login Items".to_string())
+        }
+        _ => {}
+    }
+
+    let result: Result<(), Retained<NSError>> = unsafe {
+        if enabled {
+            msg_send![&service, registerAndReturnError: _]
+        } else {
+            msg_send![&service, unregisterAndReturnError: _]
+        }
+    };
+    result.map_err(|error| unsafe { error.localizedDescription() }.to_string())?;
+
+    tracing::info!("{} the login item", if enabled { "Registered" } else { "Unregistered" });
+    Ok(())
+}
+
+/// `SMAppService.mainAppService`, on releases that have it
+fn main_app_service() -> Option<Retained<AnyObject>> {
+    let version = unsafe { NSProcessInfo::processInfo().operatingSystemVersion() };
+    if version.majorVersion < SM_APP_SERVICE_MAJOR {
+        return None;
+    }
+
+    let class = AnyClass::get("SMAppService")?;
+    Some(unsafe { msg_send_id![class, mainAppService] })
+}
+
+/// The `LSSharedFileList` login items used before macOS 13
+mod legacy {
+    use core_foundation::array::CFArray;
+    use core_foundation::base::{CFType, TCFType};
+    use core_foundation::error::CFErrorRef;
+    use core_foundation::string::{CFString, CFStringRef};
+    use core_foundation::url::CFURL;
+    use std::ptr;
+
+    #[link(name = "CoreServices", kind = "framework")]
+    extern "C" {
+        fn LSSharedFileListCreate(
+            allocator: core_foundation::base::CFAllocatorRef,
+            list_type: CFStringRef,
+            list_options: core_foundation::base::CFTypeRef,
+        ) -> core_foundation::base::CFTypeRef;
+
+        fn LSSharedFileListInsertItemURL(
+            list: core_foundation::base::CFTypeRef,
+            insert_after_item: core_foundation::base::CFTypeRef,
+            name: core_foundation::string::CFStringRef,
+            icon_ref: core_foundation::base::CFTypeRef,
+            url: core_foundation::url::CFURLRef,
+            properties: core_foundation::dictionary::CFDictionaryRef,
+            items_to_add: core_foundation::array::CFArrayRef,
+        ) -> core_foundation::base::CFTypeRef;
+
+        fn LSSharedFileListItemRemove(
+            list: core_foundation::base::CFTypeRef,
+            item: core_foundation::base::CFTypeRef,
+        ) -> core_foundation::base::OSStatus;
+
+        fn LSSharedFileListCopySnapshot(
+            list: core_foundation::base::CFTypeRef,
+            seed: *mut u32,
+        ) -> core_foundation::array::CFArrayRef;
+
+        fn LSSharedFileListItemCopyResolvedURL(
+            item: core_foundation::base::CFTypeRef,
+            flags: u32,
+            error: *mut CFErrorRef,
+        ) -> core_foundation::url::CFURLRef;
+    }
+
+    pub fn set_enabled(enabled: bool) -> Result<(), String> {
+        unsafe {
+            let session_login_items = CFString::from_static_string("com.apple.LSSharedFileList.SessionLoginItems");
+
+            let list = LSSharedFileListCreate(ptr::null(), session_login_items.as_concrete_TypeRef(), ptr::null());
+            if list.is_null() {
+                return Err("Failed to access login items".to_string());
+            }
+
+            let app_path = std::env::current_exe().map_err(|e| e.to_string())?;
+
+            // For .app bundles, get the bundle path
+            let bundle_path = if app_path.to_string_lossy().contains(".app/Contents/MacOS/") {
+                app_path
+                    .parent() // MacOS
+                    .and_then(|p| p.parent()) // Contents
+                    .and_then(|p| p.parent()) // .app
+                    .unwrap_or(&app_path)
+            } else {
+                &app_path
+            };
+
+            let app_url = CFURL::from_path(bundle_path, false).ok_or("Failed to create app URL")?;
+
+            // Listed once however often this runs
+            let mut seed: u32 = 0;
+            let items = LSSharedFileListCopySnapshot(list, &mut seed);
+            let mut listed = false;
+
+            if !items.is_null() {
+                let items = CFArray::<CFType>::wrap_under_create_rule(items);
+                for item in items.iter() {
+                    let item_url = LSSharedFileListItemCopyResolvedURL(item.as_CFTypeRef(), 0, ptr::null_mut());
+                    if item_url.is_null() {
+                        continue;
+                    }
+
+                    let item_url = CFURL::wrap_under_create_rule(item_url);
+                    if item_url.to_path().as_deref() == Some(bundle_path) {
+                        if enabled {
+                            listed = true;
+                        } else {
+                            LSSharedFileListItemRemove(list, item.as_CFTypeRef());
+                        }
+                    }
+                }
+            }
+
+            if enabled && !listed {
+                let inserted = LSSharedFileListInsertItemURL(
+                    list,
+                    core_foundation::base::kCFNull,
+                    CFString::from_static_string("Notecognito").as_concrete_TypeRef(),
+                    ptr::null(),
+                    app_url.as_concrete_TypeRef(),
+                    ptr::null(),
+                    ptr::null(),
+                );
+                if inserted.is_null() {
+                    return Err("Failed to add Notecognito to the login items".to_string());
+                }
+            }
+
+            Ok(())
+        }
+    }
+}
@@ -1,42 +1,75 @@
 use anyhow::{Context, Result};
-use notecognito_core::{ConfigManager, NotecardId, PlatformInterface};
+use notecognito_core::{
+    AppIdentity, Config, ConfigManager, HotkeyAction, HotkeyBinding, HotkeyDebouncer, MonitorSelector, Notecard,
+    NotecardId, NotecardSummary, NotificationKind, PermissionMonitor, PlatformEvent, PlatformInterface,
+    PLATFORM_EVENT_CAPACITY,
+};
 use objc2::rc::Retained;
 use objc2::runtime::ProtocolObject;
-use objc2::ClassType;
-use objc2_app_kit::{
-    NSApplication, NSApplicationActivationPolicy, NSMenu, NSMenuItem, NSStatusBar, NSStatusItem,
-    NSImage, NSEventModifierFlags,
-};
-use objc2_foundation::{
-    MainThreadMarker, NSBundle, NSData, NSString,
-};
+use objc2_app_kit::{NSApplication, NSApplicationActivationPolicy};
+use objc2_foundation::MainThreadMarker;
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::Mutex;
-
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch, Mutex};
+
+mod accessibility;
+mod capabilities;
+mod clipboard;
+mod fallback_editor;
+mod foreground;
 mod hotkey;
 mod ipc_client;
+mod login_item;
+mod menu;
+mod notecard_view;
 mod notecard_window;
+mod notification;
 mod platform_impl;
+mod screens;
+mod single_instance;
 mod app_delegate;
 
-use hotkey::HotkeyManager;
+use hotkey::{HotkeyBackend, HotkeyManager};
 use ipc_client::IpcClient;
 use notecard_window::NotecardWindowManager;
 use platform_impl::MacOSPlatform;
 use app_delegate::AppDelegate;
+use fallback_editor::NotecardEdits;
 
 const APP_NAME: &str = "Notecognito";
 
-// Global references for menu items and delegate
-static mut MENU_DELEGATE: Option<Retained<AppDelegate>> = None;
-static mut STATUS_ITEM: Option<Retained<NSStatusItem>> = None;
+/// Wait before trying again after hotkey monitoring failed to start
+const HOTKEY_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Wait before the first attempt to reach core again; doubles up to the max
+const CORE_RETRY_MIN_DELAY: Duration = Duration::from_secs(1);
+const CORE_RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// How long after the last drag the new places of moved notecards are saved
+const MOVE_SAVE_DELAY: Duration = Duration::from_millis(500);
 
 pub struct App {
     config_manager: Arc<Mutex<ConfigManager>>,
     ipc_client: Arc<Mutex<IpcClient>>,
     hotkey_manager: Arc<Mutex<HotkeyManager>>,
-    window_manager: Arc<Mutex<NotecardWindowManager>>,
     platform: Arc<Mutex<MacOSPlatform>>,
+    window_manager: Arc<Mutex<NotecardWindowManager>>,
+    /// Taken by `run`, which forwards the events to core
+    platform_events: Option<mpsc::Receiver<PlatformEvent>>,
+    /// Handed to the delegate, whose Pause Hotkeys item sends on it
+    pause_tx: mpsc::UnboundedSender<()>,
+    /// Taken by `run`, which pauses and resumes the hotkeys
+    pause_requests: Option<mpsc::UnboundedReceiver<()>>,
+    /// Handed to the delegate for the fallback editor's saves
+    edits_tx: mpsc::UnboundedSender<NotecardEdits>,
+    /// Taken by `run`, which saves the fallback editor's changes
+    notecard_edits: Option<mpsc::UnboundedReceiver<NotecardEdits>>,
+    /// Handed to the delegate, which sets it on quit and waits, briefly, for
+    /// the tasks `run` subscribes to finish
+    shutdown_tx: watch::Sender<bool>,
+    /// The application only holds its delegate weakly, so this keeps it alive
+    delegate: Option<Retained<AppDelegate>>,
 }
 
 impl App {
@@ -58,8 +91,14 @@ impl App {
         let ipc_client = Arc::new(Mutex::new(ipc_client));
 
         // Create managers
-        let hotkey_manager = Arc::new(Mutex::new(HotkeyManager::new()));
-        let window_manager = Arc::new(Mutex::new(NotecardWindowManager::new()));
+        let backend = if config_manager.lock().await.config().event_tap_hotkeys {
+            HotkeyBackend::EventTap
+        } else {
+            HotkeyBackend::Carbon
+        };
+        let hotkey_manager = Arc::new(Mutex::new(HotkeyManager::new(backend)));
+        let (events_tx, platform_events) = mpsc::channel(PLATFORM_EVENT_CAPACITY);
+        let window_manager = Arc::new(Mutex::new(NotecardWindowManager::new(events_tx)));
 
         // Create platform implementation
         let platform = MacOSPlatform::new(
@@ -67,13 +106,23 @@ impl App {
             Arc::clone(&window_manager),
         );
         let platform = Arc::new(Mutex::new(platform));
+        let (pause_tx, pause_requests) = mpsc::unbounded_channel();
+        let (edits_tx, notecard_edits) = mpsc::unbounded_channel();
+        let (shutdown_tx, _) = watch::channel(false);
 
         Ok(App {
             config_manager,
             ipc_client,
             hotkey_manager,
-            window_manager,
             platform,
+            window_manager,
+            platform_events: Some(platform_events),
+            pause_tx,
+            pause_requests: Some(pause_requests),
+            edits_tx,
+            notecard_edits: Some(notecard_edits),
+            shutdown_tx,
+            delegate: None,
         })
     }
 
@@ -85,60 +134,151 @@ impl App {
         app.setActivationPolicy(NSApplicationActivationPolicy::Accessory);
 
         // Create and set app delegate FIRST
-        let delegate = AppDelegate::new(mtm);
-        unsafe {
-            MENU_DELEGATE = Some(delegate.clone());
-            app.setDelegate(Some(ProtocolObject::from_ref(&*delegate)));
-        }
+        let delegate = AppDelegate::new(
+            mtm,
+            Arc::clone(&self.config_manager),
+            Arc::clone(&self.window_manager),
+            Arc::clone(&self.platform),
+            self.pause_tx.clone(),
+            self.edits_tx.clone(),
+            self.shutdown_tx.clone(),
+        );
+        app.setDelegate(Some(ProtocolObject::from_ref(&*delegate)));
 
         // Create menu bar item AFTER delegate is set
-        self.create_menu_bar_item(mtm)?;
+        delegate.install_status_item(mtm);
+        delegate.listen_for_other_launches();
+        delegate.observe_system_changes();
+        self.delegate = Some(delegate);
 
-        // Try to connect to IPC server
+        // Try to connect to IPC server; `run` keeps trying if core isn't up yet
         match self.connect_to_core().await {
-            Ok(_) => tracing::info!("Connected to core service"),
+            Ok(_) => {
+                tracing::info!("Connected to core service");
+                show_core_connection(true);
+            }
             Err(e) => {
                 tracing::warn!("Could not connect to core service: {}", e);
                 tracing::info!("Running in standalone mode");
+                self.ipc_client.lock().await.disconnect().await;
             }
         }
 
         // Initialize platform
         {
             let mut platform = self.platform.lock().await;
-            platform.initialize()?;
+            platform.initialize().await?;
         }
 
         // Load configuration (but don't start hotkey monitoring yet)
         self.load_configuration_without_hotkeys().await?;
 
-        // Now try to start hotkey monitoring, which will check permissions
+        // Register the hotkeys; monitoring them starts in run, once permitted
         if let Err(e) = self.setup_hotkeys().await {
             tracing::warn!("Failed to setup hotkeys: {}", e);
-            // Show permission alert if it's a permission issue
-            if e.to_string().contains("Accessibility permissions") {
-                self.show_accessibility_alert(mtm);
+        }
+
+        self.sync_menu().await;
+
+        // Hotkeys paused before the last quit stay paused
+        if !self.config_manager.lock().await.config().hotkeys_enabled {
+            if let Err(e) = apply_hotkeys_enabled(false, &self.hotkey_manager).await {
+                tracing::warn!("Failed to pause hotkeys: {}", e);
             }
         }
 
         Ok(())
     }
 
+    /// Posts a notification and badges the menu bar item until permission is granted
+    async fn notify_missing_accessibility(&self) {
+        let platform = self.platform.lock().await;
+        if let Err(e) = platform
+            .show_notification(
+                "Accessibility Permission Required",
+                "Grant Notecognito access in System Settings > Privacy & Security > Accessibility \
+                to use the hotkeys. They start working as soon as access is granted.",
+                NotificationKind::Warning,
+            )
+            .await
+        {
+            tracing::warn!("Failed to show notification: {}", e);
+        }
+
+        set_accessibility_badge(true);
+    }
 
-    fn show_accessibility_alert(&self, mtm: MainThreadMarker) {
-        use objc2_app_kit::{NSAlert, NSAlertStyle};
+    /// Keeps hotkey monitoring running: starts it as soon as accessibility
+    /// permission is there, and goes back to waiting for it whenever the event
+    /// tap stops, as it does when access is revoked
+    ///
+    /// `announce` posts a notification when hotkeys start, for when the user
+    /// was told they were missing.
+    fn supervise_hotkeys<F>(&self, callback: F, mut announce: bool)
+    where
+        F: Fn(HotkeyAction) + Send + Sync + 'static,
+    {
+        let callback = Arc::new(callback);
+        let config_manager = Arc::clone(&self.config_manager);
+        let platform = Arc::clone(&self.platform);
+        let hotkey_manager = Arc::clone(&self.hotkey_manager);
 
-        unsafe {
-            let alert = NSAlert::new(mtm);
-            alert.setMessageText(&NSString::from_str("Accessibility Permission Required"));
-            alert.setInformativeText(&NSString::from_str(
-                "Notecognito needs accessibility permissions to register global hotkeys.\n\n\
-                Please grant permission in System Preferences > Security & Privacy > Privacy > Accessibility.\n\n\
-                You may need to restart the app after granting permission."
-            ));
-            alert.setAlertStyle(NSAlertStyle::Warning);
-            alert.runModal();
-        }
+        tokio::spawn(async move {
+            loop {
+                // Polls while permission is denied and returns once it's granted
+                if let Err(e) = PermissionMonitor::new().watch(&platform).await {
+                    tracing::warn!("Stopped watching accessibility permission: {}", e);
+                    return;
+                }
+
+                let started = {
+                    let bindings = config_manager.lock().await.config().hotkey_bindings();
+                    let mut hotkey_manager = hotkey_manager.lock().await;
+                    hotkey_manager.sync_bindings(&bindings);
+
+                    let callback = Arc::clone(&callback);
+                    hotkey_manager
+                        .start_monitoring(move |action| callback(action))
+                        .map(|()| hotkey_manager.stopped())
+                };
+
+                let stopped = match started {
+                    Ok(stopped) => stopped,
+                    Err(e) => {
+                        tracing::error!("Failed to start hotkey monitoring: {}", e);
+                        announce = true;
+                        tokio::time::sleep(HOTKEY_RETRY_DELAY).await;
+                        continue;
+                    }
+                };
+
+                tracing::info!("Hotkey monitoring started");
+                set_accessibility_badge(false);
+                if announce {
+                    let platform = platform.lock().await;
+                    if let Err(e) = platform
+                        .show_notification(
+                            "Hotkeys Enabled",
+                            "Accessibility access was granted, so the Notecognito hotkeys work now.",
+                            NotificationKind::Info,
+                        )
+                        .await
+                    {
+                        tracing::warn!("Failed to show notification: {}", e);
+                    }
+                }
+
+                // Only the event tap ever stops
+                stopped.notified().await;
+                let permitted = platform.lock().await.check_permissions().await.unwrap_or(false);
+                tracing::warn!(
+                    "Hotkey monitoring stopped; accessibility access is {}",
+                    if permitted { "still granted" } else { "revoked" }
+                );
+                set_accessibility_badge(!permitted);
+                announce = !permitted;
+            }
+        });
     }
 
     async fn connect_to_core(&self) -> Result<()> {
@@ -156,150 +296,121 @@ impl App {
     }
 
 
-    fn create_menu_bar_item(&mut self, mtm: MainThreadMarker) -> Result<()> {
-        tracing::debug!("Creating menu bar item...");
-
-        unsafe {
-            // Get the status bar
-            let status_bar = NSStatusBar::systemStatusBar();
-
-            // Create status item with variable length
-            let status_item = status_bar.statusItemWithLength(-1.0); // NSVariableStatusItemLength
-
-            // Set icon
-            if let Some(button) = status_item.button(mtm) {
-                // Try to load icon from bundle first
-                if let Some(icon) = Self::load_icon(mtm) {
-                    button.setImage(Some(&icon));
-                    button.setToolTip(Some(&NSString::from_str("Notecognito")));
-                } else {
-                    // Fallback to text
-                    button.setTitle(&NSString::from_str("N"));
-                }
-            }
-
-            // Create menu with proper delegate target
-            let menu = Self::create_menu(mtm);
-            status_item.setMenu(Some(&menu));
-
-            // Store status item globally
-            STATUS_ITEM = Some(status_item);
-
-            tracing::info!("Menu bar item created successfully");
-        }
-
-        Ok(())
-    }
-
-    fn load_icon(_mtm: MainThreadMarker) -> Option<Retained<NSImage>> {
-        unsafe {
-            // Try multiple ways to load the icon
-
-            // 1. Try from app bundle resources
-            let bundle = NSBundle::mainBundle();
-            if let Some(path) = bundle.pathForResource_ofType(
-                Some(&NSString::from_str("icon")),
-                Some(&NSString::from_str("png"))
-            ) {
-                if let Some(image) = NSImage::initWithContentsOfFile(NSImage::alloc(), &path) {
-                    // Set template image for proper menu bar styling
-                    let _: () = objc2::msg_send![&image, setTemplate: true];
-                    return Some(image);
-                }
-            }
-
-            // 2. Try embedded icon data
-            let icon_data = include_bytes!("../assets/icon.png");
-            let data = NSData::dataWithBytes_length(
-                icon_data.as_ptr() as *mut std::ffi::c_void,
-                icon_data.len(),
-            );
-
-            if let Some(image) = NSImage::initWithData(NSImage::alloc(), &data) {
-                // Set template image
-                let _: () = objc2::msg_send![&image, setTemplate: true];
-                return Some(image);
-            }
-
-            None
-        }
-    }
-
-    fn create_menu(mtm: MainThreadMarker) -> Retained<NSMenu> {
-        unsafe {
-            let menu = NSMenu::new(mtm);
-
-            // Get delegate reference
-            let delegate = MENU_DELEGATE.as_ref().unwrap();
-            // Configure item
-            let configure_item = NSMenuItem::new(mtm);
-            configure_item.setTitle(&NSString::from_str("Configure..."));
-            configure_item.setAction(Some(objc2::sel!(configure:)));
-            configure_item.setTarget(Some(delegate)); // Set proper target
-            menu.addItem(&configure_item);
-
-            // Separator
-            menu.addItem(&NSMenuItem::separatorItem(mtm));
-
-            // About item
-            let about_item = NSMenuItem::new(mtm);
-            about_item.setTitle(&NSString::from_str("About Notecognito"));
-            about_item.setAction(Some(objc2::sel!(about:)));
-            about_item.setTarget(Some(delegate)); // Set proper target
-            menu.addItem(&about_item);
-
-            // Separator
-            menu.addItem(&NSMenuItem::separatorItem(mtm));
-
-            // Quit item (this targets the app, not the delegate)
-            let quit_item = NSMenuItem::new(mtm);
-            quit_item.setTitle(&NSString::from_str("Quit Notecognito"));
-            quit_item.setAction(Some(objc2::sel!(terminate:)));
-            quit_item.setKeyEquivalent(&NSString::from_str("q"));
-            quit_item.setKeyEquivalentModifierMask(NSEventModifierFlags::NSEventModifierFlagCommand);
-            menu.addItem(&quit_item);
-
-            menu
+    /// Shows the loaded configuration in the status menu
+    async fn sync_menu(&self) {
+        let supported = self.platform.lock().await.capabilities().screen_capture_exclusion;
+        if let Some(delegate) = &self.delegate {
+            delegate.sync_menu_with_config(supported).await;
         }
     }
 
     async fn run(&mut self) -> Result<()> {
-        // Create a channel for hotkey events
-        let (tx, mut rx) = tokio::sync::mpsc::channel::<NotecardId>(32);
+        // Create a channel for hotkey events, stamped with when they were
+        // pressed so a slow toggle doesn't make the next press look like a repeat
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<(HotkeyAction, Instant)>(32);
 
         // Set up hotkey callback with channel sender
-        let callback = move |notecard_id: NotecardId| {
-            tracing::info!("Hotkey pressed for notecard {}", notecard_id.value());
-
-            // Just send the notecard ID through the channel
+        // Called from the event tap, which macOS disables if it's slow, so it
+        // only sends the press on; it's logged where it's handled
+        let callback = move |action: HotkeyAction| {
+            // Just send the action through the channel
             // This is safe to do from any thread
-            if let Err(e) = tx.try_send(notecard_id) {
-                tracing::error!("Failed to send hotkey event: {}", e);
+            match tx.try_send((action, Instant::now())) {
+                Ok(()) => {}
+                // Presses this far behind would only be debounced away
+                Err(mpsc::error::TrySendError::Full(_)) => tracing::debug!("Dropped hotkey press {:?}", action),
+                Err(mpsc::error::TrySendError::Closed(_)) => tracing::warn!("Hotkey press after shutdown"),
             }
         };
 
-        // Start hotkey monitoring
-        {
-            let mut hotkey_manager = self.hotkey_manager.lock().await;
-            match hotkey_manager.start_monitoring(callback) {
-                Ok(_) => {
-                    tracing::info!("Hotkey monitoring started successfully");
+        // Start hotkey monitoring, or wait for accessibility permission if it's missing
+        let permitted = self.platform.lock().await.check_permissions().await.unwrap_or(false);
+        if !permitted {
+            // Shows the system prompt; hotkeys start once the user grants access
+            let _ = self.platform.lock().await.request_permissions().await;
+            self.notify_missing_accessibility().await;
+        }
+        self.supervise_hotkeys(callback, !permitted);
+
+        // Let core's subscribers hear about notecards closed by click or ESC,
+        // and keep where notecards were dragged
+        if let Some(events) = self.platform_events.take() {
+            tokio::spawn(forward_platform_events(
+                events,
+                Arc::clone(&self.ipc_client),
+                Arc::clone(&self.config_manager),
+                self.shutdown_tx.subscribe(),
+            ));
+        }
+
+        // Keep visible notecards in sync with edits made in the config UI,
+        // reconnecting whenever core starts or restarts
+        tokio::spawn(supervise_core_connection(
+            Arc::clone(&self.config_manager),
+            Arc::clone(&self.platform),
+            Arc::clone(&self.hotkey_manager),
+            Arc::clone(&self.ipc_client),
+            self.shutdown_tx.subscribe(),
+        ));
+
+        // Stop the hotkeys and save what's unsaved on quit
+        tokio::spawn(shut_down_on_quit(
+            Arc::clone(&self.hotkey_manager),
+            Arc::clone(&self.config_manager),
+            self.shutdown_tx.subscribe(),
+        ));
+
+        // Flip the pause state whenever the menu item is picked
+        if let Some(mut pause_requests) = self.pause_requests.take() {
+            let config_manager = Arc::clone(&self.config_manager);
+            let hotkey_manager = Arc::clone(&self.hotkey_manager);
+            let ipc_client = Arc::clone(&self.ipc_client);
+            tokio::spawn(async move {
+                while pause_requests.recv().await.is_some() {
+                    if let Err(e) = toggle_hotkeys_enabled(&config_manager, &hotkey_manager, &ipc_client).await {
+                        tracing::error!("Failed to pause or resume hotkeys: {}", e);
+                    }
                 }
-                Err(e) => {
-                    tracing::error!("Failed to start hotkey monitoring: {}", e);
-                    // Don't return error - app can still run without hotkeys
+            });
+        }
+
+        // Save what's typed into the fallback editor
+        if let Some(mut notecard_edits) = self.notecard_edits.take() {
+            let config_manager = Arc::clone(&self.config_manager);
+            let hotkey_manager = Arc::clone(&self.hotkey_manager);
+            let ipc_client = Arc::clone(&self.ipc_client);
+            tokio::spawn(async move {
+                while let Some(edits) = notecard_edits.recv().await {
+                    save_notecard_edits(edits, &config_manager, &hotkey_manager, &ipc_client).await;
                 }
-            }
+            });
         }
 
         // Spawn a task to handle hotkey events
         let config_manager = Arc::clone(&self.config_manager);
-        let window_manager = Arc::clone(&self.window_manager);
+        let platform = Arc::clone(&self.platform);
 
         tokio::spawn(async move {
-            while let Some(notecard_id) = rx.recv().await {
-                if let Err(e) = show_notecard(notecard_id, config_manager.clone(), window_manager.clone()).await {
-                    tracing::error!("Failed to show notecard: {}", e);
+            let mut debouncer = HotkeyDebouncer::default();
+
+            while let Some((action, pressed_at)) = rx.recv().await {
+                tracing::info!("Hotkey pressed: {:?}", action);
+                if !debouncer.accept(action, pressed_at) {
+                    tracing::debug!("Ignoring repeated hotkey press {:?}", action);
+                    continue;
+                }
+
+                match action {
+                    HotkeyAction::ToggleNotecard(notecard_id) => {
+                        if let Err(e) = toggle_notecard(notecard_id, config_manager.clone(), platform.clone()).await {
+                            tracing::error!("Failed to toggle notecard: {}", e);
+                        }
+                    }
+                    HotkeyAction::HideAll => {
+                        if let Err(e) = platform.lock().await.hide_all_notecards().await {
+                            tracing::error!("Failed to hide notecards: {}", e);
+                        }
+                    }
                 }
             }
         });
@@ -314,94 +425,520 @@ impl App {
         // Set launch on startup
         if config.launch_on_startup {
             let mut platform = self.platform.lock().await;
-            platform.set_launch_on_startup(true)?;
+            // Not being a login item is no reason to stop the app starting now
+            if let Err(e) = platform.set_launch_on_startup(true).await {
+                tracing::warn!("Could not launch on startup: {}", e);
+            }
         }
 
         Ok(())
     }
 
     async fn setup_hotkeys(&self) -> Result<()> {
-        let manager = self.config_manager.lock().await;
-        let config = manager.config();
+        let (bindings, strict) = {
+            let manager = self.config_manager.lock().await;
+            (manager.config().hotkey_bindings(), manager.config().strict_modifier_match)
+        };
 
-        // Register hotkeys for all notecards
         let mut hotkey_manager = self.hotkey_manager.lock().await;
-        let modifiers = &config.hotkey_modifiers;
+        hotkey_manager.set_strict_modifiers(strict);
+        hotkey_manager.sync_bindings(&bindings);
+        Ok(())
+    }
+}
+
+/// Keeps a connection to core for as long as the app runs
+///
+/// While connected, configuration pushed by core is applied as it arrives
+/// and requests go through the shared client. When core isn't running or goes
+/// away, the app runs standalone and tries again with a growing delay. Returns,
+/// closing both connections, once `shutdown` is set.
+async fn supervise_core_connection(
+    config_manager: Arc<Mutex<ConfigManager>>,
+    platform: Arc<Mutex<MacOSPlatform>>,
+    hotkey_manager: Arc<Mutex<HotkeyManager>>,
+    ipc_client: Arc<Mutex<IpcClient>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut delay = CORE_RETRY_MIN_DELAY;
+
+    loop {
+        let connected = tokio::select! {
+            connected = follow_core(&config_manager, &platform, &hotkey_manager, &ipc_client) => connected,
+            _ = shutdown.changed() => break,
+        };
 
-        for i in 1..=9 {
-            let notecard_id = NotecardId::new(i)?;
-            if let Some(notecard) = manager.get_notecard(notecard_id) {
-                if !notecard.content.is_empty() {
-                    hotkey_manager.register_hotkey(notecard_id, modifiers)?;
+        // Saves fall back to the local file until core is back
+        ipc_client.lock().await.disconnect().await;
+        show_core_connection(false);
+
+        // Core was up, so the next attempt starts from the shortest delay
+        if connected {
+            delay = CORE_RETRY_MIN_DELAY;
+        }
+        tracing::debug!("Trying to reach core again in {:?}", delay);
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = shutdown.changed() => break,
+        }
+        if !connected {
+            delay = (delay * 2).min(CORE_RETRY_MAX_DELAY);
+        }
+    }
+
+    ipc_client.lock().await.disconnect().await;
+    tracing::info!("Closed the connection to core");
+}
+
+/// Connects to core and applies the configuration it pushes until the
+/// connection fails; returns whether it got connected at all
+async fn follow_core(
+    config_manager: &Arc<Mutex<ConfigManager>>,
+    platform: &Arc<Mutex<MacOSPlatform>>,
+    hotkey_manager: &Arc<Mutex<HotkeyManager>>,
+    ipc_client: &Arc<Mutex<IpcClient>>,
+) -> bool {
+    // Subscribing turns the connection into a push stream, so it gets its own client
+    let mut subscription = IpcClient::new();
+    let subscribed = async {
+        subscription.connect().await?;
+        subscription.subscribe().await
+    };
+    if let Err(e) = subscribed.await {
+        tracing::debug!("Core service isn't reachable: {}", e);
+        return false;
+    }
+
+    // Whatever changed while disconnected is caught up on first
+    let config = {
+        let mut client = ipc_client.lock().await;
+        let fetched = async {
+            if !client.is_connected().await {
+                client.connect().await?;
+            }
+            client.get_configuration().await
+        };
+        match fetched.await {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!("Could not get the configuration from core: {}", e);
+                return false;
+            }
+        }
+    };
+    if let Err(e) = apply_config_change(config, config_manager, platform, hotkey_manager).await {
+        tracing::warn!("Could not apply the configuration from core: {}", e);
+    }
+
+    tracing::info!("Following configuration changes from core");
+    show_core_connection(true);
+
+    loop {
+        let config = match subscription.next_config_change().await {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!("Lost the connection to core, running standalone: {}", e);
+                return true;
+            }
+        };
+
+        if let Err(e) = apply_config_change(config, config_manager, platform, hotkey_manager).await {
+            tracing::warn!("Could not apply the configuration from core: {}", e);
+        }
+    }
+}
+
+/// Applies configuration from core: refreshes notecards that are on screen
+/// and re-registers hotkeys whose notecard or modifiers changed
+async fn apply_config_change(
+    config: Config,
+    config_manager: &Arc<Mutex<ConfigManager>>,
+    platform: &Arc<Mutex<MacOSPlatform>>,
+    hotkey_manager: &Arc<Mutex<HotkeyManager>>,
+) -> Result<()> {
+    {
+        let mut platform = platform.lock().await;
+
+        for id in platform.visible_notecards().await? {
+            match config.notecards.get(&id).filter(|notecard| !notecard.is_empty()) {
+                Some(notecard) => {
+                    let properties = config.display_properties_for(id);
+                    platform.update_notecard(id, &notecard.content, properties).await?
                 }
+                None => platform.hide_notecard(id).await?,
             }
         }
+    }
 
-        Ok(())
+    let supported = platform.lock().await.capabilities().screen_capture_exclusion;
+    show_capture_exclusion(config.default_display_properties.exclude_from_capture, supported);
+
+    // Visible notecards stay up when only their hotkey changes
+    let bindings = config.hotkey_bindings();
+    {
+        let mut hotkey_manager = hotkey_manager.lock().await;
+        hotkey_manager.set_strict_modifiers(config.strict_modifier_match);
+        hotkey_manager.sync_bindings(&bindings);
+    }
+
+    // The config UI can pause and resume hotkeys too
+    let paused = hotkey_manager.lock().await.is_paused();
+    if config.hotkeys_enabled == paused {
+        apply_hotkeys_enabled(config.hotkeys_enabled, hotkey_manager).await?;
     }
+
+    let notecards = {
+        let mut manager = config_manager.lock().await;
+        *manager.config_mut() = config;
+        manager.notecard_summaries()
+    };
+    show_notecards_in_menu(notecards, bindings);
+
+    Ok(())
+}
+
+/// Flips `hotkeys_enabled`, saving it through core when connected and locally otherwise
+async fn toggle_hotkeys_enabled(
+    config_manager: &Arc<Mutex<ConfigManager>>,
+    hotkey_manager: &Arc<Mutex<HotkeyManager>>,
+    ipc_client: &Arc<Mutex<IpcClient>>,
+) -> Result<()> {
+    let enabled = {
+        let mut manager = config_manager.lock().await;
+        let config = manager.config_mut();
+        config.hotkeys_enabled = !config.hotkeys_enabled;
+        config.hotkeys_enabled
+    };
+
+    apply_hotkeys_enabled(enabled, hotkey_manager).await?;
+
+    let mut client = ipc_client.lock().await;
+    if client.is_connected().await {
+        client.set_hotkeys_enabled(enabled).await?;
+    } else {
+        config_manager.lock().await.save()?;
+    }
+
+    Ok(())
+}
+
+/// Pauses or resumes the registered hotkeys and shows the state in the menu bar
+async fn apply_hotkeys_enabled(enabled: bool, hotkey_manager: &Arc<Mutex<HotkeyManager>>) -> Result<()> {
+    hotkey_manager.lock().await.set_paused(!enabled)?;
+    tracing::info!("Hotkeys {}", if enabled { "resumed" } else { "paused" });
+    show_hotkeys_paused(!enabled);
+    Ok(())
+}
+
+/// Updates the menu line saying whether notecards show up in screen sharing
+fn show_capture_exclusion(excluded: bool, supported: bool) {
+    app_delegate::on_main(move |delegate| {
+        delegate.update_menu(|state| {
+            state.capture_excluded = excluded;
+            state.capture_supported = supported;
+        });
+    });
+}
+
+/// Shows in the menu whether requests and config pushes go through core
+fn show_core_connection(connected: bool) {
+    app_delegate::on_main(move |delegate| delegate.update_menu(|state| state.core_connected = connected));
 }
 
-async fn show_notecard(
+/// Lists the notecards in the menu's submenus, with their hotkeys
+fn show_notecards_in_menu(notecards: Vec<NotecardSummary>, hotkeys: HashMap<HotkeyAction, HotkeyBinding>) {
+    app_delegate::on_main(move |delegate| delegate.set_notecards(notecards, hotkeys));
+}
+
+/// Checks the Pause Hotkeys item and dims the menu bar icon while hotkeys are paused
+fn show_hotkeys_paused(paused: bool) {
+    app_delegate::on_main(move |delegate| delegate.update_menu(|state| state.hotkeys_paused = paused));
+}
+
+async fn toggle_notecard(
     notecard_id: NotecardId,
     config_manager: Arc<Mutex<ConfigManager>>,
-    window_manager: Arc<Mutex<NotecardWindowManager>>,  // No underscore!
+    platform: Arc<Mutex<MacOSPlatform>>,
 ) -> Result<()> {
-    let manager = config_manager.lock().await;
+    let (content, properties, blocked_applications) = {
+        let manager = config_manager.lock().await;
+        let config = manager.config();
+        let content = manager
+            .get_notecard(notecard_id)
+            .map(|notecard| notecard.content.clone())
+            .unwrap_or_default();
+        (
+            content,
+            config.display_properties_for(notecard_id).clone(),
+            config.blocked_applications.clone(),
+        )
+    };
+
+    let mut platform = platform.lock().await;
+
+    if !platform.is_notecard_visible(notecard_id).await? {
+        // A card cleared while on screen can still be hidden, but never shown
+        if content.is_empty() {
+            return Ok(());
+        }
 
-    if let Some(notecard) = manager.get_notecard(notecard_id) {
-        if !notecard.content.is_empty() {
-            let properties = &manager.config().default_display_properties;
+        if !blocked_applications.is_empty() {
+            // Not knowing what's in front never keeps a notecard from showing
+            let app = platform.frontmost_application().await.unwrap_or_else(|e| {
+                tracing::debug!("Could not get the frontmost application: {}", e);
+                AppIdentity::default()
+            });
 
-            // Actually show the notecard window
-            let mut window_manager = window_manager.lock().await;
-            window_manager.show_notecard(notecard_id, &notecard.content, properties).await?;
+            if app.matches_any(&blocked_applications) {
+                tracing::debug!("Not showing notecard {} in front of {}", notecard_id.value(), app.name);
+                return Ok(());
+            }
         }
     }
 
+    let outcome = platform.toggle_notecard(notecard_id, &content, &properties).await?;
+    tracing::debug!("Notecard {} toggled: {:?}", notecard_id.value(), outcome);
+
     Ok(())
 }
 
-pub fn launch_config_ui() {
-    tracing::info!("Launching configuration UI...");
+/// Stops hotkey monitoring, unregisters the hotkeys and saves configuration
+/// changes that haven't been saved, once `shutdown` is set on quit
+async fn shut_down_on_quit(
+    hotkey_manager: Arc<Mutex<HotkeyManager>>,
+    config_manager: Arc<Mutex<ConfigManager>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    // Fails only if the delegate is gone, which means the app is too
+    if shutdown.wait_for(|quit| *quit).await.is_err() {
+        return;
+    }
+    tracing::info!("Shutting down");
 
-    // Try multiple approaches to launch the config UI
+    {
+        let mut hotkey_manager = hotkey_manager.lock().await;
+        hotkey_manager.stop_monitoring();
+        if let Err(e) = hotkey_manager.unregister_all() {
+            tracing::warn!("Could not unregister the hotkeys: {}", e);
+        }
+    }
+    tracing::info!("Hotkeys stopped");
 
-    // 1. First try using 'open' command with app name
-    let result = std::process::Command::new("open")
-        .args(&["-a", "Notecognito Config"])
-        .spawn();
+    let manager = config_manager.lock().await;
+    if manager.is_dirty() {
+        match manager.save() {
+            Ok(()) => tracing::info!("Saved unsaved configuration changes"),
+            Err(e) => tracing::warn!("Could not save the configuration: {}", e),
+        }
+    }
+}
 
-    if result.is_ok() {
-        return;
+/// Reports platform events to core, which pushes them to its subscribers
+///
+/// Drags report a stream of moves, so a moved notecard's place is saved once
+/// it has stayed put for `MOVE_SAVE_DELAY`, or right away on quit.
+async fn forward_platform_events(
+    mut events: mpsc::Receiver<PlatformEvent>,
+    ipc_client: Arc<Mutex<IpcClient>>,
+    config_manager: Arc<Mutex<ConfigManager>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    // Latest screen and position of each dragged notecard not saved yet
+    let mut moved = HashMap::new();
+
+    loop {
+        let waiting_to_save = !moved.is_empty();
+        let events = &mut events;
+        let next = async move {
+            if waiting_to_save {
+                tokio::time::timeout(MOVE_SAVE_DELAY, events.recv()).await
+            } else {
+                Ok(events.recv().await)
+            }
+        };
+
+        let event = tokio::select! {
+            next = next => match next {
+                Ok(event) => event,
+                Err(_) => {
+                    save_moved_notecards(moved.drain().collect(), &config_manager, &ipc_client).await;
+                    continue;
+                }
+            },
+            _ = shutdown.wait_for(|quit| *quit) => None,
+        };
+        let Some(event) = event else {
+            break;
+        };
+
+        tracing::debug!("Platform event: {:?}", event);
+
+        if let PlatformEvent::NotecardMoved { id, monitor, position } = event {
+            moved.insert(id, (monitor, position));
+        }
+
+        let mut client = ipc_client.lock().await;
+        if !client.is_connected().await {
+            continue;
+        }
+        if let Err(e) = client.report_platform_event(event).await {
+            tracing::debug!("Could not report platform event: {}", e);
+        }
     }
 
-    // 2. Try looking in the same directory as our executable
-    if let Ok(exe_path) = std::env::current_exe() {
-        if let Some(parent) = exe_path.parent() {
-            let config_app = parent.join("Notecognito Config.app");
-            if config_app.exists() {
-                if let Ok(_) = std::process::Command::new("open")
-                    .arg(config_app)
-                    .spawn()
-                {
-                    return;
+    if !moved.is_empty() {
+        save_moved_notecards(moved.drain().collect(), &config_manager, &ipc_client).await;
+    }
+}
+
+/// Stores where notecards were dragged as their display property overrides
+///
+/// Goes through core when connected, which saves and pushes the change, so
+/// its copy of the configuration doesn't overwrite the new places later.
+async fn save_moved_notecards(
+    moved: Vec<(NotecardId, (usize, (i32, i32)))>,
+    config_manager: &Arc<Mutex<ConfigManager>>,
+    ipc_client: &Arc<Mutex<IpcClient>>,
+) {
+    let notecards: Vec<_> = {
+        let mut manager = config_manager.lock().await;
+        moved
+            .into_iter()
+            .filter_map(|(id, (monitor, position))| {
+                // Pinned to the point it was dropped on, whatever placed it before
+                let mut properties = manager.config().display_properties_for(id).clone();
+                properties.monitor = MonitorSelector::Index(monitor);
+                properties.anchor = None;
+                properties.position = position;
+
+                if let Err(e) = manager.set_notecard_display_properties(id, properties) {
+                    tracing::warn!("Could not keep where notecard {} was moved: {}", id.value(), e);
+                    return None;
                 }
+                tracing::info!("Notecard {} moved to {:?} on screen {}", id.value(), position, monitor);
+                manager.get_notecard(id).cloned()
+            })
+            .collect()
+    };
+
+    let mut client = ipc_client.lock().await;
+    if client.is_connected().await {
+        for notecard in notecards {
+            if let Err(e) = client.update_notecard(notecard).await {
+                tracing::warn!("Could not save where a notecard was moved: {}", e);
             }
         }
+    } else if let Err(e) = config_manager.lock().await.save() {
+        tracing::warn!("Could not save where notecards were moved: {}", e);
     }
+}
 
-    // 3. Try Applications folder
-    let apps_path = "/Applications/Notecognito Config.app";
-    if std::path::Path::new(apps_path).exists() {
-        if let Ok(_) = std::process::Command::new("open")
-            .arg(apps_path)
-            .spawn()
-        {
-            return;
+/// Saves notecard content from the fallback editor, through core when connected
+/// and locally otherwise
+async fn save_notecard_edits(
+    edits: NotecardEdits,
+    config_manager: &Arc<Mutex<ConfigManager>>,
+    hotkey_manager: &Arc<Mutex<HotkeyManager>>,
+    ipc_client: &Arc<Mutex<IpcClient>>,
+) {
+    let (notecards, rejected, bindings, summaries) = {
+        let mut manager = config_manager.lock().await;
+        let mut notecards = Vec::new();
+        let mut rejected = Vec::new();
+
+        for (id, content) in edits {
+            let mut notecard = manager.get_notecard(id).cloned().unwrap_or_else(|| Notecard::empty(id));
+            if notecard.content == content {
+                continue;
+            }
+            notecard.content = content;
+
+            match manager.update_notecard(notecard.clone()) {
+                Ok(()) => notecards.push(notecard),
+                Err(e) => {
+                    tracing::warn!("Could not save notecard {}: {}", id.value(), e);
+                    rejected.push(format!("{}: {}", id.value(), e));
+                }
+            }
+        }
+
+        (notecards, rejected, manager.config().hotkey_bindings(), manager.notecard_summaries())
+    };
+
+    if !rejected.is_empty() {
+        notification::show(
+            "Some Notecards Weren't Saved",
+            &rejected.join("\n"),
+            NotificationKind::Warning,
+        );
+    }
+    if notecards.is_empty() {
+        return;
+    }
+    tracing::info!("Saving {} notecard(s) from the fallback editor", notecards.len());
+    show_notecards_in_menu(summaries, bindings.clone());
+
+    let mut client = ipc_client.lock().await;
+    if client.is_connected().await {
+        for notecard in notecards {
+            if let Err(e) = client.update_notecard(notecard).await {
+                tracing::warn!("Could not save notecard: {}", e);
+            }
+        }
+    } else {
+        if let Err(e) = config_manager.lock().await.save() {
+            tracing::warn!("Could not save notecards: {}", e);
+        }
+        // Core pushes changes it saves; without it the hotkeys are updated here
+        hotkey_manager.lock().await.sync_bindings(&bindings);
+    }
+}
+
+/// Shows or clears the "!" on the menu bar item that marks missing accessibility access
+fn set_accessibility_badge(missing: bool) {
+    app_delegate::on_main(move |delegate| delegate.set_accessibility_badge(missing));
+}
+
+/// Opens the configuration app, blocking while `open` runs
+///
+/// Fails with the places that were searched, for telling the user.
+pub fn launch_config_ui() -> std::result::Result<(), Vec<String>> {
+    tracing::info!("Launching configuration UI...");
+
+    let mut searched = vec!["Launch Services (\"Notecognito Config\")".to_string()];
+    let mut locations = Vec::new();
+    // Bundled next to our executable
+    if let Some(parent) = std::env::current_exe().ok().as_deref().and_then(std::path::Path::parent) {
+        locations.push(parent.join("Notecognito Config.app"));
+    }
+    locations.push(std::path::PathBuf::from("/Applications/Notecognito Config.app"));
+    if let Some(home) = dirs::home_dir() {
+        locations.push(home.join("Applications/Notecognito Config.app"));
+    }
+    searched.extend(locations.iter().map(|path| path.display().to_string()));
+    tracing::info!("Looking for the configuration app in: {}", searched.join(", "));
+
+    // `open` exits nonzero when Launch Services can't find the app
+    let opened = |args: &[&std::ffi::OsStr]| {
+        std::process::Command::new("open")
+            .args(args)
+            .status()
+            .is_ok_and(|status| status.success())
+    };
+
+    if opened(&["-a".as_ref(), "Notecognito Config".as_ref()]) {
+        return Ok(());
+    }
+
+    for location in locations.iter().filter(|path| path.exists()) {
+        if opened(&[location.as_os_str()]) {
+            tracing::info!("Opened the configuration app at {}", location.display());
+            return Ok(());
         }
     }
 
     tracing::error!("Failed to launch configuration UI - app not found");
+    Err(searched)
 }
 
 #[tokio::main]
@@ -410,6 +947,12 @@ async fn main() -> Result<()> {
     let mtm = MainThreadMarker::new()
         .ok_or_else(|| anyhow::anyhow!("Must be run on main thread"))?;
 
+    // Before anything else, so a second copy never installs an event tap
+    if !single_instance::claim() {
+        eprintln!("Notecognito is already running; showing its menu instead");
+        return Ok(());
+    }
+
     // Create app instance
     let mut app = App::new().await?;
 
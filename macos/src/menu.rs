@@ -0,0 +1,192 @@
+use notecognito_core::{HotkeyAction, HotkeyBinding, NotecardId, NotecardSummary};
+use std::collections::HashMap;
+
+/// What picking a status menu item does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    Configure,
+    ShowAll,
+    HideAll,
+    PauseHotkeys,
+    ToggleNotecard(NotecardId),
+    CopyNotecard(NotecardId),
+    About,
+    Quit,
+}
+
+/// App state the status menu and menu bar icon reflect
+///
+/// The delegate holds the current one; the pause toggle and the permission
+/// supervisor change it through `app_delegate::on_main`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MenuState {
+    pub hotkeys_paused: bool,
+    /// `exclude_from_capture` is set
+    pub capture_excluded: bool,
+    /// This macOS release honors capture exclusion
+    pub capture_supported: bool,
+    /// Hotkeys need accessibility access that hasn't been granted
+    pub accessibility_missing: bool,
+    /// Connected to the core service, rather than running standalone
+    pub core_connected: bool,
+}
+
+impl MenuState {
+    /// Whether notecards are actually kept out of screen sharing
+    pub fn capture_protected(&self) -> bool {
+        self.capture_excluded && self.capture_supported
+    }
+
+    /// Which menu bar icon shows; missing permission outranks pausing, since
+    /// resuming wouldn't bring the hotkeys back
+    pub fn icon(&self) -> IconState {
+        if self.accessibility_missing {
+            IconState::Attention
+        } else if self.hotkeys_paused {
+            IconState::Paused
+        } else {
+            IconState::Normal
+        }
+    }
+}
+
+/// Variant of the menu bar icon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconState {
+    /// Hotkeys are live
+    Normal,
+    /// Faded while hotkeys are paused
+    Paused,
+    /// Badged while hotkeys wait for accessibility access
+    Attention,
+}
+
+/// Tooltip for the menu bar icon
+pub fn tooltip(state: &MenuState) -> &'static str {
+    match state.icon() {
+        IconState::Normal => "Notecognito",
+        IconState::Paused => "Notecognito: Hotkeys paused",
+        IconState::Attention => "Notecognito: Accessibility permission required",
+    }
+}
+
+/// One line of the status menu
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MenuEntry {
+    /// An item without an action is a greyed-out status line
+    Item {
+        title: String,
+        action: Option<MenuAction>,
+        checked: bool,
+        /// Shown right-aligned, and pressing it picks the item while the menu is open
+        key: Option<HotkeyBinding>,
+    },
+    Submenu {
+        title: String,
+        entries: Vec<MenuEntry>,
+    },
+    Separator,
+}
+
+impl MenuEntry {
+    fn item(title: impl Into<String>, action: MenuAction) -> Self {
+        MenuEntry::Item { title: title.into(), action: Some(action), checked: false, key: None }
+    }
+
+    fn status(title: impl Into<String>, checked: bool) -> Self {
+        MenuEntry::Item { title: title.into(), action: None, checked, key: None }
+    }
+}
+
+/// Lays out the status menu for `state`, the configured notecards and the
+/// hotkeys they're bound to, top to bottom
+///
+/// Items for hotkey actions carry the hotkey as their key equivalent, which
+/// works while the menu is open even without accessibility access. While
+/// hotkeys are paused the notecard items are greyed out with them, and Hide
+/// All keeps working as a plain item.
+pub fn build(
+    state: &MenuState,
+    notecards: &[NotecardSummary],
+    hotkeys: &HashMap<HotkeyAction, HotkeyBinding>,
+) -> Vec<MenuEntry> {
+    let capture_title = match (state.capture_excluded, state.capture_supported) {
+        (true, true) => "Hidden from Screen Sharing",
+        (true, false) => "Visible in Screen Sharing (not supported on this macOS)",
+        (false, _) => "Visible in Screen Sharing",
+    };
+
+    vec![
+        MenuEntry::item("Configure...", MenuAction::Configure),
+        MenuEntry::item("Show All Notecards", MenuAction::ShowAll),
+        MenuEntry::Item {
+            title: "Hide All Notecards".to_string(),
+            action: Some(MenuAction::HideAll),
+            checked: false,
+            key: hotkeys.get(&HotkeyAction::HideAll).filter(|_| !state.hotkeys_paused).cloned(),
+        },
+        MenuEntry::Submenu {
+            title: "Notecards".to_string(),
+            entries: toggle_entries(state, notecards, hotkeys),
+        },
+        MenuEntry::Submenu {
+            title: "Copy Notecard".to_string(),
+            entries: copy_entries(notecards),
+        },
+        MenuEntry::Item {
+            title: "Pause Hotkeys".to_string(),
+            action: Some(MenuAction::PauseHotkeys),
+            checked: state.hotkeys_paused,
+            key: None,
+        },
+        MenuEntry::status(capture_title, state.capture_protected()),
+        MenuEntry::status(if state.core_connected { "Core: Connected" } else { "Core: Standalone" }, false),
+        MenuEntry::Separator,
+        MenuEntry::item("About Notecognito", MenuAction::About),
+        MenuEntry::Separator,
+        MenuEntry::item("Quit Notecognito", MenuAction::Quit),
+    ]
+}
+
+/// One item per notecard with content, in id order, showing or hiding it as
+/// its hotkey does
+fn toggle_entries(
+    state: &MenuState,
+    notecards: &[NotecardSummary],
+    hotkeys: &HashMap<HotkeyAction, HotkeyBinding>,
+) -> Vec<MenuEntry> {
+    let entries: Vec<MenuEntry> = notecards
+        .iter()
+        .filter(|notecard| !notecard.is_empty)
+        .map(|notecard| MenuEntry::Item {
+            title: format!("{}  {}", notecard.id.value(), notecard.title),
+            action: (!state.hotkeys_paused).then_some(MenuAction::ToggleNotecard(notecard.id)),
+            checked: false,
+            key: hotkeys.get(&HotkeyAction::ToggleNotecard(notecard.id)).cloned(),
+        })
+        .collect();
+
+    if entries.is_empty() {
+        return vec![MenuEntry::status("No Notecards", false)];
+    }
+    entries
+}
+
+/// One Copy item per notecard with content, in id order
+fn copy_entries(notecards: &[NotecardSummary]) -> Vec<MenuEntry> {
+    let entries: Vec<MenuEntry> = notecards
+        .iter()
+        .filter(|notecard| !notecard.is_empty)
+        .map(|notecard| {
+            MenuEntry::item(
+                format!("{}  {}", notecard.id.value(), notecard.title),
+                MenuAction::CopyNotecard(notecard.id),
+            )
+        })
+        .collect();
+
+    if entries.is_empty() {
+        return vec![MenuEntry::status("No Notecards", false)];
+    }
+    entries
+}
@@ -0,0 +1,497 @@
+use notecognito_core::{
+    attribute_runs, heading_scale, parse_markdown, AttributeRun, BackgroundStyle, BlurMaterial, DismissReason,
+    SegmentStyle, SpacedLayout,
+};
+use objc2::rc::Retained;
+use objc2::runtime::Bool;
+use objc2::{declare_class, msg_send, msg_send_id, mutability, ClassType, DeclaredClass};
+use objc2_app_kit::{
+    NSAccessibilityStaticTextRole, NSAppearance, NSAppearanceCustomization, NSAppearanceNameAqua, NSAppearanceNameDarkAqua,
+    NSAutoresizingMaskOptions, NSBackgroundColorAttributeName, NSBezierPath, NSBorderType, NSColor, NSEvent, NSFont,
+    NSFontAttributeName, NSFontManager, NSFontTraitMask, NSFontWeightRegular, NSImage, NSImageResizingMode,
+    NSMutableParagraphStyle, NSParagraphStyle, NSParagraphStyleAttributeName, NSScrollView, NSScroller, NSTextField,
+    NSTextView, NSView, NSVisualEffectBlendingMode, NSVisualEffectMaterial, NSVisualEffectState, NSVisualEffectView,
+};
+use objc2_foundation::{
+    CGFloat, CGPoint, CGRect, CGSize, MainThreadMarker, NSArray, NSEdgeInsets, NSRange, NSString,
+};
+use std::cell::{Cell, RefCell};
+
+const CORNER_RADIUS: f64 = 10.0;
+
+/// How far the mouse must move with the button down before a click becomes a drag
+const DRAG_THRESHOLD: CGFloat = 4.0;
+
+/// Space between the card's edge and its text, on every side
+pub const CONTENT_PADDING: CGFloat = 20.0;
+
+/// Point size of the page indicator
+const PAGE_INDICATOR_SIZE: CGFloat = 11.0;
+
+/// Families tried, in order, when the configured one isn't installed
+const FALLBACK_FONTS: [&str; 2] = ["Helvetica Neue", "Helvetica"];
+
+/// Families tried for "Monospace", ahead of `FALLBACK_FONTS`
+const MONOSPACE_FONTS: [&str; 3] = ["SF Mono", "Menlo", "Monaco"];
+
+pub struct NotecardViewIvars {
+    text_view: Retained<NSTextView>,
+    /// Where the button went down, until the press turns into a drag or ends
+    press_location: Cell<Option<CGPoint>>,
+    /// The text as given to `set_content`, for copying
+    content: RefCell<String>,
+    /// Under the text, and hidden unless the background is blurred
+    effect_view: Retained<NSVisualEffectView>,
+    /// "2/3" in the bottom-right corner of a card split into pages
+    page_label: Retained<NSTextField>,
+}
+
+declare_class!(
+    /// Content view of a notecard window; a click anywhere on it dismisses the
+    /// card, a drag moves it, and it draws the card in colors matching the
+    /// light or dark appearance
+    ///
+    /// The text sits in a scroll view that wraps long lines and shows a
+    /// scroller only when the content is taller than the card.
+    pub struct NotecardView;
+
+    unsafe impl ClassType for NotecardView {
+        type Super = NSView;
+        type Mutability = mutability::MainThreadOnly;
+        const NAME: &'static str = "NotecognitoNotecardView";
+    }
+
+    impl DeclaredClass for NotecardView {
+        type Ivars = NotecardViewIvars;
+    }
+
+    unsafe impl NotecardView {
+        // Not passing mouse events on keeps them from reaching anything else
+        #[method(mouseDown:)]
+        fn mouse_down(&self, event: &NSEvent) {
+            self.ivars().press_location.set(Some(unsafe { event.locationInWindow() }));
+        }
+
+        #[method(mouseDragged:)]
+        fn mouse_dragged(&self, event: &NSEvent) {
+            let Some(start) = self.ivars().press_location.get() else {
+                return;
+            };
+
+            let location = unsafe { event.locationInWindow() };
+            if (location.x - start.x).hypot(location.y - start.y) < DRAG_THRESHOLD {
+                return;
+            }
+
+            // A drag never dismisses; the window server moves the window from here
+            self.ivars().press_location.set(None);
+            if let Some(window) = self.window() {
+                unsafe { window.performWindowDragWithEvent(event) };
+            }
+        }
+
+        // Dismissing on release rather than press leaves room for a drag
+        #[method(mouseUp:)]
+        fn mouse_up(&self, event: &NSEvent) {
+            if self.ivars().press_location.take().is_some() {
+                unsafe { crate::notecard_window::close_event_window(event, DismissReason::Click) };
+            }
+        }
+
+        // Clicks on the text land here too, so they dismiss the card; only the
+        // scroller keeps its own
+        #[method(hitTest:)]
+        fn hit_test(&self, point: CGPoint) -> *mut NSView {
+            let hit: *mut NSView = unsafe { msg_send![super(self), hitTest: point] };
+            if hit.is_null() {
+                return hit;
+            }
+
+            let is_scroller: bool = unsafe { msg_send![hit, isKindOfClass: NSScroller::class()] };
+            if is_scroller {
+                hit
+            } else {
+                self as *const Self as *mut NSView
+            }
+        }
+
+        // Hit testing sends the wheel here rather than to the scroll view
+        #[method(scrollWheel:)]
+        fn scroll_wheel(&self, event: &NSEvent) {
+            if let Some(scroll_view) = unsafe { self.ivars().text_view.enclosingScrollView() } {
+                unsafe { scroll_view.scrollWheel(event) };
+            }
+        }
+
+        #[method(drawRect:)]
+        fn draw_rect(&self, _dirty_rect: CGRect) {
+            // The effect view draws a blurred background
+            if !self.ivars().effect_view.isHidden() {
+                return;
+            }
+
+            unsafe {
+                let (background, _) = colors_for(&self.effectiveAppearance());
+                background.setFill();
+                NSBezierPath::bezierPathWithRoundedRect_xRadius_yRadius(self.bounds(), CORNER_RADIUS, CORNER_RADIUS)
+                    .fill();
+            }
+        }
+
+        // Called when the system appearance changes, so visible cards restyle live
+        #[method(viewDidChangeEffectiveAppearance)]
+        fn view_did_change_effective_appearance(&self) {
+            self.apply_appearance();
+        }
+
+        // The app is never active, so without this the first click only activates it
+        #[method(acceptsFirstMouse:)]
+        fn accepts_first_mouse(&self, _event: Option<&NSEvent>) -> bool {
+            true
+        }
+    }
+);
+
+impl NotecardView {
+    pub fn new(mtm: MainThreadMarker, frame: CGRect) -> Retained<Self> {
+        unsafe {
+            let text_frame = text_frame(frame.size);
+            let scroll_view = NSScrollView::initWithFrame(mtm.alloc(), text_frame);
+            scroll_view.setHasVerticalScroller(true);
+            scroll_view.setHasHorizontalScroller(false);
+            scroll_view.setAutohidesScrollers(true);
+            scroll_view.setBorderType(NSBorderType::NSNoBorder);
+            scroll_view.setDrawsBackground(false);
+            // Follows the card when it's resized, keeping the padding
+            scroll_view.setAutoresizingMask(
+                NSAutoresizingMaskOptions::NSViewWidthSizable | NSAutoresizingMaskOptions::NSViewHeightSizable,
+            );
+
+            // Grows downwards with its text and wraps at the scroll view's width
+            let content_size = scroll_view.contentSize();
+            let text_view = NSTextView::initWithFrame(
+                mtm.alloc(),
+                CGRect::new(CGPoint::new(0.0, 0.0), content_size),
+            );
+            text_view.setEditable(false);
+            text_view.setSelectable(false);
+            text_view.setDrawsBackground(false);
+            text_view.setRichText(false);
+            text_view.setTextContainerInset(CGSize::new(0.0, 0.0));
+            text_view.setMinSize(CGSize::new(0.0, content_size.height));
+            text_view.setMaxSize(CGSize::new(CGFloat::MAX, CGFloat::MAX));
+            text_view.setVerticallyResizable(true);
+            text_view.setHorizontallyResizable(false);
+            text_view.setAutoresizingMask(NSAutoresizingMaskOptions::NSViewWidthSizable);
+            if let Some(container) = text_view.textContainer() {
+                container.setWidthTracksTextView(true);
+                container.setLineFragmentPadding(0.0);
+                container.setContainerSize(CGSize::new(content_size.width, CGFloat::MAX));
+            }
+            scroll_view.setDocumentView(Some(&text_view));
+
+            // Blurs what's behind the window, not the card's own views, and
+            // stays active though the app never is
+            let effect_view = NSVisualEffectView::initWithFrame(
+                mtm.alloc(),
+                CGRect::new(CGPoint::new(0.0, 0.0), frame.size),
+            );
+            effect_view.setBlendingMode(NSVisualEffectBlendingMode::NSVisualEffectBlendingModeBehindWindow);
+            effect_view.setState(NSVisualEffectState::NSVisualEffectStateActive);
+            effect_view.setMaskImage(Some(&rounded_mask(CORNER_RADIUS)));
+            effect_view.setAutoresizingMask(
+                NSAutoresizingMaskOptions::NSViewWidthSizable | NSAutoresizingMaskOptions::NSViewHeightSizable,
+            );
+            effect_view.setHidden(true);
+
+            // In the bottom padding, lined up with the text's right edge and
+            // following it when the card is resized
+            let page_label = NSTextField::labelWithString(&NSString::from_str(""), mtm);
+            page_label.setFont(Some(&NSFont::monospacedDigitSystemFontOfSize_weight(
+                PAGE_INDICATOR_SIZE,
+                NSFontWeightRegular,
+            )));
+            page_label.setAutoresizingMask(
+                NSAutoresizingMaskOptions::NSViewMinXMargin | NSAutoresizingMaskOptions::NSViewMaxYMargin,
+            );
+            page_label.setHidden(true);
+
+            let this = mtm.alloc::<Self>().set_ivars(NotecardViewIvars {
+                text_view,
+                press_location: Cell::new(None),
+                content: RefCell::new(String::new()),
+                effect_view: effect_view.clone(),
+                page_label: page_label.clone(),
+            });
+            let this: Retained<Self> = msg_send_id![super(this), initWithFrame: frame];
+            this.addSubview(&effect_view);
+            this.addSubview(&scroll_view);
+            this.addSubview(&page_label);
+
+            // VoiceOver reads the whole card as one piece of static text, with
+            // the shown text as its value; the text view would read it again
+            let _: () = msg_send![&this, setAccessibilityElement: true];
+            let _: () = msg_send![&this, setAccessibilityRole: NSAccessibilityStaticTextRole];
+            let _: () = msg_send![&this.ivars().text_view, setAccessibilityElement: false];
+            let _: () = msg_send![&scroll_view, setAccessibilityElement: false];
+            let _: () = msg_send![&page_label, setAccessibilityElement: false];
+            this
+        }
+    }
+
+    /// Replaces the text, keeping whitespace and tabs as they are, and scrolls
+    /// back to the top
+    ///
+    /// `spacing`, from `apply_spacing` on the same content, spaces its lines
+    /// out; without it every line gets the default paragraph style. With
+    /// `markdown` the markers are taken out and their styles drawn instead;
+    /// the content kept for copying is the original either way.
+    pub fn set_content(&self, content: &str, font: &NSFont, spacing: Option<&SpacedLayout>, markdown: bool) {
+        let text_view = &self.ivars().text_view;
+        content.clone_into(&mut self.ivars().content.borrow_mut());
+
+        // Rendering keeps every line, so spacing worked out on the content still fits
+        let (shown, runs) = if markdown {
+            attribute_runs(&parse_markdown(content))
+        } else {
+            (content.to_string(), Vec::new())
+        };
+
+        unsafe {
+            let shown_string = NSString::from_str(&shown);
+            text_view.setString(&shown_string);
+            let _: () = msg_send![self, setAccessibilityValue: &*shown_string];
+            text_view.setFont(Some(font));
+            self.apply_spacing(&shown, spacing);
+            self.apply_markdown(&runs, font);
+            text_view.scrollRangeToVisible(NSRange::new(0, 0));
+        }
+
+        self.apply_appearance();
+    }
+
+    /// Gives each line of the text its paragraph style, since a style set on
+    /// the last text would otherwise stick to the new one
+    unsafe fn apply_spacing(&self, content: &str, spacing: Option<&SpacedLayout>) {
+        let Some(storage) = self.ivars().text_view.textStorage() else {
+            return;
+        };
+
+        let Some(spacing) = spacing else {
+            let length = content.encode_utf16().count();
+            storage.addAttribute_value_range(
+                NSParagraphStyleAttributeName,
+                &NSParagraphStyle::defaultParagraphStyle(),
+                NSRange::new(0, length),
+            );
+            return;
+        };
+
+        // Ranges are in UTF-16 units, and each line's includes its newline
+        let mut location = 0;
+        for (line, line_spacing) in content.split('\n').zip(&spacing.lines) {
+            let length = line.encode_utf16().count() + 1;
+            let range = NSRange::new(location, length.min(storage.length() - location));
+            location += length;
+
+            let style = NSMutableParagraphStyle::new();
+            style.setLineHeightMultiple(spacing.line_height_multiple);
+            style.setParagraphSpacingBefore(line_spacing.space_before);
+            style.setParagraphSpacing(line_spacing.space_after);
+            storage.addAttribute_value_range(NSParagraphStyleAttributeName, &style, range);
+        }
+    }
+
+    /// Sets the fonts and code backgrounds of markdown runs, derived from the
+    /// base `font`; headings are bold and scaled by `heading_scale`
+    unsafe fn apply_markdown(&self, runs: &[AttributeRun], font: &NSFont) {
+        let Some(storage) = self.ivars().text_view.textStorage() else {
+            return;
+        };
+        let font_manager = NSFontManager::sharedFontManager(MainThreadMarker::from(self));
+        let code_background = NSColor::colorWithWhite_alpha(0.5, 0.2);
+
+        for run in runs.iter().filter(|run| run.style != SegmentStyle::default()) {
+            let style = run.style;
+            let size = font.pointSize() * style.heading.map_or(1.0, heading_scale);
+
+            let mut run_font = if style.code {
+                NSFont::monospacedSystemFontOfSize_weight(size, NSFontWeightRegular)
+            } else {
+                font_manager.convertFont_toSize(font, size)
+            };
+            if style.bold || style.heading.is_some() {
+                run_font = font_manager.convertFont_toHaveTrait(&run_font, NSFontTraitMask::NSBoldFontMask);
+            }
+            if style.italic {
+                run_font = font_manager.convertFont_toHaveTrait(&run_font, NSFontTraitMask::NSItalicFontMask);
+            }
+
+            let range = NSRange::new(run.start, run.len);
+            storage.addAttribute_value_range(NSFontAttributeName, &run_font, range);
+            if style.code {
+                storage.addAttribute_value_range(NSBackgroundColorAttributeName, &code_background, range);
+            }
+        }
+    }
+
+    /// Names the card for VoiceOver, which reads this before the text
+    pub fn set_accessibility_label(&self, label: &str) {
+        let label = NSString::from_str(label);
+        let _: () = unsafe { msg_send![self, setAccessibilityLabel: &*label] };
+    }
+
+    /// Draws the card flat, as by default, or over a blur of what's behind it
+    pub fn set_background(&self, style: BackgroundStyle) {
+        let effect_view = &self.ivars().effect_view;
+
+        unsafe {
+            match style {
+                BackgroundStyle::Solid => {
+                    effect_view.setHidden(true);
+                    self.setAppearance(None);
+                }
+                BackgroundStyle::Blur { material } => {
+                    // The HUD material is dark whatever the system appearance,
+                    // so the card takes the dark appearance, and text color, with it
+                    let (material, appearance) = match material {
+                        BlurMaterial::Hud => (
+                            NSVisualEffectMaterial::NSVisualEffectMaterialHUDWindow,
+                            NSAppearance::appearanceNamed(NSAppearanceNameDarkAqua),
+                        ),
+                        BlurMaterial::Popover => (NSVisualEffectMaterial::NSVisualEffectMaterialPopover, None),
+                    };
+                    effect_view.setMaterial(material);
+                    effect_view.setHidden(false);
+                    self.setAppearance(appearance.as_deref());
+                }
+            }
+        }
+
+        self.apply_appearance();
+    }
+
+    /// Shows which of how many pages is on the card, as `(page, count)` with
+    /// `page` counting from 0, or hides the indicator with `None`
+    pub fn set_page_indicator(&self, pages: Option<(usize, usize)>) {
+        let page_label = &self.ivars().page_label;
+
+        let Some((page, count)) = pages else {
+            unsafe { page_label.setHidden(true) };
+            return;
+        };
+
+        unsafe {
+            page_label.setStringValue(&NSString::from_str(&format!("{}/{}", page + 1, count)));
+            page_label.sizeToFit();
+            let size = page_label.frame().size;
+            page_label.setFrameOrigin(CGPoint::new(
+                self.bounds().size.width - CONTENT_PADDING - size.width,
+                ((CONTENT_PADDING - size.height) / 2.0).max(0.0),
+            ));
+            page_label.setHidden(false);
+        }
+    }
+
+    /// The card's content as given, markdown markers included
+    pub fn content(&self) -> String {
+        self.ivars().content.borrow().clone()
+    }
+
+    /// Recolors the text and redraws the background for the current appearance
+    pub fn apply_appearance(&self) {
+        let (_, text) = colors_for(unsafe { &self.effectiveAppearance() });
+
+        unsafe {
+            self.ivars().text_view.setTextColor(Some(&text));
+            // Quieter than the text, so it doesn't read as part of it
+            self.ivars().page_label.setTextColor(Some(&text.colorWithAlphaComponent(0.6)));
+            self.setNeedsDisplay(true);
+        }
+    }
+}
+
+/// Resolves a configured font family at `size` points
+///
+/// Unknown families fall back through `FALLBACK_FONTS` and finally to the
+/// system font, so a missing font never leaves the card blank or tiny.
+pub fn notecard_font(family: &str, size: CGFloat) -> Retained<NSFont> {
+    let requested: Vec<&str> = match family.trim() {
+        // SF Pro is the system font, which can't be looked up by name
+        "" | "System" | "SF Pro" => Vec::new(),
+        "Monospace" => MONOSPACE_FONTS.to_vec(),
+        name => vec![name],
+    };
+
+    if !requested.is_empty() {
+        for name in requested.iter().chain(&FALLBACK_FONTS) {
+            let Some(font) = (unsafe { NSFont::fontWithName_size(&NSString::from_str(name), size) }) else {
+                continue;
+            };
+
+            if !requested.contains(name) {
+                tracing::warn!("Font \"{}\" isn't installed, falling back to {}", family, name);
+            }
+            tracing::debug!("Using {} for font \"{}\"", unsafe { font.fontName() }, family);
+            return font;
+        }
+
+        tracing::warn!("No font found for \"{}\", using the system font", family);
+    }
+
+    unsafe { NSFont::systemFontOfSize(size) }
+}
+
+/// A stretchable rounded rectangle to clip the blur to the card's corners;
+/// a layer corner radius doesn't clip a behind-window blur
+fn rounded_mask(radius: CGFloat) -> Retained<NSImage> {
+    use block2::ConcreteBlock;
+
+    let draw = ConcreteBlock::new(move |rect: CGRect| -> Bool {
+        unsafe {
+            NSColor::blackColor().setFill();
+            NSBezierPath::bezierPathWithRoundedRect_xRadius_yRadius(rect, radius, radius).fill();
+        }
+        Bool::YES
+    });
+    let draw = draw.copy();
+
+    // Only the middle point stretches, so the corners keep their radius
+    let edge = 2.0 * radius + 1.0;
+    unsafe {
+        let image = NSImage::imageWithSize_flipped_drawingHandler(CGSize::new(edge, edge), false, &draw);
+        image.setCapInsets(NSEdgeInsets { top: radius, left: radius, bottom: radius, right: radius });
+        image.setResizingMode(NSImageResizingMode::NSImageResizingModeStretch);
+        image
+    }
+}
+
+/// Frame of the text inside a card of `size`
+fn text_frame(size: CGSize) -> CGRect {
+    CGRect::new(
+        CGPoint::new(CONTENT_PADDING, CONTENT_PADDING),
+        CGSize::new(
+            (size.width - 2.0 * CONTENT_PADDING).max(0.0),
+            (size.height - 2.0 * CONTENT_PADDING).max(0.0),
+        ),
+    )
+}
+
+/// Background and text colors: a near-black card with white text in dark mode,
+/// a light translucent one with dark text in light mode
+fn colors_for(appearance: &NSAppearance) -> (Retained<NSColor>, Retained<NSColor>) {
+    let dark = unsafe {
+        let names = NSArray::from_slice(&[NSAppearanceNameAqua, NSAppearanceNameDarkAqua]);
+        appearance
+            .bestMatchFromAppearancesWithNames(&names)
+            .is_some_and(|name| &*name == NSAppearanceNameDarkAqua)
+    };
+
+    unsafe {
+        if dark {
+            (NSColor::colorWithWhite_alpha(0.1, 0.9), NSColor::whiteColor())
+        } else {
+            (NSColor::colorWithWhite_alpha(0.96, 0.9), NSColor::colorWithWhite_alpha(0.1, 1.0))
+        }
+    }
+}
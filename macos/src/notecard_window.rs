@@ -1,36 +1,149 @@
 use anyhow::Result;
-use notecognito_core::{DisplayProperties, NotecardId};
-use std::sync::Arc;
-use tokio::sync::Mutex;
-use objc2::msg_send;
+use notecognito_core::{
+    apply_spacing, fit_to_area, locate_window, place_window_within, split_pages, turn_page, DismissReason,
+    DisplayProperties, MonitorSelector, NotecardId, Placement, PlatformEvent, SpacedLayout, SpacingParams,
+};
+use objc2::{msg_send, msg_send_id, ClassType, Message};
 use dispatch::Queue;
-use std::collections::HashMap;
+use objc2_app_kit::{NSAnimationContext, NSEvent, NSWindow};
+use objc2_foundation::{CGFloat, MainThreadMarker, NSNotificationCenter};
+use objc2::rc::Retained;
+use objc2::runtime::{AnyObject, NSObjectProtocol, ProtocolObject};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex as StdMutex;
+use std::time::Duration;
+use tokio::sync::mpsc;
 
-// Store only window IDs that can be used to find windows later
-static ACTIVE_WINDOW_IDS: once_cell::sync::Lazy<StdMutex<HashMap<u8, i64>>> =
+use crate::notecard_view::{notecard_font, NotecardView};
+
+// The manager's record of notecard windows. Module-level rather than a field,
+// since the click handler, key monitors, window observers and auto-hide
+// timers all close windows on the main thread without a manager to reach
+static WINDOWS: once_cell::sync::Lazy<StdMutex<WindowRegistry>> = once_cell::sync::Lazy::new(Default::default);
+
+// Where dismissals by click or ESC and drags are reported; the event monitors
+// and window observers have no manager to reach
+static PLATFORM_EVENTS: once_cell::sync::Lazy<StdMutex<Option<mpsc::Sender<PlatformEvent>>>> =
+    once_cell::sync::Lazy::new(|| StdMutex::new(None));
+
+// What each notecard was last shown with and the page it's on, so pages turn
+// without going back to the configuration; a show starts its card over
+static SHOWN_NOTECARDS: once_cell::sync::Lazy<StdMutex<HashMap<u8, ShownNotecard>>> =
     once_cell::sync::Lazy::new(|| StdMutex::new(HashMap::new()));
 
-// Simple window info structure
-#[derive(Clone)]
-pub struct NotecardWindowInfo {
-    notecard_id: NotecardId,
+// Whether windows fade in and out, from the properties of the latest show or update
+static ANIMATIONS: AtomicBool = AtomicBool::new(true);
+
+// Whether VoiceOver is told about cards appearing and going, likewise
+static ANNOUNCEMENTS: AtomicBool = AtomicBool::new(true);
+
+const ESCAPE_KEY_CODE: u16 = 53;
+const C_KEY_CODE: u16 = 8;
+const LEFT_ARROW_KEY_CODE: u16 = 123;
+const RIGHT_ARROW_KEY_CODE: u16 = 124;
+
+/// Length of the fade in and out, in seconds
+const FADE_DURATION: f64 = 0.15;
+
+/// Notecard windows on screen and queued to be, behind one lock so a query
+/// never sees a card between the two
+#[derive(Default)]
+struct WindowRegistry {
+    /// Window numbers of open windows, which can be used to find them later
+    active: HashMap<u8, i64>,
+    /// Notecards queued for display whose windows the main thread hasn't created yet
+    pending: HashSet<u8>,
+}
+
+impl WindowRegistry {
+    /// Open and queued notecards, in id order
+    fn visible_ids(&self) -> Vec<NotecardId> {
+        let ids: HashSet<u8> = self.active.keys().chain(&self.pending).copied().collect();
+
+        let mut ids: Vec<_> = ids.into_iter().filter_map(|id| NotecardId::new(id).ok()).collect();
+        ids.sort_by_key(|id| id.value());
+        ids
+    }
+
+    /// The notecard whose window has this number
+    fn notecard_for_window(&self, window_number: i64) -> Option<u8> {
+        self.active.iter().find(|(_, &number)| number == window_number).map(|(&id, _)| id)
+    }
+
+    /// Stops tracking a notecard's window, returning its number so it can be closed
+    fn take_window(&mut self, notecard_id_value: u8) -> Option<i64> {
+        self.active.remove(&notecard_id_value)
+    }
+
+    /// Forgets a window that closed, unless the entry already points at the
+    /// window that replaced it
+    fn window_closed(&mut self, notecard_id_value: u8, window_number: i64) {
+        if self.active.get(&notecard_id_value) == Some(&window_number) {
+            self.active.remove(&notecard_id_value);
+        }
+    }
+
+    /// Drops the queued notecards for hide-all; returns every open or queued
+    /// notecard, and the open ones whose windows still need closing, in id order
+    fn hide_all(&mut self) -> (Vec<NotecardId>, Vec<u8>) {
+        let hidden = self.visible_ids();
+        // Queued windows are simply never created
+        self.pending.clear();
+
+        let mut open: Vec<u8> = self.active.keys().copied().collect();
+        open.sort_unstable();
+        (hidden, open)
+    }
+
+    /// Checks, in debug builds, that one window is tracked per visible
+    /// notecard window, so no card has two entries or a stale one
+    fn check_visible(&self, visible: usize) {
+        debug_assert_eq!(self.active.len(), visible, "tracked {:?} for {} visible notecard windows", self.active, visible);
+    }
+}
+
+/// A notecard's content and properties as last shown or updated, and where
+/// its page cursor is
+struct ShownNotecard {
     content: String,
     properties: DisplayProperties,
+    /// Page on screen, counting from 0
+    page: usize,
+    /// Bumped by every show and page turn, so an auto-hide set before one
+    /// knows the card has moved on
+    generation: u64,
 }
 
-pub struct NotecardWindowManager {
-    pending_windows: Arc<Mutex<Vec<NotecardWindowInfo>>>,
+/// The app-wide key monitors, installed with the first notecard window
+struct KeyMonitors {
+    _local: Option<Retained<AnyObject>>,
+    _global: Option<Retained<AnyObject>>,
 }
 
+thread_local! {
+    // Windows are only created and closed on the main thread
+
+    // Will-close and did-move observers, keyed by window number
+    static WINDOW_OBSERVERS: RefCell<HashMap<i64, Vec<Retained<ProtocolObject<dyn NSObjectProtocol>>>>> =
+        RefCell::new(HashMap::new());
+
+    static KEY_MONITORS: RefCell<Option<KeyMonitors>> = const { RefCell::new(None) };
+
+    // Untracked windows still fading out, by window number
+    static FADING_WINDOWS: RefCell<HashSet<i64>> = RefCell::new(HashSet::new());
+}
+
+pub struct NotecardWindowManager;
+
 unsafe impl Send for NotecardWindowManager {}
 unsafe impl Sync for NotecardWindowManager {}
 
 impl NotecardWindowManager {
-    pub fn new() -> Self {
-        NotecardWindowManager {
-            pending_windows: Arc::new(Mutex::new(Vec::new())),
-        }
+    pub fn new(events: mpsc::Sender<PlatformEvent>) -> Self {
+        *PLATFORM_EVENTS.lock().unwrap() = Some(events);
+        NotecardWindowManager
     }
 
     pub async fn show_notecard(
@@ -39,72 +152,230 @@ impl NotecardWindowManager {
         content: &str,
         properties: &DisplayProperties,
     ) -> Result<()> {
-        let window_info = NotecardWindowInfo {
-            notecard_id,
-            content: content.to_string(),
-            properties: properties.clone(),
-        };
+        WINDOWS.lock().unwrap().pending.insert(notecard_id.value());
 
-        let mut pending = self.pending_windows.lock().await;
-        pending.push(window_info);
+        // Every show opens on the first page
+        let generation = {
+            let mut shown = SHOWN_NOTECARDS.lock().unwrap();
+            let generation = shown.get(&notecard_id.value()).map_or(0, |card| card.generation) + 1;
+            shown.insert(
+                notecard_id.value(),
+                ShownNotecard { content: content.to_string(), properties: properties.clone(), page: 0, generation },
+            );
+            generation
+        };
 
-        self.create_window_on_main_thread(notecard_id, content, properties)?;
+        self.create_window_on_main_thread(notecard_id, content, properties, generation)?;
         Ok(())
     }
 
     pub async fn hide_notecard(&mut self, notecard_id: NotecardId) -> Result<()> {
-        let mut pending = self.pending_windows.lock().await;
-        pending.retain(|w| w.notecard_id != notecard_id);
+        // A window that hasn't been created yet is simply never created
+        let notecard_id_value = notecard_id.value();
+        WINDOWS.lock().unwrap().pending.remove(&notecard_id_value);
+
+        Queue::main().exec_async(move || {
+            close_notecard_window(notecard_id_value, None);
+        });
 
+        Ok(())
+    }
+
+    /// Closes every notecard window and drops the queued ones, returning the
+    /// notecards that were open or queued; waits for the main thread, so the
+    /// windows are off the tracking by the time it returns
+    ///
+    /// Each open window is reported dismissed by hide-all; queued ones were
+    /// never shown, so aren't.
+    pub async fn hide_all(&mut self) -> Result<Vec<NotecardId>> {
+        let close_all = || {
+            let (hidden, open) = WINDOWS.lock().unwrap().hide_all();
+            for id in open {
+                close_notecard_window(id, Some(DismissReason::HideAll));
+            }
+            hidden
+        };
+
+        let hidden = match MainThreadMarker::new() {
+            Some(_) => close_all(),
+            None => Queue::main().exec_sync(close_all),
+        };
+
+        tracing::info!("Hid {} notecards", hidden.len());
+        Ok(hidden)
+    }
+
+    /// Turns an open notecard to its next or previous page; returns the page
+    /// turned to, or `None` when the card isn't open, has one page, or has no
+    /// page that way, as `turn_page` decides from its `wrap_pages`
+    pub fn turn_page(&mut self, notecard_id: NotecardId, forward: bool) -> Option<usize> {
+        if !self.is_notecard_visible(notecard_id) {
+            return None;
+        }
+        turn_notecard_page(notecard_id.value(), forward)
+    }
+
+    /// Swaps the text and geometry of an open window without recreating it
+    pub async fn update_notecard(
+        &mut self,
+        notecard_id: NotecardId,
+        content: &str,
+        properties: &DisplayProperties,
+    ) -> Result<()> {
+        use objc2_foundation::{CGPoint, CGRect, CGSize};
+
+        let content = content.to_string();
+        let opacity = properties.opacity;
+        let properties = properties.clone();
         let notecard_id_value = notecard_id.value();
+        ANIMATIONS.store(properties.animations, Ordering::Relaxed);
+        ANNOUNCEMENTS.store(properties.announcements, Ordering::Relaxed);
+
+        // The card stays on its page, or its last one if it now has fewer
+        if let Some(card) = SHOWN_NOTECARDS.lock().unwrap().get_mut(&notecard_id_value) {
+            card.content.clone_from(&content);
+            card.properties = properties.clone();
+            card.page = card.page.min(split_pages(&content).len() - 1);
+        }
+
+        // Runs after any pending creation, since the main queue is serial
         Queue::main().exec_async(move || {
-            let mut window_ids = ACTIVE_WINDOW_IDS.lock().unwrap();
-            if let Some(window_number) = window_ids.remove(&notecard_id_value) {
-                unsafe {
-                    use objc2_app_kit::NSApplication;
-                    use objc2_foundation::MainThreadMarker;
-
-                    if let Some(mtm) = MainThreadMarker::new() {
-                        let app = NSApplication::sharedApplication(mtm);
-                        let windows = app.windows();
-
-                        for i in 0..windows.count() {
-                            let window = windows.objectAtIndex(i);
-                            let window_num: i64 = msg_send![&window, windowNumber];
-                            if window_num == window_number {
-                                let _: () = msg_send![&window, close];
-                                break;
-                            }
-                        }
-                    }
+            let window_number = WINDOWS.lock().unwrap().active.get(&notecard_id_value).copied();
+            let (Some(window_number), Some(mtm)) = (window_number, MainThreadMarker::new()) else {
+                return;
+            };
+
+            unsafe {
+                let Some(window) = find_window_number(mtm, window_number) else {
+                    return;
+                };
+
+                // Screens can only be queried here, on the main thread. Cocoa
+                // works in points, so logical units need no scaling, and physical
+                // ones keep meaning points as they always have here
+                let primary_height = crate::screens::primary_height(mtm);
+
+                // Stays on its screen rather than following the cursor; one
+                // shown at the cursor stays where it opened, as if the cursor
+                // were still there
+                let current = window.frame();
+                let reference = match properties.placement {
+                    Placement::AtCursor { offset } => CGPoint::new(
+                        current.origin.x - offset.0 as CGFloat,
+                        current.origin.y + current.size.height + offset.1 as CGFloat,
+                    ),
+                    Placement::Fixed => CGPoint::new(
+                        current.origin.x + current.size.width / 2.0,
+                        current.origin.y + current.size.height / 2.0,
+                    ),
+                };
+                let (x, y, width, height) = place_window_within(
+                    &properties,
+                    &crate::screens::screens(mtm),
+                    Some(crate::screens::from_cocoa_point(reference, primary_height)),
+                );
+                let frame = CGRect::new(
+                    crate::screens::to_cocoa_origin((x, y), height, primary_height),
+                    CGSize::new(width as CGFloat, height as CGFloat),
+                );
+                window.setFrame_display(frame, true);
+                window.setAlphaValue(opacity as CGFloat / 100.0);
+                window.setSharingType(sharing_type(&properties));
+                window.setIgnoresMouseEvents(properties.click_through);
+
+                // The text follows the new frame on its own
+                if let Some(view) = notecard_view(&window) {
+                    fill_view(&view, &content, &properties, shown_page(notecard_id_value));
                 }
-                tracing::info!("Notecard {} window closed", notecard_id_value);
             }
+
+            tracing::info!("Notecard {} window updated", notecard_id_value);
         });
 
         Ok(())
     }
 
+    /// Moves an open window; queued after any pending creation like `update_notecard`
+    pub async fn set_notecard_position(&mut self, notecard_id: NotecardId, x: i32, y: i32) -> Result<()> {
+        let notecard_id_value = notecard_id.value();
+
+        Queue::main().exec_async(move || {
+            let Some((window, mtm)) = open_window(notecard_id_value) else {
+                return;
+            };
+
+            unsafe {
+                let height = window.frame().size.height as u32;
+                let origin = crate::screens::to_cocoa_origin((x, y), height, crate::screens::primary_height(mtm));
+                window.setFrameOrigin(origin);
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Resizes an open window, keeping its top-left corner where it is
+    pub async fn set_notecard_size(&mut self, notecard_id: NotecardId, width: u32, height: u32) -> Result<()> {
+        use objc2_foundation::{CGPoint, CGRect, CGSize};
+
+        let notecard_id_value = notecard_id.value();
+
+        Queue::main().exec_async(move || {
+            let Some((window, _)) = open_window(notecard_id_value) else {
+                return;
+            };
+
+            unsafe {
+                // Cocoa's origin is the bottom-left corner, so it moves by the height change
+                let frame = window.frame();
+                let top = frame.origin.y + frame.size.height;
+                let new_frame = CGRect::new(
+                    CGPoint::new(frame.origin.x, top - height as CGFloat),
+                    CGSize::new(width as CGFloat, height as CGFloat),
+                );
+                // The text keeps its padding by autoresizing
+                window.setFrame_display(new_frame, true);
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Queued notecards count as visible, so a show followed by a query agrees.
+    /// Windows leave the tracking however they close, so this never reports a
+    /// window that's gone
+    pub fn is_notecard_visible(&self, notecard_id: NotecardId) -> bool {
+        let id = notecard_id.value();
+        let windows = WINDOWS.lock().unwrap();
+        windows.active.contains_key(&id) || windows.pending.contains(&id)
+    }
+
+    /// Open and queued notecards, in id order
+    pub fn visible_ids(&self) -> Vec<NotecardId> {
+        WINDOWS.lock().unwrap().visible_ids()
+    }
+
     fn create_window_on_main_thread(
         &self,
         notecard_id: NotecardId,
         content: &str,
         properties: &DisplayProperties,
+        generation: u64,
     ) -> Result<()> {
         use objc2_app_kit::{
-            NSBackingStoreType, NSColor, NSFont, NSTextField, NSWindow,
-            NSWindowStyleMask, NSEvent, NSEventType, NSEventMask,
+            NSBackingStoreType, NSColor,
+            NSWindowDidMoveNotification, NSWindowStyleMask, NSWindowWillCloseNotification,
         };
-        use objc2_foundation::{CGFloat, CGPoint, CGRect, CGSize, MainThreadMarker, NSString};
+        use objc2_foundation::{CGPoint, CGRect, CGSize, NSNotification, NSString};
         use block2::ConcreteBlock;
         use std::ptr::NonNull;
 
         let content = content.to_string();
         let opacity = properties.opacity;
-        let font_size = properties.font_size;
-        let position = properties.position;
-        let size = properties.size;
+        let properties = properties.clone();
         let notecard_id_value = notecard_id.value();
+        ANIMATIONS.store(properties.animations, Ordering::Relaxed);
+        ANNOUNCEMENTS.store(properties.announcements, Ordering::Relaxed);
 
         Queue::main().exec_async(move || {
             unsafe {
@@ -116,9 +387,22 @@ impl NotecardWindowManager {
                     }
                 };
 
+                // Hidden again before the main thread got to it
+                if !WINDOWS.lock().unwrap().pending.remove(&notecard_id_value) {
+                    return;
+                }
+
+                // Screens and the cursor can only be queried here, on the main
+                // thread. The frame is kept inside the visible frame, clear of
+                // the menu bar and Dock, even when shown at the cursor
+                let (x, y, width, height) = place_window_within(
+                    &properties,
+                    &crate::screens::screens(mtm),
+                    Some(crate::screens::cursor_position(mtm)),
+                );
                 let frame = CGRect::new(
-                    CGPoint::new(position.0 as CGFloat, position.1 as CGFloat),
-                    CGSize::new(size.0 as CGFloat, size.1 as CGFloat),
+                    crate::screens::to_cocoa_origin((x, y), height, crate::screens::primary_height(mtm)),
+                    CGSize::new(width as CGFloat, height as CGFloat),
                 );
 
                 let window = NSWindow::initWithContentRect_styleMask_backing_defer(
@@ -129,121 +413,837 @@ impl NotecardWindowManager {
                     false,
                 );
 
+                // Owned by the Retained handles alone; closing just takes it off
+                // screen, so a second close or a late lookup is harmless
+                window.setReleasedWhenClosed(false);
                 let _: () = msg_send![&window, setLevel: 3i64];
                 window.setOpaque(false);
                 window.setBackgroundColor(Some(&NSColor::clearColor()));
-                window.setAlphaValue(opacity as CGFloat / 100.0);
+                window.setSharingType(sharing_type(&properties));
                 window.setHasShadow(true);
-                window.setIgnoresMouseEvents(false);
+                // Click-through cards never see the clicks and drags that dismiss and move them
+                window.setIgnoresMouseEvents(properties.click_through);
                 window.setAcceptsMouseMovedEvents(true);
 
-                let content_view = window.contentView().unwrap();
-                let bg_color = NSColor::colorWithWhite_alpha(0.1, 0.9);
-                content_view.setWantsLayer(true);
-
-                if let Some(layer) = content_view.layer() {
-                    let _: () = msg_send![&layer, setCornerRadius: 10.0f64];
-                }
-                let _: () = msg_send![&content_view, setBackgroundColor: &*bg_color];
-
-                let text_field = NSTextField::new(mtm);
-                text_field.setStringValue(&NSString::from_str(&content));
-                text_field.setEditable(false);
-                text_field.setBordered(false);
-                text_field.setDrawsBackground(false);
-                text_field.setTextColor(Some(&NSColor::whiteColor()));
-
-                let font = NSFont::systemFontOfSize(font_size as CGFloat);
-                text_field.setFont(Some(&font));
+                // Its mouseDown: dismisses the card on a click anywhere in the window
+                let notecard_view = NotecardView::new(mtm, CGRect::new(CGPoint::new(0.0, 0.0), frame.size));
+                window.setContentView(Some(&notecard_view));
 
-                let text_frame = CGRect::new(
-                    CGPoint::new(20.0, 20.0),
-                    CGSize::new(size.0 as CGFloat - 40.0, size.1 as CGFloat - 40.0),
-                );
-                text_field.setFrame(text_frame);
+                // Never drawn on a borderless window, but VoiceOver names the window by it
+                let label = crate::accessibility::label(notecard_id);
+                window.setTitle(&NSString::from_str(&label));
+                notecard_view.set_accessibility_label(&label);
 
-                content_view.addSubview(&text_field);
+                // Colors follow the system appearance from here on
+                fill_view(&notecard_view, &content, &properties, 0);
 
-                // Store window number
+                // Store window number, closing the window it replaces
+                close_notecard_window(notecard_id_value, None);
                 let window_number: i64 = msg_send![&window, windowNumber];
-                {
-                    let mut window_ids = ACTIVE_WINDOW_IDS.lock().unwrap();
-                    window_ids.insert(notecard_id_value, window_number);
-                }
+                WINDOWS.lock().unwrap().active.insert(notecard_id_value, window_number);
 
-                // Create event handler - remove Self:: calls and inline the logic
-                let handler = ConcreteBlock::new(move |event: NonNull<NSEvent>| -> *mut NSEvent {
-                    let event = unsafe { event.as_ref() };
-                    let event_type = event.r#type();
-
-                    if event_type == NSEventType::KeyDown {
-                        let key_code = event.keyCode();
-                        if key_code == 53 { // Escape key
-                            // Inline window closing logic
-                            if let Some(mtm) = MainThreadMarker::new() {
-                                unsafe {
-                                    if let Some(window) = event.window(mtm) {
-                                        let window_num: i64 = msg_send![&window, windowNumber];
-
-                                        // Remove from tracking
-                                        let mut window_ids = ACTIVE_WINDOW_IDS.lock().unwrap();
-                                        let notecard_to_remove = window_ids.iter()
-                                            .find_map(|(id, &win_num)| if win_num == window_num { Some(*id) } else { None });
-
-                                        if let Some(notecard_id) = notecard_to_remove {
-                                            window_ids.remove(&notecard_id);
-                                        }
-                                        drop(window_ids);
-
-                                        // Close the window
-                                        let _: () = msg_send![&window, close];
-                                    }
-                                }
-                            }
-                            return std::ptr::null_mut();
-                        }
-                    } else if event_type == NSEventType::LeftMouseDown {
-                        // Inline window closing logic for mouse click
-                        if let Some(mtm) = MainThreadMarker::new() {
-                            unsafe {
-                                if let Some(window) = event.window(mtm) {
-                                    let window_num: i64 = msg_send![&window, windowNumber];
-
-                                    // Remove from tracking
-                                    let mut window_ids = ACTIVE_WINDOW_IDS.lock().unwrap();
-                                    let notecard_to_remove = window_ids.iter()
-                                        .find_map(|(id, &win_num)| if win_num == window_num { Some(*id) } else { None });
-
-                                    if let Some(notecard_id) = notecard_to_remove {
-                                        window_ids.remove(&notecard_id);
-                                    }
-                                    drop(window_ids);
-
-                                    // Close the window
-                                    let _: () = msg_send![&window, close];
-                                }
-                            }
-                        }
-                        return std::ptr::null_mut();
-                    }
+                // Clicks are handled by the view, ESC and Cmd+C by monitors shared by every window
+                install_key_monitors();
 
-                    // Return the event pointer correctly
-                    event as *const NSEvent as *mut NSEvent
+                // Stop tracking the window however it closes, so toggling never
+                // has to hide a window that's already gone
+                let on_close = ConcreteBlock::new(move |_: NonNull<NSNotification>| {
+                    window_closed(notecard_id_value, window_number);
                 });
+                let on_close = on_close.copy();
+                let window_object: &AnyObject = &window;
+                let close_observer = NSNotificationCenter::defaultCenter()
+                    .addObserverForName_object_queue_usingBlock(
+                        Some(NSWindowWillCloseNotification),
+                        Some(window_object),
+                        None,
+                        &on_close,
+                    );
 
-                let handler = handler.copy();
+                // Report drags, so the card reopens where it was left
+                let on_move = ConcreteBlock::new(move |_: NonNull<NSNotification>| {
+                    window_moved(notecard_id_value, window_number);
+                });
+                let on_move = on_move.copy();
+                let move_observer = NSNotificationCenter::defaultCenter()
+                    .addObserverForName_object_queue_usingBlock(
+                        Some(NSWindowDidMoveNotification),
+                        Some(window_object),
+                        None,
+                        &on_move,
+                    );
 
-                let event_mask = NSEventMask::KeyDown | NSEventMask::LeftMouseDown;
-                let _monitor = NSEvent::addLocalMonitorForEventsMatchingMask_handler(
-                    event_mask,
-                    &handler,
-                );
+                WINDOW_OBSERVERS.with(|observers| {
+                    observers.borrow_mut().insert(window_number, vec![close_observer, move_observer]);
+                });
 
                 window.makeKeyAndOrderFront(None);
+                fade_in(&window, opacity as CGFloat / 100.0);
+                if ANNOUNCEMENTS.load(Ordering::Relaxed) {
+                    let first_page = split_pages(&content)[0];
+                    let announcement =
+                        crate::accessibility::shown_announcement(notecard_id, first_page, properties.markdown);
+                    crate::accessibility::announce(mtm, &announcement, true);
+                }
+                schedule_auto_hide(notecard_id_value, properties.auto_hide_duration, generation);
                 tracing::info!("Notecard {} window displayed", notecard_id_value);
+                check_tracking(mtm);
             }
         });
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Moves a notecard to its next or previous page and redraws it in place,
+/// restarting its auto-hide; returns the new page, or `None`, changing
+/// nothing, when it has one page or no page that way
+fn turn_notecard_page(notecard_id_value: u8, forward: bool) -> Option<usize> {
+    let (page, seconds, generation) = {
+        let mut shown = SHOWN_NOTECARDS.lock().unwrap();
+        let card = shown.get_mut(&notecard_id_value)?;
+
+        // With one page, wrapping would turn the card to itself
+        let count = split_pages(&card.content).len();
+        if count < 2 {
+            return None;
+        }
+
+        card.page = turn_page(card.page, count, forward, card.properties.wrap_pages)?;
+        card.generation += 1;
+        (card.page, card.properties.auto_hide_duration, card.generation)
+    };
+
+    schedule_auto_hide(notecard_id_value, seconds, generation);
+    if MainThreadMarker::new().is_some() {
+        render_page(notecard_id_value);
+    } else {
+        Queue::main().exec_async(move || render_page(notecard_id_value));
+    }
+
+    tracing::info!("Notecard {} turned to page {}", notecard_id_value, page + 1);
+    Some(page)
+}
+
+/// Redraws an open notecard on the page its cursor is at, and reads the page
+/// out; runs on the main thread
+fn render_page(notecard_id_value: u8) {
+    let Some((window, mtm)) = open_window(notecard_id_value) else {
+        return;
+    };
+    let Some(view) = (unsafe { notecard_view(&window) }) else {
+        return;
+    };
+
+    // Cloned so AppKit never runs with the lock held
+    let Some((content, properties, page)) = SHOWN_NOTECARDS
+        .lock()
+        .unwrap()
+        .get(&notecard_id_value)
+        .map(|card| (card.content.clone(), card.properties.clone(), card.page))
+    else {
+        return;
+    };
+    fill_view(&view, &content, &properties, page);
+
+    if let (true, Ok(id)) = (ANNOUNCEMENTS.load(Ordering::Relaxed), NotecardId::new(notecard_id_value)) {
+        let pages = split_pages(&content);
+        let text = pages[page.min(pages.len() - 1)];
+        crate::accessibility::announce(
+            mtm,
+            &crate::accessibility::shown_announcement(id, text, properties.markdown),
+            true,
+        );
+    }
+}
+
+/// The page a notecard is on, 0 when it was never shown
+fn shown_page(notecard_id_value: u8) -> usize {
+    SHOWN_NOTECARDS.lock().unwrap().get(&notecard_id_value).map_or(0, |card| card.page)
+}
+
+/// Closes a notecard after `seconds`, reporting `DismissReason::AutoHide`,
+/// unless it has been shown again or turned to another page by then; 0 leaves
+/// it open
+fn schedule_auto_hide(notecard_id_value: u8, seconds: u32, generation: u64) {
+    if seconds == 0 {
+        return;
+    }
+
+    Queue::main().exec_after(Duration::from_secs(seconds as u64), move || {
+        let current = SHOWN_NOTECARDS.lock().unwrap().get(&notecard_id_value).map(|card| card.generation);
+        // A card hidden in the meantime has no window left to close
+        if current == Some(generation) {
+            close_notecard_window(notecard_id_value, Some(DismissReason::AutoHide));
+        }
+    });
+}
+
+/// Moves open notecard windows back onto the screens there are now, after a
+/// display was unplugged or rearranged; runs on the main thread
+///
+/// A window goes onto the screen holding its center, or the primary screen
+/// when none does, and is kept inside its work area as shows keep it.
+pub fn refit_windows(mtm: MainThreadMarker) {
+    use objc2_foundation::{CGPoint, CGRect, CGSize};
+
+    let screens = crate::screens::screens(mtm);
+    let primary_height = crate::screens::primary_height(mtm);
+    let windows: Vec<(u8, i64)> = WINDOWS.lock().unwrap().active.iter().map(|(&id, &number)| (id, number)).collect();
+
+    for (notecard_id_value, window_number) in windows {
+        let Some(window) = (unsafe { find_window_number(mtm, window_number) }) else {
+            continue;
+        };
+
+        let frame = window.frame();
+        let (x, y) = crate::screens::from_cocoa_point(
+            CGPoint::new(frame.origin.x, frame.origin.y + frame.size.height),
+            primary_height,
+        );
+        let rect = (x, y, frame.size.width as u32, frame.size.height as u32);
+        let center = (x.saturating_add((rect.2 / 2) as i32), y.saturating_add((rect.3 / 2) as i32));
+
+        let Some(screen) = screens
+            .iter()
+            .find(|screen| screen.contains(center))
+            .or_else(|| MonitorSelector::Primary.select(&screens))
+        else {
+            continue;
+        };
+
+        let fitted = fit_to_area(rect, screen.work_area);
+        if fitted == rect {
+            continue;
+        }
+
+        let (x, y, width, height) = fitted;
+        let frame = CGRect::new(
+            crate::screens::to_cocoa_origin((x, y), height, primary_height),
+            CGSize::new(width as CGFloat, height as CGFloat),
+        );
+        unsafe { window.setFrame_display(frame, true) };
+        tracing::info!(
+            "Screens changed; moved notecard {} from {:?} onto screen {} at {:?}",
+            notecard_id_value,
+            rect,
+            screen.index,
+            fitted
+        );
+    }
+}
+
+/// Closes every notecard window before returning, without fading; for quitting,
+/// when the app won't run long enough to finish a fade; runs on the main thread
+pub fn close_all_windows_now() {
+    // Nobody needs to hear each card go as the app quits
+    ANIMATIONS.store(false, Ordering::Relaxed);
+    ANNOUNCEMENTS.store(false, Ordering::Relaxed);
+    WINDOWS.lock().unwrap().pending.clear();
+
+    let ids: Vec<u8> = WINDOWS.lock().unwrap().active.keys().copied().collect();
+    for id in ids {
+        close_notecard_window(id, None);
+    }
+}
+
+/// Forgets a closing window and stops observing it; runs on the main thread
+fn window_closed(notecard_id_value: u8, window_number: i64) {
+    WINDOWS.lock().unwrap().window_closed(notecard_id_value, window_number);
+    FADING_WINDOWS.with(|fading| fading.borrow_mut().remove(&window_number));
+
+    let observers = WINDOW_OBSERVERS.with(|observers| observers.borrow_mut().remove(&window_number));
+    for observer in observers.into_iter().flatten() {
+        unsafe { NSNotificationCenter::defaultCenter().removeObserver(&observer) };
+    }
+}
+
+/// Reports where the user dragged a notecard; runs on the main thread
+fn window_moved(notecard_id_value: u8, window_number: i64) {
+    use objc2_foundation::CGPoint;
+
+    // Only a drag moves a window with the button down; frame changes from
+    // update_notecard and the like aren't the user's to keep
+    if unsafe { NSEvent::pressedMouseButtons() } & 1 == 0 {
+        return;
+    }
+
+    let Some(mtm) = MainThreadMarker::new() else {
+        return;
+    };
+    let Some(window) = (unsafe { find_window_number(mtm, window_number) }) else {
+        return;
+    };
+
+    // Flipped the same way the window was placed, so it reopens on the same point
+    let frame = window.frame();
+    let top_left = crate::screens::from_cocoa_point(
+        CGPoint::new(frame.origin.x, frame.origin.y + frame.size.height),
+        crate::screens::primary_height(mtm),
+    );
+    let size = (frame.size.width as u32, frame.size.height as u32);
+
+    let (Ok(id), Some((monitor, position))) = (
+        NotecardId::new(notecard_id_value),
+        locate_window(&crate::screens::screens(mtm), top_left, size),
+    ) else {
+        return;
+    };
+
+    report(PlatformEvent::NotecardMoved { id, monitor, position });
+}
+
+/// Makes ESC close the frontmost notecard, Cmd+C copy it and the left and
+/// right arrows turn its pages, once per process; runs on the main thread
+///
+/// Notecards never become key, so ESC is normally sent to another app and
+/// only the global monitor sees it. That monitor can't swallow the key, and
+/// macOS only delivers key events to it once accessibility access is granted.
+/// The local one covers the moments Notecognito itself is active. Cmd+C and
+/// the arrows are only taken locally, since the app in front needs them for
+/// its own copying and moving about; the hotkey turns pages at any time.
+fn install_key_monitors() {
+    use block2::ConcreteBlock;
+    use objc2_app_kit::NSEventMask;
+    use std::ptr::NonNull;
+
+    if KEY_MONITORS.with(|monitors| monitors.borrow().is_some()) {
+        return;
+    }
+
+    let local = ConcreteBlock::new(|event: NonNull<NSEvent>| -> *mut NSEvent {
+        let event = unsafe { event.as_ref() };
+
+        // Swallowed only when it closed a notecard
+        if event.keyCode() == ESCAPE_KEY_CODE && close_frontmost_window(DismissReason::Escape) {
+            return std::ptr::null_mut();
+        }
+
+        if is_copy(event) && copy_frontmost_content() {
+            return std::ptr::null_mut();
+        }
+
+        if let Some(forward) = page_direction(event) {
+            if turn_frontmost_page(forward) {
+                return std::ptr::null_mut();
+            }
+        }
+
+        event as *const NSEvent as *mut NSEvent
+    });
+    let local = local.copy();
+
+    let global = ConcreteBlock::new(|event: NonNull<NSEvent>| {
+        let event = unsafe { event.as_ref() };
+        if event.keyCode() == ESCAPE_KEY_CODE {
+            close_frontmost_window(DismissReason::Escape);
+        }
+    });
+    let global = global.copy();
+
+    let monitors = unsafe {
+        KeyMonitors {
+            _local: NSEvent::addLocalMonitorForEventsMatchingMask_handler(NSEventMask::KeyDown, &local),
+            _global: NSEvent::addGlobalMonitorForEventsMatchingMask_handler(NSEventMask::KeyDown, &global),
+        }
+    };
+
+    KEY_MONITORS.with(|slot| *slot.borrow_mut() = Some(monitors));
+}
+
+/// Whether a key press is Cmd+C with no other modifiers
+fn is_copy(event: &NSEvent) -> bool {
+    use objc2_app_kit::NSEventModifierFlags;
+
+    let modifiers = event.modifierFlags() & NSEventModifierFlags::NSEventModifierFlagDeviceIndependentFlagsMask;
+    event.keyCode() == C_KEY_CODE && modifiers == NSEventModifierFlags::NSEventModifierFlagCommand
+}
+
+/// Whether a key press is a bare right arrow, turning forwards, or left
+/// arrow, turning back
+fn page_direction(event: &NSEvent) -> Option<bool> {
+    use objc2_app_kit::NSEventModifierFlags;
+
+    // Arrow keys come with the function and numeric pad flags set
+    let held = NSEventModifierFlags::NSEventModifierFlagCommand
+        | NSEventModifierFlags::NSEventModifierFlagOption
+        | NSEventModifierFlags::NSEventModifierFlagControl
+        | NSEventModifierFlags::NSEventModifierFlagShift;
+    if !(event.modifierFlags() & held).is_empty() {
+        return None;
+    }
+
+    match event.keyCode() {
+        RIGHT_ARROW_KEY_CODE => Some(true),
+        LEFT_ARROW_KEY_CODE => Some(false),
+        _ => None,
+    }
+}
+
+/// Turns the page of the notecard nearest the front, closing it when turned
+/// forwards past its last page as a repeated hotkey press would; returns
+/// false, doing nothing, when no notecard on screen has pages to turn
+fn turn_frontmost_page(forward: bool) -> bool {
+    use objc2_app_kit::NSApplication;
+
+    if WINDOWS.lock().unwrap().active.is_empty() {
+        return false;
+    }
+
+    let Some(mtm) = MainThreadMarker::new() else {
+        return false;
+    };
+
+    // Ordered front to back; click-through cards are left out as for ESC
+    let tracked: HashMap<i64, u8> =
+        WINDOWS.lock().unwrap().active.iter().map(|(&id, &number)| (number, id)).collect();
+    let windows = unsafe { NSApplication::sharedApplication(mtm).orderedWindows() };
+    let frontmost = (0..windows.count()).find_map(|i| {
+        let window = windows.objectAtIndex(i);
+        let window_number: i64 = unsafe { msg_send![&window, windowNumber] };
+        let id = tracked.get(&window_number).copied()?;
+        (!unsafe { window.ignoresMouseEvents() }).then_some(id)
+    });
+    let Some(notecard_id_value) = frontmost else {
+        return false;
+    };
+
+    // A card without pages leaves the arrows to whatever else wants them
+    let pages = SHOWN_NOTECARDS
+        .lock()
+        .unwrap()
+        .get(&notecard_id_value)
+        .map_or(1, |card| split_pages(&card.content).len());
+    if pages < 2 {
+        return false;
+    }
+
+    if turn_notecard_page(notecard_id_value, forward).is_some() {
+        return true;
+    }
+
+    // Back from the first page does nothing. Forward from the last is a
+    // dismissal from the keyboard, so it's reported as ESC's is
+    forward && close_notecard_window(notecard_id_value, Some(DismissReason::Escape))
+}
+
+/// Copies the content of the notecard nearest the front and flashes its
+/// window; returns false, doing nothing, when no notecard is on screen or
+/// another of our windows, like the fallback editor, has the keyboard
+fn copy_frontmost_content() -> bool {
+    use objc2_app_kit::NSApplication;
+
+    if WINDOWS.lock().unwrap().active.is_empty() {
+        return false;
+    }
+
+    let Some(mtm) = MainThreadMarker::new() else {
+        return false;
+    };
+
+    let app = NSApplication::sharedApplication(mtm);
+    // Notecards can't become key, so a key window is one that copies for itself
+    if unsafe { app.keyWindow() }.is_some() {
+        return false;
+    }
+
+    let tracked: HashSet<i64> = WINDOWS.lock().unwrap().active.values().copied().collect();
+    let windows = unsafe { app.orderedWindows() };
+    for i in 0..windows.count() {
+        let window = windows.objectAtIndex(i);
+        let window_number: i64 = unsafe { msg_send![&window, windowNumber] };
+        if !tracked.contains(&window_number) {
+            continue;
+        }
+
+        let Some(view) = (unsafe { notecard_view(&window) }) else {
+            continue;
+        };
+        if let Err(e) = crate::clipboard::write_text(&view.content()) {
+            tracing::warn!("Could not copy the notecard: {}", e);
+            return false;
+        }
+
+        unsafe { flash(&window) };
+        tracing::info!("Copied the frontmost notecard");
+        return true;
+    }
+
+    false
+}
+
+/// Dims a window for a moment as feedback, unless animations are off
+unsafe fn flash(window: &NSWindow) {
+    if !ANIMATIONS.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let alpha = window.alphaValue();
+    window.setAlphaValue(alpha * 0.4);
+    animate_alpha(window, alpha, None);
+}
+
+/// Closes the notecard window nearest the front and reports the dismissal;
+/// returns false, doing nothing, when no notecard is on screen
+fn close_frontmost_window(reason: DismissReason) -> bool {
+    use objc2_app_kit::NSApplication;
+
+    // Checked first so every ESC typed elsewhere costs only this
+    if WINDOWS.lock().unwrap().active.is_empty() {
+        return false;
+    }
+
+    let Some(mtm) = MainThreadMarker::new() else {
+        return false;
+    };
+
+    // Ordered front to back
+    let windows = unsafe { NSApplication::sharedApplication(mtm).orderedWindows() };
+    for i in 0..windows.count() {
+        let window = windows.objectAtIndex(i);
+        // Click-through cards sit over other work, where ESC means something else
+        if unsafe { window.ignoresMouseEvents() } {
+            continue;
+        }
+        if unsafe { dismiss_window(&window, reason) } {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Closes the notecard window a click landed on, stops tracking it and reports
+/// the dismissal; returns false, leaving the window alone, if the event isn't
+/// for a notecard
+pub(crate) unsafe fn close_event_window(event: &NSEvent, reason: DismissReason) -> bool {
+    let Some(mtm) = MainThreadMarker::new() else {
+        return false;
+    };
+
+    match event.window(mtm) {
+        Some(window) => dismiss_window(&window, reason),
+        None => false,
+    }
+}
+
+/// Closes a window if it's a notecard's, reporting the dismissal
+unsafe fn dismiss_window(window: &objc2_app_kit::NSWindow, reason: DismissReason) -> bool {
+    let window_num: i64 = msg_send![window, windowNumber];
+
+    let notecard_id_value = WINDOWS.lock().unwrap().notecard_for_window(window_num);
+
+    notecard_id_value.is_some_and(|id| close_notecard_window(id, Some(reason)))
+}
+
+/// Stops tracking a notecard's window and closes it, reporting the dismissal
+/// when there's a reason; every close goes through here
+///
+/// Runs on the main thread. Returns false when the notecard has no window.
+fn close_notecard_window(notecard_id_value: u8, reason: Option<DismissReason>) -> bool {
+    let Some(window_number) = WINDOWS.lock().unwrap().take_window(notecard_id_value) else {
+        return false;
+    };
+
+    if let Some(mtm) = MainThreadMarker::new() {
+        unsafe {
+            if let Some(window) = find_window_number(mtm, window_number) {
+                fade_out_and_close(&window);
+            }
+        }
+        check_tracking(mtm);
+
+        // A card replaced by a new one is announced anew, interrupting this
+        if let (true, Ok(id)) = (ANNOUNCEMENTS.load(Ordering::Relaxed), NotecardId::new(notecard_id_value)) {
+            crate::accessibility::announce(mtm, &crate::accessibility::dismissed_announcement(id), false);
+        }
+    }
+
+    if let (Some(reason), Ok(id)) = (reason, NotecardId::new(notecard_id_value)) {
+        report(PlatformEvent::NotecardDismissed { id, reason });
+    }
+
+    tracing::info!("Notecard {} window closed", notecard_id_value);
+    true
+}
+
+/// Checks, in debug builds, that the tracked windows are exactly the notecard
+/// windows on screen, leaving out ones fading out after being closed
+fn check_tracking(mtm: MainThreadMarker) {
+    use objc2_app_kit::NSApplication;
+
+    if !cfg!(debug_assertions) {
+        return;
+    }
+
+    let tracked: HashSet<i64> = WINDOWS.lock().unwrap().active.values().copied().collect();
+    let fading = FADING_WINDOWS.with(|fading| fading.borrow().clone());
+
+    let windows = NSApplication::sharedApplication(mtm).windows();
+    let mut on_screen = HashSet::new();
+    for i in 0..windows.count() {
+        let window = windows.objectAtIndex(i);
+        let window_number: i64 = unsafe { msg_send![&window, windowNumber] };
+        if window.isVisible() && unsafe { notecard_view(&window) }.is_some() && !fading.contains(&window_number) {
+            on_screen.insert(window_number);
+        }
+    }
+
+    // Windows are only tracked and forgotten on this thread, so the map hasn't moved on
+    WINDOWS.lock().unwrap().check_visible(on_screen.len());
+    debug_assert_eq!(tracked, on_screen, "tracked notecard windows don't match the ones on screen");
+}
+
+/// Sends an event to the app, dropping it if the channel is full
+fn report(event: PlatformEvent) {
+    if let Some(events) = PLATFORM_EVENTS.lock().unwrap().as_ref() {
+        if let Err(e) = events.try_send(event) {
+            tracing::debug!("Dropped platform event: {}", e);
+        }
+    }
+}
+
+/// Fades a window from transparent up to `alpha`, or sets it right away with
+/// animations off
+unsafe fn fade_in(window: &NSWindow, alpha: CGFloat) {
+    if !ANIMATIONS.load(Ordering::Relaxed) {
+        window.setAlphaValue(alpha);
+        return;
+    }
+
+    window.setAlphaValue(0.0);
+    animate_alpha(window, alpha, None);
+}
+
+/// Closes a window, fading it out first unless animations are off
+///
+/// Callers stop tracking the window right away, so a toggle during the fade
+/// shows a fresh card. The window itself closes, and its will-close observer
+/// goes, only in the completion handler; until then it counts as fading. A
+/// later fade, say from a card shown again mid-fade-out, simply takes over
+/// the alpha from wherever it got to.
+unsafe fn fade_out_and_close(window: &NSWindow) {
+    use block2::ConcreteBlock;
+
+    if !ANIMATIONS.load(Ordering::Relaxed) {
+        let _: () = msg_send![window, close];
+        return;
+    }
+
+    // Clicks go through a card on its way out
+    window.setIgnoresMouseEvents(true);
+    let window_number: i64 = msg_send![window, windowNumber];
+    FADING_WINDOWS.with(|fading| fading.borrow_mut().insert(window_number));
+
+    let closing = window.retain();
+    let on_faded = ConcreteBlock::new(move || {
+        // Windows aren't released when closed, so closing one twice is harmless
+        let _: () = msg_send![&closing, close];
+    });
+    let on_faded = on_faded.copy();
+
+    animate_alpha(window, 0.0, Some(&on_faded));
+}
+
+/// Animates a window's alpha over `FADE_DURATION`, then runs `completion`
+unsafe fn animate_alpha(window: &NSWindow, alpha: CGFloat, completion: Option<&block2::Block<dyn Fn()>>) {
+    use block2::ConcreteBlock;
+    use std::ptr::NonNull;
+
+    let window = window.retain();
+    let changes = ConcreteBlock::new(move |context: NonNull<NSAnimationContext>| {
+        context.as_ref().setDuration(FADE_DURATION);
+        let animator: Retained<NSWindow> = msg_send_id![&window, animator];
+        animator.setAlphaValue(alpha);
+    });
+    let changes = changes.copy();
+
+    NSAnimationContext::runAnimationGroup_completionHandler(&changes, completion);
+}
+
+/// Shows one page of a card's content, with the font, spacing and background
+/// its properties ask for and, when it has several pages, which one it is
+fn fill_view(view: &NotecardView, content: &str, properties: &DisplayProperties, page: usize) {
+    let pages = split_pages(content);
+    let page = page.min(pages.len() - 1);
+
+    let spacing = spacing_for(pages[page], properties);
+    let font = notecard_font(&properties.font_family, properties.font_size as CGFloat);
+    view.set_content(pages[page], &font, spacing.as_ref(), properties.markdown);
+    view.set_background(properties.background_style);
+    view.set_page_indicator((pages.len() > 1).then_some((page, pages.len())));
+}
+
+/// Line and paragraph spacing for a card's text, when the properties ask for it
+fn spacing_for(content: &str, properties: &DisplayProperties) -> Option<SpacedLayout> {
+    properties
+        .algorithmic_spacing
+        .then(|| apply_spacing(content, &SpacingParams::for_font_size(properties.font_size as f64)))
+}
+
+/// Leaves a window out of screen captures and sharing when the properties ask
+fn sharing_type(properties: &DisplayProperties) -> objc2_app_kit::NSWindowSharingType {
+    use objc2_app_kit::NSWindowSharingType;
+
+    if properties.exclude_from_capture {
+        NSWindowSharingType::NSWindowSharingNone
+    } else {
+        NSWindowSharingType::NSWindowSharingReadOnly
+    }
+}
+
+/// The content view of a notecard window
+unsafe fn notecard_view(window: &NSWindow) -> Option<Retained<NotecardView>> {
+    let content_view = window.contentView()?;
+    let is_notecard: bool = msg_send![&content_view, isKindOfClass: NotecardView::class()];
+
+    // Checked above, so the cast is sound
+    is_notecard.then(|| Retained::cast::<NotecardView>(content_view))
+}
+
+/// Looks up the window of a notecard; must be called on the main thread
+fn open_window(
+    notecard_id_value: u8,
+) -> Option<(objc2::rc::Retained<objc2_app_kit::NSWindow>, MainThreadMarker)> {
+    let window_number = WINDOWS.lock().unwrap().active.get(&notecard_id_value).copied()?;
+    let mtm = MainThreadMarker::new()?;
+    let window = unsafe { find_window_number(mtm, window_number) }?;
+    Some((window, mtm))
+}
+
+/// Looks up an open application window by its window number
+unsafe fn find_window_number(
+    mtm: MainThreadMarker,
+    window_number: i64,
+) -> Option<objc2::rc::Retained<objc2_app_kit::NSWindow>> {
+    use objc2_app_kit::NSApplication;
+
+    let app = NSApplication::sharedApplication(mtm);
+    let windows = app.windows();
+
+    for i in 0..windows.count() {
+        let window = windows.objectAtIndex(i);
+        let window_num: i64 = msg_send![&window, windowNumber];
+        if window_num == window_number {
+            return Some(window);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(values: &[u8]) -> Vec<NotecardId> {
+        values.iter().map(|&value| NotecardId::new(value).unwrap()).collect()
+    }
+
+    /// Notecards 1 and 3 open, 3 reopening and 5 queued
+    fn registry() -> WindowRegistry {
+        WindowRegistry {
+            active: HashMap::from([(1, 100), (3, 300)]),
+            pending: HashSet::from([3, 5]),
+        }
+    }
+
+    #[test]
+    fn hide_all_closes_open_windows_and_drops_queued_ones() {
+        let mut registry = registry();
+
+        let (hidden, open) = registry.hide_all();
+        assert_eq!(hidden, ids(&[1, 3, 5]));
+        assert_eq!(open, [1, 3]);
+        assert!(registry.pending.is_empty());
+
+        // What close_notecard_window does for each, without AppKit
+        let closed: Vec<Option<i64>> = open.iter().map(|&id| registry.take_window(id)).collect();
+        assert_eq!(closed, [Some(100), Some(300)]);
+        assert!(registry.visible_ids().is_empty());
+
+        // The will-close notifications arriving afterwards change nothing
+        registry.window_closed(1, 100);
+        assert_eq!(registry.hide_all(), (Vec::new(), Vec::new()));
+    }
+
+    #[test]
+    fn a_late_close_keeps_the_window_that_replaced_it() {
+        let mut registry = registry();
+
+        assert_eq!(registry.take_window(3), Some(300));
+        registry.active.insert(3, 301);
+        registry.window_closed(3, 300);
+        assert_eq!(registry.notecard_for_window(301), Some(3));
+
+        registry.window_closed(3, 301);
+        assert_eq!(registry.notecard_for_window(301), None);
+    }
+
+    #[test]
+    fn tracking_matches_the_visible_windows_through_a_stress_loop() {
+        let mut registry = WindowRegistry::default();
+        // Window number to notecard, for the windows a real screen would show
+        let mut screen: HashMap<i64, u8> = HashMap::new();
+        let mut closed: Vec<(u8, i64)> = Vec::new();
+        let mut next_window = 1;
+        let mut seed: u32 = 0x2545_f491;
+
+        // What close_notecard_window does, without AppKit
+        let close = |registry: &mut WindowRegistry, screen: &mut HashMap<i64, u8>, closed: &mut Vec<(u8, i64)>, id: u8| {
+            if let Some(number) = registry.take_window(id) {
+                screen.remove(&number);
+                closed.push((id, number));
+            }
+        };
+
+        for _ in 0..10_000 {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let id = (seed >> 8) as u8 % 9 + 1;
+
+            match (seed >> 16) % 6 {
+                // show_notecard
+                0 | 1 => {
+                    registry.pending.insert(id);
+                }
+                // The main thread creating a queued window, replacing any open one
+                2 => {
+                    if registry.pending.remove(&id) {
+                        close(&mut registry, &mut screen, &mut closed, id);
+                        registry.active.insert(id, next_window);
+                        screen.insert(next_window, id);
+                        next_window += 1;
+                    }
+                }
+                // hide_notecard
+                3 => {
+                    registry.pending.remove(&id);
+                    close(&mut registry, &mut screen, &mut closed, id);
+                }
+                // A will-close notification arriving late, maybe for a replaced window
+                4 => {
+                    if let Some((id, number)) = closed.pop() {
+                        registry.window_closed(id, number);
+                    }
+                }
+                _ => {
+                    let (_, open) = registry.hide_all();
+                    for id in open {
+                        close(&mut registry, &mut screen, &mut closed, id);
+                    }
+                    assert!(screen.is_empty());
+                }
+            }
+
+            registry.check_visible(screen.len());
+            assert_eq!(registry.active.values().copied().collect::<HashSet<_>>(), screen.keys().copied().collect());
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "visible notecard windows")]
+    fn a_stale_entry_trips_the_check() {
+        registry().check_visible(1);
+    }
+}
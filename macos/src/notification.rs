@@ -0,0 +1,25 @@
+use dispatch::Queue;
+use notecognito_core::NotificationKind;
+use objc2_foundation::{NSString, NSUserNotification, NSUserNotificationCenter};
+
+/// Delivers a banner to Notification Center
+///
+/// `NSUserNotification` is deprecated but, unlike `UNUserNotificationCenter`,
+/// works without a signed bundle and an authorization prompt.
+pub fn show(title: &str, body: &str, kind: NotificationKind) {
+    let title = title.to_string();
+    let body = body.to_string();
+
+    Queue::main().exec_async(move || unsafe {
+        let notification = NSUserNotification::new();
+        notification.setTitle(Some(&NSString::from_str(&title)));
+        notification.setInformativeText(Some(&NSString::from_str(&body)));
+
+        // Only problems make a sound
+        if kind != NotificationKind::Info {
+            notification.setSoundName(Some(&NSString::from_str("NSUserNotificationDefaultSoundName")));
+        }
+
+        NSUserNotificationCenter::defaultUserNotificationCenter().deliverNotification(&notification);
+    });
+}
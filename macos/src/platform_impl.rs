@@ -1,10 +1,10 @@
+use async_trait::async_trait;
 use notecognito_core::{
-    DisplayProperties, HotkeyModifier, NotecardId, PlatformInterface,
+    AppIdentity, DisplayProperties, HotkeyKey, HotkeyModifier, NotecardId, NotificationKind,
+    PlatformCapabilities, PlatformInterface, ScreenInfo, ToggleOutcome,
 };
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use core_foundation::string::CFStringRef;
-use core_foundation::error::CFErrorRef;
 
 use crate::hotkey::HotkeyManager;
 use crate::notecard_window::NotecardWindowManager;
@@ -26,198 +26,216 @@ impl MacOSPlatform {
             initialized: false,
         }
     }
+
+    /// Turns the event tap back on after sleep; see `HotkeyManager::ensure_event_tap_enabled`
+    pub async fn ensure_event_tap_enabled(&self) {
+        self.hotkey_manager.lock().await.ensure_event_tap_enabled();
+    }
 }
 
+#[async_trait]
 impl PlatformInterface for MacOSPlatform {
-    fn register_hotkey(
+    async fn register_hotkey(
         &mut self,
         id: NotecardId,
         modifiers: &[HotkeyModifier],
     ) -> notecognito_core::Result<()> {
-        let hotkey_manager = Arc::clone(&self.hotkey_manager);
-        let modifiers = modifiers.to_vec();
+        HotkeyModifier::check_supported(modifiers)?;
+
+        let mut manager = self.hotkey_manager.lock().await;
+        manager
+            .register_hotkey(id, modifiers, HotkeyKey::for_notecard(id))
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
 
-        let result = tokio::task::block_in_place(move || {
-            tokio::runtime::Handle::current().block_on(async move {
-                let mut manager = hotkey_manager.lock().await;
-                manager.register_hotkey(id, &modifiers)
-            })
-        });
+    async fn unregister_hotkey(&mut self, id: NotecardId) -> notecognito_core::Result<()> {
+        let mut manager = self.hotkey_manager.lock().await;
+        manager
+            .unregister_hotkey(id)
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn show_notecard(
+        &mut self,
+        id: NotecardId,
+        content: &str,
+        properties: &DisplayProperties,
+    ) -> notecognito_core::Result<()> {
+        let mut manager = self.window_manager.lock().await;
+        manager
+            .show_notecard(id, content, properties)
+            .await
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
 
-        result.map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    async fn hide_notecard(&mut self, id: NotecardId) -> notecognito_core::Result<()> {
+        let mut manager = self.window_manager.lock().await;
+        manager
+            .hide_notecard(id)
+            .await
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
     }
 
-    fn unregister_hotkey(&mut self, id: NotecardId) -> notecognito_core::Result<()> {
-        let hotkey_manager = Arc::clone(&self.hotkey_manager);
+    // A card split into pages goes on a page with each press, and hides only
+    // once past its last; the cursor is the window manager's, so presses
+    // in quick succession never skip or repeat a page
+    async fn toggle_notecard(
+        &mut self,
+        id: NotecardId,
+        content: &str,
+        properties: &DisplayProperties,
+    ) -> notecognito_core::Result<ToggleOutcome> {
+        let mut manager = self.window_manager.lock().await;
+
+        if !manager.is_notecard_visible(id) {
+            manager
+                .show_notecard(id, content, properties)
+                .await
+                .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))?;
+            return Ok(ToggleOutcome::Shown);
+        }
+
+        if let Some(page) = manager.turn_page(id, true) {
+            return Ok(ToggleOutcome::PageTurned { page });
+        }
 
-        let result = tokio::task::block_in_place(move || {
-            tokio::runtime::Handle::current().block_on(async move {
-                let mut manager = hotkey_manager.lock().await;
-                manager.unregister_hotkey(id)
-            })
-        });
+        manager
+            .hide_notecard(id)
+            .await
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))?;
+        Ok(ToggleOutcome::Hidden)
+    }
 
-        result.map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    async fn hide_all_notecards(&mut self) -> notecognito_core::Result<()> {
+        let mut manager = self.window_manager.lock().await;
+        manager
+            .hide_all()
+            .await
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))?;
+        Ok(())
     }
 
-    fn show_notecard(
+    async fn update_notecard(
         &mut self,
         id: NotecardId,
         content: &str,
         properties: &DisplayProperties,
     ) -> notecognito_core::Result<()> {
-        let window_manager = Arc::clone(&self.window_manager);
-        let content = content.to_string();
-        let properties = properties.clone();
-
-        let result = tokio::task::block_in_place(move || {
-            tokio::runtime::Handle::current().block_on(async move {
-                let mut manager = window_manager.lock().await;
-                manager.show_notecard(id, &content, &properties).await
-            })
-        });
-
-        result.map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
-    }
-
-    fn hide_notecard(&mut self, id: NotecardId) -> notecognito_core::Result<()> {
-        let window_manager = Arc::clone(&self.window_manager);
-
-        let result = tokio::task::block_in_place(move || {
-            tokio::runtime::Handle::current().block_on(async move {
-                let mut manager = window_manager.lock().await;
-                manager.hide_notecard(id).await
-            })
-        });
-
-        result.map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
-    }
-
-    fn set_launch_on_startup(&mut self, enabled: bool) -> notecognito_core::Result<()> {
-        use core_foundation::array::CFArray;
-        use core_foundation::base::{CFType, TCFType};
-        use core_foundation::string::CFString;
-        use core_foundation::url::CFURL;
-        use std::ptr;
-
-        unsafe {
-            // Dynamic loading of LaunchServices framework
-            #[link(name = "CoreServices", kind = "framework")]
-            extern "C" {
-                fn LSSharedFileListCreate(
-                    allocator: core_foundation::base::CFAllocatorRef,
-                    list_type: CFStringRef,
-                    list_options: core_foundation::base::CFTypeRef,
-                ) -> core_foundation::base::CFTypeRef;
-
-                fn LSSharedFileListInsertItemURL(
-                    list: core_foundation::base::CFTypeRef,
-                    insert_after_item: core_foundation::base::CFTypeRef,
-                    name: core_foundation::string::CFStringRef,
-                    icon_ref: core_foundation::base::CFTypeRef,
-                    url: core_foundation::url::CFURLRef,
-                    properties: core_foundation::dictionary::CFDictionaryRef,
-                    items_to_add: core_foundation::array::CFArrayRef,
-                ) -> core_foundation::base::CFTypeRef;
-
-                fn LSSharedFileListItemRemove(
-                    list: core_foundation::base::CFTypeRef,
-                    item: core_foundation::base::CFTypeRef,
-                ) -> core_foundation::base::OSStatus;
-
-                fn LSSharedFileListCopySnapshot(
-                    list: core_foundation::base::CFTypeRef,
-                    seed: *mut u32,
-                ) -> core_foundation::array::CFArrayRef;
-
-                fn LSSharedFileListItemCopyResolvedURL(
-                    item: core_foundation::base::CFTypeRef,
-                    flags: u32,
-                    error: *mut CFErrorRef,
-                ) -> core_foundation::url::CFURLRef;
-            }
-
-            // Constants
-            let k_ls_shared_file_list_session_login_items =
-                CFString::from_static_string("com.apple.LSSharedFileList.SessionLoginItems");
-            let k_ls_shared_file_list_item_last =
-                core_foundation::base::kCFNull;
-
-            // Create login items list
-            let list = LSSharedFileListCreate(
-                ptr::null(),
-                k_ls_shared_file_list_session_login_items.as_concrete_TypeRef(),
-                ptr::null(),
-            );
-
-            if list.is_null() {
-                return Err(notecognito_core::NotecognitoError::Platform(
-                    "Failed to access login items".to_string(),
-                ));
-            }
-
-            // Get app URL
-            let app_path = std::env::current_exe()
-                .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))?;
+        let mut manager = self.window_manager.lock().await;
+        manager
+            .update_notecard(id, content, properties)
+            .await
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
 
-            // For .app bundles, get the bundle path
-            let bundle_path = if app_path.to_string_lossy().contains(".app/Contents/MacOS/") {
-                app_path
-                    .parent() // MacOS
-                    .and_then(|p| p.parent()) // Contents
-                    .and_then(|p| p.parent()) // .app
-                    .unwrap_or(&app_path)
-            } else {
-                &app_path
-            };
-
-            let app_url = CFURL::from_path(bundle_path, false)
-                .ok_or_else(|| notecognito_core::NotecognitoError::Platform(
-                    "Failed to create app URL".to_string(),
-                ))?;
-
-            if enabled {
-                // Add to login items
-                LSSharedFileListInsertItemURL(
-                    list,
-                    k_ls_shared_file_list_item_last,
-                    CFString::from_static_string("Notecognito").as_concrete_TypeRef(),
-                    ptr::null(),
-                    app_url.as_concrete_TypeRef(),
-                    ptr::null(),
-                    ptr::null(),
-                );
-            } else {
-                // Remove from login items
-                let mut seed: u32 = 0;
-                let items = LSSharedFileListCopySnapshot(list, &mut seed);
-
-                if !items.is_null() {
-                    let items_array = CFArray::<CFType>::wrap_under_create_rule(items);
-
-                    for i in 0..items_array.len() {
-                        let item = items_array.get(i).unwrap();
-                        let item_url = LSSharedFileListItemCopyResolvedURL(
-                            item.as_CFTypeRef(),
-                            0,
-                            ptr::null_mut(),
-                        );
-
-                        if !item_url.is_null() {
-                            let item_url = CFURL::wrap_under_create_rule(item_url);
-                            if item_url.to_path().unwrap() == bundle_path {
-                                LSSharedFileListItemRemove(list, item.as_CFTypeRef());
-                            }
-                        }
-                    }
-                }
-            }
+    async fn set_notecard_position(
+        &mut self,
+        id: NotecardId,
+        x: i32,
+        y: i32,
+    ) -> notecognito_core::Result<()> {
+        let mut manager = self.window_manager.lock().await;
+        if !manager.is_notecard_visible(id) {
+            return Err(notecognito_core::NotecognitoError::NotVisible(id));
+        }
+        manager
+            .set_notecard_position(id, x, y)
+            .await
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
 
-            Ok(())
+    async fn set_notecard_size(
+        &mut self,
+        id: NotecardId,
+        width: u32,
+        height: u32,
+    ) -> notecognito_core::Result<()> {
+        let mut manager = self.window_manager.lock().await;
+        if !manager.is_notecard_visible(id) {
+            return Err(notecognito_core::NotecognitoError::NotVisible(id));
         }
+        manager
+            .set_notecard_size(id, width, height)
+            .await
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn is_notecard_visible(&self, id: NotecardId) -> notecognito_core::Result<bool> {
+        let manager = self.window_manager.lock().await;
+        Ok(manager.is_notecard_visible(id))
+    }
+
+    async fn visible_notecards(&self) -> notecognito_core::Result<Vec<NotecardId>> {
+        let manager = self.window_manager.lock().await;
+        Ok(manager.visible_ids())
+    }
+
+    async fn screens(&self) -> notecognito_core::Result<Vec<ScreenInfo>> {
+        use dispatch::Queue;
+        use objc2_foundation::MainThreadMarker;
+
+        // NSScreen may only be used on the main thread
+        let screens = match MainThreadMarker::new() {
+            Some(mtm) => crate::screens::screens(mtm),
+            None => Queue::main().exec_sync(|| {
+                MainThreadMarker::new()
+                    .map(crate::screens::screens)
+                    .unwrap_or_default()
+            }),
+        };
+
+        Ok(screens)
+    }
+
+    async fn set_launch_on_startup(&mut self, enabled: bool) -> notecognito_core::Result<()> {
+        crate::login_item::set_enabled(enabled).map_err(notecognito_core::NotecognitoError::Platform)
+    }
+
+    async fn launch_on_startup_status(&self) -> notecognito_core::Result<Option<bool>> {
+        Ok(crate::login_item::is_enabled())
+    }
+
+    async fn read_clipboard_text(&self) -> notecognito_core::Result<Option<String>> {
+        Ok(crate::clipboard::read_text())
+    }
+
+    async fn write_clipboard_text(&mut self, text: &str) -> notecognito_core::Result<()> {
+        crate::clipboard::write_text(text)
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
     }
 
-    fn initialize(&mut self) -> notecognito_core::Result<()> {
+    async fn frontmost_application(&self) -> notecognito_core::Result<AppIdentity> {
+        use dispatch::Queue;
+        use objc2_foundation::MainThreadMarker;
+
+        // NSMenu may only be used on the main thread
+        let app = match MainThreadMarker::new() {
+            Some(mtm) => crate::foreground::frontmost_application(mtm),
+            None => Queue::main().exec_sync(|| {
+                MainThreadMarker::new().and_then(crate::foreground::frontmost_application)
+            }),
+        };
+
+        app.ok_or_else(|| {
+            notecognito_core::NotecognitoError::Platform("No frontmost application".to_string())
+        })
+    }
+
+    async fn show_notification(
+        &self,
+        title: &str,
+        body: &str,
+        kind: NotificationKind,
+    ) -> notecognito_core::Result<()> {
+        crate::notification::show(title, body, kind);
+        Ok(())
+    }
+
+    fn capabilities(&self) -> PlatformCapabilities {
+        crate::capabilities::detect()
+    }
+
+    async fn initialize(&mut self) -> notecognito_core::Result<()> {
         if self.initialized {
             return Ok(());
         }
@@ -226,28 +244,31 @@ impl PlatformInterface for MacOSPlatform {
         Ok(())
     }
 
-    fn cleanup(&mut self) -> notecognito_core::Result<()> {
-        let hotkey_manager = Arc::clone(&self.hotkey_manager);
-
-        let result = tokio::task::block_in_place(move || {
-            tokio::runtime::Handle::current().block_on(async move {
-                let mut manager = hotkey_manager.lock().await;
-                manager.unregister_all()
-            })
-        });
-
-        result.map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    async fn cleanup(&mut self) -> notecognito_core::Result<()> {
+        let mut manager = self.hotkey_manager.lock().await;
+        manager
+            .unregister_all()
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
     }
 
-    fn check_permissions(&self) -> notecognito_core::Result<bool> {
-        // For now, assume we have permissions
-        // In a real implementation, you'd check accessibility permissions
-        Ok(true)
+    async fn check_permissions(&self) -> notecognito_core::Result<bool> {
+        // Only the event tap needs accessibility access; this is the check it makes
+        if !self.hotkey_manager.lock().await.backend().needs_accessibility() {
+            return Ok(true);
+        }
+        Ok(HotkeyManager::accessibility_trusted())
     }
 
-    fn request_permissions(&self) -> notecognito_core::Result<()> {
-        // For now, just return success
-        // In a real implementation, you'd request accessibility permissions
-        Ok(())
+    async fn request_permissions(&self) -> notecognito_core::Result<()> {
+        if !self.hotkey_manager.lock().await.backend().needs_accessibility() {
+            return Ok(());
+        }
+        if HotkeyManager::check_accessibility_permissions() {
+            Ok(())
+        } else {
+            Err(notecognito_core::NotecognitoError::PermissionDenied(
+                "Accessibility access not granted".to_string(),
+            ))
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,68 @@
+use notecognito_core::{layout, ScreenInfo};
+use objc2_app_kit::NSScreen;
+use objc2_foundation::{CGFloat, CGPoint, CGRect, MainThreadMarker};
+
+/// Enumerates screens in `NSScreen::screens` order; the first one holds the menu bar
+///
+/// Cocoa places the origin at the bottom-left of the primary screen with y
+/// growing upwards, so frames are flipped into core's top-left convention.
+pub fn screens(mtm: MainThreadMarker) -> Vec<ScreenInfo> {
+    let primary_height = primary_height(mtm);
+
+    unsafe {
+        let screens = NSScreen::screens(mtm);
+
+        (0..screens.count())
+            .map(|index| {
+                let screen = screens.objectAtIndex(index);
+                ScreenInfo {
+                    index,
+                    frame: to_top_left(screen.frame(), primary_height),
+                    work_area: to_top_left(screen.visibleFrame(), primary_height),
+                    scale_factor: screen.backingScaleFactor() as f64,
+                    is_primary: index == 0,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Height of the primary screen, which Cocoa's y axis is measured from
+pub fn primary_height(mtm: MainThreadMarker) -> CGFloat {
+    unsafe {
+        let screens = NSScreen::screens(mtm);
+        if screens.count() == 0 {
+            return 0.0;
+        }
+        screens.objectAtIndex(0).frame().size.height
+    }
+}
+
+/// Mouse cursor position in core's top-left screen space
+pub fn cursor_position(mtm: MainThreadMarker) -> (i32, i32) {
+    let location = unsafe { objc2_app_kit::NSEvent::mouseLocation() };
+    from_cocoa_point(location, primary_height(mtm))
+}
+
+/// Converts a point in Cocoa's global space, whose y grows upwards from the
+/// bottom of the primary screen, into core's top-left space
+///
+/// Screens above the primary one end up with negative y, screens below it
+/// with y past the primary's height.
+pub fn from_cocoa_point(point: CGPoint, primary_height: CGFloat) -> (i32, i32) {
+    layout::from_bottom_left_point((point.x, point.y), primary_height)
+}
+
+/// Converts a top-left window origin into the Cocoa frame origin for a window of `height`
+pub fn to_cocoa_origin(position: (i32, i32), height: u32, primary_height: CGFloat) -> CGPoint {
+    let (x, y) = layout::to_bottom_left_origin(position, height, primary_height);
+    CGPoint::new(x, y)
+}
+
+fn to_top_left(rect: CGRect, primary_height: CGFloat) -> (i32, i32, u32, u32) {
+    layout::from_bottom_left_rect(
+        (rect.origin.x, rect.origin.y),
+        (rect.size.width, rect.size.height),
+        primary_height,
+    )
+}
@@ -0,0 +1,107 @@
+use objc2_app_kit::NSRunningApplication;
+use objc2_foundation::{NSBundle, NSDistributedNotificationCenter, NSString};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Posted by a second launch; the running copy opens its menu so the user sees it
+pub const ACTIVATE_NOTIFICATION: &str = "com.notecognito.macos.activate";
+
+extern "C" {
+    fn kill(pid: i32, signal: i32) -> i32;
+}
+
+/// Claims this launch as the only running copy of Notecognito
+///
+/// Returns false when another copy is running, after asking it to show itself.
+/// Bundled copies are found by bundle identifier; the lock file catches dev
+/// builds started with `cargo run`, which have no bundle.
+pub fn claim() -> bool {
+    if let Some(pid) = running_bundle_copy().or_else(|| claim_lock_file().err()) {
+        tracing::info!("Notecognito is already running as process {}", pid);
+        ask_to_activate();
+        return false;
+    }
+    true
+}
+
+/// Removes the lock file, if this process holds it; called on normal exit
+pub fn release() {
+    if read_lock_file() == Some(std::process::id() as i32) {
+        if let Err(e) = fs::remove_file(lock_path()) {
+            tracing::warn!("Could not remove the lock file: {}", e);
+        }
+    }
+}
+
+/// Another process running this app's bundle, if there is one
+fn running_bundle_copy() -> Option<i32> {
+    let bundle_id = unsafe { NSBundle::mainBundle().bundleIdentifier() }?;
+    let own_pid = std::process::id() as i32;
+
+    let running = unsafe { NSRunningApplication::runningApplicationsWithBundleIdentifier(&bundle_id) };
+    running
+        .iter()
+        .map(|app| unsafe { app.processIdentifier() })
+        .find(|&pid| pid != own_pid)
+}
+
+/// Writes this process's id to the lock file, failing with the holder's id
+/// when a live process already holds it; a stale file is replaced
+fn claim_lock_file() -> Result<(), i32> {
+    let path = lock_path();
+    let own_pid = std::process::id() as i32;
+
+    // The second pass runs after a stale file was removed
+    for _ in 0..2 {
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                if let Err(e) = write!(file, "{}", own_pid) {
+                    tracing::warn!("Could not write the lock file: {}", e);
+                }
+                return Ok(());
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => match read_lock_file() {
+                Some(pid) if pid != own_pid && is_alive(pid) => return Err(pid),
+                _ => {
+                    tracing::debug!("Replacing a stale lock file");
+                    let _ = fs::remove_file(&path);
+                }
+            },
+            Err(e) => {
+                // Not worth refusing to start over
+                tracing::warn!("Could not create the lock file {}: {}", path.display(), e);
+                return Ok(());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn read_lock_file() -> Option<i32> {
+    fs::read_to_string(lock_path()).ok()?.trim().parse().ok()
+}
+
+/// In the per-user temporary directory, which is cleared on restart
+fn lock_path() -> PathBuf {
+    std::env::temp_dir().join("notecognito-macos.lock")
+}
+
+/// Whether a process with this id exists
+fn is_alive(pid: i32) -> bool {
+    // Signal 0 only checks that the process could be signalled
+    pid > 0 && unsafe { kill(pid, 0) } == 0
+}
+
+/// Tells the running copy a second launch happened
+fn ask_to_activate() {
+    unsafe {
+        NSDistributedNotificationCenter::defaultCenter().postNotificationName_object_userInfo_deliverImmediately(
+            &NSString::from_str(ACTIVATE_NOTIFICATION),
+            None,
+            None,
+            true,
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use notecognito_core::PlatformCapabilities;
+use std::mem;
+use windows::Wdk::System::SystemServices::RtlGetVersion;
+use windows::Win32::System::SystemInformation::OSVERSIONINFOW;
+
+/// First build with DWM rounded corners (Windows 11 21H2)
+const ROUNDED_CORNERS_BUILD: u32 = 22000;
+/// First build with `DWMWA_SYSTEMBACKDROP_TYPE` (Windows 11 22H2)
+const SYSTEM_BACKDROP_BUILD: u32 = 22621;
+/// First build honoring `WDA_EXCLUDEFROMCAPTURE` (Windows 10 2004)
+const CAPTURE_EXCLUSION_BUILD: u32 = 19041;
+
+/// Detects which optional display features this version of Windows has
+pub fn detect() -> PlatformCapabilities {
+    let Some((major, minor, build)) = os_version() else {
+        return PlatformCapabilities::conservative();
+    };
+
+    let windows_10 = major >= 10;
+
+    PlatformCapabilities {
+        os_version: Some(format!("{}.{}.{}", major, minor, build)),
+        blur: windows_10 && build >= SYSTEM_BACKDROP_BUILD,
+        rounded_corners: windows_10 && build >= ROUNDED_CORNERS_BUILD,
+        // GetDpiForMonitor arrived in Windows 8.1
+        per_monitor_dpi: (major, minor) >= (6, 3),
+        screen_capture_exclusion: windows_10 && build >= CAPTURE_EXCLUSION_BUILD,
+        ..PlatformCapabilities::conservative()
+    }
+}
+
+/// Gets the (major, minor, build) version of Windows
+fn os_version() -> Option<(u32, u32, u32)> {
+    let mut info = OSVERSIONINFOW {
+        dwOSVersionInfoSize: mem::size_of::<OSVERSIONINFOW>() as u32,
+        ..Default::default()
+    };
+
+    // Unlike GetVersionEx, this isn't capped at the version in our manifest
+    let status = unsafe { RtlGetVersion(&mut info) };
+    status
+        .is_ok()
+        .then_some((info.dwMajorVersion, info.dwMinorVersion, info.dwBuildNumber))
+}
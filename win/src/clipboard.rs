@@ -0,0 +1,93 @@
+use anyhow::{anyhow, Result};
+use std::iter;
+use std::ptr;
+use std::thread;
+use std::time::Duration;
+use windows::Win32::{
+    Foundation::*,
+    System::DataExchange::*,
+    System::Memory::*,
+};
+
+/// `CF_UNICODETEXT`, NUL-terminated UTF-16
+const CF_UNICODETEXT: u32 = 13;
+/// Other apps hold the clipboard open briefly while they use it, so opening retries
+const OPEN_ATTEMPTS: u32 = 10;
+const OPEN_RETRY_DELAY: Duration = Duration::from_millis(10);
+
+/// Keeps the clipboard open and closes it when dropped, including on early returns
+struct OpenClipboardGuard;
+
+impl OpenClipboardGuard {
+    fn open() -> Result<Self> {
+        for attempt in 1..=OPEN_ATTEMPTS {
+            match unsafe { OpenClipboard(HWND::default()) } {
+                Ok(()) => return Ok(OpenClipboardGuard),
+                Err(e) if attempt == OPEN_ATTEMPTS => {
+                    return Err(anyhow!("Clipboard is in use by another application: {}", e));
+                }
+                Err(_) => thread::sleep(OPEN_RETRY_DELAY),
+            }
+        }
+        unreachable!()
+    }
+}
+
+impl Drop for OpenClipboardGuard {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = CloseClipboard();
+        }
+    }
+}
+
+/// Reads the clipboard as text; `None` when it holds something else
+pub fn read_text() -> Result<Option<String>> {
+    let _clipboard = OpenClipboardGuard::open()?;
+
+    unsafe {
+        if IsClipboardFormatAvailable(CF_UNICODETEXT).is_err() {
+            return Ok(None);
+        }
+
+        let data = GetClipboardData(CF_UNICODETEXT)?;
+        let global = HGLOBAL(data.0 as _);
+        let text = GlobalLock(global) as *const u16;
+        if text.is_null() {
+            return Err(anyhow!("Failed to lock clipboard data"));
+        }
+
+        let len = (0..).take_while(|&i| *text.add(i) != 0).count();
+        let result = String::from_utf16_lossy(std::slice::from_raw_parts(text, len));
+        let _ = GlobalUnlock(global);
+
+        Ok(Some(result))
+    }
+}
+
+/// Replaces the clipboard contents with text
+pub fn write_text(text: &str) -> Result<()> {
+    let wide: Vec<u16> = text.encode_utf16().chain(iter::once(0)).collect();
+    let _clipboard = OpenClipboardGuard::open()?;
+
+    unsafe {
+        EmptyClipboard()?;
+
+        let global = GlobalAlloc(GMEM_MOVEABLE, wide.len() * std::mem::size_of::<u16>())?;
+        let buffer = GlobalLock(global) as *mut u16;
+        if buffer.is_null() {
+            let _ = GlobalFree(global);
+            return Err(anyhow!("Failed to lock clipboard memory"));
+        }
+        ptr::copy_nonoverlapping(wide.as_ptr(), buffer, wide.len());
+        let _ = GlobalUnlock(global);
+
+        // The clipboard owns the memory once this succeeds
+        if let Err(e) = SetClipboardData(CF_UNICODETEXT, HANDLE(global.0 as _)) {
+            let _ = GlobalFree(global);
+            return Err(e.into());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,168 @@
+use notecognito_core::NotificationKind;
+use std::ffi::c_void;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use windows::core::{w, PCWSTR};
+use windows::Win32::System::Registry::*;
+
+const CONFIG_UI_EXE: &str = "notecognito-config.exe";
+/// Where an installer registers the config UI, under HKCU and then HKLM
+const APP_PATHS_KEY: PCWSTR = w!("Software\\Microsoft\\Windows\\CurrentVersion\\App Paths\\notecognito-config.exe");
+
+/// Launches the Electron configuration UI from the first place it's found
+///
+/// When it's nowhere to be found, a notification lists the places tried and
+/// the folder holding `config_path` is opened, so the configuration can at
+/// least be edited by hand.
+pub fn launch(config_path: &Path) {
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_path_buf));
+    let program_files = std::env::var_os("ProgramFiles").map(PathBuf::from);
+    let candidates = candidates(exe_dir.as_deref(), registered_paths(), program_files.as_deref());
+
+    for path in candidates.iter().filter(|path| path.is_file()) {
+        match Command::new(path).spawn() {
+            Ok(_) => {
+                tracing::info!("Launched the config UI from {}", path.display());
+                return;
+            }
+            Err(e) => tracing::warn!("Failed to launch the config UI at {}: {}", path.display(), e),
+        }
+    }
+
+    tracing::error!("Could not launch the config UI from any of {:?}", candidates);
+
+    let tried: Vec<String> = candidates.iter().map(|path| path.display().to_string()).collect();
+    let body = format!(
+        "{} couldn't be started. Looked in:\n{}\nOpening the configuration file's folder instead.",
+        CONFIG_UI_EXE,
+        tried.join("\n")
+    );
+    if let Err(e) = crate::notification::show_toast("Can't open the configuration UI", &body, NotificationKind::Warning) {
+        tracing::warn!("Could not show the config UI notification: {}", e);
+    }
+
+    reveal(config_path);
+}
+
+/// Places to look for the config UI, in order: next to this executable,
+/// where an installer registered it, then the default install folder under
+/// Program Files; each place once
+pub fn candidates(exe_dir: Option<&Path>, registered: Vec<PathBuf>, program_files: Option<&Path>) -> Vec<PathBuf> {
+    let all = exe_dir
+        .map(|dir| dir.join(CONFIG_UI_EXE))
+        .into_iter()
+        .chain(registered)
+        .chain(program_files.map(|dir| dir.join("Notecognito").join(CONFIG_UI_EXE)));
+
+    let mut candidates = Vec::new();
+    for path in all {
+        if !candidates.contains(&path) {
+            candidates.push(path);
+        }
+    }
+    candidates
+}
+
+/// The config UI's App Paths registrations, the current user's first
+fn registered_paths() -> Vec<PathBuf> {
+    [HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE]
+        .into_iter()
+        .filter_map(|root| registry_string(root, APP_PATHS_KEY))
+        // Installers may quote the path
+        .map(|path| PathBuf::from(path.trim().trim_matches('"')))
+        .filter(|path| !path.as_os_str().is_empty())
+        .collect()
+}
+
+/// The default value of a registry key, when it's a string
+fn registry_string(root: HKEY, subkey: PCWSTR) -> Option<String> {
+    unsafe {
+        // Asked for the size first, in bytes including the terminator
+        let mut size = 0u32;
+        RegGetValueW(root, subkey, None, RRF_RT_REG_SZ, None, None, Some(&mut size))
+            .ok()
+            .ok()?;
+
+        let mut buffer = vec![0u16; (size as usize).div_ceil(2)];
+        RegGetValueW(
+            root,
+            subkey,
+            None,
+            RRF_RT_REG_SZ,
+            None,
+            Some(buffer.as_mut_ptr() as *mut c_void),
+            Some(&mut size),
+        )
+        .ok()
+        .ok()?;
+
+        let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+        Some(String::from_utf16_lossy(&buffer[..len]))
+    }
+}
+
+/// Opens Explorer on the configuration file, or its folder when there's no
+/// file yet
+fn reveal(config_path: &Path) {
+    let result = if config_path.is_file() {
+        Command::new("explorer.exe")
+            .arg(format!("/select,{}", config_path.display()))
+            .spawn()
+    } else {
+        let folder = config_path.parent().unwrap_or(config_path);
+        Command::new("explorer.exe").arg(folder).spawn()
+    };
+
+    if let Err(e) = result {
+        tracing::warn!("Could not open the configuration folder: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looks_next_to_the_exe_then_the_registry_then_program_files() {
+        let registered = vec![
+            PathBuf::from(r"C:\Users\me\AppData\Local\Notecognito\notecognito-config.exe"),
+            PathBuf::from(r"D:\Apps\Notecognito\notecognito-config.exe"),
+        ];
+
+        let found = candidates(Some(Path::new(r"C:\Portable")), registered, Some(Path::new(r"C:\Program Files")));
+
+        assert_eq!(
+            found,
+            vec![
+                PathBuf::from(r"C:\Portable\notecognito-config.exe"),
+                PathBuf::from(r"C:\Users\me\AppData\Local\Notecognito\notecognito-config.exe"),
+                PathBuf::from(r"D:\Apps\Notecognito\notecognito-config.exe"),
+                PathBuf::from(r"C:\Program Files\Notecognito\notecognito-config.exe"),
+            ]
+        );
+    }
+
+    #[test]
+    fn each_place_is_tried_once() {
+        let installed = PathBuf::from(r"C:\Program Files\Notecognito\notecognito-config.exe");
+
+        let found = candidates(
+            Some(Path::new(r"C:\Program Files\Notecognito")),
+            vec![installed.clone(), installed.clone()],
+            Some(Path::new(r"C:\Program Files")),
+        );
+
+        assert_eq!(found, vec![installed]);
+    }
+
+    #[test]
+    fn places_that_are_unknown_are_left_out() {
+        assert_eq!(candidates(None, Vec::new(), None), Vec::<PathBuf>::new());
+        assert_eq!(
+            candidates(None, Vec::new(), Some(Path::new(r"C:\Program Files"))),
+            vec![PathBuf::from(r"C:\Program Files\Notecognito\notecognito-config.exe")]
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use crate::notecard_window::{dismiss_most_recent, WindowMap};
+use anyhow::{anyhow, Result};
+use notecognito_core::NotecardId;
+use std::cell::{Cell, RefCell};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use windows::Win32::{
+    Foundation::*,
+    System::Threading::GetCurrentThreadId,
+    UI::Input::KeyboardAndMouse::VK_ESCAPE,
+    UI::WindowsAndMessaging::*,
+};
+
+/// The thread the keyboard hook runs on, while one is installed
+static HOOK_THREAD: Mutex<Option<HookThread>> = Mutex::new(None);
+
+struct HookThread {
+    thread_id: u32,
+    handle: JoinHandle<()>,
+}
+
+thread_local! {
+    /// Open windows, for the hook on this thread to pick the card Escape closes
+    static WATCHED: RefCell<Option<WindowMap>> = const { RefCell::new(None) };
+    /// Whether the Escape press being held closed a card, so its repeats and
+    /// release are swallowed along with it
+    static SWALLOWING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// What picking the card Escape closes needs to know about an open one
+#[derive(Debug, Clone, Copy)]
+pub struct OpenCard {
+    pub notecard_id: NotecardId,
+    /// Order the card was shown in
+    pub shown: u64,
+    pub click_through: bool,
+}
+
+/// The card Escape closes: the one shown last, passing over click-through
+/// cards, which sit over other work where Escape means something else
+pub fn escape_target(cards: impl IntoIterator<Item = OpenCard>) -> Option<NotecardId> {
+    cards
+        .into_iter()
+        .filter(|card| !card.click_through)
+        .max_by_key(|card| card.shown)
+        .map(|card| card.notecard_id)
+}
+
+/// Installs the Escape hook while `windows` has a card in it, and removes it
+/// as soon as the last one goes
+///
+/// Notecards are created with `WS_EX_NOACTIVATE` and never get keyboard focus,
+/// so Escape is caught with a low-level keyboard hook instead. It only exists
+/// while a card is on screen, so other apps get their Escape back right away.
+pub fn sync(windows: &WindowMap) {
+    let mut hook = HOOK_THREAD.lock().unwrap();
+
+    // Checked under the hook lock, so a show and a close racing can't leave a
+    // card open without the hook
+    let open = !windows.lock().unwrap().is_empty();
+
+    match (open, hook.is_some()) {
+        (true, false) => match start(Arc::clone(windows)) {
+            Ok(thread) => {
+                tracing::debug!("Installed the Escape hook");
+                *hook = Some(thread);
+            }
+            Err(e) => tracing::warn!("Escape will not dismiss notecards: {}", e),
+        },
+        (false, true) => {
+            if let Some(thread) = hook.take() {
+                stop(thread);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Installs the hook on a thread of its own, which pumps the messages the
+/// system delivers keystrokes to it with
+fn start(windows: WindowMap) -> Result<HookThread> {
+    let (ready_tx, ready_rx) = mpsc::channel();
+
+    let handle = thread::spawn(move || unsafe {
+        WATCHED.with(|watched| *watched.borrow_mut() = Some(windows));
+
+        // Makes sure the thread has a message queue before anyone posts to it
+        let mut msg = MSG::default();
+        let _ = PeekMessageW(&mut msg, HWND::default(), WM_USER, WM_USER, PM_NOREMOVE);
+
+        let hook = match SetWindowsHookExW(WH_KEYBOARD_LL, Some(escape_hook_proc), None, 0) {
+            Ok(hook) => hook,
+            Err(e) => {
+                let _ = ready_tx.send(Err(anyhow!("Could not install the keyboard hook: {}", e)));
+                return;
+            }
+        };
+        let _ = ready_tx.send(Ok(GetCurrentThreadId()));
+
+        // Runs until `stop` posts WM_QUIT
+        while GetMessageW(&mut msg, HWND::default(), 0, 0).0 > 0 {
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+
+        if let Err(e) = UnhookWindowsHookEx(hook) {
+            tracing::warn!("Could not remove the keyboard hook: {}", e);
+        }
+    });
+
+    let thread_id = ready_rx
+        .recv()
+        .map_err(|_| anyhow!("The keyboard hook thread exited"))??;
+
+    Ok(HookThread { thread_id, handle })
+}
+
+/// Ends the hook thread's message loop, which removes the hook, and waits for it
+fn stop(thread: HookThread) {
+    unsafe {
+        if let Err(e) = PostThreadMessageW(thread.thread_id, WM_QUIT, WPARAM(0), LPARAM(0)) {
+            tracing::warn!("Could not stop the keyboard hook thread: {}", e);
+            return;
+        }
+    }
+
+    if thread.handle.join().is_err() {
+        tracing::warn!("The keyboard hook thread panicked");
+    }
+    tracing::debug!("Removed the Escape hook");
+}
+
+unsafe extern "system" fn escape_hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == HC_ACTION as i32 {
+        let event = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        if event.vkCode == VK_ESCAPE.0 as u32 && handle_escape(wparam.0 as u32) {
+            // Swallowed, so the app in front doesn't act on it as well
+            return LRESULT(1);
+        }
+    }
+
+    CallNextHookEx(None, code, wparam, lparam)
+}
+
+/// Closes the most recent card on an Escape press; returns whether to swallow
+/// the key message
+fn handle_escape(message: u32) -> bool {
+    match message {
+        WM_KEYDOWN | WM_SYSKEYDOWN => {
+            // Repeats while held only ever close the one card
+            if SWALLOWING.with(Cell::get) {
+                return true;
+            }
+
+            let closed = WATCHED.with(|watched| watched.borrow().as_ref().is_some_and(dismiss_most_recent));
+            if closed {
+                tracing::debug!("Escape dismissed a notecard");
+            }
+            SWALLOWING.with(|swallowing| swallowing.set(closed));
+            closed
+        }
+        WM_KEYUP | WM_SYSKEYUP => SWALLOWING.with(|swallowing| swallowing.replace(false)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(id: u8, shown: u64, click_through: bool) -> OpenCard {
+        OpenCard { notecard_id: NotecardId::new(id).unwrap(), shown, click_through }
+    }
+
+    fn target(cards: &[OpenCard]) -> Option<u8> {
+        escape_target(cards.iter().copied()).map(|id| id.value())
+    }
+
+    #[test]
+    fn nothing_open_means_nothing_to_close() {
+        assert_eq!(target(&[]), None);
+    }
+
+    #[test]
+    fn closes_the_card_shown_last_whatever_the_order() {
+        assert_eq!(target(&[card(1, 3, false), card(2, 7, false), card(3, 5, false)]), Some(2));
+        assert_eq!(target(&[card(3, 5, false), card(2, 7, false), card(1, 3, false)]), Some(2));
+    }
+
+    #[test]
+    fn passes_over_click_through_cards() {
+        assert_eq!(target(&[card(1, 3, false), card(2, 7, true)]), Some(1));
+    }
+
+    #[test]
+    fn only_click_through_cards_leave_escape_alone() {
+        assert_eq!(target(&[card(1, 3, true), card(2, 7, true)]), None);
+    }
+}
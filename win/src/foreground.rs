@@ -0,0 +1,82 @@
+use anyhow::{anyhow, Result};
+use notecognito_core::AppIdentity;
+use std::mem;
+use std::path::Path;
+use windows::core::PWSTR;
+use windows::Win32::{
+    Foundation::*,
+    Graphics::Gdi::*,
+    System::Threading::*,
+    UI::WindowsAndMessaging::*,
+};
+
+/// Identifies the application owning the foreground window
+pub fn frontmost_application() -> Result<AppIdentity> {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0 == 0 {
+            return Err(anyhow!("No foreground window"));
+        }
+
+        let mut process_id = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+
+        let path = process_image_path(process_id)?;
+        let name = Path::new(&path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.clone());
+
+        Ok(AppIdentity {
+            name,
+            identifier: Some(path),
+            is_fullscreen: is_fullscreen(hwnd),
+        })
+    }
+}
+
+/// Gets the executable path of a process
+unsafe fn process_image_path(process_id: u32) -> Result<String> {
+    // Limited access is enough for the image name and works on elevated processes
+    let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id)?;
+
+    let mut buffer = [0u16; MAX_PATH as usize];
+    let mut len = buffer.len() as u32;
+    let result = QueryFullProcessImageNameW(
+        process,
+        PROCESS_NAME_WIN32,
+        PWSTR(buffer.as_mut_ptr()),
+        &mut len,
+    );
+    let _ = CloseHandle(process);
+    result?;
+
+    Ok(String::from_utf16_lossy(&buffer[..len as usize]))
+}
+
+/// Whether a window covers its whole monitor; the desktop and shell never count
+unsafe fn is_fullscreen(hwnd: HWND) -> bool {
+    if hwnd == GetDesktopWindow() || hwnd == GetShellWindow() {
+        return false;
+    }
+
+    let mut window_rect = RECT::default();
+    if GetWindowRect(hwnd, &mut window_rect).is_err() {
+        return false;
+    }
+
+    let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+    let mut info = MONITORINFO {
+        cbSize: mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    if !GetMonitorInfoW(monitor, &mut info).as_bool() {
+        return false;
+    }
+
+    let monitor_rect = info.rcMonitor;
+    window_rect.left <= monitor_rect.left
+        && window_rect.top <= monitor_rect.top
+        && window_rect.right >= monitor_rect.right
+        && window_rect.bottom >= monitor_rect.bottom
+}
@@ -1,25 +1,68 @@
-use anyhow::{anyhow, Result};
-use notecognito_core::{HotkeyModifier, NotecardId};
-use std::collections::HashMap;
-use std::sync::Arc;
+use anyhow::{anyhow, Context, Result};
+use notecognito_core::{HotkeyAction, HotkeyBinding, HotkeyChanges, HotkeyKey, HotkeyModifier, NotecardId};
+use std::collections::{HashMap, HashSet};
+use std::sync::{mpsc, Arc};
 use std::thread;
+use std::time::{Duration, Instant};
 use windows::Win32::{
     Foundation::*,
+    System::Threading::GetCurrentThreadId,
     UI::Input::KeyboardAndMouse::*,
     UI::WindowsAndMessaging::*,
 };
 
+/// Notecard hotkeys use this plus the notecard id; the hide-all hotkey uses it as is
 const HOTKEY_BASE_ID: i32 = 1000;
 
+/// Carries a boxed `Command` in LPARAM to the message thread
+const WM_HOTKEY_COMMAND: u32 = WM_APP + 1;
+
+/// How long `shutdown` waits for the message thread to finish
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
 pub struct HotkeyManager {
-    registered_hotkeys: HashMap<NotecardId, i32>,
-    message_thread: Option<thread::JoinHandle<()>>,
+    registered_hotkeys: HashMap<HotkeyAction, HotkeyBinding>,
+    /// Hotkeys the last `sync_bindings` couldn't register
+    failed_hotkeys: Vec<FailedHotkey>,
+    message_thread: Option<MessageThread>,
+}
+
+/// A hotkey that couldn't be registered, and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedHotkey {
+    pub action: HotkeyAction,
+    pub binding: HotkeyBinding,
+    pub reason: String,
+}
+
+/// The thread pumping WM_HOTKEY, with its id for posting WM_QUIT to
+struct MessageThread {
+    thread_id: u32,
+    handle: thread::JoinHandle<()>,
+}
+
+/// Registration work for the message thread
+///
+/// Hotkeys registered without a window post WM_HOTKEY to the thread that
+/// registered them, so they're all registered on the thread that pumps it.
+enum Command {
+    Register {
+        hotkey_id: i32,
+        modifiers: HOT_KEY_MODIFIERS,
+        key: VIRTUAL_KEY,
+        done: mpsc::Sender<windows::core::Result<()>>,
+    },
+    Unregister {
+        hotkey_id: i32,
+        done: mpsc::Sender<bool>,
+    },
 }
 
 impl HotkeyManager {
     pub fn new() -> Self {
         HotkeyManager {
             registered_hotkeys: HashMap::new(),
+            failed_hotkeys: Vec::new(),
             message_thread: None,
         }
     }
@@ -28,9 +71,36 @@ impl HotkeyManager {
         &mut self,
         notecard_id: NotecardId,
         modifiers: &[HotkeyModifier],
+        key: HotkeyKey,
     ) -> Result<()> {
-        // Convert modifiers to Windows format
-        let mut win_modifiers = HOT_KEY_MODIFIERS::default();
+        self.register(HotkeyAction::ToggleNotecard(notecard_id), modifiers, key)?;
+        tracing::info!(
+            "Registered hotkey {} for notecard {}",
+            HotkeyBinding::new(modifiers, key),
+            notecard_id.value()
+        );
+        Ok(())
+    }
+
+    /// Registers the panic hotkey, the notecard modifiers with 0
+    pub fn register_hide_all_hotkey(&mut self, modifiers: &[HotkeyModifier]) -> Result<()> {
+        let key = HotkeyKey::hide_all();
+        self.register(HotkeyAction::HideAll, modifiers, key)?;
+        tracing::info!("Registered hotkey {} to hide all notecards", HotkeyBinding::new(modifiers, key));
+        Ok(())
+    }
+
+    /// Registers with `RegisterHotKey`, which only fires when exactly these
+    /// modifiers are held, so `strict_modifier_match` holds by construction
+    ///
+    /// Fails on a modifier Windows doesn't have, such as Command from a
+    /// configuration saved on macOS, rather than registering the combination
+    /// without it.
+    fn register(&mut self, action: HotkeyAction, modifiers: &[HotkeyModifier], key: HotkeyKey) -> Result<()> {
+        HotkeyModifier::check_supported(modifiers)?;
+
+        // Convert modifiers to Windows format; a held-down hotkey toggles once, not on every repeat
+        let mut win_modifiers = MOD_NOREPEAT;
 
         for modifier in modifiers {
             win_modifiers |= match modifier {
@@ -38,55 +108,108 @@ impl HotkeyManager {
                 HotkeyModifier::Alt => MOD_ALT,
                 HotkeyModifier::Shift => MOD_SHIFT,
                 HotkeyModifier::Windows => MOD_WIN,
+                HotkeyModifier::Command => unreachable!("rejected by check_supported above"),
             };
         }
 
-        // Virtual key code for numbers 1-9
-        let vk_code = VIRTUAL_KEY((0x30 + notecard_id.value()) as u16);
+        let vk_code = key
+            .to_windows_vk()
+            .map(VIRTUAL_KEY)
+            .ok_or_else(|| anyhow!("Key {} has no Windows virtual-key code", key))?;
 
-        // Generate unique ID for this hotkey
-        let hotkey_id = HOTKEY_BASE_ID + notecard_id.value() as i32;
+        // A combination already bound to this action is replaced
+        self.unregister(action)?;
 
-        // Register the hotkey
-        unsafe {
-            if !RegisterHotKey(HWND::default(), hotkey_id, win_modifiers, vk_code).as_bool() {
-                return Err(anyhow!("Failed to register hotkey for notecard {}", notecard_id.value()));
-            }
-        }
+        self.send(|done| Command::Register {
+            hotkey_id: hotkey_id(action),
+            modifiers: win_modifiers,
+            key: vk_code,
+            done,
+        })?
+        .map_err(|e| anyhow!(failure_reason(&e)))
+        .with_context(|| format!("Failed to register hotkey {}", HotkeyBinding::new(modifiers, key)))?;
 
-        self.registered_hotkeys.insert(notecard_id, hotkey_id);
-        tracing::info!("Registered hotkey for notecard {}", notecard_id.value());
+        self.registered_hotkeys.insert(action, HotkeyBinding::new(modifiers, key));
 
         Ok(())
     }
 
     pub fn unregister_hotkey(&mut self, notecard_id: NotecardId) -> Result<()> {
-        if let Some(hotkey_id) = self.registered_hotkeys.remove(&notecard_id) {
-            unsafe {
-                UnregisterHotKey(HWND::default(), hotkey_id)?;
+        self.unregister(HotkeyAction::ToggleNotecard(notecard_id))
+    }
+
+    fn unregister(&mut self, action: HotkeyAction) -> Result<()> {
+        if self.registered_hotkeys.remove(&action).is_some() {
+            let unregistered = self.send(|done| Command::Unregister { hotkey_id: hotkey_id(action), done })?;
+            if !unregistered {
+                return Err(anyhow!("Failed to unregister the hotkey for {:?}", action));
             }
         }
         Ok(())
     }
 
     pub fn unregister_all(&mut self) -> Result<()> {
-        for (_, hotkey_id) in self.registered_hotkeys.drain() {
-            unsafe {
-                let _ = UnregisterHotKey(HWND::default(), hotkey_id);
-            }
+        let actions: Vec<_> = self.registered_hotkeys.keys().copied().collect();
+        for action in actions {
+            let _ = self.unregister(action);
         }
         Ok(())
     }
 
+    /// Registers and unregisters hotkeys until exactly `wanted` is bound,
+    /// returning the ones that couldn't be registered
+    ///
+    /// Unchanged hotkeys are left registered, so running this again, or while
+    /// hotkeys are being pressed, never drops a press. A hotkey that fails to
+    /// register is skipped so it doesn't cost the others, and is tried again
+    /// on the next sync.
+    pub fn sync_bindings(&mut self, wanted: &HashMap<HotkeyAction, HotkeyBinding>) -> Vec<FailedHotkey> {
+        let changes = HotkeyChanges::between(&self.registered_hotkeys, wanted);
+        let mut failed = Vec::new();
+
+        for action in changes.unregister {
+            match self.unregister(action) {
+                Ok(()) => tracing::info!("Unregistered hotkey for {:?}", action),
+                Err(e) => tracing::warn!("{}", e),
+            }
+        }
+
+        for (action, binding) in changes.register {
+            let result = match action {
+                HotkeyAction::ToggleNotecard(id) => self.register_hotkey(id, &binding.modifiers, binding.key),
+                HotkeyAction::HideAll => self.register_hide_all_hotkey(&binding.modifiers),
+            };
+            if let Err(e) = result {
+                tracing::warn!("Could not register hotkey {} for {:?}: {:#}", binding, action, e);
+                failed.push(FailedHotkey { action, binding, reason: e.root_cause().to_string() });
+            }
+        }
+
+        self.failed_hotkeys = failed.clone();
+        failed
+    }
+
+    /// Hotkeys the last `sync_bindings` couldn't register
+    pub fn failed_hotkeys(&self) -> &[FailedHotkey] {
+        &self.failed_hotkeys
+    }
+
     pub fn start_message_loop<F>(&mut self, callback: F) -> Result<()>
     where
-        F: Fn(NotecardId) + Send + 'static,
+        F: Fn(HotkeyAction) + Send + 'static,
     {
         let callback = Arc::new(callback);
+        let (ready_tx, ready_rx) = mpsc::channel();
 
         let handle = thread::spawn(move || {
             unsafe {
                 let mut msg = MSG::default();
+                // Ids registered on this thread, which only it can unregister
+                let mut registered = HashSet::new();
+
+                // Makes sure the thread has a message queue before anyone posts to it
+                let _ = PeekMessageW(&mut msg, HWND::default(), WM_USER, WM_USER, PM_NOREMOVE);
+                let _ = ready_tx.send(GetCurrentThreadId());
 
                 loop {
                     let result = GetMessageW(&mut msg, HWND::default(), 0, 0);
@@ -101,28 +224,235 @@ impl HotkeyManager {
                         break;
                     }
 
-                    if msg.message == WM_HOTKEY {
-                        let hotkey_id = msg.wParam.0 as i32;
-                        let notecard_number = (hotkey_id - HOTKEY_BASE_ID) as u8;
+                    if msg.message == WM_HOTKEY_COMMAND {
+                        run_command(*Box::from_raw(msg.lParam.0 as *mut Command), &mut registered);
+                        continue;
+                    }
 
-                        if let Ok(notecard_id) = NotecardId::new(notecard_number) {
-                            callback(notecard_id);
+                    if msg.message == WM_HOTKEY {
+                        if let Some(action) = hotkey_action(msg.wParam.0 as i32) {
+                            callback(action);
                         }
                     }
 
                     TranslateMessage(&msg);
                     DispatchMessageW(&msg);
                 }
+
+                // Whatever is still registered goes with the thread, so
+                // nothing outlives it system-wide
+                for hotkey_id in registered {
+                    let _ = UnregisterHotKey(HWND::default(), hotkey_id);
+                }
             }
         });
 
-        self.message_thread = Some(handle);
+        let thread_id = ready_rx
+            .recv()
+            .map_err(|_| anyhow!("The hotkey message thread exited"))?;
+
+        self.message_thread = Some(MessageThread { thread_id, handle });
         Ok(())
     }
+
+    /// Ends the message loop with WM_QUIT, which unregisters every hotkey on
+    /// its thread, and waits up to `SHUTDOWN_TIMEOUT` for the thread to finish
+    ///
+    /// Safe to call more than once; the manager can start a new loop afterwards.
+    pub fn shutdown(&mut self) -> Result<()> {
+        self.registered_hotkeys.clear();
+        self.failed_hotkeys.clear();
+
+        let Some(thread) = self.message_thread.take() else {
+            return Ok(());
+        };
+
+        unsafe {
+            PostThreadMessageW(thread.thread_id, WM_QUIT, WPARAM(0), LPARAM(0))?;
+        }
+
+        let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+        while !thread.handle.is_finished() {
+            if Instant::now() >= deadline {
+                return Err(anyhow!("The hotkey message thread didn't stop within {:?}", SHUTDOWN_TIMEOUT));
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        thread
+            .handle
+            .join()
+            .map_err(|_| anyhow!("The hotkey message thread panicked"))
+    }
+
+    /// Runs a command on the message thread and waits for how it went
+    fn send<T>(&self, command: impl FnOnce(mpsc::Sender<T>) -> Command) -> Result<T> {
+        let thread = self
+            .message_thread
+            .as_ref()
+            .ok_or_else(|| anyhow!("The hotkey message loop isn't running"))?;
+
+        let (done_tx, done_rx) = mpsc::channel();
+        let command = Box::into_raw(Box::new(command(done_tx)));
+
+        unsafe {
+            if let Err(e) = PostThreadMessageW(thread.thread_id, WM_HOTKEY_COMMAND, WPARAM(0), LPARAM(command as isize)) {
+                drop(Box::from_raw(command));
+                return Err(e.into());
+            }
+        }
+
+        done_rx
+            .recv()
+            .map_err(|_| anyhow!("The hotkey message thread exited"))
+    }
 }
 
 impl Drop for HotkeyManager {
     fn drop(&mut self) {
-        let _ = self.unregister_all();
+        if let Err(e) = self.shutdown() {
+            tracing::warn!("Failed to shut down hotkeys: {}", e);
+        }
     }
-}
\ No newline at end of file
+}
+
+/// The id a hotkey is registered under, which WM_HOTKEY carries back
+fn hotkey_id(action: HotkeyAction) -> i32 {
+    match action {
+        HotkeyAction::ToggleNotecard(notecard_id) => HOTKEY_BASE_ID + notecard_id.value() as i32,
+        HotkeyAction::HideAll => HOTKEY_BASE_ID,
+    }
+}
+
+/// The action registered under a WM_HOTKEY id; `None` for ids this manager
+/// never hands out
+fn hotkey_action(hotkey_id: i32) -> Option<HotkeyAction> {
+    match u8::try_from(hotkey_id.checked_sub(HOTKEY_BASE_ID)?).ok()? {
+        0 => Some(HotkeyAction::HideAll),
+        value => NotecardId::new(value).ok().map(HotkeyAction::ToggleNotecard),
+    }
+}
+
+/// Why RegisterHotKey failed, in words for the user
+fn failure_reason(error: &windows::core::Error) -> String {
+    if error.code() == ERROR_HOTKEY_ALREADY_REGISTERED.to_hresult() {
+        "another app already uses it".to_string()
+    } else {
+        format!("{} (error 0x{:08X})", error.message(), error.code().0)
+    }
+}
+
+/// Carries out a command, keeping `registered` up to date; called on the
+/// message thread
+unsafe fn run_command(command: Command, registered: &mut HashSet<i32>) {
+    match command {
+        Command::Register { hotkey_id, modifiers, key, done } => {
+            let result = if RegisterHotKey(HWND::default(), hotkey_id, modifiers, key).as_bool() {
+                registered.insert(hotkey_id);
+                Ok(())
+            } else {
+                Err(windows::core::Error::from_win32())
+            };
+            let _ = done.send(result);
+        }
+        Command::Unregister { hotkey_id, done } => {
+            registered.remove(&hotkey_id);
+            let _ = done.send(UnregisterHotKey(HWND::default(), hotkey_id).is_ok());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use windows::Win32::System::Threading::{GetCurrentProcess, GetProcessHandleCount};
+
+    /// Handle counts are per process, so these tests take turns
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    /// With F23 or F24, combinations nothing else is likely to hold
+    const MODIFIERS: [HotkeyModifier; 3] = [HotkeyModifier::Control, HotkeyModifier::Alt, HotkeyModifier::Shift];
+
+    fn handle_count() -> u32 {
+        let mut count = 0;
+        unsafe { GetProcessHandleCount(GetCurrentProcess(), &mut count) }.unwrap();
+        count
+    }
+
+    /// Whether `MODIFIERS` with `key` is free, by taking it on this thread
+    /// and letting it go again
+    fn combination_is_free(key: HotkeyKey) -> bool {
+        let vk = VIRTUAL_KEY(key.to_windows_vk().unwrap());
+        unsafe {
+            if !RegisterHotKey(HWND::default(), 1, MOD_CONTROL | MOD_ALT | MOD_SHIFT | MOD_NOREPEAT, vk).as_bool() {
+                return false;
+            }
+            UnregisterHotKey(HWND::default(), 1).is_ok()
+        }
+    }
+
+    #[test]
+    fn starting_and_shutting_down_leaks_nothing() {
+        let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        let key = HotkeyKey::Function(24);
+        let id = NotecardId::new(9).unwrap();
+
+        // The first round loads whatever the thread and hotkey calls need
+        let mut manager = HotkeyManager::new();
+        manager.start_message_loop(|_| {}).unwrap();
+        manager.shutdown().unwrap();
+        let baseline = handle_count();
+
+        for _ in 0..50 {
+            let mut manager = HotkeyManager::new();
+            manager.start_message_loop(|_| {}).unwrap();
+            manager.register_hotkey(id, &MODIFIERS, key).unwrap();
+            assert!(!combination_is_free(key));
+
+            manager.shutdown().unwrap();
+
+            assert!(manager.message_thread.is_none());
+            assert!(manager.registered_hotkeys.is_empty());
+            assert!(combination_is_free(key), "the hotkey outlived its thread");
+        }
+
+        // Some slack for handles the system opens on its own
+        let after = handle_count();
+        assert!(after <= baseline + 5, "{} handles before, {} after", baseline, after);
+    }
+
+    #[test]
+    fn one_manager_can_be_restarted_and_dropped() {
+        let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        let key = HotkeyKey::Function(23);
+        let mut manager = HotkeyManager::new();
+
+        for _ in 0..10 {
+            manager.start_message_loop(|_| {}).unwrap();
+            manager.register_hotkey(NotecardId::new(8).unwrap(), &MODIFIERS, key).unwrap();
+            manager.shutdown().unwrap();
+            // Twice is fine
+            manager.shutdown().unwrap();
+        }
+
+        manager.start_message_loop(|_| {}).unwrap();
+        manager.register_hotkey(NotecardId::new(8).unwrap(), &MODIFIERS, key).unwrap();
+        drop(manager);
+
+        assert!(combination_is_free(key), "dropping the manager left the hotkey registered");
+    }
+
+    #[test]
+    fn hotkey_ids_map_back_to_their_actions() {
+        let actions = std::iter::once(HotkeyAction::HideAll)
+            .chain((1..=9).map(|value| HotkeyAction::ToggleNotecard(NotecardId::new(value).unwrap())));
+        for action in actions {
+            assert_eq!(hotkey_action(hotkey_id(action)), Some(action));
+        }
+
+        assert_eq!(hotkey_id(HotkeyAction::HideAll), HOTKEY_BASE_ID);
+        for stray in [HOTKEY_BASE_ID - 1, HOTKEY_BASE_ID + 10, HOTKEY_BASE_ID + 257, i32::MIN] {
+            assert_eq!(hotkey_action(stray), None, "hotkey id {}", stray);
+        }
+    }
+}
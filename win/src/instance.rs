@@ -0,0 +1,250 @@
+use anyhow::{anyhow, bail, Context, Result};
+use notecognito_core::NotecardId;
+use std::cell::RefCell;
+use std::ffi::c_void;
+use std::mem;
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use windows::core::{w, PCWSTR};
+use windows::Win32::{
+    Foundation::*,
+    System::DataExchange::COPYDATASTRUCT,
+    System::LibraryLoader::GetModuleHandleW,
+    System::Threading::GetCurrentThreadId,
+    UI::WindowsAndMessaging::*,
+};
+
+const LISTENER_CLASS_NAME: PCWSTR = w!("NotecognitoInstance");
+
+/// Marks a WM_COPYDATA as a signal, so data from anything else is ignored
+const SIGNAL_COPYDATA: usize = 0x4E43_0001;
+
+/// How long a second copy looks for the running one's window, which may
+/// still be starting up
+const FIND_ATTEMPTS: u32 = 20;
+const FIND_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a second copy waits for the running one to take a signal
+const SEND_TIMEOUT_MS: u32 = 2000;
+
+thread_local! {
+    /// Run by the listener window when a signal comes in
+    static ON_SIGNAL: RefCell<Option<Box<dyn Fn(Signal)>>> = const { RefCell::new(None) };
+}
+
+/// What a second copy of the app asks the running one to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// Started again with no arguments, as when the icon is double-clicked
+    Relaunched,
+    /// Started with `--show <id>`
+    Show(NotecardId),
+}
+
+impl Signal {
+    /// The signal the command line asks for: `--show <id>`, or nothing
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self> {
+        let signal = match args.next().as_deref() {
+            None => Signal::Relaunched,
+            Some("--show") => {
+                let id = args.next().ok_or_else(|| anyhow!("--show needs a notecard id from 1 to 9"))?;
+                Signal::Show(parse_notecard_id(&id)?)
+            }
+            Some(arg) => bail!("Unknown argument {}; the only one is --show <id>", arg),
+        };
+
+        if let Some(arg) = args.next() {
+            bail!("Unexpected argument {}", arg);
+        }
+        Ok(signal)
+    }
+
+    fn to_message(self) -> String {
+        match self {
+            Signal::Relaunched => "relaunched".to_string(),
+            Signal::Show(id) => format!("show {}", id.value()),
+        }
+    }
+
+    fn from_message(message: &str) -> Option<Self> {
+        match message.split_once(' ') {
+            None if message == "relaunched" => Some(Signal::Relaunched),
+            Some(("show", id)) => parse_notecard_id(id).ok().map(Signal::Show),
+            _ => None,
+        }
+    }
+}
+
+fn parse_notecard_id(text: &str) -> Result<NotecardId> {
+    let value: u8 = text.parse().with_context(|| format!("{} isn't a notecard id", text))?;
+    Ok(NotecardId::new(value)?)
+}
+
+/// Hands `signal` to the copy of the app already running in this session
+///
+/// Waits a little for its window, in case it's still starting up.
+pub fn signal_running(signal: Signal) -> Result<()> {
+    let message = signal.to_message();
+
+    unsafe {
+        let mut hwnd = HWND::default();
+        for _ in 0..FIND_ATTEMPTS {
+            hwnd = FindWindowExW(HWND_MESSAGE, HWND::default(), LISTENER_CLASS_NAME, PCWSTR::null());
+            if hwnd.0 != 0 {
+                break;
+            }
+            thread::sleep(FIND_INTERVAL);
+        }
+        if hwnd.0 == 0 {
+            bail!("The running copy of Notecognito isn't listening");
+        }
+
+        let data = COPYDATASTRUCT {
+            dwData: SIGNAL_COPYDATA,
+            cbData: message.len() as u32,
+            lpData: message.as_ptr() as *mut c_void,
+        };
+        let mut result = 0usize;
+        let sent = SendMessageTimeoutW(
+            hwnd,
+            WM_COPYDATA,
+            WPARAM(0),
+            LPARAM(&data as *const COPYDATASTRUCT as isize),
+            SMTO_ABORTIFHUNG | SMTO_BLOCK,
+            SEND_TIMEOUT_MS,
+            Some(&mut result),
+        );
+        if sent.0 == 0 || result == 0 {
+            bail!("The running copy of Notecognito didn't take the signal");
+        }
+    }
+
+    Ok(())
+}
+
+/// The thread of a hidden message-only window that later copies of the app
+/// send their signals to
+pub struct InstanceListener {
+    thread_id: u32,
+    handle: JoinHandle<()>,
+}
+
+impl InstanceListener {
+    /// Calls `on_signal`, on the listener's thread, for each signal a later
+    /// copy of the app sends
+    pub fn start(on_signal: impl Fn(Signal) + Send + 'static) -> Result<Self> {
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        let handle = thread::spawn(move || unsafe {
+            ON_SIGNAL.with(|callback| *callback.borrow_mut() = Some(Box::new(on_signal)));
+
+            // Makes sure the thread has a message queue before anyone posts to it
+            let mut msg = MSG::default();
+            let _ = PeekMessageW(&mut msg, HWND::default(), WM_USER, WM_USER, PM_NOREMOVE);
+
+            let hwnd = match create_listener_window() {
+                Ok(hwnd) => hwnd,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+            let _ = ready_tx.send(Ok(GetCurrentThreadId()));
+
+            // Runs until `stop` posts WM_QUIT
+            while GetMessageW(&mut msg, HWND::default(), 0, 0).0 > 0 {
+                TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+
+            let _ = DestroyWindow(hwnd);
+        });
+
+        let thread_id = ready_rx
+            .recv()
+            .map_err(|_| anyhow!("The instance listener thread exited"))??;
+
+        Ok(InstanceListener { thread_id, handle })
+    }
+
+    /// Ends the listener's message loop, which destroys its window, and waits for it
+    pub fn stop(self) -> Result<()> {
+        unsafe {
+            PostThreadMessageW(self.thread_id, WM_QUIT, WPARAM(0), LPARAM(0))?;
+        }
+        self.handle
+            .join()
+            .map_err(|_| anyhow!("The instance listener thread panicked"))
+    }
+}
+
+unsafe fn create_listener_window() -> Result<HWND> {
+    let instance = GetModuleHandleW(None)?;
+
+    let wc = WNDCLASSEXW {
+        cbSize: mem::size_of::<WNDCLASSEXW>() as u32,
+        lpfnWndProc: Some(listener_window_proc),
+        hInstance: instance.into(),
+        lpszClassName: LISTENER_CLASS_NAME,
+        ..Default::default()
+    };
+
+    // Already there when the listener is started a second time
+    if RegisterClassExW(&wc) == 0 && GetLastError() != ERROR_CLASS_ALREADY_EXISTS {
+        return Err(anyhow!("Failed to register the instance listener window class"));
+    }
+
+    let hwnd = CreateWindowExW(
+        WINDOW_EX_STYLE::default(),
+        LISTENER_CLASS_NAME,
+        w!("Notecognito Instance Listener"),
+        WINDOW_STYLE::default(),
+        0,
+        0,
+        0,
+        0,
+        HWND_MESSAGE,
+        None,
+        instance,
+        None,
+    );
+    if hwnd.0 == 0 {
+        return Err(anyhow!("Failed to create the instance listener window"));
+    }
+
+    // A copy started without elevation can still reach one running elevated
+    let _ = ChangeWindowMessageFilterEx(hwnd, WM_COPYDATA, MSGFLT_ALLOW, None);
+
+    Ok(hwnd)
+}
+
+unsafe extern "system" fn listener_window_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if msg == WM_COPYDATA && lparam.0 != 0 {
+        let data = &*(lparam.0 as *const COPYDATASTRUCT);
+        if data.dwData != SIGNAL_COPYDATA || data.lpData.is_null() {
+            return LRESULT(0);
+        }
+
+        let bytes = std::slice::from_raw_parts(data.lpData as *const u8, data.cbData as usize);
+        let Some(signal) = std::str::from_utf8(bytes).ok().and_then(Signal::from_message) else {
+            tracing::warn!("Ignoring a signal that couldn't be read");
+            return LRESULT(0);
+        };
+
+        tracing::info!("Signal from another copy of the app: {:?}", signal);
+        ON_SIGNAL.with(|callback| {
+            if let Some(callback) = callback.borrow().as_ref() {
+                callback(signal);
+            }
+        });
+        return LRESULT(1);
+    }
+
+    DefWindowProcW(hwnd, msg, wparam, lparam)
+}
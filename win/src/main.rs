@@ -1,36 +1,89 @@
 use anyhow::{Context, Result};
-use notecognito_core::{ConfigManager, NotecardId};
+use notecognito_core::{
+    AppIdentity, Config, ConfigManager, HotkeyAction, HotkeyDebouncer, MonitorSelector, NotecardId, NotificationKind,
+    PlatformEvent, PlatformInterface, PLATFORM_EVENT_CAPACITY,
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch, Mutex, Notify};
+use tokio::task::JoinHandle;
 use tray_icon::{
-    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
-    TrayIcon, TrayIconBuilder,
+    menu::{Menu, MenuEvent},
+    ClickType, TrayIcon, TrayIconBuilder, TrayIconEvent,
 };
+use windows::core::HSTRING;
 use windows::Win32::{
     Foundation::*,
+    System::Threading::*,
     UI::WindowsAndMessaging::*,
 };
 
+mod capabilities;
+mod clipboard;
+mod config_ui;
+mod escape_hook;
+mod foreground;
 mod hotkey;
+mod instance;
 mod ipc_client;
 mod notecard_window;
+mod notification;
 mod platform_impl;
-
-use hotkey::HotkeyManager;
+mod screens;
+mod startup;
+mod text_render;
+mod theme;
+mod tray_menu;
+
+use hotkey::{FailedHotkey, HotkeyManager};
+use instance::{InstanceListener, Signal};
 use ipc_client::IpcClient;
-use notecard_window::NotecardWindowManager;
+use notecard_window::{NotecardWindowManager, WindowMap};
 use platform_impl::WindowsPlatform;
+use theme::ThemeWatcher;
+use tray_menu::{ActionRegistry, AppState, TrayAction, TrayIcons};
 
 const APP_NAME: &str = "Notecognito";
 const WM_USER_TRAY: u32 = WM_USER + 1;
 
+/// How long after the last drag the new places of moved notecards are saved
+const MOVE_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Wait before reconnecting to core, doubled after each failed attempt up to the max
+const CORE_RETRY_MIN_DELAY: Duration = Duration::from_secs(1);
+const CORE_RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
 struct App {
     config_manager: Arc<Mutex<ConfigManager>>,
     ipc_client: Arc<Mutex<IpcClient>>,
     hotkey_manager: Arc<Mutex<HotkeyManager>>,
-    window_manager: Arc<Mutex<NotecardWindowManager>>,
     platform: Arc<Mutex<WindowsPlatform>>,
+    /// The platform's open notecard windows, repainted when the theme changes
+    notecard_windows: WindowMap,
+    theme_watcher: Option<ThemeWatcher>,
+    /// Hears from copies of the app started while this one runs
+    instance_listener: Option<InstanceListener>,
+    /// Taken by `run`, which acts on them
+    signals: Option<mpsc::Receiver<Signal>>,
+    /// Taken by `run`, which forwards the events to core
+    platform_events: Option<mpsc::Receiver<PlatformEvent>>,
+    /// Forwards the events; awaited on shutdown so a pending move is saved
+    platform_events_task: Option<JoinHandle<()>>,
     tray_icon: Option<TrayIcon>,
+    /// The tray icon for each `AppState`
+    tray_icons: Option<TrayIcons>,
+    /// What the tray menu's items do, by menu id
+    tray_actions: ActionRegistry,
+    /// Notified when the configuration changes, so `run` rebuilds the tray menu
+    menu_refresh: Arc<Notify>,
+    /// Whether configuration pushes from core are coming in, shown in the tray
+    core_connected: Arc<AtomicBool>,
+    /// Follows core; awaited on shutdown so the connection is closed
+    core_task: Option<JoinHandle<()>>,
+    /// Set to true by the tray's Quit item; `run` tears down and returns
+    shutdown: watch::Sender<bool>,
 }
 
 impl App {
@@ -53,12 +106,15 @@ impl App {
 
         // Create managers
         let hotkey_manager = Arc::new(Mutex::new(HotkeyManager::new()));
-        let window_manager = Arc::new(Mutex::new(NotecardWindowManager::new()));
+        let (events_tx, platform_events) = mpsc::channel(PLATFORM_EVENT_CAPACITY);
+        let window_manager = NotecardWindowManager::new(events_tx);
+        let notecard_windows = window_manager.window_map();
+        let window_manager = Arc::new(Mutex::new(window_manager));
 
         // Create platform implementation
         let platform = WindowsPlatform::new(
             Arc::clone(&hotkey_manager),
-            Arc::clone(&window_manager),
+            window_manager,
         );
         let platform = Arc::new(Mutex::new(platform));
 
@@ -66,13 +122,42 @@ impl App {
             config_manager,
             ipc_client,
             hotkey_manager,
-            window_manager,
             platform,
+            notecard_windows,
+            theme_watcher: None,
+            instance_listener: None,
+            signals: None,
+            platform_events: Some(platform_events),
+            platform_events_task: None,
             tray_icon: None,
+            tray_icons: None,
+            tray_actions: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            menu_refresh: Arc::new(Notify::new()),
+            core_connected: Arc::new(AtomicBool::new(false)),
+            core_task: None,
+            shutdown: watch::channel(false).0,
         })
     }
 
-    async fn initialize(&mut self) -> Result<()> {
+    /// Gets everything but the hotkeys going; `launch_signal` is what this
+    /// copy was started to do, as if another copy had sent it
+    async fn initialize(&mut self, launch_signal: Signal) -> Result<()> {
+        // Listens first, so a copy started while this one connects to core
+        // still finds it
+        let (signal_tx, signals) = mpsc::channel(8);
+        if let Signal::Show(_) = launch_signal {
+            let _ = signal_tx.try_send(launch_signal);
+        }
+        match InstanceListener::start(move |signal| {
+            if signal_tx.try_send(signal).is_err() {
+                tracing::warn!("Dropped signal {:?}", signal);
+            }
+        }) {
+            Ok(listener) => self.instance_listener = Some(listener),
+            Err(e) => tracing::warn!("Copies of the app started later won't reach this one: {}", e),
+        }
+        self.signals = Some(signals);
+
         // Try to connect to IPC server
         match self.connect_to_core().await {
             Ok(_) => tracing::info!("Connected to core service"),
@@ -85,14 +170,14 @@ impl App {
         // Initialize platform
         {
             let mut platform = self.platform.lock().await;
-            platform.initialize()?;
+            platform.initialize().await?;
         }
 
-        // Load configuration and setup hotkeys
+        // Load configuration
         self.load_configuration().await?;
 
         // Create system tray
-        self.create_system_tray()?;
+        self.create_system_tray().await?;
 
         Ok(())
     }
@@ -111,66 +196,85 @@ impl App {
         Ok(())
     }
 
+    /// Applies settings that don't need the hotkey message loop; `run`
+    /// registers the hotkeys once it's going
     async fn load_configuration(&self) -> Result<()> {
         let manager = self.config_manager.lock().await;
-        let config = manager.config();
-
-        // Register hotkeys for all notecards
-        let mut hotkey_manager = self.hotkey_manager.lock().await;
-        let modifiers = &config.hotkey_modifiers;
 
-        for i in 1..=9 {
-            let notecard_id = NotecardId::new(i)?;
-            hotkey_manager.register_hotkey(notecard_id, modifiers)?;
-        }
-
-        // Set launch on startup
-        if config.launch_on_startup {
-            self.set_launch_on_startup(true).await?;
+        // The app runs all the same when it can't change how it's launched
+        if let Err(e) = apply_launch_on_startup(manager.config(), &self.platform).await {
+            tracing::warn!("Could not set up launching at logon: {}", e);
         }
 
         Ok(())
     }
 
-    fn create_system_tray(&mut self) -> Result<()> {
-        // Load tray icon
+    async fn create_system_tray(&mut self) -> Result<()> {
+        // Load tray icon, and the variants showing what state the app is in
         let icon_bytes = include_bytes!("../assets/icon.ico");
-        let icon = image::load_from_memory(icon_bytes)?;
+        let icons = TrayIcons::new(image::load_from_memory(icon_bytes)?.to_rgba8());
 
         // Create tray menu
-        let show_config = MenuItem::new("Configure", true, None);
-        let separator = PredefinedMenuItem::separator();
-        let quit = MenuItem::new("Quit", true, None);
-
-        let menu = Menu::new();
-        menu.append(&show_config)?;
-        menu.append(&separator)?;
-        menu.append(&quit)?;
+        let menu = self.build_tray_menu().await?;
 
         // Create tray icon
+        let state = self.app_state().await;
         let tray_icon = TrayIconBuilder::new()
             .with_menu(Box::new(menu))
-            .with_tooltip(APP_NAME)
-            .with_icon(tray_icon::Icon::from_rgba(
-                icon.to_rgba8().into_raw(),
-                icon.width(),
-                icon.height(),
-            )?)
+            .with_tooltip(tray_menu::tooltip(state))
+            .with_icon(icons.get(state)?)
             .build()?;
 
         self.tray_icon = Some(tray_icon);
+        self.tray_icons = Some(icons);
+
+        // Clicking the icon while some hotkeys aren't registered lists them
+        let hotkey_manager = Arc::clone(&self.hotkey_manager);
+        tokio::spawn(async move {
+            let tray_channel = TrayIconEvent::receiver();
+            while let Ok(event) = tray_channel.recv() {
+                if event.click_type != ClickType::Left {
+                    continue;
+                }
+                let failed = hotkey_manager.lock().await.failed_hotkeys().to_vec();
+                if !failed.is_empty() {
+                    notify_failed_hotkeys(&failed);
+                }
+            }
+        });
 
-        // Handle menu events
-        let show_config_id = show_config.id();
-        let quit_id = quit.id();
+        // Handle menu events; ids are looked up in the registry, since the
+        // menu is rebuilt with new ones when the configuration changes
+        let actions = Arc::clone(&self.tray_actions);
+        let config_manager = Arc::clone(&self.config_manager);
+        let platform = Arc::clone(&self.platform);
+        let shutdown = self.shutdown.clone();
 
         tokio::spawn(async move {
             let menu_channel = MenuEvent::receiver();
             while let Ok(event) = menu_channel.recv() {
-                if event.id == show_config_id {
-                    Self::launch_config_ui();
-                } else if event.id == quit_id {
-                    std::process::exit(0);
+                let action = actions.lock().unwrap().get(&event.id).copied();
+                match action {
+                    Some(TrayAction::Configure) => {
+                        let config_path = config_manager.lock().await.config_path().to_path_buf();
+                        config_ui::launch(&config_path);
+                    }
+                    Some(TrayAction::ShowAll) => {
+                        show_all_notecards(Arc::clone(&config_manager), Arc::clone(&platform)).await
+                    }
+                    Some(TrayAction::HideAll) => hide_all_notecards(Arc::clone(&platform)).await,
+                    Some(TrayAction::ToggleNotecard(notecard_id)) => {
+                        let result =
+                            toggle_notecard(notecard_id, Arc::clone(&config_manager), Arc::clone(&platform)).await;
+                        if let Err(e) = result {
+                            tracing::error!("Failed to toggle notecard: {}", e);
+                        }
+                    }
+                    Some(TrayAction::Quit) => {
+                        let _ = shutdown.send(true);
+                        break;
+                    }
+                    None => {}
                 }
             }
         });
@@ -178,135 +282,622 @@ impl App {
         Ok(())
     }
 
-    fn launch_config_ui() {
-        // Launch the Electron configuration UI
-        let config_path = std::env::current_exe()
-            .ok()
-            .and_then(|p| p.parent().map(|p| p.to_path_buf()))
-            .map(|p| p.join("notecognito-config.exe"))
-            .unwrap_or_else(|| "notecognito-config.exe".into());
+    async fn build_tray_menu(&self) -> Result<Menu> {
+        let manager = self.config_manager.lock().await;
+        tray_menu::build(
+            &manager.notecard_summaries(),
+            manager.config().hotkeys_enabled,
+            self.core_connected.load(Ordering::Relaxed),
+            &self.tray_actions,
+        )
+    }
 
-        if let Err(e) = std::process::Command::new(&config_path).spawn() {
-            tracing::error!("Failed to launch config UI: {}", e);
+    /// Swaps in a menu built from the current configuration, and brings the
+    /// icon and tooltip in line with the app's state
+    async fn refresh_tray_menu(&self) -> Result<()> {
+        let menu = self.build_tray_menu().await?;
+        let state = self.app_state().await;
+        if let (Some(tray_icon), Some(icons)) = (&self.tray_icon, &self.tray_icons) {
+            tray_icon.set_menu(Some(Box::new(menu)));
+            tray_icon.set_icon(Some(icons.get(state)?))?;
+            tray_icon.set_tooltip(Some(tray_menu::tooltip(state)))?;
         }
+        Ok(())
     }
 
-    async fn set_launch_on_startup(&self, enabled: bool) -> Result<()> {
-        use windows::Win32::System::Registry::*;
-
-        unsafe {
-            let key_path = w!("Software\\Microsoft\\Windows\\CurrentVersion\\Run");
-            let mut hkey = HKEY::default();
-
-            RegOpenKeyExW(
-                HKEY_CURRENT_USER,
-                key_path,
-                0,
-                KEY_SET_VALUE,
-                &mut hkey,
-            )?;
-
-            if enabled {
-                let exe_path = std::env::current_exe()?;
-                let exe_path = exe_path.to_string_lossy();
-                let value = format!("\"{}\"", exe_path);
-
-                RegSetValueExW(
-                    hkey,
-                    w!("Notecognito"),
-                    0,
-                    REG_SZ,
-                    Some(value.as_bytes()),
-                )?;
-            } else {
-                let _ = RegDeleteValueW(hkey, w!("Notecognito"));
+    /// What the tray shows: whether hotkeys are all registered, paused, and
+    /// whether core is connected
+    async fn app_state(&self) -> AppState {
+        let hotkeys_enabled = self.config_manager.lock().await.config().hotkeys_enabled;
+        let failed_hotkeys = self.hotkey_manager.lock().await.failed_hotkeys().len();
+        AppState::new(hotkeys_enabled, failed_hotkeys, self.core_connected.load(Ordering::Relaxed))
+    }
+
+    async fn run(&mut self) -> Result<()> {
+        // Presses are stamped with when they were made, so a slow toggle
+        // doesn't make the next press look like a repeat
+        let (hotkey_tx, mut hotkey_rx) = mpsc::channel::<(HotkeyAction, Instant)>(32);
+
+        {
+            let mut hotkey_manager = self.hotkey_manager.lock().await;
+
+            // Runs on the hotkey message thread, so it only passes the press on
+            hotkey_manager.start_message_loop(move |action| match hotkey_tx.try_send((action, Instant::now())) {
+                Ok(()) => {}
+                // Presses this far behind would only be debounced away
+                Err(mpsc::error::TrySendError::Full(_)) => tracing::debug!("Dropped hotkey press {:?}", action),
+                Err(mpsc::error::TrySendError::Closed(_)) => tracing::warn!("Hotkey press after shutdown"),
+            })?;
+        }
+
+        // A press shows its card, or hides it when it's already up; one press
+        // at a time, so pressing quickly can't race two toggles of a card
+        let config_manager = Arc::clone(&self.config_manager);
+        let platform = Arc::clone(&self.platform);
+        tokio::spawn(async move {
+            let mut debouncer = HotkeyDebouncer::default();
+
+            while let Some((action, pressed_at)) = hotkey_rx.recv().await {
+                tracing::info!("Hotkey pressed: {:?}", action);
+                if !debouncer.accept(action, pressed_at) {
+                    tracing::debug!("Ignoring repeated hotkey press {:?}", action);
+                    continue;
+                }
+
+                match action {
+                    HotkeyAction::ToggleNotecard(notecard_id) => {
+                        if let Err(e) = toggle_notecard(notecard_id, config_manager.clone(), platform.clone()).await {
+                            tracing::error!("Failed to toggle notecard: {}", e);
+                        }
+                    }
+                    HotkeyAction::HideAll => hide_all_notecards(platform.clone()).await,
+                }
             }
+        });
+
+        // Starting the app again opens the config UI, since the tray icon is
+        // easy to miss; `--show <id>` pops up that notecard
+        if let Some(mut signals) = self.signals.take() {
+            let config_manager = Arc::clone(&self.config_manager);
+            let platform = Arc::clone(&self.platform);
+            tokio::spawn(async move {
+                while let Some(signal) = signals.recv().await {
+                    match signal {
+                        Signal::Relaunched => {
+                            let config_path = config_manager.lock().await.config_path().to_path_buf();
+                            config_ui::launch(&config_path);
+                        }
+                        Signal::Show(notecard_id) => {
+                            if let Err(e) = show_notecard(notecard_id, config_manager.clone(), platform.clone()).await {
+                                tracing::error!("Failed to show notecard: {}", e);
+                            }
+                        }
+                    }
+                }
+            });
+        }
+
+        // Hotkeys are registered on the message loop's thread, which WM_HOTKEY goes to
+        sync_hotkeys(&self.config_manager, &self.hotkey_manager, &self.menu_refresh).await;
+
+        // Let core's subscribers hear about notecards closed by click, ESC or
+        // timer, and keep where notecards were dragged
+        if let Some(events) = self.platform_events.take() {
+            self.platform_events_task = Some(tokio::spawn(forward_platform_events(
+                events,
+                Arc::clone(&self.ipc_client),
+                Arc::clone(&self.config_manager),
+                self.shutdown.subscribe(),
+            )));
+        }
+
+        // Cards without colors of their own follow the light or dark theme live
+        let notecard_windows = Arc::clone(&self.notecard_windows);
+        match ThemeWatcher::start(move || notecard_window::repaint_all(&notecard_windows)) {
+            Ok(watcher) => self.theme_watcher = Some(watcher),
+            Err(e) => tracing::warn!("Notecards won't follow theme changes until they're shown again: {}", e),
+        }
 
-            RegCloseKey(hkey)?;
+        // Keep visible notecards in sync with edits made in the config UI,
+        // reconnecting whenever core starts or restarts
+        self.core_task = Some(tokio::spawn(supervise_core_connection(
+            CoreFollower {
+                config_manager: Arc::clone(&self.config_manager),
+                platform: Arc::clone(&self.platform),
+                hotkey_manager: Arc::clone(&self.hotkey_manager),
+                ipc_client: Arc::clone(&self.ipc_client),
+                menu_refresh: Arc::clone(&self.menu_refresh),
+                connected: Arc::clone(&self.core_connected),
+            },
+            self.shutdown.subscribe(),
+        )));
+
+        // The hotkey message loop runs in a separate thread; this one keeps
+        // the tray menu current until Quit from the tray, or Ctrl+C when
+        // there's a console
+        let mut shutdown = self.shutdown.subscribe();
+        loop {
+            tokio::select! {
+                _ = shutdown.wait_for(|quit| *quit) => {
+                    tracing::info!("Quitting from the tray menu");
+                    break;
+                }
+                _ = ctrl_c() => {
+                    tracing::info!("Quitting on Ctrl+C");
+                    let _ = self.shutdown.send(true);
+                    break;
+                }
+                _ = self.menu_refresh.notified() => {
+                    if let Err(e) = self.refresh_tray_menu().await {
+                        tracing::warn!("Failed to rebuild the tray menu: {}", e);
+                    }
+                }
+            }
         }
 
+        self.shutdown().await;
         Ok(())
     }
 
-    async fn run(&mut self) -> Result<()> {
-        // Set up hotkey message handler
-        let config_manager = Arc::clone(&self.config_manager);
-        let window_manager = Arc::clone(&self.window_manager);
+    /// Tears everything down in order, so nothing is left registered behind;
+    /// a failing step is logged and the rest still run
+    async fn shutdown(&mut self) {
+        {
+            let mut platform = self.platform.lock().await;
+            if let Err(e) = platform.hide_all_notecards().await {
+                tracing::warn!("Failed to hide notecards: {}", e);
+            }
+            if let Err(e) = platform.cleanup().await {
+                tracing::warn!("Failed to unregister hotkeys: {}", e);
+            }
+        }
+
+        if let Err(e) = self.hotkey_manager.lock().await.shutdown() {
+            tracing::warn!("Failed to stop the hotkey message loop: {}", e);
+        }
+
+        if let Some(watcher) = self.theme_watcher.take() {
+            if let Err(e) = watcher.stop() {
+                tracing::warn!("Failed to stop the theme watcher: {}", e);
+            }
+        }
+
+        if let Some(listener) = self.instance_listener.take() {
+            if let Err(e) = listener.stop() {
+                tracing::warn!("Failed to stop the instance listener: {}", e);
+            }
+        }
+
+        // Saves where notecards were dragged, if that's still pending
+        if let Some(task) = self.platform_events_task.take() {
+            let _ = task.await;
+        }
+
+        // Closes the connection to core once nothing else is sent through it
+        if let Some(task) = self.core_task.take() {
+            let _ = task.await;
+        }
 
         {
-            let mut hotkey_manager = self.hotkey_manager.lock().await;
+            let manager = self.config_manager.lock().await;
+            if manager.is_dirty() {
+                if let Err(e) = manager.save() {
+                    tracing::warn!("Failed to save the configuration: {}", e);
+                }
+            }
+        }
 
-            hotkey_manager.start_message_loop(move |notecard_id| {
-                let config_manager = Arc::clone(&config_manager);
-                let window_manager = Arc::clone(&window_manager);
+        self.tray_icon = None;
+        tracing::info!("Notecognito stopped");
+    }
+}
 
-                // Use a separate runtime for the callback
-                std::thread::spawn(move || {
-                    let rt = tokio::runtime::Runtime::new().unwrap();
-                    rt.block_on(async move {
-                        if let Err(e) = show_notecard(notecard_id, config_manager, window_manager).await {
-                            tracing::error!("Failed to show notecard: {}", e);
-                        }
-                    });
-                });
-            })?;
+/// Resolves on Ctrl+C; never, when there's no console to listen on
+async fn ctrl_c() {
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        tracing::debug!("Not listening for Ctrl+C: {}", e);
+        std::future::pending::<()>().await;
+    }
+}
+
+/// What following core keeps up to date
+struct CoreFollower {
+    config_manager: Arc<Mutex<ConfigManager>>,
+    platform: Arc<Mutex<WindowsPlatform>>,
+    hotkey_manager: Arc<Mutex<HotkeyManager>>,
+    ipc_client: Arc<Mutex<IpcClient>>,
+    menu_refresh: Arc<Notify>,
+    connected: Arc<AtomicBool>,
+}
+
+impl CoreFollower {
+    /// Records whether core is reachable, for the tray to show
+    fn set_connected(&self, connected: bool) {
+        if self.connected.swap(connected, Ordering::Relaxed) != connected {
+            self.menu_refresh.notify_one();
         }
+    }
+}
 
-        // Keep the main thread alive
-        // The hotkey message loop runs in a separate thread
-        loop {
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+/// Follows configuration changes from core for as long as the app runs,
+/// connecting again with backoff whenever core is down or goes away
+///
+/// While disconnected the app runs standalone on its local configuration.
+async fn supervise_core_connection(follower: CoreFollower, mut shutdown: watch::Receiver<bool>) {
+    let mut delay = CORE_RETRY_MIN_DELAY;
+
+    loop {
+        let connected = tokio::select! {
+            connected = follow_core(&follower) => connected,
+            _ = shutdown.wait_for(|quit| *quit) => break,
+        };
+
+        // Saves fall back to the local file until core is back
+        follower.ipc_client.lock().await.disconnect().await;
+        follower.set_connected(false);
+
+        // Core was up, so the next attempt starts from the shortest delay
+        if connected {
+            delay = CORE_RETRY_MIN_DELAY;
+        }
+        tracing::debug!("Trying to reach core again in {:?}", delay);
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = shutdown.wait_for(|quit| *quit) => break,
+        }
+        if !connected {
+            delay = (delay * 2).min(CORE_RETRY_MAX_DELAY);
+        }
+    }
+
+    follower.ipc_client.lock().await.disconnect().await;
+    tracing::info!("Closed the connection to core");
+}
+
+/// Connects to core and applies the configuration it pushes until the
+/// connection fails; returns whether it got connected at all
+async fn follow_core(follower: &CoreFollower) -> bool {
+    // Subscribing turns the connection into a push stream, so it gets its own client
+    let mut subscription = IpcClient::new();
+    let subscribed = async {
+        subscription.connect().await?;
+        subscription.subscribe().await
+    };
+    if let Err(e) = subscribed.await {
+        tracing::debug!("Core service isn't reachable: {}", e);
+        return false;
+    }
 
-            // Check if we should exit (this could be triggered by a shutdown event)
-            if self.should_exit().await {
-                break;
+    // Whatever changed while disconnected is caught up on first
+    let config = {
+        let mut client = follower.ipc_client.lock().await;
+        let fetched = async {
+            if !client.is_connected().await {
+                client.connect().await?;
+            }
+            client.get_configuration().await
+        };
+        match fetched.await {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!("Could not get the configuration from core: {}", e);
+                return false;
             }
         }
+    };
+    if let Err(e) = apply_config_change(config, follower).await {
+        tracing::warn!("Could not apply the configuration from core: {}", e);
+    }
 
-        Ok(())
+    tracing::info!("Following configuration changes from core");
+    follower.set_connected(true);
+
+    loop {
+        let config = match subscription.next_config_change().await {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!("Lost the connection to core, running standalone: {}", e);
+                return true;
+            }
+        };
+
+        if let Err(e) = apply_config_change(config, follower).await {
+            tracing::warn!("Could not apply the configuration from core: {}", e);
+        }
+    }
+}
+
+/// Applies configuration from core: refreshes notecards that are on screen,
+/// brings the hotkeys and launching at logon in line with it and rebuilds the
+/// tray menu
+async fn apply_config_change(config: Config, follower: &CoreFollower) -> Result<()> {
+    {
+        let mut platform = follower.platform.lock().await;
+
+        for id in platform.visible_notecards().await? {
+            match config.notecards.get(&id).filter(|notecard| !notecard.is_empty()) {
+                Some(notecard) => {
+                    let properties = config.display_properties_for(id);
+                    platform.update_notecard(id, &notecard.content, properties).await?
+                }
+                None => platform.hide_notecard(id).await?,
+            }
+        }
+    }
+
+    let startup_changed = {
+        let manager = follower.config_manager.lock().await;
+        let current = manager.config();
+        current.launch_on_startup != config.launch_on_startup || current.startup_method != config.startup_method
+    };
+    if startup_changed {
+        if let Err(e) = apply_launch_on_startup(&config, &follower.platform).await {
+            tracing::warn!("Could not change launching at logon: {}", e);
+        }
+    }
+
+    *follower.config_manager.lock().await.config_mut() = config;
+    sync_hotkeys(&follower.config_manager, &follower.hotkey_manager, &follower.menu_refresh).await;
+    follower.menu_refresh.notify_one();
+    Ok(())
+}
+
+/// Launches the app at logon or stops it, with the configured method; a
+/// method switched to replaces the old one
+async fn apply_launch_on_startup(config: &Config, platform: &Mutex<WindowsPlatform>) -> Result<()> {
+    let mut platform = platform.lock().await;
+    platform.set_startup_method(config.startup_method);
+    platform.set_launch_on_startup(config.launch_on_startup).await?;
+    Ok(())
+}
+
+/// Registers and unregisters hotkeys until they match the configuration: one
+/// per notecard with content, plus hide-all when it's on, and none at all
+/// while hotkeys are paused
+///
+/// When the hotkeys that couldn't be registered change, they're listed in a
+/// notification and the tray is refreshed to show it; the rest still work.
+async fn sync_hotkeys(config_manager: &Mutex<ConfigManager>, hotkey_manager: &Mutex<HotkeyManager>, menu_refresh: &Notify) {
+    let bindings = config_manager.lock().await.config().active_hotkey_bindings();
+    let (failed, changed) = {
+        let mut hotkey_manager = hotkey_manager.lock().await;
+        let before = hotkey_manager.failed_hotkeys().to_vec();
+        let failed = hotkey_manager.sync_bindings(&bindings);
+        let changed = failed != before;
+        (failed, changed)
+    };
+    if !changed {
+        return;
+    }
+
+    menu_refresh.notify_one();
+    if !failed.is_empty() {
+        notify_failed_hotkeys(&failed);
+    }
+}
+
+/// Lists hotkeys that couldn't be registered, and why, in a notification
+fn notify_failed_hotkeys(failed: &[FailedHotkey]) {
+    let lines: Vec<String> = failed
+        .iter()
+        .map(|failure| match failure.action {
+            HotkeyAction::ToggleNotecard(id) => {
+                format!("{} for notecard {}: {}", failure.binding, id.value(), failure.reason)
+            }
+            HotkeyAction::HideAll => format!("{} to hide all notecards: {}", failure.binding, failure.reason),
+        })
+        .collect();
+
+    if let Err(e) = notification::show_toast("Some hotkeys could not be registered", &lines.join("\n"), NotificationKind::Warning) {
+        tracing::warn!("Could not show the hotkey notification: {}", e);
+    }
+}
+
+/// Reports platform events to core, which pushes them to its subscribers
+///
+/// A moved notecard's place is saved once no card has been dragged for
+/// `MOVE_SAVE_DELAY`, or right away on quit.
+async fn forward_platform_events(
+    mut events: mpsc::Receiver<PlatformEvent>,
+    ipc_client: Arc<Mutex<IpcClient>>,
+    config_manager: Arc<Mutex<ConfigManager>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    // Latest screen and position of each dragged notecard not saved yet
+    let mut moved = HashMap::new();
+
+    loop {
+        let waiting_to_save = !moved.is_empty();
+        let events = &mut events;
+        let next = async move {
+            if waiting_to_save {
+                tokio::time::timeout(MOVE_SAVE_DELAY, events.recv()).await
+            } else {
+                Ok(events.recv().await)
+            }
+        };
+
+        let event = tokio::select! {
+            next = next => match next {
+                Ok(event) => event,
+                Err(_) => {
+                    save_moved_notecards(moved.drain().collect(), &config_manager, &ipc_client).await;
+                    continue;
+                }
+            },
+            _ = shutdown.wait_for(|quit| *quit) => None,
+        };
+        let Some(event) = event else {
+            break;
+        };
+
+        tracing::debug!("Platform event: {:?}", event);
+
+        if let PlatformEvent::NotecardMoved { id, monitor, position } = event {
+            moved.insert(id, (monitor, position));
+        }
+
+        let mut client = ipc_client.lock().await;
+        if !client.is_connected().await {
+            continue;
+        }
+        if let Err(e) = client.report_platform_event(event).await {
+            tracing::debug!("Could not report platform event: {}", e);
+        }
+    }
+
+    if !moved.is_empty() {
+        save_moved_notecards(moved.drain().collect(), &config_manager, &ipc_client).await;
+    }
+}
+
+/// Stores where notecards were dragged as their display property overrides
+///
+/// Goes through core when connected, which saves and pushes the change, so
+/// its copy of the configuration doesn't overwrite the new places later.
+async fn save_moved_notecards(
+    moved: Vec<(NotecardId, (usize, (i32, i32)))>,
+    config_manager: &Arc<Mutex<ConfigManager>>,
+    ipc_client: &Arc<Mutex<IpcClient>>,
+) {
+    let notecards: Vec<_> = {
+        let mut manager = config_manager.lock().await;
+        moved
+            .into_iter()
+            .filter_map(|(id, (monitor, position))| {
+                // Pinned to the point it was dropped on, whatever placed it before
+                let mut properties = manager.config().display_properties_for(id).clone();
+                properties.monitor = MonitorSelector::Index(monitor);
+                properties.anchor = None;
+                properties.position = position;
+
+                if let Err(e) = manager.set_notecard_display_properties(id, properties) {
+                    tracing::warn!("Could not keep where notecard {} was moved: {}", id.value(), e);
+                    return None;
+                }
+                tracing::info!("Notecard {} moved to {:?} on screen {}", id.value(), position, monitor);
+                manager.get_notecard(id).cloned()
+            })
+            .collect()
+    };
+
+    let mut client = ipc_client.lock().await;
+    if client.is_connected().await {
+        for notecard in notecards {
+            if let Err(e) = client.update_notecard(notecard).await {
+                tracing::warn!("Could not save where a notecard was moved: {}", e);
+            }
+        }
+    } else if let Err(e) = config_manager.lock().await.save() {
+        tracing::warn!("Could not save where notecards were moved: {}", e);
     }
+}
 
-    async fn should_exit(&self) -> bool {
-        // This could check for a shutdown flag set by the tray menu
-        // For now, we'll rely on process termination
-        false
+/// Shows every notecard with content, for the tray menu
+async fn show_all_notecards(config_manager: Arc<Mutex<ConfigManager>>, platform: Arc<Mutex<WindowsPlatform>>) {
+    let notecards = config_manager.lock().await.config().notecards_with_content();
+    let mut platform = platform.lock().await;
+    if let Err(e) = platform.show_all_notecards(&notecards).await {
+        tracing::error!("Failed to show notecards: {}", e);
     }
 }
 
+/// Takes every notecard off screen, for the panic hotkey and the tray menu
+async fn hide_all_notecards(platform: Arc<Mutex<WindowsPlatform>>) {
+    let mut platform = platform.lock().await;
+    if let Err(e) = platform.hide_all_notecards().await {
+        tracing::error!("Failed to hide notecards: {}", e);
+    }
+}
+
+/// Shows a notecard unless it's already on screen, for `--show`
 async fn show_notecard(
     notecard_id: NotecardId,
     config_manager: Arc<Mutex<ConfigManager>>,
-    window_manager: Arc<Mutex<NotecardWindowManager>>,
+    platform: Arc<Mutex<WindowsPlatform>>,
 ) -> Result<()> {
-    let manager = config_manager.lock().await;
+    if platform.lock().await.is_notecard_visible(notecard_id).await? {
+        return Ok(());
+    }
+    toggle_notecard(notecard_id, config_manager, platform).await
+}
 
-    if let Some(notecard) = manager.get_notecard(notecard_id) {
-        if !notecard.content.is_empty() {
-            let properties = &manager.config().default_display_properties;
-            let mut window_manager = window_manager.lock().await;
-            window_manager.show_notecard(notecard_id, &notecard.content, properties)?;
+async fn toggle_notecard(
+    notecard_id: NotecardId,
+    config_manager: Arc<Mutex<ConfigManager>>,
+    platform: Arc<Mutex<WindowsPlatform>>,
+) -> Result<()> {
+    let (content, properties, blocked_applications) = {
+        let manager = config_manager.lock().await;
+        let config = manager.config();
+        let content = manager
+            .get_notecard(notecard_id)
+            .map(|notecard| notecard.content.clone())
+            .unwrap_or_default();
+        (
+            content,
+            config.display_properties_for(notecard_id).clone(),
+            config.blocked_applications.clone(),
+        )
+    };
+
+    let mut platform = platform.lock().await;
+
+    if !platform.is_notecard_visible(notecard_id).await? {
+        // A card cleared while on screen can still be hidden, but never shown
+        if content.is_empty() {
+            return Ok(());
+        }
+
+        if !blocked_applications.is_empty() {
+            // Not knowing what's in front never keeps a notecard from showing
+            let app = platform.frontmost_application().await.unwrap_or_else(|e| {
+                tracing::debug!("Could not get the frontmost application: {}", e);
+                AppIdentity::default()
+            });
+
+            if app.matches_any(&blocked_applications) {
+                tracing::debug!("Not showing notecard {} in front of {}", notecard_id.value(), app.name);
+                return Ok(());
+            }
         }
     }
 
+    let outcome = platform.toggle_notecard(notecard_id, &content, &properties).await?;
+    tracing::debug!("Notecard {} toggled: {:?}", notecard_id.value(), outcome);
+
     Ok(())
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Check if already running
+    let launch_signal = Signal::from_args(std::env::args().skip(1))?;
+
+    // Check if already running; if so, it's the running copy that opens the
+    // config UI or shows the card
     let mutex_name = format!("Global\\{}", APP_NAME);
-    unsafe {
+    let mutex = unsafe {
         let mutex = CreateMutexW(None, true, &HSTRING::from(&mutex_name))?;
         if GetLastError() == ERROR_ALREADY_EXISTS {
-            eprintln!("Notecognito is already running");
+            if let Err(e) = instance::signal_running(launch_signal) {
+                eprintln!("Notecognito is already running, but couldn't be reached: {}", e);
+            }
             return Ok(());
         }
-    }
+        mutex
+    };
 
     // Create and run app
     let mut app = App::new().await?;
-    app.initialize().await?;
-    app.run().await?;
+    app.initialize(launch_signal).await?;
+    let result = app.run().await;
+    drop(app);
 
-    Ok(())
+    // Lets a new copy start as soon as this one is gone
+    unsafe {
+        let _ = ReleaseMutex(mutex);
+        let _ = CloseHandle(mutex);
+    }
+
+    result
 }
\ No newline at end of file
@@ -1,34 +1,77 @@
-use anyhow::Result;
-use notecognito_core::{DisplayProperties, NotecardId};
+use anyhow::{anyhow, Result};
+use notecognito_core::{
+    locate_window, parse_hex_color, DismissReason, DisplayProperties, NotecardId, PhysicalRect, PlatformEvent, Rgba,
+};
 use std::collections::HashMap;
 use std::ffi::c_void;
 use std::mem;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 use windows::Win32::{
     Foundation::*,
     Graphics::Dwm::*,
     Graphics::Gdi::*,
     System::LibraryLoader::*,
+    UI::Input::KeyboardAndMouse::{GetCapture, ReleaseCapture, SetCapture},
     UI::WindowsAndMessaging::*,
 };
 
+use crate::escape_hook::{escape_target, OpenCard};
+use crate::text_render::{self, TextStyle};
+
 const NOTECARD_CLASS_NAME: &str = "NotecognitoNotecard";
+/// Carries one of the `CLOSED_BY_*` values in WPARAM
 const WM_NOTECARD_CLOSE: u32 = WM_USER + 100;
+const CLOSED_BY_CLICK: usize = 0;
+const CLOSED_BY_ESCAPE: usize = 1;
+const CLOSED_BY_TIMER: usize = 2;
+/// Carries a boxed `NotecardUpdate` in LPARAM; sent so the swap happens on the window's thread
+const WM_NOTECARD_UPDATE: u32 = WM_USER + 101;
+
+const AUTO_HIDE_TIMER: usize = 1;
+/// Steps the window's `Fade`
+const FADE_TIMER: usize = 2;
+/// Length of the fade in and out
+const FADE_DURATION: Duration = Duration::from_millis(150);
+/// Time between fade steps, about a frame at 60Hz
+const FADE_STEP_MS: u32 = 16;
+
+/// Default colors, after the system's dark and light app surfaces
+const DARK_TEXT_COLOR: Rgba = Rgba { r: 0xFF, g: 0xFF, b: 0xFF, a: 0xFF };
+const DARK_BACKGROUND_COLOR: Rgba = Rgba { r: 0x20, g: 0x20, b: 0x20, a: 0xFF };
+const LIGHT_TEXT_COLOR: Rgba = Rgba { r: 0x1B, g: 0x1B, b: 0x1B, a: 0xFF };
+const LIGHT_BACKGROUND_COLOR: Rgba = Rgba { r: 0xF3, g: 0xF3, b: 0xF3, a: 0xFF };
 
 pub struct NotecardWindow {
     hwnd: HWND,
     notecard_id: NotecardId,
+    /// Order the window was shown in; Escape closes the highest first
+    shown: u64,
+    /// Clicks go through to what's underneath, and Escape leaves it be
+    click_through: bool,
 }
 
+/// Open windows, shared with the window procedure so it can drop windows the
+/// user closes (click, ESC or auto-hide timer)
+pub type WindowMap = Arc<Mutex<HashMap<NotecardId, NotecardWindow>>>;
+
 pub struct NotecardWindowManager {
-    windows: HashMap<NotecardId, NotecardWindow>,
+    windows: WindowMap,
     class_registered: bool,
+    /// Windows shown so far, numbering each one's `shown`
+    shown_count: u64,
+    /// Where dismissals by click, ESC or auto-hide timer are reported
+    events: mpsc::Sender<PlatformEvent>,
 }
 
 impl NotecardWindowManager {
-    pub fn new() -> Self {
+    pub fn new(events: mpsc::Sender<PlatformEvent>) -> Self {
         NotecardWindowManager {
-            windows: HashMap::new(),
+            windows: Arc::new(Mutex::new(HashMap::new())),
             class_registered: false,
+            shown_count: 0,
+            events,
         }
     }
 
@@ -41,21 +84,36 @@ impl NotecardWindowManager {
         // Hide existing window if any
         self.hide_notecard(notecard_id)?;
 
+        // Cards without colors of their own follow the theme as it is now
+        crate::theme::refresh();
+
         // Register window class if needed
         if !self.class_registered {
             self.register_window_class()?;
         }
 
         // Create window
-        let hwnd = self.create_notecard_window(notecard_id, content, properties)?;
+        let bounds = card_bounds(content, properties);
+        let hwnd = self.create_notecard_window(notecard_id, content, properties, bounds)?;
 
         // Store window handle
-        self.windows.insert(notecard_id, NotecardWindow { hwnd, notecard_id });
+        self.shown_count += 1;
+        self.windows.lock().unwrap().insert(
+            notecard_id,
+            NotecardWindow {
+                hwnd,
+                notecard_id,
+                shown: self.shown_count,
+                click_through: properties.click_through,
+            },
+        );
+        crate::escape_hook::sync(&self.windows);
 
         // Show window
         unsafe {
             ShowWindow(hwnd, SW_SHOWNA);
             UpdateWindow(hwnd)?;
+            fade_in(hwnd);
         }
 
         // Set auto-hide timer if configured
@@ -63,7 +121,7 @@ impl NotecardWindowManager {
             unsafe {
                 SetTimer(
                     hwnd,
-                    1,
+                    AUTO_HIDE_TIMER,
                     properties.auto_hide_duration * 1000,
                     None,
                 )?;
@@ -74,14 +132,146 @@ impl NotecardWindowManager {
     }
 
     pub fn hide_notecard(&mut self, notecard_id: NotecardId) -> Result<()> {
-        if let Some(window) = self.windows.remove(&notecard_id) {
+        // Release the lock first; WM_DESTROY takes it again
+        let window = self.windows.lock().unwrap().remove(&notecard_id);
+        if let Some(window) = window {
+            unsafe {
+                fade_out_and_destroy(window.hwnd);
+            }
+        }
+        crate::escape_hook::sync(&self.windows);
+        Ok(())
+    }
+
+    /// Closes every open window, reporting each as dismissed by hide-all
+    pub fn hide_all(&mut self) -> Result<()> {
+        // Release the lock first; WM_DESTROY takes it again
+        let windows: Vec<_> = self.windows.lock().unwrap().drain().collect();
+        for (notecard_id, window) in windows {
             unsafe {
-                DestroyWindow(window.hwnd)?;
+                fade_out_and_destroy(window.hwnd);
+            }
+            let event = PlatformEvent::NotecardDismissed { id: notecard_id, reason: DismissReason::HideAll };
+            if let Err(e) = self.events.try_send(event) {
+                tracing::debug!("Dropped notecard dismissal: {}", e);
             }
         }
+        crate::escape_hook::sync(&self.windows);
+        Ok(())
+    }
+
+    /// Swaps the content and geometry of an open window without recreating it
+    pub fn update_notecard(
+        &mut self,
+        notecard_id: NotecardId,
+        content: &str,
+        properties: &DisplayProperties,
+    ) -> Result<()> {
+        let hwnd = match self.windows.lock().unwrap().get_mut(&notecard_id) {
+            Some(window) => {
+                window.click_through = properties.click_through;
+                window.hwnd
+            }
+            None => return Ok(()),
+        };
+
+        let update = Box::new(NotecardUpdate {
+            content: content.to_string(),
+            properties: properties.clone(),
+            bounds: card_bounds(content, properties),
+        });
+
+        unsafe {
+            SendMessageW(
+                hwnd,
+                WM_NOTECARD_UPDATE,
+                WPARAM(0),
+                LPARAM(Box::into_raw(update) as isize),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Moves an open window without activating it or changing its size
+    pub fn set_notecard_position(&mut self, notecard_id: NotecardId, x: i32, y: i32) -> Result<()> {
+        let hwnd = self.window_handle(notecard_id)?;
+        unsafe {
+            SetWindowPos(hwnd, HWND_TOPMOST, x, y, 0, 0, SWP_NOSIZE | SWP_NOACTIVATE)?;
+        }
+        Ok(())
+    }
+
+    /// Resizes an open window in place; WM_PAINT lays the text out in the new client area
+    pub fn set_notecard_size(&mut self, notecard_id: NotecardId, width: u32, height: u32) -> Result<()> {
+        let hwnd = self.window_handle(notecard_id)?;
+        unsafe {
+            SetWindowPos(
+                hwnd,
+                HWND_TOPMOST,
+                0,
+                0,
+                width as i32,
+                height as i32,
+                SWP_NOMOVE | SWP_NOACTIVATE,
+            )?;
+            InvalidateRect(hwnd, None, false);
+        }
         Ok(())
     }
 
+    /// The open windows, for restyling them from other threads
+    pub fn window_map(&self) -> WindowMap {
+        Arc::clone(&self.windows)
+    }
+
+    pub fn is_notecard_visible(&self, notecard_id: NotecardId) -> bool {
+        self.prune();
+        self.windows
+            .lock()
+            .unwrap()
+            .get(&notecard_id)
+            .map(|window| unsafe { IsWindowVisible(window.hwnd).as_bool() })
+            .unwrap_or(false)
+    }
+
+    pub fn visible_notecards(&self) -> Vec<NotecardId> {
+        self.prune();
+        let windows = self.windows.lock().unwrap();
+        let mut ids: Vec<_> = windows
+            .values()
+            .filter(|window| unsafe { IsWindowVisible(window.hwnd).as_bool() })
+            .map(|window| window.notecard_id)
+            .collect();
+        ids.sort_by_key(|id| id.value());
+        ids
+    }
+
+    /// Forgets windows destroyed without their window procedure cleaning up,
+    /// as happens with the thread that created them, so a toggle never
+    /// mistakes a dead handle for an open card
+    fn prune(&self) {
+        let mut windows = self.windows.lock().unwrap();
+        let before = windows.len();
+        windows.retain(|_, window| unsafe { IsWindow(window.hwnd).as_bool() });
+        let pruned = windows.len() != before;
+        drop(windows);
+
+        if pruned {
+            tracing::debug!("Forgot notecard windows that were already gone");
+            crate::escape_hook::sync(&self.windows);
+        }
+    }
+
+    fn window_handle(&self, notecard_id: NotecardId) -> Result<HWND> {
+        self.windows
+            .lock()
+            .unwrap()
+            .get(&notecard_id)
+            .map(|window| window.hwnd)
+            .ok_or_else(|| anyhow!("Notecard {} has no open window", notecard_id.value()))
+    }
+
     fn register_window_class(&mut self) -> Result<()> {
         unsafe {
             let instance = GetModuleHandleW(None)?;
@@ -115,28 +305,43 @@ impl NotecardWindowManager {
         notecard_id: NotecardId,
         content: &str,
         properties: &DisplayProperties,
+        bounds: PhysicalRect,
     ) -> Result<HWND> {
         unsafe {
             let instance = GetModuleHandleW(None)?;
 
+            // Starts out transparent when it's going to fade in
+            let alpha = if animates(properties) { 0 } else { window_alpha(properties) };
+
             // Create window data
             let window_data = Box::new(NotecardWindowData {
                 notecard_id,
                 content: content.to_string(),
                 properties: properties.clone(),
                 font: HFONT::default(),
+                windows: Arc::clone(&self.windows),
+                events: self.events.clone(),
+                press: None,
+                alpha,
+                fade: None,
             });
 
+            // Layered and transparent, the window is left out of hit-testing
+            let mut ex_style = WS_EX_LAYERED | WS_EX_TOPMOST | WS_EX_TOOLWINDOW | WS_EX_NOACTIVATE;
+            if properties.click_through {
+                ex_style |= WS_EX_TRANSPARENT;
+            }
+
             // Create the window
             let hwnd = CreateWindowExW(
-                WS_EX_LAYERED | WS_EX_TOPMOST | WS_EX_TOOLWINDOW | WS_EX_NOACTIVATE,
+                ex_style,
                 w!(NOTECARD_CLASS_NAME),
                 w!("Notecognito"),
                 WS_POPUP,
-                properties.position.0,
-                properties.position.1,
-                properties.size.0 as i32,
-                properties.size.1 as i32,
+                bounds.x,
+                bounds.y,
+                bounds.width as i32,
+                bounds.height as i32,
                 None,
                 None,
                 instance,
@@ -148,22 +353,86 @@ impl NotecardWindowManager {
             }
 
             // Set window transparency
-            let alpha = ((properties.opacity as u32 * 255) / 100) as u8;
             SetLayeredWindowAttributes(hwnd, COLORREF(0), alpha, LWA_ALPHA)?;
 
             // Enable blur behind for Windows 10/11
             let _ = enable_blur_behind(hwnd);
 
+            set_capture_exclusion(hwnd, properties.exclude_from_capture);
+
             Ok(hwnd)
         }
     }
 }
 
+/// Closes the card Escape is for, as `escape_target` picks it; false when
+/// there's none
+pub fn dismiss_most_recent(windows: &WindowMap) -> bool {
+    let hwnd = {
+        let windows = windows.lock().unwrap();
+        let open = windows.values().map(|window| OpenCard {
+            notecard_id: window.notecard_id,
+            shown: window.shown,
+            click_through: window.click_through,
+        });
+        escape_target(open).and_then(|id| windows.get(&id)).map(|window| window.hwnd)
+    };
+    match hwnd {
+        Some(hwnd) => unsafe { PostMessageW(hwnd, WM_NOTECARD_CLOSE, WPARAM(CLOSED_BY_ESCAPE), LPARAM(0)).is_ok() },
+        None => false,
+    }
+}
+
+/// Repaints every open card, say in the colors of a new theme
+pub fn repaint_all(windows: &WindowMap) {
+    for window in windows.lock().unwrap().values() {
+        unsafe {
+            // Their background alpha, and so the window's, doesn't change with the theme
+            InvalidateRect(window.hwnd, None, false);
+        }
+    }
+}
+
+struct NotecardUpdate {
+    content: String,
+    properties: DisplayProperties,
+    /// Window bounds in device pixels, resolved from the properties' anchor,
+    /// monitor and units
+    bounds: PhysicalRect,
+}
+
 struct NotecardWindowData {
     notecard_id: NotecardId,
     content: String,
     properties: DisplayProperties,
     font: HFONT,
+    windows: WindowMap,
+    events: mpsc::Sender<PlatformEvent>,
+    /// Set while the left button is down on the card
+    press: Option<Press>,
+    /// The layered window's alpha as last set
+    alpha: u8,
+    fade: Option<Fade>,
+}
+
+/// A fade of the window's alpha in progress, stepped by `FADE_TIMER`
+struct Fade {
+    from: u8,
+    to: u8,
+    started: Instant,
+    /// Destroy the window once the fade ends
+    closing: bool,
+}
+
+/// A left-button press on a card, which dismisses it on release unless it
+/// turned into a drag
+#[derive(Clone, Copy)]
+struct Press {
+    /// Cursor position when the button went down, in screen coordinates
+    start: POINT,
+    /// The window's top-left corner then
+    origin: POINT,
+    dragging: bool,
 }
 
 unsafe extern "system" fn notecard_window_proc(
@@ -180,25 +449,7 @@ unsafe extern "system" fn notecard_window_proc(
 
             // Create font
             if let Some(data) = window_data.as_mut() {
-                let font_name = match data.properties.font_family.as_str() {
-                    "System" => "Segoe UI",
-                    name => name,
-                };
-
-                data.font = CreateFontW(
-                    -(data.properties.font_size as i32),
-                    0, 0, 0,
-                    FW_NORMAL.0 as i32,
-                    false.into(),
-                    false.into(),
-                    false.into(),
-                    DEFAULT_CHARSET.0 as u32,
-                    OUT_DEFAULT_PRECIS.0 as u32,
-                    CLIP_DEFAULT_PRECIS.0 as u32,
-                    CLEARTYPE_QUALITY.0 as u32,
-                    DEFAULT_PITCH.0 as u32 | FF_DONTCARE.0 as u32,
-                    &HSTRING::from(font_name),
-                );
+                data.font = create_font(&data.properties, crate::screens::window_scale_factor(hwnd));
             }
 
             LRESULT(0)
@@ -208,62 +459,232 @@ unsafe extern "system" fn notecard_window_proc(
             let mut ps = PAINTSTRUCT::default();
             let hdc = BeginPaint(hwnd, &mut ps);
 
-            if let Some(window_data) = get_window_data(hwnd) {
-                // Set up drawing
-                SetBkMode(hdc, TRANSPARENT);
-                SetTextColor(hdc, COLORREF(0xFFFFFF)); // White text
-                SelectObject(hdc, window_data.font);
-
-                // Get client rect
-                let mut rect = RECT::default();
-                GetClientRect(hwnd, &mut rect)?;
-
-                // Draw dark background
-                let brush = CreateSolidBrush(COLORREF(0x202020));
-                FillRect(hdc, &rect, brush);
-                DeleteObject(brush);
-
-                // Add padding
-                rect.left += 10;
-                rect.top += 10;
-                rect.right -= 10;
-                rect.bottom -= 10;
-
-                // Draw text
-                let text = HSTRING::from(&window_data.content);
-                DrawTextW(
-                    hdc,
-                    &text,
-                    &mut rect,
-                    DT_LEFT | DT_TOP | DT_WORDBREAK | DT_EXPANDTABS,
-                );
+            // Get client rect; without it there's nothing to paint into
+            let mut rect = RECT::default();
+            let client_rect = GetClientRect(hwnd, &mut rect);
+            if let Err(e) = &client_rect {
+                tracing::warn!("Could not get the notecard's client area: {}", e);
+            }
+
+            if let Some(window_data) = get_window_data(hwnd).filter(|_| client_rect.is_ok()) {
+                let properties = &window_data.properties;
+                let (text_color, background_color) = card_colors(properties);
+
+                let style = TextStyle {
+                    font_face: font_face(properties),
+                    font_size: font_height(properties, crate::screens::window_scale_factor(hwnd)) as f32,
+                    color: text_color,
+                    background: background_color,
+                };
+
+                // GDI is the fallback for when Direct2D can't draw
+                if let Err(e) = text_render::draw(hdc, &rect, &window_data.content, &style) {
+                    tracing::debug!("Drawing notecard {} with GDI: {}", window_data.notecard_id.value(), e);
+
+                    // Set up drawing
+                    SetBkMode(hdc, TRANSPARENT);
+                    SetTextColor(hdc, colorref(text_color));
+                    SelectObject(hdc, window_data.font);
+
+                    // Draw background; its alpha is in the window's, see `window_alpha`
+                    let brush = CreateSolidBrush(colorref(background_color));
+                    FillRect(hdc, &rect, brush);
+                    DeleteObject(brush);
+
+                    // Add padding
+                    rect.left += text_render::PADDING;
+                    rect.top += text_render::PADDING;
+                    rect.right -= text_render::PADDING;
+                    rect.bottom -= text_render::PADDING;
+
+                    // Draw text
+                    let text = HSTRING::from(&window_data.content);
+                    DrawTextW(
+                        hdc,
+                        &text,
+                        &mut rect,
+                        DT_LEFT | DT_TOP | DT_WORDBREAK | DT_EXPANDTABS,
+                    );
+                }
             }
 
             EndPaint(hwnd, &ps);
             LRESULT(0)
         }
 
+        WM_TIMER if wparam.0 == FADE_TIMER => {
+            step_fade(hwnd);
+            LRESULT(0)
+        }
+
         WM_TIMER => {
             // Auto-hide timer fired
-            PostMessageW(hwnd, WM_NOTECARD_CLOSE, WPARAM(0), LPARAM(0))?;
+            if let Err(e) = PostMessageW(hwnd, WM_NOTECARD_CLOSE, WPARAM(CLOSED_BY_TIMER), LPARAM(0)) {
+                tracing::warn!("Could not auto-hide the notecard: {}", e);
+            }
             LRESULT(0)
         }
 
+        // Dismissing on release rather than press leaves room for a drag
         WM_LBUTTONDOWN => {
-            // Close on click
-            PostMessageW(hwnd, WM_NOTECARD_CLOSE, WPARAM(0), LPARAM(0))?;
+            // Click-through windows aren't hit-tested, but don't count on it
+            if let Some(window_data) = get_window_data_mut(hwnd).filter(|data| !data.properties.click_through) {
+                let mut cursor = POINT::default();
+                let mut window_rect = RECT::default();
+                if GetCursorPos(&mut cursor).is_ok() && GetWindowRect(hwnd, &mut window_rect).is_ok() {
+                    window_data.press = Some(Press {
+                        start: cursor,
+                        origin: POINT { x: window_rect.left, y: window_rect.top },
+                        dragging: false,
+                    });
+                    SetCapture(hwnd);
+                }
+            }
+            LRESULT(0)
+        }
+
+        WM_MOUSEMOVE => {
+            if let Some(press) = get_window_data_mut(hwnd).and_then(|data| data.press.as_mut()) {
+                let mut cursor = POINT::default();
+                if GetCursorPos(&mut cursor).is_ok() {
+                    let (dx, dy) = (cursor.x - press.start.x, cursor.y - press.start.y);
+
+                    // The same distance Windows waits for before a drag starts
+                    if dx.abs() >= GetSystemMetrics(SM_CXDRAG) || dy.abs() >= GetSystemMetrics(SM_CYDRAG) {
+                        press.dragging = true;
+                    }
+
+                    // Moved by hand rather than by the system's move loop, so the
+                    // card stays topmost and never activates
+                    if press.dragging {
+                        let _ = SetWindowPos(
+                            hwnd,
+                            HWND_TOPMOST,
+                            press.origin.x + dx,
+                            press.origin.y + dy,
+                            0,
+                            0,
+                            SWP_NOSIZE | SWP_NOACTIVATE,
+                        );
+                    }
+                }
+            }
+            LRESULT(0)
+        }
+
+        WM_LBUTTONUP => {
+            // Taken first, so the WM_CAPTURECHANGED from ReleaseCapture sees no press
+            let press = get_window_data_mut(hwnd).and_then(|data| data.press.take());
+            let _ = ReleaseCapture();
+
+            match press {
+                Some(Press { dragging: true, .. }) => report_move(hwnd),
+                Some(_) => {
+                    if let Err(e) = PostMessageW(hwnd, WM_NOTECARD_CLOSE, WPARAM(CLOSED_BY_CLICK), LPARAM(0)) {
+                        tracing::warn!("Could not dismiss the clicked notecard: {}", e);
+                    }
+                }
+                None => {}
+            }
             LRESULT(0)
         }
 
-        WM_KEYDOWN => {
-            if wparam.0 == VK_ESCAPE.0 as usize {
-                PostMessageW(hwnd, WM_NOTECARD_CLOSE, WPARAM(0), LPARAM(0))?;
+        WM_CAPTURECHANGED => {
+            // Capture taken away mid-press: a drag keeps where it got to, and a
+            // click is called off
+            if let Some(Press { dragging: true, .. }) = get_window_data_mut(hwnd).and_then(|data| data.press.take()) {
+                report_move(hwnd);
             }
             LRESULT(0)
         }
 
         WM_NOTECARD_CLOSE => {
-            DestroyWindow(hwnd)?;
+            if let Some(window_data) = get_window_data(hwnd) {
+                // Stop tracking the window first, so only a close that wins a race
+                // with hide_notecard is reported
+                let mut windows = window_data.windows.lock().unwrap();
+                let tracked = windows.get(&window_data.notecard_id).map(|w| w.hwnd) == Some(hwnd);
+                if tracked {
+                    windows.remove(&window_data.notecard_id);
+                }
+                drop(windows);
+
+                if tracked {
+                    let reason = match wparam.0 {
+                        CLOSED_BY_ESCAPE => DismissReason::Escape,
+                        CLOSED_BY_TIMER => DismissReason::AutoHide,
+                        _ => DismissReason::Click,
+                    };
+                    let event = PlatformEvent::NotecardDismissed { id: window_data.notecard_id, reason };
+                    if let Err(e) = window_data.events.try_send(event) {
+                        tracing::debug!("Dropped notecard dismissal: {}", e);
+                    }
+                }
+            }
+
+            fade_out_and_destroy(hwnd);
+            LRESULT(0)
+        }
+
+        WM_NOTECARD_UPDATE => {
+            let update = Box::from_raw(lparam.0 as *mut NotecardUpdate);
+
+            if let Some(window_data) = get_window_data_mut(hwnd) {
+                let NotecardUpdate { content, properties, bounds } = *update;
+
+                // Move first, so the font is scaled for the monitor the window ends up on;
+                // a card that can't be moved still gets its new content
+                if let Err(e) = SetWindowPos(
+                    hwnd,
+                    None,
+                    bounds.x,
+                    bounds.y,
+                    bounds.width as i32,
+                    bounds.height as i32,
+                    SWP_NOZORDER | SWP_NOACTIVATE,
+                ) {
+                    tracing::warn!("Could not move notecard {}: {}", window_data.notecard_id.value(), e);
+                }
+
+                // Only rebuild the font when it actually changed
+                if properties.font_family != window_data.properties.font_family
+                    || properties.font_size != window_data.properties.font_size
+                    || properties.units != window_data.properties.units
+                    || properties.monitor != window_data.properties.monitor
+                {
+                    if window_data.font.0 != 0 {
+                        DeleteObject(window_data.font);
+                    }
+                    window_data.font = create_font(&properties, crate::screens::window_scale_factor(hwnd));
+                }
+
+                if properties.click_through != window_data.properties.click_through {
+                    set_click_through(hwnd, properties.click_through);
+
+                    // A press in progress stops here, a drag where it got to
+                    if properties.click_through && GetCapture() == hwnd {
+                        let _ = ReleaseCapture();
+                    }
+                }
+
+                if properties.exclude_from_capture != window_data.properties.exclude_from_capture {
+                    set_capture_exclusion(hwnd, properties.exclude_from_capture);
+                }
+
+                window_data.content = content;
+                window_data.properties = properties;
+
+                // A fade in still in progress carries on to the new alpha
+                let alpha = window_alpha(&window_data.properties);
+                match window_data.fade.as_mut() {
+                    Some(fade) => fade.to = alpha,
+                    None => set_alpha(hwnd, window_data, alpha),
+                }
+
+                // WM_PAINT fills the whole client area, so skip the erase
+                InvalidateRect(hwnd, None, false);
+            }
+
             LRESULT(0)
         }
 
@@ -273,6 +694,17 @@ unsafe extern "system" fn notecard_window_proc(
                 if window_data.font.0 != 0 {
                     DeleteObject(window_data.font);
                 }
+
+                // Stop tracking the window, unless it was already replaced
+                let mut windows = window_data.windows.lock().unwrap();
+                if windows.get(&window_data.notecard_id).map(|w| w.hwnd) == Some(hwnd) {
+                    windows.remove(&window_data.notecard_id);
+                }
+                drop(windows);
+
+                // Gives Escape back to other apps once the last card is gone
+                crate::escape_hook::sync(&window_data.windows);
+
                 // Free the window data
                 let _ = Box::from_raw(window_data);
             }
@@ -284,6 +716,287 @@ unsafe extern "system" fn notecard_window_proc(
     }
 }
 
+/// Whether cards fade in and out: `animations` is on, and so is the system's
+/// "Animation effects" setting, which turning off asks apps to reduce motion
+fn animates(properties: &DisplayProperties) -> bool {
+    let mut enabled = BOOL(1);
+    let system = unsafe {
+        SystemParametersInfoW(
+            SPI_GETCLIENTAREAANIMATION,
+            0,
+            Some(&mut enabled as *mut BOOL as *mut c_void),
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        )
+    };
+    properties.animations && (system.is_err() || enabled.as_bool())
+}
+
+/// Fades a just-shown window up to its configured alpha, or sets it right
+/// away with animations off
+unsafe fn fade_in(hwnd: HWND) {
+    let Some(window_data) = get_window_data_mut(hwnd) else {
+        return;
+    };
+
+    let alpha = window_alpha(&window_data.properties);
+    if !(animates(&window_data.properties) && start_fade(hwnd, window_data, alpha, false)) {
+        set_alpha(hwnd, window_data, alpha);
+    }
+}
+
+/// Destroys a window, fading it out first unless animations are off
+///
+/// Callers stop tracking the window first, so a toggle during the fade shows
+/// a fresh card. Until the fade ends the window lets clicks through and
+/// ignores its auto-hide timer, and closing it again does nothing, so every
+/// fading window is destroyed exactly once when its fade ends.
+unsafe fn fade_out_and_destroy(hwnd: HWND) {
+    if let Some(window_data) = get_window_data_mut(hwnd) {
+        if window_data.fade.as_ref().is_some_and(|fade| fade.closing) {
+            return;
+        }
+
+        if animates(&window_data.properties) {
+            let _ = KillTimer(hwnd, AUTO_HIDE_TIMER);
+
+            // A drag in progress ends where it got to, see WM_CAPTURECHANGED
+            if GetCapture() == hwnd {
+                let _ = ReleaseCapture();
+            }
+            set_click_through(hwnd, true);
+
+            // Fades out from wherever a fade in got to
+            if start_fade(hwnd, window_data, 0, true) {
+                return;
+            }
+        }
+    }
+
+    let _ = DestroyWindow(hwnd);
+}
+
+/// Starts fading the window's alpha from where it is to `to`, replacing any
+/// fade in progress; false when the timer couldn't be set
+unsafe fn start_fade(hwnd: HWND, window_data: &mut NotecardWindowData, to: u8, closing: bool) -> bool {
+    window_data.fade = Some(Fade { from: window_data.alpha, to, started: Instant::now(), closing });
+
+    if SetTimer(hwnd, FADE_TIMER, FADE_STEP_MS, None) == 0 {
+        window_data.fade = None;
+        return false;
+    }
+    true
+}
+
+/// Moves the window's fade along, ending it, and destroying a closing window,
+/// once `FADE_DURATION` has passed
+unsafe fn step_fade(hwnd: HWND) {
+    let Some(window_data) = get_window_data_mut(hwnd) else {
+        return;
+    };
+    let Some(fade) = &window_data.fade else {
+        let _ = KillTimer(hwnd, FADE_TIMER);
+        return;
+    };
+
+    let progress = (fade.started.elapsed().as_secs_f64() / FADE_DURATION.as_secs_f64()).min(1.0);
+    let alpha = (fade.from as f64 + (fade.to as f64 - fade.from as f64) * progress).round() as u8;
+    let closing = fade.closing;
+    set_alpha(hwnd, window_data, alpha);
+
+    if progress < 1.0 {
+        return;
+    }
+    let _ = KillTimer(hwnd, FADE_TIMER);
+    window_data.fade = None;
+
+    if closing {
+        let _ = DestroyWindow(hwnd);
+    }
+}
+
+/// Lets clicks through the window to whatever is underneath, or stops
+/// letting them through
+unsafe fn set_click_through(hwnd: HWND, click_through: bool) {
+    let ex_style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+    let ex_style = if click_through {
+        ex_style | WS_EX_TRANSPARENT.0 as isize
+    } else {
+        ex_style & !(WS_EX_TRANSPARENT.0 as isize)
+    };
+    SetWindowLongPtrW(hwnd, GWL_EXSTYLE, ex_style);
+}
+
+/// Leaves the window out of screenshots, recordings and screen sharing, or
+/// lets it back in
+///
+/// `WDA_EXCLUDEFROMCAPTURE` needs Windows 10 2004, as
+/// `PlatformCapabilities::screen_capture_exclusion` reports. Before that,
+/// `WDA_MONITOR` at least shows the card as a black box in captures, so its
+/// text still stays private.
+unsafe fn set_capture_exclusion(hwnd: HWND, exclude: bool) {
+    let affinity = match (exclude, crate::capabilities::detect().screen_capture_exclusion) {
+        (false, _) => WDA_NONE,
+        (true, true) => WDA_EXCLUDEFROMCAPTURE,
+        (true, false) => {
+            tracing::info!("This Windows can't leave notecards out of captures; blacking them out instead");
+            WDA_MONITOR
+        }
+    };
+
+    // Works on layered windows only with desktop composition, which is always on since Windows 8
+    if let Err(e) = SetWindowDisplayAffinity(hwnd, affinity) {
+        tracing::warn!("Could not set the notecard's capture affinity: {}", e);
+    }
+}
+
+unsafe fn set_alpha(hwnd: HWND, window_data: &mut NotecardWindowData, alpha: u8) {
+    if SetLayeredWindowAttributes(hwnd, COLORREF(0), alpha, LWA_ALPHA).is_ok() {
+        window_data.alpha = alpha;
+    }
+}
+
+/// Reports where the user dragged a card: its top-left corner relative to the
+/// monitor it's on, in the card's configured units, as
+/// `DisplayProperties::position` is without an anchor
+unsafe fn report_move(hwnd: HWND) {
+    let Some(window_data) = get_window_data(hwnd) else {
+        return;
+    };
+
+    let mut rect = RECT::default();
+    if GetWindowRect(hwnd, &mut rect).is_err() {
+        return;
+    }
+    let size = ((rect.right - rect.left).max(0) as u32, (rect.bottom - rect.top).max(0) as u32);
+
+    let screens = crate::screens::screens().unwrap_or_default();
+    let Some((monitor, (x, y))) = locate_window(&screens, (rect.left, rect.top), size) else {
+        return;
+    };
+
+    // Back from device pixels, as `place_window_physical` scaled them
+    let scale = screens
+        .iter()
+        .find(|screen| screen.index == monitor)
+        .map_or(1.0, |screen| window_data.properties.scale_for(screen.scale_factor));
+    let position = ((x as f64 / scale).round() as i32, (y as f64 / scale).round() as i32);
+
+    let event = PlatformEvent::NotecardMoved { id: window_data.notecard_id, monitor, position };
+    if let Err(e) = window_data.events.try_send(event) {
+        tracing::debug!("Dropped notecard move: {}", e);
+    }
+}
+
+/// Where a card goes and how big it is, sized to `content` when its
+/// `size_mode` asks
+fn card_bounds(content: &str, properties: &DisplayProperties) -> PhysicalRect {
+    crate::screens::place(properties, text_render::PADDING as u32, |max_width, scale_factor| {
+        measure_content(content, properties, max_width, scale_factor)
+    })
+}
+
+/// Size `content` takes wrapped to `max_width`, in device pixels on a monitor
+/// with `scale_factor`, measured the way WM_PAINT draws it
+fn measure_content(content: &str, properties: &DisplayProperties, max_width: u32, scale_factor: f64) -> (u32, u32) {
+    let (color, background) = card_colors(properties);
+    let style = TextStyle {
+        font_face: font_face(properties),
+        font_size: font_height(properties, scale_factor) as f32,
+        color,
+        background,
+    };
+
+    text_render::measure(content, &style, max_width).unwrap_or_else(|e| {
+        tracing::debug!("Measuring notecard text with GDI: {}", e);
+        unsafe { measure_with_gdi(content, properties, max_width, scale_factor) }
+    })
+}
+
+/// `measure_content` with GDI's `DrawTextW(DT_CALCRECT)`, for when
+/// DirectWrite isn't available
+unsafe fn measure_with_gdi(content: &str, properties: &DisplayProperties, max_width: u32, scale_factor: f64) -> (u32, u32) {
+    let hdc = GetDC(None);
+    let font = create_font(properties, scale_factor);
+    let previous = SelectObject(hdc, font);
+
+    let mut rect = RECT { left: 0, top: 0, right: max_width as i32, bottom: 0 };
+    let text = HSTRING::from(content);
+    DrawTextW(
+        hdc,
+        &text,
+        &mut rect,
+        DT_LEFT | DT_TOP | DT_WORDBREAK | DT_EXPANDTABS | DT_CALCRECT,
+    );
+
+    SelectObject(hdc, previous);
+    DeleteObject(font);
+    ReleaseDC(None, hdc);
+
+    ((rect.right - rect.left).max(0) as u32, (rect.bottom - rect.top).max(0) as u32)
+}
+
+/// Text and background colors for a card; where none is set, the defaults
+/// for the light or dark theme apps use
+fn card_colors(properties: &DisplayProperties) -> (Rgba, Rgba) {
+    let (default_text, default_background) = if crate::theme::is_light() {
+        (LIGHT_TEXT_COLOR, LIGHT_BACKGROUND_COLOR)
+    } else {
+        (DARK_TEXT_COLOR, DARK_BACKGROUND_COLOR)
+    };
+
+    let color = |text: &Option<String>, default: Rgba| text.as_deref().and_then(parse_hex_color).unwrap_or(default);
+    (
+        color(&properties.text_color, default_text),
+        color(&properties.background_color, default_background),
+    )
+}
+
+fn colorref(color: Rgba) -> COLORREF {
+    COLORREF(color.r as u32 | (color.g as u32) << 8 | (color.b as u32) << 16)
+}
+
+/// Alpha of the whole layered window: `opacity` scaled by the background
+/// color's alpha
+///
+/// LWA_ALPHA fades the window as one, so a see-through background takes the
+/// text with it rather than being drawn opaque.
+fn window_alpha(properties: &DisplayProperties) -> u8 {
+    let (_, background) = card_colors(properties);
+    (properties.opacity.min(100) as f64 / 100.0 * background.alpha() * 255.0).round() as u8
+}
+
+/// Creates the notecard font, with its height scaled for a monitor with `scale_factor`
+unsafe fn create_font(properties: &DisplayProperties, scale_factor: f64) -> HFONT {
+    CreateFontW(
+        -font_height(properties, scale_factor),
+        0, 0, 0,
+        FW_NORMAL.0 as i32,
+        false.into(),
+        false.into(),
+        false.into(),
+        DEFAULT_CHARSET.0 as u32,
+        OUT_DEFAULT_PRECIS.0 as u32,
+        CLIP_DEFAULT_PRECIS.0 as u32,
+        CLEARTYPE_QUALITY.0 as u32,
+        DEFAULT_PITCH.0 as u32 | FF_DONTCARE.0 as u32,
+        &HSTRING::from(font_face(properties)),
+    )
+}
+
+/// The installed font face a card's font family names
+fn font_face(properties: &DisplayProperties) -> &str {
+    match properties.font_family.as_str() {
+        "System" => "Segoe UI",
+        "Monospace" => "Consolas",
+        name => name,
+    }
+}
+
+/// Em height of a card's font in device pixels on a monitor with `scale_factor`
+fn font_height(properties: &DisplayProperties, scale_factor: f64) -> i32 {
+    (properties.font_size as f64 * properties.scale_for(scale_factor)).round() as i32
+}
+
 unsafe fn get_window_data(hwnd: HWND) -> Option<&'static NotecardWindowData> {
     let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const NotecardWindowData;
     ptr.as_ref()
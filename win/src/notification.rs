@@ -0,0 +1,55 @@
+use anyhow::Result;
+use notecognito_core::NotificationKind;
+use windows::core::HSTRING;
+use windows::Data::Xml::Dom::XmlDocument;
+use windows::UI::Notifications::{ToastNotification, ToastNotificationManager};
+use windows::Win32::System::Com::{CoInitializeEx, COINIT_MULTITHREADED};
+use windows::Win32::UI::Shell::SetCurrentProcessExplicitAppUserModelID;
+
+/// AppUserModelID toasts are sent under; Windows only shows toasts for an ID
+/// that a Start menu shortcut is registered with
+const APP_USER_MODEL_ID: &str = "Notecognito";
+
+/// Tags the process with our AppUserModelID so toasts are attributed to it
+pub fn register_app_id() -> Result<()> {
+    unsafe {
+        SetCurrentProcessExplicitAppUserModelID(&HSTRING::from(APP_USER_MODEL_ID))?;
+    }
+    Ok(())
+}
+
+/// Shows a toast in the action center
+pub fn show_toast(title: &str, body: &str, kind: NotificationKind) -> Result<()> {
+    // WinRT calls need COM; this is a no-op when the thread already joined the MTA
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+    }
+
+    let sound = match kind {
+        NotificationKind::Info => "ms-winsoundevent:Notification.Default",
+        NotificationKind::Warning | NotificationKind::Error => "ms-winsoundevent:Notification.Reminder",
+    };
+
+    let xml = format!(
+        "<toast><visual><binding template=\"ToastGeneric\"><text>{}</text><text>{}</text></binding></visual><audio src=\"{}\"/></toast>",
+        escape_xml(title),
+        escape_xml(body),
+        sound
+    );
+
+    let document = XmlDocument::new()?;
+    document.LoadXml(&HSTRING::from(xml))?;
+
+    let toast = ToastNotification::CreateToastNotification(&document)?;
+    ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(APP_USER_MODEL_ID))?
+        .Show(&toast)?;
+
+    Ok(())
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
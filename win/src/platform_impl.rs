@@ -1,6 +1,8 @@
 use anyhow::Result;
+use async_trait::async_trait;
 use notecognito_core::{
-    DisplayProperties, HotkeyModifier, NotecardId, PlatformInterface,
+    AppIdentity, DisplayProperties, HotkeyKey, HotkeyModifier, NotecardId, NotificationKind,
+    PlatformCapabilities, PlatformInterface, ScreenInfo, StartupMethod,
 };
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -12,6 +14,8 @@ pub struct WindowsPlatform {
     hotkey_manager: Arc<Mutex<HotkeyManager>>,
     window_manager: Arc<Mutex<NotecardWindowManager>>,
     initialized: bool,
+    /// How `set_launch_on_startup` launches the app at logon
+    startup_method: StartupMethod,
 }
 
 impl WindowsPlatform {
@@ -23,148 +27,186 @@ impl WindowsPlatform {
             hotkey_manager,
             window_manager,
             initialized: false,
+            startup_method: StartupMethod::default(),
         }
     }
+
+    /// Picks how later `set_launch_on_startup` calls launch the app at logon
+    pub fn set_startup_method(&mut self, method: StartupMethod) {
+        self.startup_method = method;
+    }
 }
 
+#[async_trait]
 impl PlatformInterface for WindowsPlatform {
-    fn register_hotkey(
+    async fn register_hotkey(
         &mut self,
         id: NotecardId,
         modifiers: &[HotkeyModifier],
     ) -> notecognito_core::Result<()> {
-        // Use tokio runtime to run async code
-        let hotkey_manager = Arc::clone(&self.hotkey_manager);
-        let modifiers = modifiers.to_vec();
+        HotkeyModifier::check_supported(modifiers)?;
 
-        let result = tokio::task::block_in_place(move || {
-            tokio::runtime::Handle::current().block_on(async move {
-                let mut manager = hotkey_manager.lock().await;
-                manager.register_hotkey(id, &modifiers)
-            })
-        });
+        let mut manager = self.hotkey_manager.lock().await;
+        manager
+            .register_hotkey(id, modifiers, HotkeyKey::for_notecard(id))
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
 
-        result.map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    async fn unregister_hotkey(&mut self, id: NotecardId) -> notecognito_core::Result<()> {
+        let mut manager = self.hotkey_manager.lock().await;
+        manager
+            .unregister_hotkey(id)
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
     }
 
-    fn unregister_hotkey(&mut self, id: NotecardId) -> notecognito_core::Result<()> {
-        let hotkey_manager = Arc::clone(&self.hotkey_manager);
+    async fn show_notecard(
+        &mut self,
+        id: NotecardId,
+        content: &str,
+        properties: &DisplayProperties,
+    ) -> notecognito_core::Result<()> {
+        let mut manager = self.window_manager.lock().await;
+        manager
+            .show_notecard(id, content, properties)
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
 
-        let result = tokio::task::block_in_place(move || {
-            tokio::runtime::Handle::current().block_on(async move {
-                let mut manager = hotkey_manager.lock().await;
-                manager.unregister_hotkey(id)
-            })
-        });
+    async fn hide_notecard(&mut self, id: NotecardId) -> notecognito_core::Result<()> {
+        let mut manager = self.window_manager.lock().await;
+        manager
+            .hide_notecard(id)
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
 
-        result.map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    async fn hide_all_notecards(&mut self) -> notecognito_core::Result<()> {
+        let mut manager = self.window_manager.lock().await;
+        manager
+            .hide_all()
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
     }
 
-    fn show_notecard(
+    async fn update_notecard(
         &mut self,
         id: NotecardId,
         content: &str,
         properties: &DisplayProperties,
     ) -> notecognito_core::Result<()> {
-        let window_manager = Arc::clone(&self.window_manager);
-        let content = content.to_string();
-        let properties = properties.clone();
-
-        let result = tokio::task::block_in_place(move || {
-            tokio::runtime::Handle::current().block_on(async move {
-                let mut manager = window_manager.lock().await;
-                manager.show_notecard(id, &content, &properties)
-            })
-        });
-
-        result.map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
-    }
-
-    fn hide_notecard(&mut self, id: NotecardId) -> notecognito_core::Result<()> {
-        let window_manager = Arc::clone(&self.window_manager);
-
-        let result = tokio::task::block_in_place(move || {
-            tokio::runtime::Handle::current().block_on(async move {
-                let mut manager = window_manager.lock().await;
-                manager.hide_notecard(id)
-            })
-        });
-
-        result.map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
-    }
-
-    fn set_launch_on_startup(&mut self, enabled: bool) -> notecognito_core::Result<()> {
-        use windows::Win32::System::Registry::*;
-        use windows::Win32::Foundation::*;
-
-        unsafe {
-            let key_path = w!("Software\\Microsoft\\Windows\\CurrentVersion\\Run");
-            let mut hkey = HKEY::default();
-
-            RegOpenKeyExW(
-                HKEY_CURRENT_USER,
-                key_path,
-                0,
-                KEY_SET_VALUE,
-                &mut hkey,
-            ).map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))?;
-
-            let result = if enabled {
-                let exe_path = std::env::current_exe()
-                    .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))?;
-                let exe_path = exe_path.to_string_lossy();
-                let value = format!("\"{}\"", exe_path);
-
-                RegSetValueExW(
-                    hkey,
-                    w!("Notecognito"),
-                    0,
-                    REG_SZ,
-                    Some(value.as_bytes()),
-                ).map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
-            } else {
-                match RegDeleteValueW(hkey, w!("Notecognito")) {
-                    Ok(_) => Ok(()),
-                    Err(e) if e.code() == ERROR_FILE_NOT_FOUND.to_hresult() => Ok(()),
-                    Err(e) => Err(notecognito_core::NotecognitoError::Platform(e.to_string())),
-                }
-            };
-
-            RegCloseKey(hkey)
-                .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))?;
-
-            result
+        let mut manager = self.window_manager.lock().await;
+        manager
+            .update_notecard(id, content, properties)
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn set_notecard_position(
+        &mut self,
+        id: NotecardId,
+        x: i32,
+        y: i32,
+    ) -> notecognito_core::Result<()> {
+        let mut manager = self.window_manager.lock().await;
+        if !manager.is_notecard_visible(id) {
+            return Err(notecognito_core::NotecognitoError::NotVisible(id));
         }
+        manager
+            .set_notecard_position(id, x, y)
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn set_notecard_size(
+        &mut self,
+        id: NotecardId,
+        width: u32,
+        height: u32,
+    ) -> notecognito_core::Result<()> {
+        let mut manager = self.window_manager.lock().await;
+        if !manager.is_notecard_visible(id) {
+            return Err(notecognito_core::NotecognitoError::NotVisible(id));
+        }
+        manager
+            .set_notecard_size(id, width, height)
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn is_notecard_visible(&self, id: NotecardId) -> notecognito_core::Result<bool> {
+        let manager = self.window_manager.lock().await;
+        Ok(manager.is_notecard_visible(id))
     }
 
-    fn initialize(&mut self) -> notecognito_core::Result<()> {
+    async fn visible_notecards(&self) -> notecognito_core::Result<Vec<NotecardId>> {
+        let manager = self.window_manager.lock().await;
+        Ok(manager.visible_notecards())
+    }
+
+    async fn screens(&self) -> notecognito_core::Result<Vec<ScreenInfo>> {
+        crate::screens::screens()
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn set_launch_on_startup(&mut self, enabled: bool) -> notecognito_core::Result<()> {
+        crate::startup::set_enabled(self.startup_method, enabled)
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(format!("{:#}", e)))
+    }
+
+    async fn launch_on_startup_status(&self) -> notecognito_core::Result<Option<bool>> {
+        Ok(crate::startup::is_enabled())
+    }
+
+    async fn read_clipboard_text(&self) -> notecognito_core::Result<Option<String>> {
+        crate::clipboard::read_text()
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn write_clipboard_text(&mut self, text: &str) -> notecognito_core::Result<()> {
+        crate::clipboard::write_text(text)
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn frontmost_application(&self) -> notecognito_core::Result<AppIdentity> {
+        crate::foreground::frontmost_application()
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    async fn show_notification(
+        &self,
+        title: &str,
+        body: &str,
+        kind: NotificationKind,
+    ) -> notecognito_core::Result<()> {
+        crate::notification::show_toast(title, body, kind)
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    }
+
+    fn capabilities(&self) -> PlatformCapabilities {
+        crate::capabilities::detect()
+    }
+
+    async fn initialize(&mut self) -> notecognito_core::Result<()> {
         if self.initialized {
             return Ok(());
         }
 
+        // Not fatal; toasts may just not show up without it
+        if let Err(e) = crate::notification::register_app_id() {
+            tracing::warn!("Failed to set the AppUserModelID: {}", e);
+        }
+
         self.initialized = true;
         Ok(())
     }
 
-    fn cleanup(&mut self) -> notecognito_core::Result<()> {
-        let hotkey_manager = Arc::clone(&self.hotkey_manager);
-
-        let result = tokio::task::block_in_place(move || {
-            tokio::runtime::Handle::current().block_on(async move {
-                let mut manager = hotkey_manager.lock().await;
-                manager.unregister_all()
-            })
-        });
-
-        result.map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
+    async fn cleanup(&mut self) -> notecognito_core::Result<()> {
+        let mut manager = self.hotkey_manager.lock().await;
+        manager
+            .unregister_all()
+            .map_err(|e| notecognito_core::NotecognitoError::Platform(e.to_string()))
     }
 
-    fn check_permissions(&self) -> notecognito_core::Result<bool> {
+    async fn check_permissions(&self) -> notecognito_core::Result<bool> {
         // Windows doesn't require special permissions for hotkeys or overlays
         Ok(true)
     }
 
-    fn request_permissions(&self) -> notecognito_core::Result<()> {
+    async fn request_permissions(&self) -> notecognito_core::Result<()> {
         // No permissions needed on Windows
         Ok(())
     }
@@ -0,0 +1,207 @@
+use anyhow::{anyhow, Result};
+use notecognito_core::{place_fitted_window_physical_at, DisplayProperties, PhysicalRect, ScreenInfo};
+use std::mem;
+use windows::Win32::{
+    Foundation::*,
+    Graphics::Gdi::*,
+    UI::HiDpi::*,
+    UI::WindowsAndMessaging::GetCursorPos,
+};
+
+/// DPI that corresponds to a scale factor of 1.0
+const BASE_DPI: f64 = 96.0;
+
+/// Enumerates connected monitors in `EnumDisplayMonitors` order
+pub fn screens() -> Result<Vec<ScreenInfo>> {
+    let mut monitors: Vec<HMONITOR> = Vec::new();
+
+    unsafe {
+        if !EnumDisplayMonitors(
+            None,
+            None,
+            Some(collect_monitor),
+            LPARAM(&mut monitors as *mut Vec<HMONITOR> as isize),
+        ).as_bool() {
+            return Err(anyhow!("Failed to enumerate monitors"));
+        }
+    }
+
+    monitors
+        .into_iter()
+        .enumerate()
+        .map(|(index, monitor)| screen_info(index, monitor))
+        .collect()
+}
+
+/// Resolves a notecard window's bounds in device pixels on the current
+/// monitor layout: on the monitor its selector picks, against that monitor's
+/// work area so the taskbar stays clear, and kept on screen
+///
+/// With `SizeMode::FitContent` the window is sized around what `measure`
+/// returns plus `padding`, see `place_fitted_window_physical_at`.
+pub fn place(
+    properties: &DisplayProperties,
+    padding: u32,
+    measure: impl FnOnce(u32, f64) -> (u32, u32),
+) -> PhysicalRect {
+    let screens = screens().unwrap_or_else(|e| {
+        tracing::warn!("Placing a notecard without monitor info: {}", e);
+        Vec::new()
+    });
+    place_fitted_window_physical_at(properties, &screens, cursor_position(), padding, measure)
+}
+
+/// The mouse cursor in virtual screen coordinates, which are device pixels
+/// since the process is per-monitor DPI aware
+pub fn cursor_position() -> Option<(i32, i32)> {
+    let mut point = POINT::default();
+    unsafe { GetCursorPos(&mut point).ok()? };
+    Some((point.x, point.y))
+}
+
+fn screen_info(index: usize, monitor: HMONITOR) -> Result<ScreenInfo> {
+    unsafe {
+        let mut info = MONITORINFO {
+            cbSize: mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+
+        if !GetMonitorInfoW(monitor, &mut info).as_bool() {
+            return Err(anyhow!("Failed to get info for monitor {}", index));
+        }
+
+        Ok(screen_from_info(index, &info, monitor_dpi(monitor)))
+    }
+}
+
+/// The screen Windows describes with `info`, at `dpi` where it reports one
+fn screen_from_info(index: usize, info: &MONITORINFO, dpi: Option<u32>) -> ScreenInfo {
+    ScreenInfo {
+        index,
+        frame: rect_to_tuple(&info.rcMonitor),
+        work_area: rect_to_tuple(&info.rcWork),
+        scale_factor: scale_factor(dpi),
+        is_primary: info.dwFlags & MONITORINFOF_PRIMARY != 0,
+    }
+}
+
+/// Scale factor of the monitor a window is mostly on
+pub fn window_scale_factor(hwnd: HWND) -> f64 {
+    unsafe { scale_factor(monitor_dpi(MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST))) }
+}
+
+/// Effective DPI of a monitor; per-monitor DPI needs Windows 8.1
+unsafe fn monitor_dpi(monitor: HMONITOR) -> Option<u32> {
+    let (mut dpi_x, mut dpi_y) = (0u32, 0u32);
+    GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y).ok()?;
+    Some(dpi_x)
+}
+
+/// Scale factor for an effective DPI, assuming 100% scaling without one
+fn scale_factor(dpi: Option<u32>) -> f64 {
+    dpi.map_or(1.0, |dpi| dpi as f64 / BASE_DPI)
+}
+
+unsafe extern "system" fn collect_monitor(
+    monitor: HMONITOR,
+    _hdc: HDC,
+    _rect: *mut RECT,
+    data: LPARAM,
+) -> BOOL {
+    let monitors = &mut *(data.0 as *mut Vec<HMONITOR>);
+    monitors.push(monitor);
+    TRUE
+}
+
+fn rect_to_tuple(rect: &RECT) -> (i32, i32, u32, u32) {
+    (
+        rect.left,
+        rect.top,
+        (rect.right - rect.left).max(0) as u32,
+        (rect.bottom - rect.top).max(0) as u32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notecognito_core::MonitorSelector;
+
+    fn info(monitor: (i32, i32, i32, i32), work: (i32, i32, i32, i32), primary: bool) -> MONITORINFO {
+        let rect = |(left, top, right, bottom)| RECT { left, top, right, bottom };
+        MONITORINFO {
+            cbSize: mem::size_of::<MONITORINFO>() as u32,
+            rcMonitor: rect(monitor),
+            rcWork: rect(work),
+            dwFlags: if primary { MONITORINFOF_PRIMARY } else { 0 },
+        }
+    }
+
+    /// A 150% monitor left of and reaching above the primary one, enumerated
+    /// first, as Windows is free to
+    fn screens() -> Vec<ScreenInfo> {
+        vec![
+            screen_from_info(0, &info((-1920, -300, 0, 780), (-1920, -300, 0, 740), false), Some(144)),
+            screen_from_info(1, &info((0, 0, 2560, 1440), (0, 0, 2560, 1400), true), Some(96)),
+        ]
+    }
+
+    fn selected(selector: MonitorSelector, cursor: Option<(i32, i32)>) -> Option<usize> {
+        selector.select_at(&screens(), cursor).map(|screen| screen.index)
+    }
+
+    #[test]
+    fn monitor_info_becomes_a_screen() {
+        let left = &screens()[0];
+
+        assert_eq!(left.frame, (-1920, -300, 1920, 1080));
+        assert_eq!(left.work_area, (-1920, -300, 1920, 1040));
+        assert_eq!(left.scale_factor, 1.5);
+        assert!(!left.is_primary);
+    }
+
+    #[test]
+    fn missing_dpi_means_no_scaling() {
+        assert_eq!(scale_factor(None), 1.0);
+        assert_eq!(scale_factor(Some(192)), 2.0);
+    }
+
+    #[test]
+    fn inverted_rect_has_no_size() {
+        assert_eq!(rect_to_tuple(&RECT { left: -10, top: -10, right: -20, bottom: -30 }), (-10, -10, 0, 0));
+    }
+
+    #[test]
+    fn primary_is_found_wherever_it_is_enumerated() {
+        assert_eq!(selected(MonitorSelector::Primary, None), Some(1));
+    }
+
+    #[test]
+    fn index_picks_the_monitor_at_a_negative_origin() {
+        assert_eq!(selected(MonitorSelector::Index(0), None), Some(0));
+        assert_eq!(selected(MonitorSelector::Index(5), None), Some(1));
+    }
+
+    #[test]
+    fn cursor_picks_the_monitor_under_it() {
+        assert_eq!(selected(MonitorSelector::Cursor, Some((-10, -200))), Some(0));
+        assert_eq!(selected(MonitorSelector::Cursor, Some((0, 0))), Some(1));
+        // Below the left monitor, on no monitor at all
+        assert_eq!(selected(MonitorSelector::Cursor, Some((-100, 900))), Some(1));
+        assert_eq!(selected(MonitorSelector::Cursor, None), Some(1));
+    }
+
+    #[test]
+    fn card_is_scaled_for_the_monitor_at_a_negative_origin() {
+        let properties = DisplayProperties {
+            monitor: MonitorSelector::Index(0),
+            position: (100, 100),
+            size: (400, 200),
+            ..DisplayProperties::default()
+        };
+
+        let bounds = place_fitted_window_physical_at(&properties, &screens(), None, 0, |_, _| (0, 0));
+
+        assert_eq!(bounds, PhysicalRect { x: -1770, y: -150, width: 600, height: 300 });
+    }
+}
@@ -0,0 +1,224 @@
+use anyhow::Result;
+use notecognito_core::StartupMethod;
+
+/// Turns launching at logon on or off with `method`, and removes what the
+/// other method left behind so the app doesn't start twice after switching
+///
+/// Where Task Scheduler won't take the task, the Run key is used instead.
+pub fn set_enabled(method: StartupMethod, enabled: bool) -> Result<()> {
+    match (method, enabled) {
+        (StartupMethod::RunKey, true) => {
+            run_key::add()?;
+            remove_leftover("scheduled task", scheduled_task::remove());
+        }
+        (StartupMethod::RunKey, false) => {
+            run_key::remove()?;
+            remove_leftover("scheduled task", scheduled_task::remove());
+        }
+        (StartupMethod::ScheduledTask { delay_seconds }, true) => match scheduled_task::register(delay_seconds) {
+            Ok(()) => remove_leftover("Run key entry", run_key::remove()),
+            Err(e) => {
+                tracing::warn!("Could not register the startup task, using the Run key instead: {:#}", e);
+                run_key::add()?;
+            }
+        },
+        (StartupMethod::ScheduledTask { .. }, false) => {
+            scheduled_task::remove()?;
+            remove_leftover("Run key entry", run_key::remove());
+        }
+    }
+    Ok(())
+}
+
+/// Whether the app is launched at logon by either method; `None` when
+/// neither can be read
+pub fn is_enabled() -> Option<bool> {
+    let task = scheduled_task::is_enabled()
+        .map_err(|e| tracing::warn!("Could not read the startup task: {:#}", e))
+        .ok();
+    let run_key = run_key::is_enabled()
+        .map_err(|e| tracing::warn!("Could not read the Run key: {:#}", e))
+        .ok();
+
+    match (task, run_key) {
+        (None, None) => None,
+        (task, run_key) => Some(task.unwrap_or(false) || run_key.unwrap_or(false)),
+    }
+}
+
+fn remove_leftover(what: &str, result: Result<()>) {
+    if let Err(e) = result {
+        tracing::warn!("Could not remove the old startup {}: {:#}", what, e);
+    }
+}
+
+/// A value under HKCU's Run key, which Explorer starts once it's up
+mod run_key {
+    use anyhow::{Context, Result};
+    use windows::core::w;
+    use windows::Win32::{Foundation::*, System::Registry::*};
+
+    pub fn add() -> Result<()> {
+        let exe_path = std::env::current_exe().context("Could not find the app's executable")?;
+        let command: Vec<u16> = format!("\"{}\"", exe_path.display()).encode_utf16().chain(Some(0)).collect();
+        // REG_SZ data is UTF-16 with its terminator, handed over as bytes
+        let data: Vec<u8> = command.iter().flat_map(|unit| unit.to_le_bytes()).collect();
+
+        unsafe {
+            let hkey = open(KEY_SET_VALUE)?;
+            let result = RegSetValueExW(hkey, w!("Notecognito"), 0, REG_SZ, Some(&data))
+                .context("Failed to add the startup entry");
+            let _ = RegCloseKey(hkey);
+            result
+        }
+    }
+
+    pub fn remove() -> Result<()> {
+        unsafe {
+            let hkey = open(KEY_SET_VALUE)?;
+            let result = match RegDeleteValueW(hkey, w!("Notecognito")) {
+                Ok(_) => Ok(()),
+                Err(e) if e.code() == ERROR_FILE_NOT_FOUND.to_hresult() => Ok(()),
+                Err(e) => Err(e).context("Failed to remove the startup entry"),
+            };
+            let _ = RegCloseKey(hkey);
+            result
+        }
+    }
+
+    pub fn is_enabled() -> Result<bool> {
+        unsafe {
+            let hkey = open(KEY_QUERY_VALUE)?;
+            let result = match RegQueryValueExW(hkey, w!("Notecognito"), None, None, None, None) {
+                Ok(_) => Ok(true),
+                Err(e) if e.code() == ERROR_FILE_NOT_FOUND.to_hresult() => Ok(false),
+                Err(e) => Err(e).context("Failed to read the startup entry"),
+            };
+            let _ = RegCloseKey(hkey);
+            result
+        }
+    }
+
+    unsafe fn open(access: REG_SAM_FLAGS) -> Result<HKEY> {
+        let mut hkey = HKEY::default();
+        RegOpenKeyExW(
+            HKEY_CURRENT_USER,
+            w!("Software\\Microsoft\\Windows\\CurrentVersion\\Run"),
+            0,
+            access,
+            &mut hkey,
+        )
+        .context("Failed to open the Run key")?;
+        Ok(hkey)
+    }
+}
+
+/// A Task Scheduler task started by the current user's logon
+///
+/// The task runs as that user with their normal rights, so registering it
+/// needs no elevation. It starts earlier than the Run key and isn't among the
+/// entries startup managers tend to switch off.
+mod scheduled_task {
+    use anyhow::{Context, Result};
+    use windows::core::{ComInterface, BSTR};
+    use windows::Win32::{
+        Foundation::*,
+        System::Com::*,
+        System::TaskScheduler::*,
+        System::Variant::VARIANT,
+    };
+
+    const TASK_NAME: &str = "Notecognito";
+
+    pub fn register(delay_seconds: u32) -> Result<()> {
+        unsafe {
+            let service = connect()?;
+            let task = service.NewTask(0)?;
+
+            task.RegistrationInfo()?
+                .SetDescription(&BSTR::from("Starts Notecognito when you sign in"))?;
+
+            let settings = task.Settings()?;
+            settings.SetDisallowStartIfOnBatteries(VARIANT_FALSE)?;
+            settings.SetStopIfGoingOnBatteries(VARIANT_FALSE)?;
+            // Runs for the whole session rather than the default three days
+            settings.SetExecutionTimeLimit(&BSTR::from("PT0S"))?;
+
+            // Only this user's logon; a trigger for any user would need elevation
+            let trigger: ILogonTrigger = task.Triggers()?.Create(TASK_TRIGGER_LOGON)?.cast()?;
+            trigger.SetUserId(&BSTR::from(current_user()))?;
+            if delay_seconds > 0 {
+                trigger.SetDelay(&BSTR::from(format!("PT{}S", delay_seconds)))?;
+            }
+
+            let action: IExecAction = task.Actions()?.Create(TASK_ACTION_EXEC)?.cast()?;
+            let exe_path = std::env::current_exe().context("Could not find the app's executable")?;
+            action.SetPath(&BSTR::from(exe_path.display().to_string()))?;
+
+            let principal = task.Principal()?;
+            principal.SetLogonType(TASK_LOGON_INTERACTIVE_TOKEN)?;
+            principal.SetRunLevel(TASK_RUNLEVEL_LUA)?;
+
+            // With no user given, the task belongs to whoever registers it
+            root_folder(&service)?
+                .RegisterTaskDefinition(
+                    &BSTR::from(TASK_NAME),
+                    &task,
+                    TASK_CREATE_OR_UPDATE.0,
+                    VARIANT::default(),
+                    VARIANT::default(),
+                    TASK_LOGON_INTERACTIVE_TOKEN,
+                    VARIANT::default(),
+                )
+                .context("Failed to register the startup task")?;
+        }
+        Ok(())
+    }
+
+    pub fn remove() -> Result<()> {
+        unsafe {
+            let folder = root_folder(&connect()?)?;
+            match folder.DeleteTask(&BSTR::from(TASK_NAME), 0) {
+                Ok(()) => Ok(()),
+                Err(e) if e.code() == ERROR_FILE_NOT_FOUND.to_hresult() => Ok(()),
+                Err(e) => Err(e).context("Failed to remove the startup task"),
+            }
+        }
+    }
+
+    pub fn is_enabled() -> Result<bool> {
+        unsafe {
+            let folder = root_folder(&connect()?)?;
+            match folder.GetTask(&BSTR::from(TASK_NAME)) {
+                Ok(task) => Ok(task.Enabled()?.as_bool()),
+                Err(e) if e.code() == ERROR_FILE_NOT_FOUND.to_hresult() => Ok(false),
+                Err(e) => Err(e).context("Failed to read the startup task"),
+            }
+        }
+    }
+
+    unsafe fn connect() -> Result<ITaskService> {
+        // Already initialized on this thread is fine
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+
+        let service: ITaskService = CoCreateInstance(&TaskScheduler, None, CLSCTX_INPROC_SERVER)
+            .context("Task Scheduler isn't available")?;
+        service
+            .Connect(VARIANT::default(), VARIANT::default(), VARIANT::default(), VARIANT::default())
+            .context("Failed to connect to Task Scheduler")?;
+        Ok(service)
+    }
+
+    unsafe fn root_folder(service: &ITaskService) -> Result<ITaskFolder> {
+        Ok(service.GetFolder(&BSTR::from("\\"))?)
+    }
+
+    /// DOMAIN\user, as a logon trigger names its user
+    fn current_user() -> String {
+        let user = std::env::var("USERNAME").unwrap_or_default();
+        match std::env::var("USERDOMAIN") {
+            Ok(domain) if !domain.is_empty() => format!("{}\\{}", domain, user),
+            _ => user,
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use anyhow::{anyhow, Result};
+use notecognito_core::Rgba;
+use windows::core::{w, HSTRING};
+use windows::Win32::{
+    Foundation::*,
+    Graphics::Direct2D::Common::*,
+    Graphics::Direct2D::*,
+    Graphics::DirectWrite::*,
+    Graphics::Dxgi::Common::*,
+    Graphics::Gdi::HDC,
+};
+
+/// Space between the card's edge and its text, in pixels
+pub const PADDING: i32 = 10;
+
+/// Direct2D and DirectWrite factories, made once per thread that paints cards
+struct Factories {
+    d2d: ID2D1Factory,
+    dwrite: IDWriteFactory,
+}
+
+thread_local! {
+    /// `None` where Direct2D or DirectWrite isn't available, so cards are
+    /// drawn with GDI without trying again on every paint
+    static FACTORIES: Option<Factories> = Factories::new()
+        .map_err(|e| tracing::warn!("Drawing notecards with GDI: {}", e))
+        .ok();
+}
+
+impl Factories {
+    fn new() -> Result<Self> {
+        unsafe {
+            Ok(Factories {
+                d2d: D2D1CreateFactory(D2D1_FACTORY_TYPE_SINGLE_THREADED, None)?,
+                dwrite: DWriteCreateFactory(DWRITE_FACTORY_TYPE_SHARED)?,
+            })
+        }
+    }
+}
+
+/// What a card's text is drawn with
+pub struct TextStyle<'a> {
+    pub font_face: &'a str,
+    /// Em size in device pixels
+    pub font_size: f32,
+    pub color: Rgba,
+    pub background: Rgba,
+}
+
+/// Fills `rect` of `hdc` with the background and draws `content` wrapped
+/// inside it, with Direct2D and DirectWrite
+///
+/// Unlike GDI's DrawTextW, this draws color emoji and kerns and antialiases
+/// large text properly. Fails when Direct2D isn't available or drawing
+/// fails, for the caller to draw with GDI instead.
+pub unsafe fn draw(hdc: HDC, rect: &RECT, content: &str, style: &TextStyle) -> Result<()> {
+    FACTORIES.with(|factories| {
+        let factories = factories.as_ref().ok_or_else(|| anyhow!("Direct2D isn't available"))?;
+
+        // At 96 DPI one DIP is one pixel, so the client rect and font size
+        // are used as they are
+        let properties = D2D1_RENDER_TARGET_PROPERTIES {
+            r#type: D2D1_RENDER_TARGET_TYPE_DEFAULT,
+            pixelFormat: D2D1_PIXEL_FORMAT {
+                format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                alphaMode: D2D1_ALPHA_MODE_IGNORE,
+            },
+            dpiX: 96.0,
+            dpiY: 96.0,
+            usage: D2D1_RENDER_TARGET_USAGE_NONE,
+            minLevel: D2D1_FEATURE_LEVEL_DEFAULT,
+        };
+        let target = factories.d2d.CreateDCRenderTarget(&properties)?;
+        target.BindDC(hdc, rect)?;
+
+        let width = (rect.right - rect.left - 2 * PADDING).max(0) as f32;
+        let height = (rect.bottom - rect.top - 2 * PADDING).max(0) as f32;
+        let layout = text_layout(factories, content, style, width, height)?;
+
+        target.BeginDraw();
+        // The background's alpha is applied to the whole window instead
+        target.Clear(Some(&color_f(style.background, 1.0)));
+        let brush = target.CreateSolidColorBrush(&color_f(style.color, style.color.alpha() as f32), None)?;
+        target.DrawTextLayout(
+            D2D_POINT_2F { x: PADDING as f32, y: PADDING as f32 },
+            &layout,
+            &brush,
+            D2D1_DRAW_TEXT_OPTIONS_ENABLE_COLOR_FONT | D2D1_DRAW_TEXT_OPTIONS_CLIP,
+        );
+        target.EndDraw(None, None)?;
+
+        Ok(())
+    })
+}
+
+/// Size `content` takes wrapped to `max_width`, in pixels, as `draw` lays it
+/// out without the padding; fails when DirectWrite isn't available
+pub fn measure(content: &str, style: &TextStyle, max_width: u32) -> Result<(u32, u32)> {
+    FACTORIES.with(|factories| {
+        let factories = factories.as_ref().ok_or_else(|| anyhow!("DirectWrite isn't available"))?;
+        let layout = text_layout(factories, content, style, max_width as f32, f32::MAX)?;
+
+        let mut metrics = DWRITE_TEXT_METRICS::default();
+        unsafe { layout.GetMetrics(&mut metrics)? };
+        Ok((
+            metrics.widthIncludingTrailingWhitespace.ceil() as u32,
+            metrics.height.ceil() as u32,
+        ))
+    })
+}
+
+/// `content` laid out in `style`'s font, wrapped to `width`
+fn text_layout(
+    factories: &Factories,
+    content: &str,
+    style: &TextStyle,
+    width: f32,
+    height: f32,
+) -> Result<IDWriteTextLayout> {
+    unsafe {
+        let format = factories.dwrite.CreateTextFormat(
+            &HSTRING::from(style.font_face),
+            None,
+            DWRITE_FONT_WEIGHT_NORMAL,
+            DWRITE_FONT_STYLE_NORMAL,
+            DWRITE_FONT_STRETCH_NORMAL,
+            style.font_size,
+            w!("en-us"),
+        )?;
+        format.SetWordWrapping(DWRITE_WORD_WRAPPING_WRAP)?;
+
+        let text: Vec<u16> = content.encode_utf16().collect();
+        Ok(factories.dwrite.CreateTextLayout(&text, &format, width, height)?)
+    }
+}
+
+fn color_f(color: Rgba, alpha: f32) -> D2D1_COLOR_F {
+    D2D1_COLOR_F {
+        r: color.r as f32 / 255.0,
+        g: color.g as f32 / 255.0,
+        b: color.b as f32 / 255.0,
+        a: alpha,
+    }
+}
@@ -0,0 +1,177 @@
+use anyhow::{anyhow, Result};
+use std::cell::RefCell;
+use std::ffi::c_void;
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+use windows::core::{w, PCWSTR};
+use windows::Win32::{
+    Foundation::*,
+    System::LibraryLoader::GetModuleHandleW,
+    System::Registry::*,
+    System::Threading::GetCurrentThreadId,
+    UI::WindowsAndMessaging::*,
+};
+
+const PERSONALIZE_KEY: PCWSTR = w!("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize");
+const WATCHER_CLASS_NAME: PCWSTR = w!("NotecognitoThemeWatcher");
+
+/// Whether apps are set to the light theme, as `refresh` last read it
+static LIGHT: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Run by the watcher window when the theme changes
+    static ON_CHANGE: RefCell<Option<Box<dyn Fn()>>> = const { RefCell::new(None) };
+}
+
+/// Whether apps use the light theme; dark until `refresh` reads otherwise
+pub fn is_light() -> bool {
+    LIGHT.load(Ordering::Relaxed)
+}
+
+/// Reads the apps theme from Personalize settings, returning whether it changed
+///
+/// Dark, as cards were before themes, where the setting can't be read.
+pub fn refresh() -> bool {
+    let light = apps_use_light_theme().unwrap_or(false);
+    LIGHT.swap(light, Ordering::Relaxed) != light
+}
+
+fn apps_use_light_theme() -> Option<bool> {
+    let mut value = 0u32;
+    let mut size = mem::size_of::<u32>() as u32;
+    unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            PERSONALIZE_KEY,
+            w!("AppsUseLightTheme"),
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut value as *mut u32 as *mut c_void),
+            Some(&mut size),
+        )
+        .ok()
+        .ok()?;
+    }
+    Some(value != 0)
+}
+
+/// The thread of a hidden window that hears about theme changes
+pub struct ThemeWatcher {
+    thread_id: u32,
+    handle: JoinHandle<()>,
+}
+
+impl ThemeWatcher {
+    /// Calls `on_change` after `refresh` whenever the user switches between
+    /// the light and dark theme
+    ///
+    /// WM_SETTINGCHANGE is only broadcast to top-level windows, so it's a
+    /// hidden popup rather than a message-only window that listens for it.
+    pub fn start(on_change: impl Fn() + Send + 'static) -> Result<Self> {
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        let handle = thread::spawn(move || unsafe {
+            ON_CHANGE.with(|callback| *callback.borrow_mut() = Some(Box::new(on_change)));
+
+            // Makes sure the thread has a message queue before anyone posts to it
+            let mut msg = MSG::default();
+            let _ = PeekMessageW(&mut msg, HWND::default(), WM_USER, WM_USER, PM_NOREMOVE);
+
+            let hwnd = match create_watcher_window() {
+                Ok(hwnd) => hwnd,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+            let _ = ready_tx.send(Ok(GetCurrentThreadId()));
+
+            // Runs until `stop` posts WM_QUIT
+            while GetMessageW(&mut msg, HWND::default(), 0, 0).0 > 0 {
+                TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+
+            let _ = DestroyWindow(hwnd);
+        });
+
+        let thread_id = ready_rx
+            .recv()
+            .map_err(|_| anyhow!("The theme watcher thread exited"))??;
+
+        Ok(ThemeWatcher { thread_id, handle })
+    }
+
+    /// Ends the watcher's message loop, which destroys its window, and waits for it
+    pub fn stop(self) -> Result<()> {
+        unsafe {
+            PostThreadMessageW(self.thread_id, WM_QUIT, WPARAM(0), LPARAM(0))?;
+        }
+        self.handle
+            .join()
+            .map_err(|_| anyhow!("The theme watcher thread panicked"))
+    }
+}
+
+unsafe fn create_watcher_window() -> Result<HWND> {
+    let instance = GetModuleHandleW(None)?;
+
+    let wc = WNDCLASSEXW {
+        cbSize: mem::size_of::<WNDCLASSEXW>() as u32,
+        lpfnWndProc: Some(watcher_window_proc),
+        hInstance: instance.into(),
+        lpszClassName: WATCHER_CLASS_NAME,
+        ..Default::default()
+    };
+
+    // Already there when the watcher is started a second time
+    if RegisterClassExW(&wc) == 0 && GetLastError() != ERROR_CLASS_ALREADY_EXISTS {
+        return Err(anyhow!("Failed to register the theme watcher window class"));
+    }
+
+    let hwnd = CreateWindowExW(
+        WS_EX_TOOLWINDOW,
+        WATCHER_CLASS_NAME,
+        w!("Notecognito Theme Watcher"),
+        WS_POPUP,
+        0,
+        0,
+        0,
+        0,
+        None,
+        None,
+        instance,
+        None,
+    );
+    if hwnd.0 == 0 {
+        return Err(anyhow!("Failed to create the theme watcher window"));
+    }
+
+    Ok(hwnd)
+}
+
+unsafe extern "system" fn watcher_window_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    // Sent with "ImmersiveColorSet" when the light or dark theme is picked,
+    // among other color changes
+    if msg == WM_SETTINGCHANGE && lparam.0 != 0 {
+        let area = PCWSTR(lparam.0 as *const u16).to_string().unwrap_or_default();
+        if area == "ImmersiveColorSet" && refresh() {
+            tracing::info!("Apps switched to the {} theme", if is_light() { "light" } else { "dark" });
+            ON_CHANGE.with(|callback| {
+                if let Some(callback) = callback.borrow().as_ref() {
+                    callback();
+                }
+            });
+        }
+        return LRESULT(0);
+    }
+
+    DefWindowProcW(hwnd, msg, wparam, lparam)
+}
@@ -0,0 +1,174 @@
+use anyhow::Result;
+use image::{Rgba, RgbaImage};
+use notecognito_core::{NotecardId, NotecardSummary};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tray_icon::menu::{Menu, MenuId, MenuItem, PredefinedMenuItem, Submenu};
+use tray_icon::Icon;
+
+/// What picking a tray menu item does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayAction {
+    Configure,
+    ShowAll,
+    HideAll,
+    ToggleNotecard(NotecardId),
+    Quit,
+}
+
+/// Actions of the current tray menu's items by menu id, shared with the task
+/// handling menu events; replaced whenever the menu is rebuilt
+pub type ActionRegistry = Arc<Mutex<HashMap<MenuId, TrayAction>>>;
+
+/// Builds the tray menu for the configured notecards, recording what each
+/// item does in `actions` in place of the previous menu's items
+///
+/// The Notecards submenu has one item per notecard with content, in id
+/// order, showing or hiding it as its hotkey does. While hotkeys are paused
+/// those items are greyed out with them, and Show All and Hide All keep working.
+/// A status line says whether the configuration comes from core.
+pub fn build(
+    notecards: &[NotecardSummary],
+    hotkeys_enabled: bool,
+    core_connected: bool,
+    actions: &ActionRegistry,
+) -> Result<Menu> {
+    let mut registered = HashMap::new();
+    let mut item = |title: &str, enabled: bool, action: TrayAction| {
+        let item = MenuItem::new(title, enabled, None);
+        registered.insert(item.id().clone(), action);
+        item
+    };
+
+    let menu = Menu::new();
+    menu.append(&item("Configure", true, TrayAction::Configure))?;
+    menu.append(&item("Show All Notecards", true, TrayAction::ShowAll))?;
+    menu.append(&item("Hide All Notecards", true, TrayAction::HideAll))?;
+
+    let submenu = Submenu::new("Notecards", true);
+    let mut any = false;
+    for notecard in notecards.iter().filter(|notecard| !notecard.is_empty) {
+        let title = format!("{}  {}", notecard.id.value(), notecard.title);
+        submenu.append(&item(&title, hotkeys_enabled, TrayAction::ToggleNotecard(notecard.id)))?;
+        any = true;
+    }
+    if !any {
+        submenu.append(&MenuItem::new("No Notecards", false, None))?;
+    }
+    menu.append(&submenu)?;
+
+    if !hotkeys_enabled {
+        menu.append(&MenuItem::new("Hotkeys Paused", false, None))?;
+    }
+    let core = if core_connected { "Core: Connected" } else { "Core: Standalone" };
+    menu.append(&MenuItem::new(core, false, None))?;
+
+    menu.append(&PredefinedMenuItem::separator())?;
+    menu.append(&item("Quit", true, TrayAction::Quit))?;
+
+    *actions.lock().unwrap() = registered;
+    Ok(menu)
+}
+
+/// What the tray icon and tooltip show; where several apply, the first one
+/// listed here wins
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppState {
+    /// This many hotkeys couldn't be registered; clicking the icon lists them
+    Degraded { failed_hotkeys: usize },
+    /// Hotkeys are paused from the configuration
+    Paused,
+    /// Running standalone, without configuration pushes from core
+    Disconnected,
+    /// Hotkeys are live and core is connected
+    Active,
+}
+
+impl AppState {
+    pub fn new(hotkeys_enabled: bool, failed_hotkeys: usize, core_connected: bool) -> Self {
+        if failed_hotkeys > 0 {
+            AppState::Degraded { failed_hotkeys }
+        } else if !hotkeys_enabled {
+            AppState::Paused
+        } else if !core_connected {
+            AppState::Disconnected
+        } else {
+            AppState::Active
+        }
+    }
+}
+
+/// The tray icon's tooltip for `state`
+pub fn tooltip(state: AppState) -> String {
+    match state {
+        AppState::Degraded { failed_hotkeys: 1 } => "Notecognito - 1 hotkey not registered, click for details".to_string(),
+        AppState::Degraded { failed_hotkeys } => {
+            format!("Notecognito - {} hotkeys not registered, click for details", failed_hotkeys)
+        }
+        AppState::Paused => "Notecognito - Hotkeys paused".to_string(),
+        AppState::Disconnected => "Notecognito - Core: standalone".to_string(),
+        AppState::Active => "Notecognito".to_string(),
+    }
+}
+
+/// The tray icon in each `AppState`, generated from the one embedded icon
+pub struct TrayIcons {
+    active: RgbaImage,
+    paused: RgbaImage,
+    degraded: RgbaImage,
+    disconnected: RgbaImage,
+}
+
+impl TrayIcons {
+    /// Opacity of the paused icon
+    const PAUSED_ALPHA: f32 = 0.5;
+    const DEGRADED_BADGE: Rgba<u8> = Rgba([0xF0, 0xA3, 0x0A, 0xFF]);
+    const DISCONNECTED_BADGE: Rgba<u8> = Rgba([0x8A, 0x8A, 0x8A, 0xFF]);
+
+    pub fn new(icon: RgbaImage) -> Self {
+        let mut paused = icon.clone();
+        for pixel in paused.pixels_mut() {
+            pixel[3] = (pixel[3] as f32 * Self::PAUSED_ALPHA).round() as u8;
+        }
+
+        TrayIcons {
+            paused,
+            degraded: badged(&icon, Self::DEGRADED_BADGE),
+            disconnected: badged(&icon, Self::DISCONNECTED_BADGE),
+            active: icon,
+        }
+    }
+
+    pub fn get(&self, state: AppState) -> Result<Icon> {
+        let image = match state {
+            AppState::Degraded { .. } => &self.degraded,
+            AppState::Paused => &self.paused,
+            AppState::Disconnected => &self.disconnected,
+            AppState::Active => &self.active,
+        };
+        Ok(Icon::from_rgba(image.as_raw().clone(), image.width(), image.height())?)
+    }
+}
+
+/// `icon` with a dot of `color` in its bottom-right corner, set apart from
+/// the glyph by a transparent ring
+fn badged(icon: &RgbaImage, color: Rgba<u8>) -> RgbaImage {
+    let mut image = icon.clone();
+    let size = image.width().min(image.height()) as f32;
+    let radius = size * 0.225;
+    let gap = (size / 16.0).max(1.0);
+    let center_x = image.width() as f32 - radius;
+    let center_y = image.height() as f32 - radius;
+
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let dx = x as f32 + 0.5 - center_x;
+        let dy = y as f32 + 0.5 - center_y;
+        let distance = (dx * dx + dy * dy).sqrt();
+        if distance <= radius {
+            *pixel = color;
+        } else if distance <= radius + gap {
+            pixel[3] = 0;
+        }
+    }
+    image
+}